@@ -0,0 +1,331 @@
+//! Local, opt-in usage statistics.
+//!
+//! Nothing is recorded unless a [`CookLog`] is explicitly created and events are appended to it.
+//! The log is a plain tab-separated file that never leaves the machine.
+
+use crate::{
+    cook::CookSession,
+    recipe::{
+        amount::Amount,
+        unit::{QuantityOf, Time},
+        Recipe,
+    },
+};
+use chrono::{Datelike, NaiveDate};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Instant,
+};
+
+/// Tag prefix identifying a recipe's cuisine (e.g., `#cuisine/italian`).
+const CUISINE_TAG_PREFIX: &str = "cuisine/";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CookEvent {
+    pub recipe: String,
+    pub date: NaiveDate,
+    pub duration: Option<QuantityOf<Time>>,
+    pub cuisine: Option<String>,
+}
+
+impl CookEvent {
+    /// Creates an event for a recipe, deriving its cuisine from the first `cuisine/...` tag.
+    pub fn from_recipe(
+        recipe: &Recipe,
+        date: NaiveDate,
+        duration: Option<QuantityOf<Time>>,
+    ) -> Self {
+        Self {
+            recipe: recipe.name().to_string(),
+            date,
+            duration,
+            cuisine: recipe
+                .metadata()
+                .tags()
+                .iter()
+                .find_map(|t| t.strip_prefix(CUISINE_TAG_PREFIX))
+                .map(|c| c.to_string()),
+        }
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.date.format(DATE_FORMAT),
+            escape_field(&self.recipe),
+            self.duration
+                .map(|d| (d.as_seconds() / Amount::from_int(60)).to_exact_string())
+                .unwrap_or_default(),
+            escape_field(self.cuisine.as_deref().unwrap_or_default())
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 4 || fields[1].is_empty() {
+            return None;
+        }
+        let duration = match fields[2] {
+            "" => None,
+            minutes => Some(QuantityOf::from_str(&format!("{} min", minutes)).ok()?),
+        };
+        Some(Self {
+            date: NaiveDate::parse_from_str(fields[0], DATE_FORMAT).ok()?,
+            recipe: unescape_field(fields[1])?,
+            duration,
+            cuisine: match fields[3] {
+                "" => None,
+                cuisine => Some(unescape_field(cuisine)?),
+            },
+        })
+    }
+}
+
+/// Escapes the characters which would break a line of the log (e.g., a tab as `\t`).
+fn escape_field(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverses [`escape_field`], or returns `None` for an unknown escape.
+fn unescape_field(field: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                't' => '\t',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            },
+            _ => c,
+        });
+    }
+    Some(unescaped)
+}
+
+/// An append-only log of cook events backed by a local file.
+pub struct CookLog {
+    path: PathBuf,
+    events: Vec<CookEvent>,
+}
+
+impl CookLog {
+    /// Opens the log at `path`, loading existing events (the file is created on first record).
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut events = vec![];
+        if path.exists() {
+            for (i, line) in BufReader::new(File::open(&path)?).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                events.push(CookEvent::from_line(&line).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("malformed cook log entry on line {}", i + 1),
+                    )
+                })?);
+            }
+        }
+        Ok(Self { path, events })
+    }
+
+    pub fn record(&mut self, event: CookEvent) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", event.to_line())?;
+        self.events.push(event);
+        Ok(())
+    }
+
+    /// Records a cook session which lasted from `started` to `now`, as cooked on `date`. The
+    /// duration is rounded to the minute.
+    pub fn record_session(
+        &mut self,
+        session: &CookSession,
+        date: NaiveDate,
+        started: Instant,
+        now: Instant,
+    ) -> io::Result<()> {
+        let minutes = (now.saturating_duration_since(started).as_secs() + 30) / 60;
+        let duration = QuantityOf::new(Time::Minute, Amount::from_int(minutes as i64));
+        self.record(CookEvent::from_recipe(
+            session.recipe(),
+            date,
+            Some(duration),
+        ))
+    }
+
+    pub fn events(&self) -> &[CookEvent] {
+        &self.events
+    }
+
+    pub fn stats(&self) -> Stats<'_> {
+        Stats {
+            events: &self.events,
+        }
+    }
+}
+
+/// Statistics computed over a set of cook events.
+pub struct Stats<'a> {
+    events: &'a [CookEvent],
+}
+
+impl<'a> Stats<'a> {
+    pub fn new(events: &'a [CookEvent]) -> Self {
+        Self { events }
+    }
+
+    /// Returns the `n` most cooked recipes with their cook count, most cooked first (ties are
+    /// ordered by name).
+    pub fn most_cooked(&self, n: usize) -> Vec<(&'a str, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for event in self.events {
+            *counts.entry(&event.recipe).or_default() += 1;
+        }
+        let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts.truncate(n);
+        counts
+    }
+
+    /// Returns, for each month (year, month) with at least one event, how many times each cuisine
+    /// was cooked. Events without a cuisine are not counted.
+    pub fn cuisine_distribution(&self) -> BTreeMap<(i32, u32), BTreeMap<&'a str, usize>> {
+        let mut distribution: BTreeMap<(i32, u32), BTreeMap<&str, usize>> = BTreeMap::new();
+        for event in self.events {
+            if let Some(cuisine) = &event.cuisine {
+                *distribution
+                    .entry((event.date.year(), event.date.month()))
+                    .or_default()
+                    .entry(cuisine)
+                    .or_default() += 1;
+            }
+        }
+        distribution
+    }
+
+    /// Returns the average time spent cooking per week, over the weeks spanned by the events.
+    /// Events without a duration count as zero.
+    pub fn average_weekly_cooking_time(&self) -> Option<QuantityOf<Time>> {
+        let first = self.events.iter().map(|e| e.date).min()?;
+        let last = self.events.iter().map(|e| e.date).max()?;
+        let weeks = Amount::from_int((last - first).num_days() / 7 + 1);
+        let total: Amount = self
+            .events
+            .iter()
+            .filter_map(|e| e.duration.map(|d| d.as_seconds()))
+            .sum();
+        Some(QuantityOf {
+            unit: Time::Minute,
+            amount: total / Amount::from_int(60) / weeks,
+            max_amount: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn event(recipe: &str, date: &str, minutes: Option<i64>, cuisine: Option<&str>) -> CookEvent {
+        CookEvent {
+            recipe: recipe.to_string(),
+            date: NaiveDate::parse_from_str(date, DATE_FORMAT).unwrap(),
+            duration: minutes.map(|minutes| QuantityOf {
+                unit: Time::Minute,
+                amount: Amount::from_int(minutes),
+                max_amount: None,
+            }),
+            cuisine: cuisine.map(|c| c.to_string()),
+        }
+    }
+
+    #[test]
+    fn event_line_roundtrip() {
+        let e = event("Pasta", "2024-05-01", Some(30), Some("italian"));
+        assert_eq!(CookEvent::from_line(&e.to_line()), Some(e));
+        let e = event("Toast", "2024-05-02", None, None);
+        assert_eq!(CookEvent::from_line(&e.to_line()), Some(e));
+        assert_eq!(CookEvent::from_line("2024-05-02\tToast"), None);
+
+        // Tabs and newlines in names do not break the line.
+        let e = event("Tab\tand\nnewline \\t", "2024-05-03", None, Some("a\tb"));
+        let line = e.to_line();
+        assert_eq!(line.matches('\t').count(), 3);
+        assert!(!line.contains('\n'));
+        assert_eq!(CookEvent::from_line(&line), Some(e));
+        assert_eq!(CookEvent::from_line("2024-05-02\tTo\\ast\t\t"), None);
+    }
+
+    #[test]
+    fn cook_log_persists() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("dtc-cook-log-{}.tsv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut log = CookLog::open(&path)?;
+        log.record(event("Pasta", "2024-05-01", Some(30), Some("italian")))?;
+        log.record(event("Toast", "2024-05-02", None, None))?;
+        assert_eq!(CookLog::open(&path)?.events(), log.events());
+
+        let recipe = Recipe::from_mdast(
+            "---\ntags: [\"#cuisine/italian\"]\n---\n# Pesto\n## Ingredients\n\n- Basil, 1 bunch\n\
+             ## Instructions\n\n- Blend the *basil*\n",
+        )
+        .unwrap();
+        let session = CookSession::new(&recipe);
+        let started = Instant::now();
+        let date = NaiveDate::from_ymd_opt(2024, 5, 3).unwrap();
+        log.record_session(&session, date, started, started + Duration::from_secs(1250))?;
+        assert_eq!(
+            CookLog::open(&path)?.events()[2],
+            event("Pesto", "2024-05-03", Some(21), Some("italian"))
+        );
+        std::fs::remove_file(&path)
+    }
+
+    #[test]
+    fn stats() {
+        let events = vec![
+            event("Pasta", "2024-05-01", Some(30), Some("italian")),
+            event("Curry", "2024-05-03", Some(60), Some("indian")),
+            event("Pasta", "2024-05-10", Some(30), Some("italian")),
+            event("Toast", "2024-06-01", None, None),
+        ];
+        let stats = Stats::new(&events);
+        assert_eq!(stats.most_cooked(2), vec![("Pasta", 2), ("Curry", 1)]);
+
+        let distribution = stats.cuisine_distribution();
+        assert_eq!(distribution.len(), 1);
+        assert_eq!(distribution[&(2024, 5)]["italian"], 2);
+        assert_eq!(distribution[&(2024, 5)]["indian"], 1);
+
+        // 120 minutes over 5 weeks.
+        assert_eq!(
+            stats.average_weekly_cooking_time().unwrap().amount,
+            Amount::from_int(24)
+        );
+        assert!(Stats::new(&[]).average_weekly_cooking_time().is_none());
+    }
+}
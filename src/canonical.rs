@@ -0,0 +1,223 @@
+//! Canonical formatting of recipes, as written by [`render::markdown`](crate::render::markdown).
+//!
+//! A recipe written in canonical form parses back to the same recipe, and writing a recipe parsed
+//! from canonical Markdown reproduces the Markdown byte for byte. Tools rewriting recipes (e.g., to
+//! scale them or fix their metadata) thus only produce meaningful diffs. The rules are given as
+//! data in [`RULES`], so that other tools (e.g., editor plugins) can follow them too:
+//!
+//! ```markdown
+//! ---
+//! tags:
+//!   - "#dessert"
+//! servings: 4
+//! ---
+//! # Lemonade
+//! ## Ingredients
+//!
+//! ### Syrup
+//! - Sugar, 100 g / 1/2 cup
+//! - Water, 1 L (cold)
+//!
+//! ## Instructions
+//!
+//! - Dissolve the *Sugar*
+//!     - Stir for **2 min**
+//! ```
+
+use crate::recipe::{
+    amount::Amount,
+    language::Language,
+    unit::{Approximation, Quantity, QuantityOf, Unit, UnitTrait},
+};
+
+/// Rules of the canonical format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rules {
+    /// Marker of list items (ingredients, tools, steps, and notes). Numbered lists of steps stay
+    /// numbered.
+    pub list_marker: char,
+    /// Delimiter after the numbers of numbered steps (e.g., "1.").
+    pub number_delimiter: char,
+    /// Spaces before the marker of substeps, per level of nesting, or more if the marker of their
+    /// parent step is longer (e.g., "100.").
+    pub indent: usize,
+    /// Spaces before the entries of nested YAML sequences and mappings in the frontmatter.
+    pub yaml_indent: usize,
+    /// Delimiter of the frontmatter.
+    pub frontmatter_delimiter: &'static str,
+    /// Order of the frontmatter keys. Sizes (`size | <name>`) come in alphabetical order in place
+    /// of [`SIZES_KEY`], and keys without dedicated support in alphabetical order after all
+    /// others.
+    pub key_order: &'static [&'static str],
+    /// Marker around ingredient references in steps (e.g., "*Lemons*").
+    pub reference_marker: &'static str,
+    /// Marker around timers and temperatures in steps (e.g., "**2 min**", "**180 °C**").
+    pub timer_marker: &'static str,
+    /// Between the name of an ingredient and its quantity.
+    pub quantity_separator: &'static str,
+    /// Between the quantity of an ingredient and its alternative quantities.
+    pub alt_quantity_separator: &'static str,
+    /// Between an ingredient and its alternatives.
+    pub alternative_separator: &'static str,
+    /// Between the branches of scale-dependent text.
+    pub branch_separator: &'static str,
+    /// Blank lines after a section's heading and between sections. Ingredient groups are
+    /// separated by blank lines too, but their heading is directly followed by their list.
+    pub blank_lines: usize,
+}
+
+/// Stands for the sizes in [`Rules::key_order`].
+pub const SIZES_KEY: &str = "size | *";
+
+pub const RULES: Rules = Rules {
+    list_marker: '-',
+    number_delimiter: '.',
+    indent: 4,
+    yaml_indent: 2,
+    frontmatter_delimiter: "---",
+    key_order: &[
+        "tags",
+        "aliases",
+        "category",
+        "image",
+        "source",
+        "author",
+        "url",
+        "rating",
+        "difficulty",
+        "created",
+        "last_cooked",
+        "cook_log",
+        "servings",
+        "yield",
+        "prep_time",
+        "cook_time",
+        "total_time",
+        SIZES_KEY,
+        "glass",
+        "nutrition",
+        "ratio",
+        "canning",
+        "scales",
+        "densities",
+    ],
+    reference_marker: "*",
+    timer_marker: "**",
+    quantity_separator: ", ",
+    alt_quantity_separator: " / ",
+    alternative_separator: " | ",
+    branch_separator: " | ",
+    blank_lines: 1,
+};
+
+/// Formats an amount canonically: as a whole or mixed number for common fractions (e.g., "1
+/// 1/2"), as a decimal number for other amounts with a finite decimal expansion (e.g., "0.2"), and
+/// as a fraction otherwise (e.g., "1/7"). Parsing the result gives back the exact amount.
+pub fn amount(amount: Amount) -> String {
+    amount
+        .as_fraction()
+        .or_else(|| decimal(amount))
+        .unwrap_or_else(|| amount.to_exact_string())
+}
+
+/// Formats an amount as a decimal number, if it has a finite decimal expansion.
+fn decimal(amount: Amount) -> Option<String> {
+    let (mut den, mut twos, mut fives) = (amount.denominator(), 0, 0);
+    while den % 2 == 0 {
+        den /= 2;
+        twos += 1;
+    }
+    while den % 5 == 0 {
+        den /= 5;
+        fives += 1;
+    }
+    (den == 1).then(|| format!("{:.*}", twos.max(fives), amount))
+}
+
+/// Formats a quantity with canonical amounts (e.g., "~1 1/2-2 cup").
+pub fn quantity(quantity: &Quantity) -> String {
+    quantity_in(quantity, Language::English)
+}
+
+/// Formats a quantity with canonical amounts, and units named in a language (e.g., "2 EL" in
+/// German).
+pub fn quantity_in(quantity: &Quantity, language: Language) -> String {
+    let approximation = match quantity.approximation {
+        Some(Approximation::ToTaste) => return quantity.to_string(),
+        Some(Approximation::About) => "~",
+        None => "",
+    };
+    let unit = match quantity.unit {
+        Unit::Part(_) if quantity.amount == Amount::ONE && !quantity.is_range() => "part".into(),
+        _ => match quantity.unit.names_in(language) {
+            Some(names) => names.symbol.to_string(),
+            None => quantity.unit.to_string(),
+        },
+    };
+    format!(
+        "{}{}",
+        approximation,
+        with_unit(quantity.amount, quantity.max_amount, &unit)
+    )
+}
+
+/// Formats a quantity with canonical amounts (e.g., "10-15 min").
+pub fn quantity_of<T: for<'a> UnitTrait<'a>>(quantity: &QuantityOf<T>) -> String {
+    with_unit(
+        quantity.amount,
+        quantity.max_amount,
+        &quantity.unit.to_string(),
+    )
+}
+
+fn with_unit(min: Amount, max: Option<Amount>, unit: &str) -> String {
+    let amounts = match max {
+        Some(max) => format!("{}-{}", amount(min), amount(max)),
+        None => amount(min),
+    };
+    match unit {
+        "" => amounts,
+        "%" => format!("{}%", amounts),
+        "gas mark" => format!("gas mark {}", amounts),
+        _ => format!("{} {}", amounts, unit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::unit::{Time, Volume};
+    use std::str::FromStr;
+
+    #[test]
+    fn canonical_amounts() {
+        assert_eq!(amount(Amount::from_int(12)), "12");
+        assert_eq!(amount(Amount::new(3, 2)), "1 1/2");
+        assert_eq!(amount(Amount::new(6, 5)), "1.2");
+        assert_eq!(amount(Amount::new(-1, 8)), "-0.125");
+        assert_eq!(amount(Amount::new(1, 7)), "1/7");
+        for text in ["1 1/2", "1.2", "-0.125", "1/7", "0.0625"] {
+            let parsed = Amount::from_str(text).unwrap();
+            assert_eq!(Amount::from_str(&amount(parsed)).unwrap(), parsed);
+        }
+    }
+
+    #[test]
+    fn canonical_quantities() {
+        let cup = Quantity {
+            unit: Unit::Volume(Volume::Cup),
+            amount: Amount::new(3, 2),
+            max_amount: Some(Amount::from_int(2)),
+            approximation: Some(Approximation::About),
+        };
+        assert_eq!(quantity(&cup), "~1 1/2-2 cup");
+        for text in [
+            "1 part", "2 parts", "75%", "3", "to taste", "1/7 L", "1.2 L",
+        ] {
+            let parsed = Quantity::from_str(text).unwrap();
+            assert_eq!(quantity(&parsed), text);
+        }
+        let time = QuantityOf::<Time>::from_str("10-15 min").unwrap();
+        assert_eq!(quantity_of(&time), "10-15 min");
+    }
+}
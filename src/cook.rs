@@ -0,0 +1,257 @@
+//! Cook mode: walking through a recipe's steps one at a time while keeping track of timers.
+//!
+//! Sessions do not read the clock themselves; callers pass the current [`Instant`] to the methods
+//! dealing with timers.
+
+#[cfg(feature = "events")]
+pub mod events;
+
+use crate::recipe::{
+    amount::Amount,
+    ingredients::IngredientOptions,
+    instructions::{FlatStep, StepPath, TextElem},
+    unit::{QuantityOf, Time},
+    Recipe,
+};
+use std::time::{Duration, Instant};
+
+/// A timer started from a step.
+#[derive(Clone, Debug)]
+pub struct Timer {
+    /// Step the timer comes from.
+    pub path: StepPath,
+    pub time: QuantityOf<Time>,
+    started: Instant,
+}
+
+impl Timer {
+    /// Duration of the timer. For ranges (e.g., "30-40 min"), this is the lower bound.
+    pub fn duration(&self) -> Duration {
+        self.time.to_duration()
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.duration()
+            .saturating_sub(now.saturating_duration_since(self.started))
+    }
+
+    pub fn is_done(&self, now: Instant) -> bool {
+        self.remaining(now).is_zero()
+    }
+}
+
+/// Progress through a recipe's steps, substeps included, in reading order.
+#[derive(Debug)]
+pub struct CookSession<'a> {
+    recipe: &'a Recipe,
+    steps: Vec<FlatStep<'a>>,
+    current: usize,
+    timers: Vec<Timer>,
+}
+
+impl<'a> CookSession<'a> {
+    pub fn new(recipe: &'a Recipe) -> Self {
+        Self {
+            recipe,
+            steps: recipe.instructions().flatten(),
+            current: 0,
+            timers: vec![],
+        }
+    }
+
+    pub fn recipe(&self) -> &'a Recipe {
+        self.recipe
+    }
+
+    /// Number of steps, substeps included.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Index of the current step among all steps.
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    /// The current step, or `None` if the recipe has no steps.
+    pub fn current(&self) -> Option<&FlatStep<'a>> {
+        self.steps.get(self.current)
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.current + 1 >= self.steps.len()
+    }
+
+    /// Moves to the next step. Returns `false` (and stays put) on the last step.
+    pub fn next_step(&mut self) -> bool {
+        self.jump(self.current + 1)
+    }
+
+    /// Moves to the previous step. Returns `false` (and stays put) on the first step.
+    pub fn prev_step(&mut self) -> bool {
+        self.current > 0 && self.jump(self.current - 1)
+    }
+
+    /// Moves to the step at the given index among all steps. Returns `false` (and stays put) if
+    /// there is no such step.
+    pub fn jump(&mut self, index: usize) -> bool {
+        let exists = index < self.steps.len();
+        if exists {
+            self.current = index;
+        }
+        exists
+    }
+
+    /// Moves to the step at the given path (e.g., `[2, 0]` for step "3.1").
+    pub fn jump_to(&mut self, path: &StepPath) -> bool {
+        match self.steps.iter().position(|s| s.path == *path) {
+            Some(index) => self.jump(index),
+            None => false,
+        }
+    }
+
+    /// Ingredients referenced in the current step, in order of appearance. Unresolved references
+    /// are skipped.
+    pub fn current_ingredients(&self) -> Vec<&'a IngredientOptions> {
+        let mut ingredients: Vec<&IngredientOptions> = vec![];
+        for elem in self.current_description() {
+            if let TextElem::IngredientRef(r) = elem {
+                let ingredient = r.index().and_then(|i| self.recipe.ingredients().get(i));
+                if let Some(ingredient) = ingredient {
+                    if !ingredients.iter().any(|i| std::ptr::eq(*i, ingredient)) {
+                        ingredients.push(ingredient);
+                    }
+                }
+            }
+        }
+        ingredients
+    }
+
+    /// Timers mentioned in the current step.
+    pub fn current_timers(&self) -> Vec<QuantityOf<Time>> {
+        self.current_description()
+            .into_iter()
+            .filter_map(|elem| match elem {
+                TextElem::Timer(time) => Some(*time),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Starts the timers of the current step.
+    pub fn start_timers(&mut self, now: Instant) {
+        let Some(step) = self.current() else {
+            return;
+        };
+        let path = step.path.clone();
+        for time in self.current_timers() {
+            self.timers.push(Timer {
+                path: path.clone(),
+                time,
+                started: now,
+            });
+        }
+    }
+
+    /// Timers started so far, including finished ones, in start order.
+    pub fn timers(&self) -> &[Timer] {
+        &self.timers
+    }
+
+    /// Timers still running.
+    pub fn active_timers(&self, now: Instant) -> impl Iterator<Item = &Timer> {
+        self.timers.iter().filter(move |t| !t.is_done(now))
+    }
+
+    /// Removes finished timers, returning them.
+    pub fn dismiss_finished(&mut self, now: Instant) -> Vec<Timer> {
+        let (done, running) = self.timers.drain(..).partition(|t| t.is_done(now));
+        self.timers = running;
+        done
+    }
+
+    fn current_description(&self) -> Vec<&'a TextElem> {
+        self.steps
+            .get(self.current)
+            .map_or(vec![], |s| s.step.resolved(Amount::ONE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn recipe() -> Recipe {
+        Recipe::from_mdast(indoc! {"
+            # Pasta
+            ## Ingredients
+
+            - Water, 2 L
+            - Salt, to taste
+            - Pasta, 200 g
+
+            ## Instructions
+
+            - Boil the *water* with the *salt*
+                - Add the *pasta* and cook for **10 min**
+            - Drain
+        "})
+        .unwrap()
+    }
+
+    #[test]
+    fn navigate() {
+        let recipe = recipe();
+        let mut session = CookSession::new(&recipe);
+        assert_eq!(session.len(), 3);
+        assert!(!session.prev_step());
+
+        let names = |session: &CookSession| -> Vec<String> {
+            session
+                .current_ingredients()
+                .iter()
+                .map(|i| i.ingredient().name().to_string())
+                .collect()
+        };
+        assert_eq!(names(&session), vec!["Water", "Salt"]);
+        assert!(session.next_step());
+        assert_eq!(session.current().unwrap().path.to_string(), "1.1");
+        assert_eq!(names(&session), vec!["Pasta"]);
+        assert!(session.next_step());
+        assert!(session.is_last());
+        assert!(!session.next_step());
+
+        assert!(session.jump_to(&StepPath(vec![0, 0])));
+        assert_eq!(session.position(), 1);
+        assert!(!session.jump(3));
+        assert!(session.prev_step());
+        assert_eq!(session.position(), 0);
+    }
+
+    #[test]
+    fn timers() {
+        let recipe = recipe();
+        let mut session = CookSession::new(&recipe);
+        let start = Instant::now();
+        session.start_timers(start);
+        assert!(session.timers().is_empty());
+
+        session.next_step();
+        session.start_timers(start);
+        let timer = &session.timers()[0];
+        assert_eq!(timer.path.to_string(), "1.1");
+        assert_eq!(timer.remaining(start), Duration::from_secs(600));
+
+        let later = start + Duration::from_secs(300);
+        assert_eq!(session.active_timers(later).count(), 1);
+        let done = start + Duration::from_secs(600);
+        assert_eq!(session.active_timers(done).count(), 0);
+        assert_eq!(session.dismiss_finished(done).len(), 1);
+        assert!(session.timers().is_empty());
+    }
+}
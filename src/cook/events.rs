@@ -0,0 +1,544 @@
+//! Events from cook sessions, for home automation (e.g., flashing the kitchen lights when the oven
+//! timer is up).
+//!
+//! An [`EmittingSession`] wraps a [`CookSession`] and sends an event to an [`EventSink`] on each
+//! step change, and when timers start and finish. Sinks are closures, or one of the adapters
+//! publishing events as JSON: [`Mqtt`] to an MQTT broker, and [`Webhook`] to an HTTP endpoint.
+//! Both speak the bare minimum of their protocol over plain TCP (MQTT 3.1.1 at QoS 0, and
+//! HTTP/1.1 without TLS), which is what home automation hubs on the local network expect.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    ops::Deref,
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+
+use super::{CookSession, Timer};
+use crate::recipe::{instructions::StepPath, Recipe};
+
+#[derive(Clone, Debug)]
+pub enum SessionEvent {
+    /// The session moved from one step to another.
+    StepChanged {
+        from: StepPath,
+        to: StepPath,
+    },
+    TimerStarted(Timer),
+    TimerFinished(Timer),
+}
+
+impl SessionEvent {
+    /// Name of the event, in snake case (e.g., "timer_finished").
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::StepChanged { .. } => "step_changed",
+            Self::TimerStarted(_) => "timer_started",
+            Self::TimerFinished(_) => "timer_finished",
+        }
+    }
+
+    /// The event as a JSON object, as sent by the adapters:
+    ///
+    /// ```json
+    /// { "event": "timer_finished", "recipe": "Pasta", "step": "1.1", "time": "10 min", "secs": 600 }
+    /// ```
+    pub fn to_json(&self, recipe: &Recipe) -> Value {
+        let mut json = json!({ "event": self.name(), "recipe": recipe.name() });
+        match self {
+            Self::StepChanged { from, to } => {
+                json["from"] = from.to_string().into();
+                json["step"] = to.to_string().into();
+            }
+            Self::TimerStarted(timer) | Self::TimerFinished(timer) => {
+                json["step"] = timer.path.to_string().into();
+                json["time"] = timer.time.to_string().into();
+                json["secs"] = timer.duration().as_secs().into();
+            }
+        }
+        json
+    }
+}
+
+/// Receiver of session events.
+pub trait EventSink {
+    fn emit(&mut self, recipe: &Recipe, event: &SessionEvent) -> io::Result<()>;
+}
+
+impl<F: FnMut(&Recipe, &SessionEvent) -> io::Result<()>> EventSink for F {
+    fn emit(&mut self, recipe: &Recipe, event: &SessionEvent) -> io::Result<()> {
+        self(recipe, event)
+    }
+}
+
+/// A cook session sending its events to a sink. Sessions are read through [`Deref`], and moved
+/// through the methods below, which mirror those of [`CookSession`].
+///
+/// Errors of the sink do not interrupt cooking: they are kept until taken with
+/// [`EmittingSession::take_errors`].
+pub struct EmittingSession<'a, S: EventSink> {
+    session: CookSession<'a>,
+    sink: S,
+    /// Finished timers already announced, as the step and start of the timer.
+    announced: Vec<(StepPath, Instant)>,
+    errors: Vec<io::Error>,
+}
+
+impl<'a, S: EventSink> EmittingSession<'a, S> {
+    pub fn new(session: CookSession<'a>, sink: S) -> Self {
+        Self {
+            session,
+            sink,
+            announced: vec![],
+            errors: vec![],
+        }
+    }
+
+    pub fn next_step(&mut self) -> bool {
+        self.step_change(|session| session.next_step())
+    }
+
+    pub fn prev_step(&mut self) -> bool {
+        self.step_change(|session| session.prev_step())
+    }
+
+    pub fn jump(&mut self, index: usize) -> bool {
+        self.step_change(|session| session.jump(index))
+    }
+
+    pub fn jump_to(&mut self, path: &StepPath) -> bool {
+        self.step_change(|session| session.jump_to(path))
+    }
+
+    pub fn start_timers(&mut self, now: Instant) {
+        let started = self.session.timers().len();
+        self.session.start_timers(now);
+        let timers = self.session.timers()[started..].to_vec();
+        for timer in timers {
+            self.emit(SessionEvent::TimerStarted(timer));
+        }
+    }
+
+    /// Announces the timers that finished since the last call. Call it regularly (e.g., every
+    /// second) for timely events.
+    pub fn poll(&mut self, now: Instant) {
+        let finished: Vec<Timer> = self
+            .session
+            .timers()
+            .iter()
+            .filter(|t| t.is_done(now))
+            .filter(|t| !self.announced.contains(&(t.path.clone(), t.started)))
+            .cloned()
+            .collect();
+        for timer in finished {
+            self.announced.push((timer.path.clone(), timer.started));
+            self.emit(SessionEvent::TimerFinished(timer));
+        }
+    }
+
+    /// Removes finished timers, returning them, after announcing those not announced yet.
+    pub fn dismiss_finished(&mut self, now: Instant) -> Vec<Timer> {
+        self.poll(now);
+        let done = self.session.dismiss_finished(now);
+        self.announced.retain(|(path, started)| {
+            !done
+                .iter()
+                .any(|t| t.path == *path && t.started == *started)
+        });
+        done
+    }
+
+    /// Errors of the sink so far, oldest first.
+    pub fn take_errors(&mut self) -> Vec<io::Error> {
+        std::mem::take(&mut self.errors)
+    }
+
+    pub fn into_inner(self) -> (CookSession<'a>, S) {
+        (self.session, self.sink)
+    }
+
+    fn step_change(&mut self, change: impl FnOnce(&mut CookSession<'a>) -> bool) -> bool {
+        let from = self.session.current().map(|s| s.path.clone());
+        let moved = change(&mut self.session);
+        let to = self.session.current().map(|s| s.path.clone());
+        if let (Some(from), Some(to)) = (from, to) {
+            if from != to {
+                self.emit(SessionEvent::StepChanged { from, to });
+            }
+        }
+        moved
+    }
+
+    fn emit(&mut self, event: SessionEvent) {
+        if let Err(e) = self.sink.emit(self.session.recipe(), &event) {
+            self.errors.push(e);
+        }
+    }
+}
+
+impl<'a, S: EventSink> Deref for EmittingSession<'a, S> {
+    type Target = CookSession<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.session
+    }
+}
+
+/// How long the adapters wait to connect, send, or receive before failing by default, so that an
+/// unreachable hub does not block the session.
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Connects to the first address which accepts, with `timeout` for connecting, reading, and
+/// writing.
+fn connect(addr: impl ToSocketAddrs, timeout: Duration) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    for addr in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error
+        .unwrap_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to")))
+}
+
+/// Publishes events to an MQTT broker, as JSON on `<prefix>/<event name>` (e.g.,
+/// "kitchen/timer_finished").
+#[derive(Debug)]
+pub struct Mqtt {
+    stream: TcpStream,
+    prefix: String,
+}
+
+impl Mqtt {
+    /// Seconds the broker waits for a message before dropping the connection, 0 for never, as
+    /// events may be hours apart.
+    const KEEP_ALIVE: u16 = 0;
+
+    /// Connects to a broker (e.g., "192.168.1.2:1883") as `client_id`.
+    pub fn connect(addr: impl ToSocketAddrs, client_id: &str, prefix: &str) -> io::Result<Self> {
+        let mut stream = connect(addr, NETWORK_TIMEOUT)?;
+        let mut body = mqtt_string("MQTT");
+        // Protocol level 4 (3.1.1), clean session.
+        body.extend([4, 0x02]);
+        body.extend(Self::KEEP_ALIVE.to_be_bytes());
+        body.extend(mqtt_string(client_id));
+        stream.write_all(&mqtt_packet(0x10, &body))?;
+        let mut connack = [0; 4];
+        stream.read_exact(&mut connack)?;
+        if connack[0] != 0x20 || connack[3] != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("MQTT connection refused with code {}", connack[3]),
+            ));
+        }
+        Ok(Self {
+            stream,
+            prefix: prefix.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Publishes a message, at most once (QoS 0).
+    pub fn publish(&mut self, topic: &str, payload: &[u8]) -> io::Result<()> {
+        let mut body = mqtt_string(topic);
+        body.extend(payload);
+        self.stream.write_all(&mqtt_packet(0x30, &body))
+    }
+}
+
+impl EventSink for Mqtt {
+    fn emit(&mut self, recipe: &Recipe, event: &SessionEvent) -> io::Result<()> {
+        let topic = format!("{}/{}", self.prefix, event.name());
+        self.publish(&topic, event.to_json(recipe).to_string().as_bytes())
+    }
+}
+
+/// A packet with its fixed header: the packet type and flags, and the remaining length.
+fn mqtt_packet(kind: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![kind];
+    let mut len = body.len();
+    loop {
+        let byte = (len % 128) as u8;
+        len /= 128;
+        match len {
+            0 => {
+                packet.push(byte);
+                break;
+            }
+            _ => packet.push(byte | 0x80),
+        }
+    }
+    packet.extend(body);
+    packet
+}
+
+/// A length-prefixed UTF-8 string.
+fn mqtt_string(s: &str) -> Vec<u8> {
+    let mut bytes = (s.len() as u16).to_be_bytes().to_vec();
+    bytes.extend(s.as_bytes());
+    bytes
+}
+
+/// Posts events as JSON to an HTTP endpoint (e.g., "http://homeassistant.local:8123/api/webhook/oven").
+#[derive(Clone, Debug)]
+pub struct Webhook {
+    host: String,
+    port: u16,
+    path: String,
+    timeout: Duration,
+}
+
+impl Webhook {
+    /// Parses the URL of the endpoint, which must use plain HTTP.
+    pub fn new(url: &str) -> io::Result<Self> {
+        let invalid = |msg: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid webhook URL {:?}: {}", url, msg),
+            )
+        };
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or(invalid("expected http:// URL"))?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| invalid("invalid port"))?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(invalid("missing host"));
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+            timeout: NETWORK_TIMEOUT,
+        })
+    }
+
+    /// Sets how long to wait to connect, send, or receive before failing (5 s by default).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Posts a JSON body, failing unless the endpoint answers with a success status.
+    pub fn post(&self, body: &Value) -> io::Result<()> {
+        let body = body.to_string();
+        let mut stream = connect((self.host.as_str(), self.port), self.timeout)?;
+        // The port is part of the host unless it is the default one.
+        let host = match self.port {
+            80 => self.host.clone(),
+            port => format!("{}:{}", self.host, port),
+        };
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.path,
+            host,
+            body.len(),
+            body
+        )?;
+        let mut status = String::new();
+        BufReader::new(stream).read_line(&mut status)?;
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => Ok(()),
+            _ => Err(io::Error::other(format!(
+                "webhook failed: {:?}",
+                status.trim_end()
+            ))),
+        }
+    }
+}
+
+impl EventSink for Webhook {
+    fn emit(&mut self, recipe: &Recipe, event: &SessionEvent) -> io::Result<()> {
+        self.post(&event.to_json(recipe))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use std::{net::TcpListener, thread, time::Duration};
+
+    fn recipe() -> Recipe {
+        Recipe::from_mdast(indoc! {"
+            # Pasta
+            ## Ingredients
+
+            - Water, 2 L
+            - Pasta, 200 g
+
+            ## Instructions
+
+            - Boil the *water*
+                - Add the *pasta* and cook for **10 min**
+            - Drain
+        "})
+        .unwrap()
+    }
+
+    #[test]
+    fn emit_events() {
+        let recipe = recipe();
+        let mut events = vec![];
+        let sink = |recipe: &Recipe, event: &SessionEvent| {
+            events.push(event.to_json(recipe));
+            match event {
+                SessionEvent::TimerFinished(_) => Err(io::Error::other("lights are off")),
+                _ => Ok(()),
+            }
+        };
+        let mut session = EmittingSession::new(CookSession::new(&recipe), sink);
+        let start = Instant::now();
+        assert!(!session.prev_step());
+        assert!(session.next_step());
+        session.start_timers(start);
+        session.poll(start + Duration::from_secs(300));
+        session.poll(start + Duration::from_secs(600));
+        session.poll(start + Duration::from_secs(700));
+        assert_eq!(
+            session
+                .dismiss_finished(start + Duration::from_secs(700))
+                .len(),
+            1
+        );
+        assert!(session.jump(2));
+        assert_eq!(session.position(), 2);
+        assert_eq!(session.take_errors().len(), 1);
+        assert!(session.take_errors().is_empty());
+        drop(session);
+
+        assert_eq!(
+            events,
+            [
+                json!({ "event": "step_changed", "recipe": "Pasta", "from": "1", "step": "1.1" }),
+                json!({
+                    "event": "timer_started",
+                    "recipe": "Pasta",
+                    "step": "1.1",
+                    "time": "10 min",
+                    "secs": 600,
+                }),
+                json!({
+                    "event": "timer_finished",
+                    "recipe": "Pasta",
+                    "step": "1.1",
+                    "time": "10 min",
+                    "secs": 600,
+                }),
+                json!({ "event": "step_changed", "recipe": "Pasta", "from": "1.1", "step": "2" }),
+            ]
+        );
+    }
+
+    #[test]
+    fn mqtt() -> io::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        // Packets of the test are shorter than 128 bytes, so their length takes a single byte.
+        fn read_packet(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+            let mut header = [0; 2];
+            stream.read_exact(&mut header)?;
+            let mut body = vec![0; header[1] as usize];
+            stream.read_exact(&mut body)?;
+            Ok([header.to_vec(), body].concat())
+        }
+        let broker = thread::spawn(move || -> io::Result<(Vec<u8>, Vec<u8>)> {
+            let (mut stream, _) = listener.accept()?;
+            let connect = read_packet(&mut stream)?;
+            stream.write_all(&[0x20, 0x02, 0x00, 0x00])?;
+            Ok((connect, read_packet(&mut stream)?))
+        });
+
+        let recipe = recipe();
+        let mut mqtt = Mqtt::connect(addr, "oven", "kitchen/")?;
+        let timer = Timer {
+            path: StepPath(vec![0, 0]),
+            time: "10 min".parse().unwrap(),
+            started: Instant::now(),
+        };
+        mqtt.emit(&recipe, &SessionEvent::TimerFinished(timer))?;
+
+        let (connect, publish) = broker.join().unwrap()?;
+        assert_eq!(connect[0], 0x10);
+        assert_eq!(&connect[2..8], b"\x00\x04MQTT");
+        assert!(connect.ends_with(b"\x00\x04oven"));
+        assert_eq!(publish[0], 0x30);
+        let topic = b"\x00\x16kitchen/timer_finished";
+        assert_eq!(&publish[2..2 + topic.len()], topic);
+        let payload: Value = serde_json::from_slice(&publish[2 + topic.len()..]).unwrap();
+        assert_eq!(payload["secs"], 600);
+        Ok(())
+    }
+
+    #[test]
+    fn webhook() -> io::Result<()> {
+        assert!(Webhook::new("https://example.org/hook").is_err());
+        assert!(Webhook::new("http://example.org:port/hook").is_err());
+
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let url = format!("http://{}/api/webhook/oven", addr);
+        let server = thread::spawn(move || -> io::Result<String> {
+            let (mut stream, _) = listener.accept()?;
+            let mut request = String::new();
+            let mut reader = BufReader::new(stream.try_clone()?);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line)?;
+                if let Some(value) = line.strip_prefix("Content-Length: ") {
+                    length = value.trim().parse().unwrap();
+                }
+                request.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body)?;
+            request.push_str(&String::from_utf8(body).unwrap());
+            stream.write_all(b"HTTP/1.1 204 No Content\r\n\r\n")?;
+            Ok(request)
+        });
+
+        let recipe = recipe();
+        let mut webhook = Webhook::new(&url)?;
+        let event = SessionEvent::StepChanged {
+            from: StepPath(vec![0]),
+            to: StepPath(vec![1]),
+        };
+        webhook.emit(&recipe, &event)?;
+        let request = server.join().unwrap()?;
+        assert!(request.starts_with("POST /api/webhook/oven HTTP/1.1\r\n"));
+        assert!(request.contains(&format!("\r\nHost: {}\r\n", addr)));
+        assert!(
+            request.ends_with(r#""event":"step_changed","from":"1","recipe":"Pasta","step":"2"}"#)
+        );
+
+        // An endpoint which accepts but never answers fails after the timeout.
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let webhook = Webhook::new(&format!("http://{}/", listener.local_addr()?))?
+            .timeout(Duration::from_millis(100));
+        let error = webhook.post(&json!({})).unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+        ));
+        drop(listener);
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+//! Synthetic recipes for benchmarks, fuzzing, and collection-scale tests.
+//!
+//! Recipes are generated from a seed, so that the same corpus can be rebuilt anywhere without
+//! shipping real recipes. They vary in size (from a handful of ingredients to a few dozen), and mix
+//! ingredient groups, nested steps, ingredient references, timers, alternatives, and the common
+//! units, so that they exercise the parser like a real collection would.
+
+use std::{fmt::Write, path::PathBuf};
+
+use crate::{
+    library::RecipeLibrary,
+    random::{Rng, SeededRng},
+    recipe::Recipe,
+};
+
+const DISHES: &[&str] = &[
+    "Soup",
+    "Stew",
+    "Curry",
+    "Salad",
+    "Pie",
+    "Tart",
+    "Risotto",
+    "Gratin",
+    "Cake",
+    "Bread",
+    "Pasta",
+    "Noodles",
+    "Tacos",
+    "Pancakes",
+    "Chili",
+    "Casserole",
+];
+
+const ADJECTIVES: &[&str] = &[
+    "Spicy", "Creamy", "Rustic", "Smoky", "Quick", "Sunday", "Summer", "Winter", "Lemony",
+    "Garlicky", "Crispy", "Classic",
+];
+
+const TAGS: &[&str] = &[
+    "vegetarian",
+    "vegan",
+    "dessert",
+    "weeknight",
+    "batch",
+    "spicy",
+    "baking",
+    "soup",
+];
+
+/// Ingredients, with the units they are usually measured in ("" for counts).
+const INGREDIENTS: &[(&str, &[&str])] = &[
+    ("Flour", &["g", "kg", "cup"]),
+    ("Sugar", &["g", "tbsp", "cup"]),
+    ("Butter", &["g", "tbsp"]),
+    ("Milk", &["mL", "L", "cup"]),
+    ("Eggs", &[""]),
+    ("Onions", &[""]),
+    ("Garlic cloves", &[""]),
+    ("Tomatoes", &["g", ""]),
+    ("Olive oil", &["mL", "tbsp"]),
+    ("Salt", &["tsp", "g"]),
+    ("Black pepper", &["tsp"]),
+    ("Rice", &["g", "cup"]),
+    ("Chicken thighs", &["g", "kg", ""]),
+    ("Chickpeas", &["g"]),
+    ("Coconut milk", &["mL"]),
+    ("Vegetable stock", &["mL", "L"]),
+    ("Carrots", &["", "g"]),
+    ("Potatoes", &["kg", "g"]),
+    ("Cumin", &["tsp"]),
+    ("Paprika", &["tsp", "tbsp"]),
+    ("Lemon juice", &["mL", "tbsp"]),
+    ("Parmesan", &["g"]),
+    ("Spinach", &["g"]),
+    ("Yeast", &["g", "tsp"]),
+    ("Water", &["mL", "L"]),
+    ("Cream", &["mL"]),
+    ("Honey", &["tbsp"]),
+    ("Ginger", &["g", "tbsp"]),
+];
+
+const GROUPS: &[&str] = &[
+    "Base", "Sauce", "Filling", "Topping", "Dough", "Garnish", "Dressing",
+];
+
+const VERBS: &[&str] = &[
+    "Chop",
+    "Mix",
+    "Whisk",
+    "Fold in",
+    "Add",
+    "Stir in",
+    "Season with",
+    "Sprinkle",
+    "Combine",
+];
+
+const ACTIONS: &[&str] = &[
+    "Simmer", "Bake", "Rest", "Fry", "Roast", "Boil", "Chill", "Knead",
+];
+
+const MANNERS: &[&str] = &[
+    "until golden",
+    "until smooth",
+    "gently",
+    "over medium heat",
+    "in a large bowl",
+    "without stirring",
+];
+
+/// Generates `n` recipes as Markdown, the same for a given seed.
+pub fn generate(n: usize, seed: u64) -> Vec<String> {
+    let mut rng = SeededRng::new(seed);
+    (0..n).map(|i| recipe(&mut rng, i)).collect()
+}
+
+/// Parses [`generate`]'s recipes.
+pub fn recipes(n: usize, seed: u64) -> Vec<Recipe> {
+    generate(n, seed)
+        .iter()
+        .map(|md| {
+            Recipe::from_mdast(md)
+                .unwrap_or_else(|e| panic!("invalid generated recipe: {}\n{}", e, md))
+        })
+        .collect()
+}
+
+/// A library of [`generate`]'s recipes, at made-up paths (e.g., "corpus/0042.md").
+pub fn library(n: usize, seed: u64) -> RecipeLibrary {
+    let mut library = RecipeLibrary::default();
+    for (i, recipe) in recipes(n, seed).into_iter().enumerate() {
+        library.insert(PathBuf::from(format!("corpus/{:04}.md", i)), recipe);
+    }
+    library
+}
+
+fn pick<'a>(rng: &mut impl Rng, items: &[&'a str]) -> &'a str {
+    items[rng.below(items.len())]
+}
+
+/// Random number between `min` and `max`, inclusive.
+fn between(rng: &mut impl Rng, min: usize, max: usize) -> usize {
+    min + rng.below(max - min + 1)
+}
+
+fn recipe(rng: &mut impl Rng, index: usize) -> String {
+    let mut md = String::new();
+    md.push_str("---\n");
+    let tags = between(rng, 0, 3);
+    if tags > 0 {
+        md.push_str("tags:\n");
+        let mut all = TAGS.to_vec();
+        rng.shuffle(&mut all);
+        for tag in &all[..tags] {
+            let _ = writeln!(md, "  - \"#{}\"", tag);
+        }
+    }
+    let _ = writeln!(md, "servings: {}", between(rng, 1, 8));
+    let _ = writeln!(md, "prep_time: {} min", 5 * between(rng, 1, 12));
+    if rng.below(2) == 0 {
+        let _ = writeln!(md, "cook_time: {} min", 5 * between(rng, 2, 36));
+    }
+    md.push_str("---\n");
+    let _ = writeln!(
+        md,
+        "# {} {} {}\n## Ingredients\n",
+        pick(rng, ADJECTIVES),
+        pick(rng, DISHES),
+        index + 1
+    );
+
+    // Small recipes are the most common, with a long tail of large ones.
+    let size = match rng.below(10) {
+        0 => between(rng, 15, 30),
+        1..=3 => between(rng, 8, 14),
+        _ => between(rng, 3, 7),
+    };
+    let mut names: Vec<&str> = INGREDIENTS.iter().map(|(name, _)| *name).collect();
+    rng.shuffle(&mut names);
+    let names = &names[..size.min(names.len())];
+    let lines: Vec<String> = names.iter().map(|name| ingredient(rng, name)).collect();
+    if lines.len() >= 6 && rng.below(3) == 0 {
+        let mut groups = GROUPS.to_vec();
+        rng.shuffle(&mut groups);
+        let count = between(rng, 2, 3);
+        for (i, chunk) in lines.chunks(lines.len().div_ceil(count)).enumerate() {
+            if i > 0 {
+                md.push('\n');
+            }
+            let _ = writeln!(md, "### {}", groups[i]);
+            for line in chunk {
+                let _ = writeln!(md, "- {}", line);
+            }
+        }
+    } else {
+        for line in &lines {
+            let _ = writeln!(md, "- {}", line);
+        }
+    }
+
+    md.push_str("\n## Instructions\n\n");
+    let steps = between(rng, 2, 4 + size / 2);
+    for _ in 0..steps {
+        steps_at(rng, &mut md, names, 0);
+    }
+    md
+}
+
+fn ingredient(rng: &mut impl Rng, name: &str) -> String {
+    let units = INGREDIENTS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map_or(&[""][..], |(_, units)| units);
+    let unit = pick(rng, units);
+    if (name == "Salt" || name == "Black pepper") && rng.below(3) == 0 {
+        return format!("{}, to taste", name);
+    }
+    let amount = match unit {
+        "" => between(rng, 1, 6).to_string(),
+        "g" | "mL" => (25 * between(rng, 1, 20)).to_string(),
+        "kg" | "L" => ["0.5", "1", "1.5", "2"][rng.below(4)].to_string(),
+        _ => ["1/4", "1/2", "1", "1 1/2", "2", "3"][rng.below(6)].to_string(),
+    };
+    let mut line = match (unit, rng.below(8)) {
+        ("", _) => format!("{}, {}", name, amount),
+        (_, 0) => format!("{}, ~{} {}", name, amount, unit),
+        _ => format!("{}, {} {}", name, amount, unit),
+    };
+    match rng.below(10) {
+        0 => line.push_str(" (finely chopped)"),
+        1 => line.push_str(" (at room temperature)"),
+        2 if name == "Butter" => line.push_str(" | Olive oil, 2 tbsp"),
+        2 if name == "Milk" => line.push_str(&format!(" | Oat drink, {} {}", amount, unit)),
+        _ => {}
+    }
+    line
+}
+
+/// Writes a step at some depth, with substeps below it.
+fn steps_at(rng: &mut impl Rng, md: &mut String, names: &[&str], depth: usize) {
+    let _ = writeln!(md, "{}- {}", "    ".repeat(depth), step(rng, names));
+    // Nesting gets rarer with depth, and stops at three levels.
+    let substeps = match (depth, rng.below(6)) {
+        (0, 0) => between(rng, 1, 3),
+        (1, 0) => 1,
+        _ => 0,
+    };
+    for _ in 0..substeps {
+        steps_at(rng, md, names, depth + 1);
+    }
+}
+
+fn step(rng: &mut impl Rng, names: &[&str]) -> String {
+    let name = names[rng.below(names.len())].to_lowercase();
+    match rng.below(4) {
+        0 => format!(
+            "{} for **{} min** {}",
+            pick(rng, ACTIONS),
+            5 * between(rng, 1, 12),
+            pick(rng, MANNERS)
+        ),
+        1 => {
+            let other = names[rng.below(names.len())].to_lowercase();
+            if other == name {
+                format!("{} the *{}*", pick(rng, VERBS), name)
+            } else {
+                format!("{} the *{}* and the *{}*", pick(rng, VERBS), name, other)
+            }
+        }
+        2 => format!(
+            "{} the *{}* {}, then {} for **{}-{} min**",
+            pick(rng, VERBS),
+            name,
+            pick(rng, MANNERS),
+            pick(rng, ACTIONS).to_lowercase(),
+            between(rng, 2, 5),
+            between(rng, 6, 10)
+        ),
+        _ => format!("{} the *{}* {}", pick(rng, VERBS), name, pick(rng, MANNERS)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        assert_eq!(generate(20, 1), generate(20, 1));
+        assert_ne!(generate(20, 1), generate(20, 2));
+        assert_eq!(generate(0, 1), Vec::<String>::new());
+    }
+
+    #[test]
+    fn recipes_parse_cleanly() {
+        for md in generate(200, 7) {
+            let (recipe, diagnostics) = Recipe::from_mdast_with_diagnostics(&md, true)
+                .unwrap_or_else(|e| panic!("{}\n{}", e, md));
+            assert!(diagnostics.is_empty(), "{:?}\n{}", diagnostics, md);
+            assert!(recipe.is_complete(), "{}", md);
+            crate::test_utils::assert_round_trip(&recipe);
+        }
+    }
+
+    #[test]
+    fn variety() {
+        let recipes = recipes(200, 3);
+        let grouped = recipes
+            .iter()
+            .filter(|r| r.ingredients().iter().count() >= 6)
+            .filter(|r| {
+                matches!(
+                    r.ingredients(),
+                    crate::recipe::ingredients::Ingredients::IngredientGroups(_)
+                )
+            })
+            .count();
+        assert!(grouped > 0);
+        let nested = recipes
+            .iter()
+            .flat_map(|r| r.instructions().flatten())
+            .any(|s| s.depth() == 2);
+        assert!(nested);
+        let largest = recipes.iter().map(|r| r.ingredients().iter().count()).max();
+        assert!(largest > Some(14));
+
+        let library = library(30, 3);
+        assert_eq!(library.len(), 30);
+        assert!(library.failures().is_empty());
+    }
+}
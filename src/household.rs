@@ -0,0 +1,329 @@
+//! The people a household cooks for, so that recipes can be scaled to their appetite, adapted to
+//! their dietary restrictions, and their nutrition facts split between them.
+//!
+//! Households are read from JSON, with portions as numbers or fractions:
+//!
+//! ```json
+//! {
+//!   "members": [
+//!     { "name": "Alex", "portion": 1, "restrictions": ["gluten"] },
+//!     { "name": "Sam", "portion": 1 },
+//!     { "name": "Robin", "portion": "1/2", "restrictions": ["milk", "peanuts"] }
+//!   ]
+//! }
+//! ```
+
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::{
+    library::bundle::{Bundle, ALLERGENS},
+    recipe::{amount::Amount, metadata::Nutrition, MDError, MDResult, Recipe},
+};
+
+/// Someone in a household.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Member {
+    pub name: String,
+    /// How much the member eats, in servings (e.g., 1/2 for a child).
+    pub portion: Amount,
+    /// Ingredients (e.g., "mushroom") or allergens (e.g., "gluten") the member does not eat.
+    pub restrictions: Vec<String>,
+}
+
+impl Member {
+    pub fn new(name: &str, portion: Amount) -> Self {
+        Self {
+            name: name.to_string(),
+            portion,
+            restrictions: vec![],
+        }
+    }
+
+    /// Adds a dietary restriction.
+    pub fn restriction(mut self, restriction: &str) -> Self {
+        self.restrictions.push(restriction.to_string());
+        self
+    }
+
+    /// Whether the member cannot eat an ingredient, by name. Names containing a restricted
+    /// ingredient are restricted (e.g., "Button mushrooms" for "mushroom"), as are names
+    /// containing an ingredient that the allergens table of `allergens` maps to a restricted
+    /// allergen (e.g., "Whole milk" for "milk"). Matching is deliberately broad: it is safer to
+    /// flag "Peanut butter" for "milk" than to miss an allergen.
+    pub fn avoids(&self, ingredient: &str, allergens: &Bundle) -> bool {
+        let ingredient = ingredient.to_lowercase();
+        let table = allergens.table(ALLERGENS);
+        self.restrictions.iter().any(|restriction| {
+            let restriction = restriction.to_lowercase();
+            ingredient.contains(&restriction)
+                || table.into_iter().flatten().any(|(name, allergen)| {
+                    allergen.to_lowercase() == restriction && ingredient.contains(name.as_str())
+                })
+        })
+    }
+}
+
+/// An ingredient line of a recipe that has no alternative everyone in the household can eat.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    /// Index of the line in [`Ingredients::iter`](crate::recipe::ingredients::Ingredients::iter).
+    pub index: usize,
+    /// Name of the main ingredient of the line.
+    pub ingredient: String,
+    /// Members who cannot eat the ingredient.
+    pub members: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Household {
+    members: Vec<Member>,
+}
+
+impl Household {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a member.
+    pub fn member(mut self, member: Member) -> Self {
+        self.members.push(member);
+        self
+    }
+
+    pub fn members(&self) -> &[Member] {
+        &self.members
+    }
+
+    /// Servings eaten by the household at a meal (e.g., 2 1/2 for two adults and a child).
+    pub fn portions(&self) -> Amount {
+        self.members.iter().map(|m| m.portion).sum()
+    }
+
+    /// Factor to scale a recipe by to feed the household, or `None` if the recipe has no
+    /// servings.
+    pub fn scale_factor(&self, recipe: &Recipe) -> Option<Amount> {
+        let servings = recipe.metadata().servings()?.amount;
+        (!servings.is_zero()).then(|| self.portions() / servings)
+    }
+
+    /// Returns the recipe scaled to feed the household, or `None` if the recipe has no servings.
+    pub fn scaled(&self, recipe: &Recipe) -> Option<Recipe> {
+        Some(recipe.scaled(self.scale_factor(recipe)?))
+    }
+
+    /// Returns the recipe with each ingredient someone cannot eat replaced by its first
+    /// alternative that everyone can eat, along with the lines for which there is no such
+    /// alternative. Restrictions are checked with [`Member::avoids`].
+    pub fn substituted(&self, recipe: &Recipe, allergens: &Bundle) -> (Recipe, Vec<Conflict>) {
+        let avoiding = |name: &str| -> Vec<String> {
+            self.members
+                .iter()
+                .filter(|m| m.avoids(name, allergens))
+                .map(|m| m.name.clone())
+                .collect()
+        };
+        let mut conflicts = vec![];
+        for (index, options) in recipe.ingredients().iter().enumerate() {
+            let members = avoiding(options.ingredient().name());
+            let allowed = options
+                .alternatives()
+                .iter()
+                .any(|a| avoiding(a.name()).is_empty());
+            if !members.is_empty() && !allowed {
+                conflicts.push(Conflict {
+                    index,
+                    ingredient: options.ingredient().name().to_string(),
+                    members,
+                });
+            }
+        }
+        let recipe = recipe.with_alternatives(|options| {
+            if avoiding(options.ingredient().name()).is_empty() {
+                return None;
+            }
+            options
+                .alternatives()
+                .iter()
+                .position(|a| avoiding(a.name()).is_empty())
+        });
+        (recipe, conflicts)
+    }
+
+    /// Nutrition facts of each member's portion of a recipe, or `None` if the recipe has no
+    /// nutrition facts per serving.
+    pub fn nutrition_per_member(&self, recipe: &Recipe) -> Option<Vec<(&Member, Nutrition)>> {
+        let per_serving = recipe.metadata().nutrition_per_serving()?;
+        Some(
+            self.members
+                .iter()
+                .map(|m| (m, per_serving.scale(m.portion)))
+                .collect(),
+        )
+    }
+
+    pub fn from_json(json: &str) -> MDResult<Self> {
+        let invalid = |msg: &str| MDError::new(&format!("invalid household: {}", msg), None);
+        let json: Value =
+            serde_json::from_str(json).map_err(|e| invalid(&e.to_string()).with_source(e))?;
+        let members = json
+            .get("members")
+            .and_then(Value::as_array)
+            .ok_or(invalid("expected array of members"))?;
+        let mut household = Self::new();
+        for member in members {
+            let name = member
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or(invalid("expected name of member"))?;
+            let portion = match member.get("portion") {
+                None => Some(Amount::ONE),
+                Some(Value::String(portion)) => Amount::from_str(portion).ok(),
+                Some(portion) => portion.as_f64().and_then(Amount::approximate),
+            }
+            .filter(|p| *p >= Amount::ZERO)
+            .ok_or(invalid(&format!("expected portion of {:?}", name)))?;
+            let mut parsed = Member::new(name, portion);
+            let restrictions = match member.get("restrictions") {
+                None => &vec![],
+                Some(restrictions) => restrictions.as_array().ok_or(invalid(&format!(
+                    "expected array of restrictions of {:?}",
+                    name
+                )))?,
+            };
+            for restriction in restrictions {
+                let restriction = restriction.as_str().ok_or(invalid(&format!(
+                    "expected string restriction of {:?}",
+                    name
+                )))?;
+                parsed = parsed.restriction(restriction);
+            }
+            household = household.member(parsed);
+        }
+        Ok(household)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets;
+    use indoc::indoc;
+
+    const PANCAKES: &str = indoc! {"
+        ---
+        servings: 4
+        nutrition:
+          calories: 1200
+        ---
+        # Pancakes
+        ## Ingredients
+
+        - Flour, 200 g | Buckwheat, 200 g | Cornstarch, 200 g
+        - Milk, 300 mL | Oat drink, 300 mL
+        - Butter, 20 g
+        - Eggs, 2
+
+        ## Instructions
+
+        - Whisk the *flour*, *milk*, and *eggs*
+        - Fry in the *butter*
+    "};
+
+    fn household() -> Household {
+        Household::from_json(
+            r#"{"members": [
+                {"name": "Alex", "portion": 1, "restrictions": ["gluten"]},
+                {"name": "Sam", "portion": 1},
+                {"name": "Robin", "portion": "1/2", "restrictions": ["milk"]}
+            ]}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn from_json() {
+        let household = household();
+        assert_eq!(household.members().len(), 3);
+        assert_eq!(household.members()[2].portion, Amount::new(1, 2));
+        assert_eq!(household.members()[0].restrictions, ["gluten"]);
+        assert_eq!(household.portions(), Amount::new(5, 2));
+
+        assert!(Household::from_json(r#"{"members": [{"portion": 1}]}"#).is_err());
+        assert!(Household::from_json(r#"{"members": [{"name": "A", "portion": -1}]}"#).is_err());
+        assert!(
+            Household::from_json(r#"{"members": [{"name": "A", "restrictions": "milk"}]}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn scaled() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(PANCAKES)?;
+        let household = household();
+        assert_eq!(household.scale_factor(&recipe), Some(Amount::new(5, 8)));
+        let scaled = household.scaled(&recipe).unwrap();
+        assert_eq!(
+            scaled.metadata().servings().unwrap().amount,
+            Amount::new(5, 2)
+        );
+        let without_servings = Recipe::from_mdast(
+            "# Toast\n## Ingredients\n\n- Bread, 2\n\n## Instructions\n\n- Toast\n",
+        )?;
+        assert!(household.scaled(&without_servings).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn substituted() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(PANCAKES)?;
+        let allergens = presets::load("allergens")?;
+        let household = household();
+        assert!(household.members()[2].avoids("Whole milk", &allergens));
+        assert!(!household.members()[1].avoids("Whole milk", &allergens));
+
+        let (substituted, conflicts) = household.substituted(&recipe, &allergens);
+        let names: Vec<&str> = substituted
+            .ingredients()
+            .iter()
+            .map(|o| o.ingredient().name())
+            .collect();
+        // Buckwheat is gluten-free, but its name contains "wheat".
+        assert_eq!(names, ["Cornstarch", "Oat drink", "Butter", "Eggs"]);
+        assert_eq!(
+            conflicts,
+            vec![Conflict {
+                index: 2,
+                ingredient: "Butter".to_string(),
+                members: vec!["Robin".to_string()],
+            }]
+        );
+        assert_eq!(
+            substituted.instructions().steps()[0].text(),
+            "Whisk the Cornstarch, Oat drink, and eggs"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn nutrition_per_member() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(PANCAKES)?;
+        let household = household();
+        let calories: Vec<(&str, String)> = household
+            .nutrition_per_member(&recipe)
+            .unwrap()
+            .iter()
+            .map(|(m, n)| (m.name.as_str(), n.calories.unwrap().to_string()))
+            .collect();
+        assert_eq!(
+            calories,
+            [
+                ("Alex", "300".to_string()),
+                ("Sam", "300".to_string()),
+                ("Robin", "150".to_string())
+            ]
+        );
+        Ok(())
+    }
+}
@@ -1 +1,18 @@
+pub mod analytics;
+pub mod canonical;
+pub mod cook;
+#[cfg(feature = "test-utils")]
+pub mod corpus;
+pub mod household;
+pub mod library;
+pub mod matching;
+pub mod meal_plan;
+pub mod prelude;
+pub mod presets;
+pub mod random;
 pub mod recipe;
+pub mod reference;
+pub mod render;
+pub mod shopping;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
@@ -0,0 +1,818 @@
+//! Collections of recipes read from a directory of Markdown files (e.g., an Obsidian vault).
+
+pub mod bundle;
+#[cfg(feature = "exif")]
+pub mod photos;
+pub mod search;
+pub mod synonyms;
+pub mod tags;
+
+use crate::{
+    random::{Rng, SeededRng},
+    recipe::{
+        metadata::Category,
+        rating::{Difficulty, Rating},
+        MDError, MDResult, Recipe,
+    },
+};
+use chrono::NaiveDate;
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    fs,
+    hash::Hash,
+    io,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
+use tags::TagTree;
+
+const RECIPE_EXTENSION: &str = "md";
+
+/// A recipe along with the file it was read from.
+#[derive(Clone, Debug)]
+pub struct LibraryEntry {
+    path: PathBuf,
+    recipe: Recipe,
+}
+
+impl LibraryEntry {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn recipe(&self) -> &Recipe {
+        &self.recipe
+    }
+}
+
+/// All recipes found under a directory, indexed by name, tag, and category.
+#[derive(Debug, Default)]
+pub struct RecipeLibrary {
+    entries: Vec<LibraryEntry>,
+    failures: Vec<(PathBuf, MDError)>,
+    by_name: HashMap<String, Vec<usize>>,
+    by_tag: HashMap<String, Vec<usize>>,
+    by_category: HashMap<Category, Vec<usize>>,
+    tag_tree: TagTree,
+}
+
+impl RecipeLibrary {
+    /// Recursively parses every `.md` file under `path`, in path order. Hidden files and
+    /// directories (e.g., `.obsidian`) are skipped. Files that fail to parse do not prevent loading
+    /// the rest of the library, and are reported by [`RecipeLibrary::failures`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let files = markdown_files(path)?;
+        let mut library = Self::default();
+        for file in files {
+            let recipe = fs::read_to_string(&file)
+                .map_err(|e| MDError::new(&e.to_string(), None).with_source(e))
+                .and_then(|content| Recipe::from_mdast(&content));
+            match recipe {
+                Ok(recipe) => library.insert(file, recipe),
+                Err(e) => library.failures.push((file, e)),
+            }
+        }
+        Ok(library)
+    }
+
+    pub fn insert(&mut self, path: PathBuf, recipe: Recipe) {
+        let idx = self.entries.len();
+        for name in std::iter::once(recipe.name())
+            .chain(recipe.metadata().aliases().iter().map(String::as_str))
+        {
+            let indices = self.by_name.entry(name.to_lowercase()).or_default();
+            if indices.last() != Some(&idx) {
+                indices.push(idx);
+            }
+        }
+        for tag in recipe.metadata().tags() {
+            let indices = self.by_tag.entry(tag.clone()).or_default();
+            if indices.last() != Some(&idx) {
+                indices.push(idx);
+            }
+            self.tag_tree.insert(tag, idx);
+        }
+        if let Some(category) = recipe.metadata().category() {
+            self.by_category.entry(category).or_default().push(idx);
+        }
+        self.entries.push(LibraryEntry { path, recipe });
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[LibraryEntry] {
+        &self.entries
+    }
+
+    pub fn recipes(&self) -> impl Iterator<Item = &Recipe> {
+        self.entries.iter().map(|e| &e.recipe)
+    }
+
+    /// Files which could not be read or parsed, with the first error in each.
+    pub fn failures(&self) -> &[(PathBuf, MDError)] {
+        &self.failures
+    }
+
+    /// Finds recipes by name or alias, case-insensitively (several files may define the same
+    /// recipe).
+    pub fn by_name(&self, name: &str) -> impl Iterator<Item = &LibraryEntry> {
+        self.lookup(&self.by_name, &name.to_lowercase())
+    }
+
+    /// Finds recipes with the given tag (without the leading '#') or a tag nested in it (e.g.,
+    /// "cuisine/italian" for "cuisine").
+    pub fn by_tag(&self, tag: &str) -> impl Iterator<Item = &LibraryEntry> {
+        self.tag_tree
+            .get(tag)
+            .into_iter()
+            .flat_map(|node| node.recipes())
+            .map(|idx| &self.entries[*idx])
+    }
+
+    /// Tags used in the library in their hierarchy, with the number of recipes under each.
+    pub fn tag_tree(&self) -> &TagTree {
+        &self.tag_tree
+    }
+
+    /// All tags used in the library, with the number of recipes using each.
+    pub fn tags(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.by_tag
+            .iter()
+            .map(|(tag, idx)| (tag.as_str(), idx.len()))
+    }
+
+    /// Finds recipes of the given category.
+    pub fn by_category(&self, category: Category) -> impl Iterator<Item = &LibraryEntry> {
+        self.lookup(&self.by_category, &category)
+    }
+
+    /// Recipes grouped by category, in menu order (starters before mains, etc.). Recipes without
+    /// a category are left out.
+    pub fn group_by_category(&self) -> Vec<(Category, Vec<&LibraryEntry>)> {
+        Category::ALL
+            .into_iter()
+            .filter(|c| self.by_category.contains_key(c))
+            .map(|c| (c, self.by_category(c).collect()))
+            .collect()
+    }
+
+    /// Iterates over recipes having all the given tags, or tags nested in them.
+    pub fn filter_tags<'a>(&'a self, tags: &'a [&str]) -> impl Iterator<Item = &'a LibraryEntry> {
+        self.entries.iter().filter(move |e| {
+            tags.iter().all(|tag| {
+                e.recipe
+                    .metadata()
+                    .tags()
+                    .iter()
+                    .any(|t| tags::is_within(t, tag))
+            })
+        })
+    }
+
+    /// Iterates over recipes whose tags match a query.
+    pub fn query<'a>(&'a self, query: &'a TagQuery) -> impl Iterator<Item = &'a LibraryEntry> {
+        self.entries
+            .iter()
+            .filter(move |e| query.matches(e.recipe.metadata().tags()))
+    }
+
+    /// Finds the recipe a link from another recipe points to (see
+    /// [`Ingredient::sub_recipe`](crate::recipe::ingredients::Ingredient::sub_recipe)). Links are
+    /// paths relative to the linking recipe (e.g., "../bases/dough.md"), with or without the
+    /// extension, or else file or recipe names, as in wikilinks (e.g., "[[Pizza dough]]"). Headings
+    /// the link points to (e.g., "dough.md#steps") are ignored.
+    pub fn resolve_link(&self, from: &LibraryEntry, target: &str) -> Option<&LibraryEntry> {
+        let target = percent_decode(target.split('#').next().unwrap_or_default());
+        let dir = from.path.parent().unwrap_or(Path::new(""));
+        let mut path = normalize(&dir.join(&target));
+        if path.extension().is_none() {
+            path.set_extension(RECIPE_EXTENSION);
+        }
+        self.entries
+            .iter()
+            .find(|e| normalize(&e.path) == path)
+            .or_else(|| {
+                let name = Path::new(&target).file_stem()?;
+                self.entries
+                    .iter()
+                    .find(|e| e.path.file_stem() == Some(name))
+                    .or_else(|| self.by_name(&name.to_string_lossy()).next())
+            })
+    }
+
+    /// All recipes, ordered by name (case-insensitively), then by path.
+    pub fn sorted_by_name(&self) -> Vec<&LibraryEntry> {
+        let mut entries: Vec<&LibraryEntry> = self.entries.iter().collect();
+        entries.sort_by_cached_key(|e| (e.recipe.name().to_lowercase(), e.path.clone()));
+        entries
+    }
+
+    /// Iterates over recipes rated at least `min`.
+    pub fn rated_at_least(&self, min: Rating) -> impl Iterator<Item = &LibraryEntry> {
+        self.entries
+            .iter()
+            .filter(move |e| e.recipe.metadata().rating() >= Some(min))
+    }
+
+    /// Iterates over recipes with a difficulty of at most `max`. Recipes without a difficulty are
+    /// left out.
+    pub fn at_most_as_hard_as(&self, max: Difficulty) -> impl Iterator<Item = &LibraryEntry> {
+        self.entries.iter().filter(move |e| {
+            e.recipe
+                .metadata()
+                .difficulty()
+                .is_some_and(|difficulty| difficulty <= max)
+        })
+    }
+
+    /// Iterates over recipes not cooked since a date, including those never cooked.
+    pub fn not_cooked_since(&self, date: NaiveDate) -> impl Iterator<Item = &LibraryEntry> {
+        self.entries
+            .iter()
+            .filter(move |e| e.recipe.metadata().last_cooked() < Some(date))
+    }
+
+    /// All recipes, from the best rated to the worst, then unrated ones. Ties are ordered by name.
+    pub fn sorted_by_rating(&self) -> Vec<&LibraryEntry> {
+        let mut entries = self.sorted_by_name();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.recipe.metadata().rating()));
+        entries
+    }
+
+    /// All recipes, from the easiest to the hardest, then those without a difficulty. Ties are
+    /// ordered by name.
+    pub fn sorted_by_difficulty(&self) -> Vec<&LibraryEntry> {
+        let mut entries = self.sorted_by_name();
+        entries.sort_by_key(|e| {
+            let difficulty = e.recipe.metadata().difficulty();
+            (difficulty.is_none(), difficulty)
+        });
+        entries
+    }
+
+    /// Picks a random recipe among those matching `query`, or among all recipes. Picks depend on
+    /// the generator and on the recipes, but not on the order they were loaded in.
+    pub fn pick(&self, rng: &mut impl Rng, query: Option<&TagQuery>) -> Option<&LibraryEntry> {
+        let candidates: Vec<&LibraryEntry> = self
+            .sorted_by_name()
+            .into_iter()
+            .filter(|e| query.is_none_or(|q| q.matches(e.recipe.metadata().tags())))
+            .collect();
+        rng.choose(&candidates).copied()
+    }
+
+    /// Recipe suggested for a day, which is the same on every device with the same recipes.
+    pub fn recipe_of_the_day(
+        &self,
+        date: NaiveDate,
+        query: Option<&TagQuery>,
+    ) -> Option<&LibraryEntry> {
+        self.pick(&mut SeededRng::for_day(date), query)
+    }
+
+    fn lookup<'a, K, Q>(
+        &'a self,
+        index: &'a HashMap<K, Vec<usize>>,
+        key: &Q,
+    ) -> impl Iterator<Item = &'a LibraryEntry>
+    where
+        K: Borrow<Q> + Eq + Hash,
+        Q: Eq + Hash + ?Sized,
+    {
+        index
+            .get(key)
+            .into_iter()
+            .flatten()
+            .map(|idx| &self.entries[*idx])
+    }
+}
+
+/// A boolean expression over tags, such as `vegetarian & (dessert | snack) & !nuts`.
+///
+/// `!` binds tighter than `&`, which binds tighter than `|`. Tags may be written with or without
+/// their leading '#', and match the tags nested in them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TagQuery {
+    Tag(String),
+    Not(Box<TagQuery>),
+    All(Vec<TagQuery>),
+    Any(Vec<TagQuery>),
+}
+
+impl TagQuery {
+    pub fn matches(&self, tags: &[String]) -> bool {
+        match self {
+            Self::Tag(tag) => tags.iter().any(|t| tags::is_within(t, tag)),
+            Self::Not(query) => !query.matches(tags),
+            Self::All(queries) => queries.iter().all(|q| q.matches(tags)),
+            Self::Any(queries) => queries.iter().any(|q| q.matches(tags)),
+        }
+    }
+}
+
+impl FromStr for TagQuery {
+    type Err = MDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = QueryParser { query: s, pos: 0 };
+        let query = parser.any()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(query),
+            Some(_) => Err(parser.error("unexpected character in tag query")),
+        }
+    }
+}
+
+struct QueryParser<'a> {
+    query: &'a str,
+    pos: usize,
+}
+
+impl QueryParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.query[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// Consumes `c` if it is the next non-whitespace character.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += c.len_utf8();
+        }
+        found
+    }
+
+    fn error(&self, msg: &str) -> MDError {
+        let len = self.peek().map_or(0, char::len_utf8);
+        MDError::in_line(msg, self.pos..self.pos + len)
+    }
+
+    fn any(&mut self) -> MDResult<TagQuery> {
+        let mut queries = vec![self.all()?];
+        while self.eat('|') {
+            queries.push(self.all()?);
+        }
+        Ok(match queries.len() {
+            1 => queries.pop().unwrap(),
+            _ => TagQuery::Any(queries),
+        })
+    }
+
+    fn all(&mut self) -> MDResult<TagQuery> {
+        let mut queries = vec![self.unary()?];
+        while self.eat('&') {
+            queries.push(self.unary()?);
+        }
+        Ok(match queries.len() {
+            1 => queries.pop().unwrap(),
+            _ => TagQuery::All(queries),
+        })
+    }
+
+    fn unary(&mut self) -> MDResult<TagQuery> {
+        if self.eat('!') {
+            return Ok(TagQuery::Not(Box::new(self.unary()?)));
+        }
+        if self.eat('(') {
+            let query = self.any()?;
+            return match self.eat(')') {
+                true => Ok(query),
+                false => Err(self.error("expected closing parenthesis in tag query")),
+            };
+        }
+        self.eat('#');
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '/' | '-' | '_'))
+        {
+            self.pos += self.peek().unwrap().len_utf8();
+        }
+        match start == self.pos {
+            true => Err(self.error("expected tag in tag query")),
+            false => Ok(TagQuery::Tag(self.query[start..self.pos].to_string())),
+        }
+    }
+}
+
+/// Paths of the `.md` files under `path`, recursively and in path order, skipping hidden files and
+/// directories.
+pub fn markdown_files(path: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    collect_markdown_files(path.as_ref(), &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// Resolves `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Decodes the escapes of a link target (e.g., "Pizza%20dough.md").
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn collect_markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            collect_markdown_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(RECIPE_EXTENSION))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, content: &str) -> io::Result<()> {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, content)
+    }
+
+    fn recipe(name: &str, tags: &[&str]) -> String {
+        let mut md = String::new();
+        if !tags.is_empty() {
+            md.push_str("---\ntags:\n");
+            for tag in tags {
+                md.push_str(&format!("  - \"#{}\"\n", tag));
+            }
+            md.push_str("---\n");
+        }
+        md.push_str(&format!(
+            "# {}\n## Ingredients\n\n- Water, 1 L\n\n## Instructions\n\n- Boil\n",
+            name
+        ));
+        md
+    }
+
+    #[test]
+    fn load_library() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dtc-library-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        write(&dir, "soup.md", &recipe("Soup", &["dinner", "vegan"]))?;
+        write(&dir, "desserts/flan.md", &recipe("Flan", &["dessert"]))?;
+        write(&dir, "desserts/notes.txt", "not a recipe")?;
+        write(&dir, ".obsidian/template.md", &recipe("Template", &[]))?;
+        write(&dir, "broken.md", "# Broken\n")?;
+        write(
+            &dir,
+            "draft.md",
+            "# Draft\n## Ingredients\nTBD\n## Instructions\n",
+        )?;
+
+        let library = RecipeLibrary::load(&dir)?;
+        let names: Vec<&str> = library.recipes().map(|r| r.name()).collect();
+        assert_eq!(names, vec!["Flan", "Draft", "Soup"]);
+        assert_eq!(library.failures().len(), 1);
+        assert!(library.failures()[0].0.ends_with("broken.md"));
+
+        assert_eq!(
+            library.by_name("soup").next().unwrap().path(),
+            dir.join("soup.md")
+        );
+        assert_eq!(library.by_tag("vegan").count(), 1);
+        assert_eq!(library.by_tag("lunch").count(), 0);
+        assert_eq!(library.tags().count(), 3);
+        fs::remove_dir_all(&dir)
+    }
+
+    fn library(recipes: &[(&str, &[&str])]) -> RecipeLibrary {
+        let mut library = RecipeLibrary::default();
+        for (name, tags) in recipes {
+            let recipe = Recipe::from_mdast(&recipe(name, tags)).unwrap();
+            library.insert(PathBuf::from(format!("{}.md", name)), recipe);
+        }
+        library
+    }
+
+    #[test]
+    fn filter_tags() -> MDResult<()> {
+        let library = library(&[
+            ("Salad", &["vegetarian", "lunch"]),
+            ("Brownie", &["vegetarian", "dessert", "nuts"]),
+            ("Sorbet", &["vegetarian", "dessert"]),
+            ("Steak", &["dinner"]),
+        ]);
+        let names = |entries: Vec<&LibraryEntry>| -> Vec<String> {
+            entries
+                .iter()
+                .map(|e| e.recipe().name().to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            names(library.filter_tags(&["vegetarian", "dessert"]).collect()),
+            vec!["Brownie", "Sorbet"]
+        );
+        let query = TagQuery::from_str("#vegetarian & !nuts & (dessert | lunch)")?;
+        assert_eq!(
+            names(library.query(&query).collect()),
+            vec!["Salad", "Sorbet"]
+        );
+        let query = TagQuery::from_str("dinner | nuts")?;
+        assert_eq!(
+            names(library.query(&query).collect()),
+            vec!["Brownie", "Steak"]
+        );
+        assert_eq!(
+            names(library.sorted_by_name()),
+            vec!["Brownie", "Salad", "Sorbet", "Steak"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn nested_tags() -> MDResult<()> {
+        let library = library(&[
+            ("Carbonara", &["cuisine/italian", "dinner"]),
+            ("Quiche", &["cuisine/french"]),
+            ("Tiramisu", &["cuisine/italian", "dessert"]),
+            ("Cuisines", &["cuisines"]),
+        ]);
+        let names = |entries: Vec<&LibraryEntry>| -> Vec<String> {
+            entries
+                .iter()
+                .map(|e| e.recipe().name().to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            names(library.by_tag("cuisine").collect()),
+            vec!["Carbonara", "Quiche", "Tiramisu"]
+        );
+        assert_eq!(library.by_tag("cuisine/italian").count(), 2);
+        assert_eq!(
+            names(library.filter_tags(&["cuisine", "dessert"]).collect()),
+            vec!["Tiramisu"]
+        );
+        let query = TagQuery::from_str("#cuisine & !cuisine/italian")?;
+        assert_eq!(names(library.query(&query).collect()), vec!["Quiche"]);
+
+        let counts: Vec<(&str, usize)> = library
+            .tag_tree()
+            .iter()
+            .map(|node| (node.tag(), node.count()))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("cuisine", 3),
+                ("cuisine/french", 1),
+                ("cuisine/italian", 2),
+                ("cuisines", 1),
+                ("dessert", 1),
+                ("dinner", 1)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pick() -> MDResult<()> {
+        let recipes: [(&str, &[&str]); 4] = [
+            ("Salad", &["vegetarian"]),
+            ("Brownie", &["vegetarian", "dessert"]),
+            ("Sorbet", &["vegetarian", "dessert"]),
+            ("Steak", &[]),
+        ];
+        let mut reversed = recipes;
+        reversed.reverse();
+        let (library, reversed) = (library(&recipes), library(&reversed));
+        let day = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let name = |entry: Option<&LibraryEntry>| entry.unwrap().recipe().name().to_string();
+
+        // Picks do not depend on the order of the recipes.
+        for seed in 0..10 {
+            assert_eq!(
+                name(library.pick(&mut SeededRng::new(seed), None)),
+                name(reversed.pick(&mut SeededRng::new(seed), None))
+            );
+        }
+        assert_eq!(
+            name(library.recipe_of_the_day(day, None)),
+            name(reversed.recipe_of_the_day(day, None))
+        );
+        let query = TagQuery::from_str("dessert")?;
+        let picked = name(library.recipe_of_the_day(day, Some(&query)));
+        assert!(picked == "Brownie" || picked == "Sorbet");
+        let query = TagQuery::from_str("nuts")?;
+        assert!(library.recipe_of_the_day(day, Some(&query)).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_links() {
+        let mut library = RecipeLibrary::default();
+        for (path, name) in [
+            ("pizza/margherita.md", "Margherita"),
+            ("bases/pizza dough.md", "Pizza dough"),
+            ("bases/sauce.md", "Sauce"),
+            ("bases/focaccia-v2.md", "Focaccia"),
+        ] {
+            let recipe = Recipe::from_mdast(&recipe(name, &[])).unwrap();
+            library.insert(PathBuf::from(path), recipe);
+        }
+        let pizza = library.by_name("Margherita").next().unwrap();
+        let resolve = |target| library.resolve_link(pizza, target).map(|e| e.path());
+
+        assert_eq!(
+            resolve("../bases/pizza%20dough.md"),
+            Some(Path::new("bases/pizza dough.md"))
+        );
+        assert_eq!(
+            resolve("./../bases/sauce"),
+            Some(Path::new("bases/sauce.md"))
+        );
+        // Links which are not relative paths fall back to names.
+        assert_eq!(resolve("bases/sauce.md"), Some(Path::new("bases/sauce.md")));
+        assert_eq!(
+            resolve("Pizza dough"),
+            Some(Path::new("bases/pizza dough.md"))
+        );
+        assert_eq!(
+            resolve("focaccia-v2"),
+            Some(Path::new("bases/focaccia-v2.md"))
+        );
+        assert_eq!(
+            resolve("../bases/sauce.md#steps"),
+            Some(Path::new("bases/sauce.md"))
+        );
+        assert_eq!(resolve("../bases/pesto.md"), None);
+    }
+
+    #[test]
+    fn group_by_category() {
+        let mut library = RecipeLibrary::default();
+        for (name, category) in [
+            ("Flan", "dessert"),
+            ("Soup", "starter"),
+            ("Cake", "dessert"),
+        ] {
+            let content = format!("---\ncategory: {}\n---\n{}", category, recipe(name, &[]));
+            library.insert(
+                PathBuf::from(format!("{}.md", name)),
+                Recipe::from_mdast(&content).unwrap(),
+            );
+        }
+        library.insert(
+            PathBuf::from("Water.md"),
+            Recipe::from_mdast(&recipe("Water", &[])).unwrap(),
+        );
+
+        assert_eq!(library.by_category(Category::Dessert).count(), 2);
+        let groups: Vec<(Category, Vec<&str>)> = library
+            .group_by_category()
+            .into_iter()
+            .map(|(c, entries)| (c, entries.iter().map(|e| e.recipe().name()).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (Category::Starter, vec!["Soup"]),
+                (Category::Dessert, vec!["Flan", "Cake"])
+            ]
+        );
+    }
+
+    #[test]
+    fn rating_and_difficulty() -> MDResult<()> {
+        let mut library = RecipeLibrary::default();
+        for (name, frontmatter) in [
+            ("Flan", "rating: 4.5\ndifficulty: medium"),
+            ("Soup", "rating: 3\ndifficulty: easy"),
+            ("Cake", "rating: 4.5"),
+            ("Water", "difficulty: 1"),
+        ] {
+            let content = format!("---\n{}\n---\n{}", frontmatter, recipe(name, &[]));
+            library.insert(
+                PathBuf::from(format!("{}.md", name)),
+                Recipe::from_mdast(&content).unwrap(),
+            );
+        }
+        let names = |entries: Vec<&LibraryEntry>| -> Vec<String> {
+            entries
+                .iter()
+                .map(|e| e.recipe().name().to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            names(library.rated_at_least(Rating::from_str("4")?).collect()),
+            vec!["Flan", "Cake"]
+        );
+        assert_eq!(
+            names(library.at_most_as_hard_as(Difficulty::EASY).collect()),
+            vec!["Soup", "Water"]
+        );
+        assert_eq!(
+            names(library.sorted_by_rating()),
+            vec!["Cake", "Flan", "Soup", "Water"]
+        );
+        assert_eq!(
+            names(library.sorted_by_difficulty()),
+            vec!["Soup", "Water", "Flan", "Cake"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn not_cooked_since() {
+        let mut library = RecipeLibrary::default();
+        for (name, frontmatter) in [
+            ("Flan", "last_cooked: 2024-05-01"),
+            ("Soup", "cook_log: [2023-12-24, 2024-01-10]"),
+            ("Cake", "created: 2024-01-01"),
+        ] {
+            let content = format!("---\n{}\n---\n{}", frontmatter, recipe(name, &[]));
+            library.insert(
+                PathBuf::from(format!("{}.md", name)),
+                Recipe::from_mdast(&content).unwrap(),
+            );
+        }
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let names: Vec<&str> = library
+            .not_cooked_since(date)
+            .map(|e| e.recipe().name())
+            .collect();
+        assert_eq!(names, vec!["Soup", "Cake"]);
+    }
+
+    #[test]
+    fn parse_tag_query() {
+        assert_eq!(
+            TagQuery::from_str("a | b & !c").unwrap(),
+            TagQuery::Any(vec![
+                TagQuery::Tag("a".to_string()),
+                TagQuery::All(vec![
+                    TagQuery::Tag("b".to_string()),
+                    TagQuery::Not(Box::new(TagQuery::Tag("c".to_string()))),
+                ]),
+            ])
+        );
+        assert_eq!(TagQuery::from_str("a & ").unwrap_err().span(), Some(4..4));
+        assert_eq!(TagQuery::from_str("(a | b").unwrap_err().span(), Some(6..6));
+        assert_eq!(TagQuery::from_str("a b").unwrap_err().span(), Some(2..3));
+    }
+}
@@ -0,0 +1,210 @@
+//! Bundles of the tables curated alongside a library (e.g., ingredient synonyms, densities,
+//! shopping categories, or prices), which households and communities can exchange.
+//!
+//! A bundle is a set of named tables mapping keys to values, serialized as JSON:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "tables": {
+//!     "synonyms": { "scallion": "green onion" },
+//!     "densities": { "flour": "0.53 g/ml" },
+//!     "unit-sizes": { "stick": "113 g" }
+//!   }
+//! }
+//! ```
+//!
+//! Values are kept as written; interpreting them is left to the code using each table (e.g.,
+//! [`UnitRegistry::from_bundle`](crate::recipe::unit::UnitRegistry::from_bundle) or
+//! [`Synonyms::from_bundle`](super::synonyms::Synonyms::from_bundle)).
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::recipe::{MDError, MDResult};
+
+const VERSION: u64 = 1;
+
+/// Preferred unit for each kind of quantity (e.g., "mass": "g").
+pub const UNITS: &str = "units";
+/// Sizes of custom units (e.g., "stick": "113 g").
+pub const UNIT_SIZES: &str = "unit-sizes";
+pub const SYNONYMS: &str = "synonyms";
+pub const DENSITIES: &str = "densities";
+pub const CATEGORIES: &str = "categories";
+pub const PRICES: &str = "prices";
+pub const ALLERGENS: &str = "allergens";
+
+type Table = BTreeMap<String, String>;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bundle {
+    tables: BTreeMap<String, Table>,
+}
+
+/// Which value to keep when merging a bundle with a different value for an existing key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    #[default]
+    KeepOurs,
+    TakeTheirs,
+}
+
+/// A key with different values in merged bundles.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub table: String,
+    pub key: String,
+    pub ours: String,
+    pub theirs: String,
+}
+
+impl Bundle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn table(&self, name: &str) -> Option<&BTreeMap<String, String>> {
+        self.tables.get(name)
+    }
+
+    /// Names of the tables in the bundle, in alphabetical order.
+    pub fn tables(&self) -> impl Iterator<Item = &str> {
+        self.tables.keys().map(String::as_str)
+    }
+
+    pub fn get(&self, table: &str, key: &str) -> Option<&str> {
+        self.tables.get(table)?.get(key).map(String::as_str)
+    }
+
+    /// Sets the value of a key, returning its previous value.
+    pub fn insert(&mut self, table: &str, key: &str, value: &str) -> Option<String> {
+        self.tables
+            .entry(table.to_string())
+            .or_default()
+            .insert(key.to_string(), value.to_string())
+    }
+
+    /// Adds the entries of `other`, returning the keys with different values in both bundles,
+    /// whose value is picked according to `policy`.
+    pub fn merge(&mut self, other: Bundle, policy: MergePolicy) -> Vec<Conflict> {
+        let mut conflicts = vec![];
+        for (name, table) in other.tables {
+            let ours = self.tables.entry(name.clone()).or_default();
+            for (key, theirs) in table {
+                match ours.get_mut(&key) {
+                    Some(value) if *value != theirs => {
+                        conflicts.push(Conflict {
+                            table: name.clone(),
+                            key,
+                            ours: value.clone(),
+                            theirs: theirs.clone(),
+                        });
+                        if policy == MergePolicy::TakeTheirs {
+                            *value = theirs;
+                        }
+                    }
+                    Some(_) => (),
+                    None => {
+                        ours.insert(key, theirs);
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    pub fn to_json(&self) -> String {
+        let tables: Map<String, Value> = self
+            .tables
+            .iter()
+            .map(|(name, table)| (name.clone(), json!(table)))
+            .collect();
+        let json = json!({ "version": VERSION, "tables": tables });
+        format!("{:#}\n", json)
+    }
+
+    pub fn from_json(json: &str) -> MDResult<Self> {
+        let invalid = |msg: &str| MDError::new(&format!("invalid bundle: {}", msg), None);
+        let json: Value =
+            serde_json::from_str(json).map_err(|e| invalid(&e.to_string()).with_source(e))?;
+        match json.get("version").and_then(Value::as_u64) {
+            Some(VERSION) => (),
+            Some(version) => return Err(invalid(&format!("unsupported version {}", version))),
+            None => return Err(invalid("missing version")),
+        }
+        let mut bundle = Self::new();
+        let tables = json
+            .get("tables")
+            .and_then(Value::as_object)
+            .ok_or(invalid("expected object of tables"))?;
+        for (name, table) in tables {
+            let table = table
+                .as_object()
+                .ok_or(invalid(&format!("expected object for table {:?}", name)))?;
+            for (key, value) in table {
+                let value = value.as_str().ok_or(invalid(&format!(
+                    "expected string value for {:?} in table {:?}",
+                    key, name
+                )))?;
+                bundle.insert(name, key, value);
+            }
+        }
+        Ok(bundle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() -> MDResult<()> {
+        let mut bundle = Bundle::new();
+        bundle.insert(SYNONYMS, "scallion", "green onion");
+        bundle.insert(DENSITIES, "flour", "0.53 g/ml");
+        let json = bundle.to_json();
+        assert_eq!(Bundle::from_json(&json)?, bundle);
+        assert_eq!(
+            bundle.tables().collect::<Vec<_>>(),
+            vec![DENSITIES, SYNONYMS]
+        );
+
+        assert!(Bundle::from_json(r#"{"tables": {}}"#).is_err());
+        assert!(Bundle::from_json(r#"{"version": 2, "tables": {}}"#).is_err());
+        assert!(
+            Bundle::from_json(r#"{"version": 1, "tables": {"prices": {"flour": 2}}}"#).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn merge() {
+        let mut ours = Bundle::new();
+        ours.insert(CATEGORIES, "flour", "baking");
+        ours.insert(CATEGORIES, "milk", "dairy");
+        let mut theirs = Bundle::new();
+        theirs.insert(CATEGORIES, "flour", "pantry");
+        theirs.insert(CATEGORIES, "milk", "dairy");
+        theirs.insert(PRICES, "milk", "1.20 EUR/l");
+
+        let conflicts = ours.clone().merge(theirs.clone(), MergePolicy::KeepOurs);
+        assert_eq!(
+            conflicts,
+            vec![Conflict {
+                table: CATEGORIES.to_string(),
+                key: "flour".to_string(),
+                ours: "baking".to_string(),
+                theirs: "pantry".to_string(),
+            }]
+        );
+
+        let mut merged = ours.clone();
+        merged.merge(theirs.clone(), MergePolicy::KeepOurs);
+        assert_eq!(merged.get(CATEGORIES, "flour"), Some("baking"));
+        assert_eq!(merged.get(PRICES, "milk"), Some("1.20 EUR/l"));
+        ours.merge(theirs, MergePolicy::TakeTheirs);
+        assert_eq!(ours.get(CATEGORIES, "flour"), Some("pantry"));
+    }
+}
@@ -0,0 +1,230 @@
+//! Cook dates suggested by the photos linked from recipes: a recipe photographed on a day was
+//! likely cooked that day. Dates are read from the photos' EXIF metadata.
+//!
+//! Suggestions are never recorded on their own, but left for the user to review before adding
+//! them to a [`CookLog`].
+
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+use exif::{In, Reader, Tag, Value};
+
+use super::{normalize, percent_decode, LibraryEntry, RecipeLibrary};
+use crate::{
+    analytics::{CookEvent, CookLog},
+    recipe::{instructions::TextElem, Recipe},
+};
+
+/// A cook event suggested by a photo.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CookDateSuggestion {
+    /// Photo the date was read from.
+    pub photo: PathBuf,
+    pub event: CookEvent,
+}
+
+/// Suggests a cook event for each day a recipe was photographed, from the photos in the
+/// `attachments` folder linked from the recipe (by path relative to the recipe, or by file name as
+/// in Obsidian). Days already in `log` for the recipe are left out, as are photos without a date.
+pub fn suggest_cook_dates(
+    library: &RecipeLibrary,
+    attachments: &Path,
+    log: &CookLog,
+) -> Vec<CookDateSuggestion> {
+    let attachments = normalize(attachments);
+    let mut suggestions: Vec<CookDateSuggestion> = vec![];
+    for entry in library.entries() {
+        let recipe = entry.recipe();
+        for url in image_urls(recipe) {
+            let Some(photo) = locate(entry, url, &attachments) else {
+                continue;
+            };
+            let Some(date) = photo_date(&photo) else {
+                continue;
+            };
+            let known = log
+                .events()
+                .iter()
+                .chain(suggestions.iter().map(|s| &s.event))
+                .any(|e| e.recipe == recipe.name() && e.date == date);
+            if !known {
+                suggestions.push(CookDateSuggestion {
+                    photo,
+                    event: CookEvent::from_recipe(recipe, date, None),
+                });
+            }
+        }
+    }
+    suggestions
+}
+
+/// Targets of the cover image and of the images in steps, in reading order.
+fn image_urls(recipe: &Recipe) -> Vec<&str> {
+    fn collect<'a>(elems: &'a [TextElem], urls: &mut Vec<&'a str>) {
+        for elem in elems {
+            match elem {
+                TextElem::Image { url, .. } => urls.push(url),
+                TextElem::Conditional(conditional) => {
+                    for (_, branch) in conditional.branches() {
+                        collect(branch, urls);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    let mut urls: Vec<&str> = recipe.image().map(|i| i.url.as_str()).into_iter().collect();
+    for flat in recipe.instructions().flatten() {
+        collect(flat.step.description(), &mut urls);
+    }
+    urls
+}
+
+/// Finds the photo an image links to in the attachments folder.
+fn locate(entry: &LibraryEntry, url: &str, attachments: &Path) -> Option<PathBuf> {
+    if url.contains("://") {
+        return None;
+    }
+    let url = percent_decode(url);
+    let dir = entry.path().parent().unwrap_or(Path::new(""));
+    let relative = normalize(&dir.join(&url));
+    if relative.starts_with(attachments) && relative.is_file() {
+        return Some(relative);
+    }
+    let by_name = attachments.join(Path::new(&url).file_name()?);
+    by_name.is_file().then_some(by_name)
+}
+
+/// Day a photo was taken, or else last modified, according to its EXIF metadata.
+fn photo_date(path: &Path) -> Option<NaiveDate> {
+    let mut file = BufReader::new(File::open(path).ok()?);
+    let exif = Reader::new().read_from_container(&mut file).ok()?;
+    let field = exif
+        .get_field(Tag::DateTimeOriginal, In::PRIMARY)
+        .or_else(|| exif.get_field(Tag::DateTime, In::PRIMARY))?;
+    let Value::Ascii(values) = &field.value else {
+        return None;
+    };
+    let date = exif::DateTime::from_ascii(values.first()?).ok()?;
+    NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exif::{experimental::Writer, Field};
+    use std::{fs, io};
+
+    /// Writes a TIFF file holding only EXIF metadata, with the given date and time.
+    fn write_photo(path: &Path, tag: Tag, date: &str) -> io::Result<()> {
+        let field = Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![date.as_bytes().to_vec()]),
+        };
+        let mut writer = Writer::new();
+        writer.push_field(&field);
+        let mut buf = io::Cursor::new(vec![]);
+        writer.write(&mut buf, false).unwrap();
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, buf.into_inner())
+    }
+
+    fn recipe(name: &str, cover: &str, steps: &[&str]) -> Recipe {
+        let steps: String = steps.iter().map(|s| format!("- {}\n", s)).collect();
+        let content = format!(
+            "# {}\n\n{}\n\n## Ingredients\n\n- Water, 1 L\n\n## Instructions\n\n{}",
+            name, cover, steps
+        );
+        Recipe::from_mdast(&content).unwrap()
+    }
+
+    #[test]
+    fn suggest_from_photos() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dtc-photos-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let attachments = dir.join("attachments");
+        let original = Tag::DateTimeOriginal;
+        write_photo(
+            &attachments.join("soup 1.tif"),
+            original,
+            "2024:05:01 19:30:00",
+        )?;
+        write_photo(
+            &attachments.join("soup-2.tif"),
+            Tag::DateTime,
+            "2024:05:03 12:00:00",
+        )?;
+        write_photo(
+            &attachments.join("soup-3.tif"),
+            original,
+            "2024:05:03 12:05:00",
+        )?;
+        write_photo(
+            &attachments.join("flan.tif"),
+            original,
+            "2024:06:10 20:00:00",
+        )?;
+        write_photo(
+            &dir.join("elsewhere/bread.tif"),
+            original,
+            "2024:07:01 08:00:00",
+        )?;
+        fs::write(attachments.join("blank.tif"), "no metadata")?;
+
+        let mut library = RecipeLibrary::default();
+        library.insert(
+            dir.join("soups/soup.md"),
+            recipe(
+                "Soup",
+                "![Soup](../attachments/soup%201.tif)",
+                &[
+                    "Simmer ![](soup-2.tif)",
+                    "Serve ![](soup-3.tif) ![](blank.tif)",
+                ],
+            ),
+        );
+        library.insert(dir.join("flan.md"), recipe("Flan", "![](flan.tif)", &[]));
+        library.insert(
+            dir.join("bread.md"),
+            recipe("Bread", "![](elsewhere/bread.tif)", &["Bake"]),
+        );
+
+        let log_path = dir.join("log.tsv");
+        let mut log = CookLog::open(&log_path)?;
+        let flan = library.by_name("Flan").next().unwrap().recipe();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        log.record(CookEvent::from_recipe(flan, date, None))?;
+
+        let suggestions = suggest_cook_dates(&library, &attachments, &log);
+        let found: Vec<(&str, String, &Path)> = suggestions
+            .iter()
+            .map(|s| {
+                let photo = s.photo.strip_prefix(&dir).unwrap();
+                (&*s.event.recipe, s.event.date.to_string(), photo)
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "Soup",
+                    "2024-05-01".to_string(),
+                    Path::new("attachments/soup 1.tif")
+                ),
+                (
+                    "Soup",
+                    "2024-05-03".to_string(),
+                    Path::new("attachments/soup-2.tif")
+                ),
+            ]
+        );
+        // Suggestions are not recorded.
+        assert_eq!(CookLog::open(&log_path)?.events().len(), 1);
+        fs::remove_dir_all(&dir)
+    }
+}
@@ -0,0 +1,289 @@
+//! Full-text search over the ingredients and instructions of a [`RecipeLibrary`].
+//!
+//! Text is split into lowercase alphanumeric tokens. A query is a list of phrases separated by
+//! "and" or commas (e.g., "contains chicken and coconut milk"); a recipe matches when each phrase
+//! appears, as consecutive tokens, in one of its ingredient names or steps.
+
+use super::{LibraryEntry, RecipeLibrary};
+use crate::recipe::instructions::Step;
+use std::{collections::HashMap, ops::Range};
+
+/// A lowercase word and its byte range in the original text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    pub span: Range<usize>,
+}
+
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut start = None;
+    for (idx, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(idx),
+            (false, Some(s)) => {
+                tokens.push(Token {
+                    text: text[s..idx].to_lowercase(),
+                    span: s..idx,
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Where a piece of searchable text comes from within a recipe.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Field {
+    /// An ingredient (or alternative) name, by its index in `Ingredients::iter`.
+    Ingredient(usize),
+    /// A step's text, by its path through the steps and substeps (e.g., `[1, 0]` is the first
+    /// substep of the second step).
+    Step(Vec<usize>),
+}
+
+/// An occurrence of a query phrase.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub phrase: String,
+    pub field: Field,
+    /// Byte range of the phrase in the field's text.
+    pub span: Range<usize>,
+}
+
+#[derive(Debug)]
+pub struct SearchHit<'a> {
+    pub entry: &'a LibraryEntry,
+    pub matches: Vec<Match>,
+}
+
+struct FieldText {
+    entry: usize,
+    field: Field,
+    tokens: Vec<Token>,
+}
+
+/// Inverted index of the words of all recipes in a library.
+pub struct SearchIndex<'a> {
+    library: &'a RecipeLibrary,
+    fields: Vec<FieldText>,
+    /// For each token, the (field, token) positions where it appears.
+    postings: HashMap<String, Vec<(usize, usize)>>,
+}
+
+impl<'a> SearchIndex<'a> {
+    pub fn new(library: &'a RecipeLibrary) -> Self {
+        let mut index = Self {
+            library,
+            fields: vec![],
+            postings: HashMap::new(),
+        };
+        for (entry, e) in library.entries().iter().enumerate() {
+            for (i, options) in e.recipe().ingredients().iter().enumerate() {
+                for name in options.names() {
+                    index.add(entry, Field::Ingredient(i), name);
+                }
+            }
+            index.add_steps(entry, e.recipe().instructions().steps(), &mut vec![]);
+        }
+        index
+    }
+
+    fn add_steps(&mut self, entry: usize, steps: &[Step], path: &mut Vec<usize>) {
+        for (i, step) in steps.iter().enumerate() {
+            path.push(i);
+            self.add(entry, Field::Step(path.clone()), &step.text());
+            self.add_steps(entry, step.substeps(), path);
+            path.pop();
+        }
+    }
+
+    fn add(&mut self, entry: usize, field: Field, text: &str) {
+        let tokens = tokenize(text);
+        let idx = self.fields.len();
+        for (pos, token) in tokens.iter().enumerate() {
+            self.postings
+                .entry(token.text.clone())
+                .or_default()
+                .push((idx, pos));
+        }
+        self.fields.push(FieldText {
+            entry,
+            field,
+            tokens,
+        });
+    }
+
+    /// Returns the recipes containing every phrase of the query, in library order, along with all
+    /// occurrences of the phrases.
+    pub fn search(&self, query: &str) -> Vec<SearchHit<'a>> {
+        let phrases = parse_query(query);
+        if phrases.is_empty() {
+            return vec![];
+        }
+        let mut found: Vec<HashMap<usize, Vec<Match>>> = phrases
+            .iter()
+            .map(|phrase| {
+                let mut by_entry: HashMap<usize, Vec<Match>> = HashMap::new();
+                for (entry, m) in self.find_phrase(phrase) {
+                    by_entry.entry(entry).or_default().push(m);
+                }
+                by_entry
+            })
+            .collect();
+        let mut entries: Vec<usize> = found[0]
+            .keys()
+            .filter(|entry| found.iter().all(|f| f.contains_key(entry)))
+            .copied()
+            .collect();
+        entries.sort();
+        entries
+            .into_iter()
+            .map(|entry| SearchHit {
+                entry: &self.library.entries()[entry],
+                matches: found
+                    .iter_mut()
+                    .flat_map(|f| f.remove(&entry).unwrap())
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn find_phrase(&self, phrase: &[String]) -> Vec<(usize, Match)> {
+        let Some(first) = self.postings.get(&phrase[0]) else {
+            return vec![];
+        };
+        first
+            .iter()
+            .filter_map(|&(field_idx, pos)| {
+                let field = &self.fields[field_idx];
+                let tokens = field.tokens.get(pos..pos + phrase.len())?;
+                tokens
+                    .iter()
+                    .zip(phrase)
+                    .all(|(t, p)| t.text == *p)
+                    .then(|| {
+                        let span = tokens[0].span.start..tokens[tokens.len() - 1].span.end;
+                        let m = Match {
+                            phrase: phrase.join(" "),
+                            field: field.field.clone(),
+                            span,
+                        };
+                        (field.entry, m)
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Splits a query into phrases, each a list of tokens.
+fn parse_query(query: &str) -> Vec<Vec<String>> {
+    let mut phrases: Vec<Vec<String>> = vec![vec![]];
+    for (i, token) in tokenize(query).into_iter().enumerate() {
+        match token.text.as_str() {
+            "contains" | "contain" | "with" if i == 0 => {}
+            "and" => phrases.push(vec![]),
+            _ => phrases.last_mut().unwrap().push(token.text),
+        }
+        if query[token.span.end..].trim_start().starts_with(',') {
+            phrases.push(vec![]);
+        }
+    }
+    phrases.retain(|p| !p.is_empty());
+    phrases
+}
+
+impl RecipeLibrary {
+    /// Searches the library; see the [module documentation](crate::library::search) for the query syntax. For
+    /// repeated searches, build a [`SearchIndex`] once instead.
+    pub fn search(&self, query: &str) -> Vec<SearchHit<'_>> {
+        SearchIndex::new(self).search(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::Recipe;
+    use indoc::indoc;
+    use std::path::PathBuf;
+
+    fn library() -> RecipeLibrary {
+        let mut library = RecipeLibrary::default();
+        let recipes = [
+            indoc! {"
+                # Curry
+                ## Ingredients
+
+                - Chicken, 500 g
+                - Coconut milk, 400 mL
+
+                ## Instructions
+
+                - Brown the *chicken*
+                    - Add the *coconut milk* and simmer for **20 min**
+            "},
+            indoc! {"
+                # Roast chicken
+                ## Ingredients
+
+                - Chicken, 1
+
+                ## Instructions
+
+                - Roast for **1 h**
+            "},
+        ];
+        for (i, content) in recipes.iter().enumerate() {
+            library.insert(
+                PathBuf::from(format!("{}.md", i)),
+                Recipe::from_mdast(content).unwrap(),
+            );
+        }
+        library
+    }
+
+    #[test]
+    fn tokenize_text() {
+        let tokens = tokenize("Add the coconut-milk, now!");
+        let words: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(words, vec!["add", "the", "coconut", "milk", "now"]);
+        assert_eq!(tokens[2].span, 8..15);
+    }
+
+    #[test]
+    fn parse_queries() {
+        assert_eq!(
+            parse_query("contains chicken and coconut milk"),
+            vec![vec!["chicken"], vec!["coconut", "milk"]]
+        );
+        assert_eq!(
+            parse_query("Chicken, coconut milk"),
+            vec![vec!["chicken"], vec!["coconut", "milk"]]
+        );
+        assert!(parse_query("and ,").is_empty());
+    }
+
+    #[test]
+    fn search_library() {
+        let library = library();
+        let hits = library.search("contains chicken and coconut milk");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.recipe().name(), "Curry");
+        let coconut: Vec<&Match> = hits[0]
+            .matches
+            .iter()
+            .filter(|m| m.phrase == "coconut milk")
+            .collect();
+        assert_eq!(coconut[0].field, Field::Ingredient(1));
+        assert_eq!(coconut[0].span, 0..12);
+        assert_eq!(coconut[1].field, Field::Step(vec![0, 0]));
+        assert_eq!(coconut[1].span, 8..20);
+
+        assert_eq!(library.search("chicken").len(), 2);
+        assert_eq!(library.search("milk coconut").len(), 0);
+        assert_eq!(library.search("").len(), 0);
+    }
+}
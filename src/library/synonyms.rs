@@ -0,0 +1,118 @@
+//! Synonyms of ingredient names (e.g., "scallion" for "green onion"), curated alongside a library
+//! and exchanged through the `synonyms` table of [`Bundle`]s.
+
+use std::collections::BTreeMap;
+
+use super::bundle::{Bundle, Conflict, MergePolicy, SYNONYMS};
+use crate::recipe::{MDError, MDResult};
+
+/// Names ingredients are also known by, mapped to the name to use for them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Synonyms {
+    /// Names by lowercase synonym.
+    names: BTreeMap<String, String>,
+}
+
+impl Synonyms {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Synonyms of the `synonyms` table of a bundle (e.g., "scallion": "green onion").
+    pub fn from_bundle(bundle: &Bundle) -> MDResult<Self> {
+        let mut this = Self::new();
+        for (synonym, name) in bundle.table(SYNONYMS).into_iter().flatten() {
+            if name.trim().is_empty() {
+                return Err(MDError::new(
+                    &format!("invalid synonym {:?}: expected a name", synonym),
+                    None,
+                ));
+            }
+            this.insert(synonym, name);
+        }
+        Ok(this)
+    }
+
+    /// Bundle with the synonyms in its `synonyms` table, to share them (see
+    /// [`Synonyms::from_bundle`]).
+    pub fn to_bundle(&self) -> Bundle {
+        let mut bundle = Bundle::new();
+        for (synonym, name) in &self.names {
+            bundle.insert(SYNONYMS, synonym, name);
+        }
+        bundle
+    }
+
+    /// Adds the synonyms of a bundle, returning the synonyms of different names in both, whose
+    /// name is picked according to `policy`.
+    pub fn merge_bundle(
+        &mut self,
+        bundle: &Bundle,
+        policy: MergePolicy,
+    ) -> MDResult<Vec<Conflict>> {
+        let mut merged = self.to_bundle();
+        let conflicts = merged.merge(Self::from_bundle(bundle)?.to_bundle(), policy);
+        *self = Self::from_bundle(&merged)?;
+        Ok(conflicts)
+    }
+
+    /// Makes `synonym` (compared case-insensitively) stand for `name`.
+    pub fn insert(&mut self, synonym: &str, name: &str) {
+        self.names
+            .insert(synonym.trim().to_lowercase(), name.trim().to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Synonyms by lowercase name, with the names they stand for, in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.names.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Name to use for an ingredient: the name a synonym stands for, or else the name as given.
+    pub fn canonical<'a>(&'a self, name: &'a str) -> &'a str {
+        self.names
+            .get(&name.trim().to_lowercase())
+            .map_or(name, String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synonyms() -> MDResult<()> {
+        let mut synonyms = Synonyms::new();
+        synonyms.insert("Scallion", "green onion");
+        assert_eq!(synonyms.canonical("scallion"), "green onion");
+        assert_eq!(synonyms.canonical("Leek"), "Leek");
+
+        let bundle = synonyms.to_bundle();
+        assert_eq!(bundle.get(SYNONYMS, "scallion"), Some("green onion"));
+        assert_eq!(Synonyms::from_bundle(&bundle)?, synonyms);
+
+        let mut theirs = Bundle::new();
+        theirs.insert(SYNONYMS, "Scallion", "spring onion");
+        theirs.insert(SYNONYMS, "courgette", "zucchini");
+        let conflicts = synonyms.merge_bundle(&theirs, MergePolicy::KeepOurs)?;
+        assert_eq!(
+            conflicts,
+            vec![Conflict {
+                table: SYNONYMS.to_string(),
+                key: "scallion".to_string(),
+                ours: "green onion".to_string(),
+                theirs: "spring onion".to_string(),
+            }]
+        );
+        assert_eq!(synonyms.canonical("Courgette"), "zucchini");
+        synonyms.merge_bundle(&theirs, MergePolicy::TakeTheirs)?;
+        assert_eq!(synonyms.canonical("scallion"), "spring onion");
+
+        theirs.insert(SYNONYMS, "aubergine", " ");
+        assert!(Synonyms::from_bundle(&theirs).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+//! Nested tags, as Obsidian writes them (e.g., "#cuisine/italian" within "#cuisine").
+//!
+//! A tag stands for itself and all the tags nested in it, so that recipes tagged
+//! "cuisine/italian" are found under "cuisine" too. A [`TagTree`] arranges the tags of a
+//! [`RecipeLibrary`](super::RecipeLibrary) in their hierarchy, with how many recipes fall under
+//! each.
+
+/// Whether a tag is `ancestor` or nested in it (e.g., "cuisine/italian" in "cuisine", but not
+/// "cuisines").
+pub fn is_within(tag: &str, ancestor: &str) -> bool {
+    tag.strip_prefix(ancestor)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// A tag in a [`TagTree`], with the tags nested in it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagNode {
+    /// Full tag (e.g., "cuisine/italian").
+    tag: String,
+    depth: usize,
+    children: Vec<TagNode>,
+    /// Recipes tagged with this tag or one nested in it, by index in the library, in increasing
+    /// order.
+    recipes: Vec<usize>,
+}
+
+impl TagNode {
+    /// Full tag, without the leading '#' (e.g., "cuisine/italian").
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Last component of the tag (e.g., "italian").
+    pub fn name(&self) -> &str {
+        self.tag.rsplit('/').next().unwrap_or_default()
+    }
+
+    /// Number of ancestors of the tag (0 for top-level tags).
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Tags nested directly in this one, in alphabetical order.
+    pub fn children(&self) -> &[TagNode] {
+        &self.children
+    }
+
+    /// Number of recipes tagged with this tag or one nested in it.
+    pub fn count(&self) -> usize {
+        self.recipes.len()
+    }
+
+    pub(super) fn recipes(&self) -> &[usize] {
+        &self.recipes
+    }
+}
+
+/// Tags in their hierarchy.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagTree {
+    roots: Vec<TagNode>,
+}
+
+impl TagTree {
+    /// Top-level tags, in alphabetical order.
+    pub fn roots(&self) -> &[TagNode] {
+        &self.roots
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Finds a tag, without its leading '#'.
+    pub fn get(&self, tag: &str) -> Option<&TagNode> {
+        let mut nodes = &self.roots;
+        let mut found = None;
+        for (end, _) in components(tag) {
+            let path = &tag[..end];
+            let node = nodes.iter().find(|n| n.tag == path)?;
+            nodes = &node.children;
+            found = Some(node);
+        }
+        found
+    }
+
+    /// All tags, depth-first, with each tag before those nested in it.
+    pub fn iter(&self) -> impl Iterator<Item = &TagNode> {
+        let mut stack: Vec<&TagNode> = self.roots.iter().rev().collect();
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            Some(node)
+        })
+    }
+
+    /// Adds a recipe's tag, along with the tags it is nested in. Recipes must be added in
+    /// increasing order.
+    pub(super) fn insert(&mut self, tag: &str, recipe: usize) {
+        let mut nodes = &mut self.roots;
+        for (depth, (end, _)) in components(tag).enumerate() {
+            let path = &tag[..end];
+            let i = match nodes.binary_search_by(|n| n.tag.as_str().cmp(path)) {
+                Ok(i) => i,
+                Err(i) => {
+                    let node = TagNode {
+                        tag: path.to_string(),
+                        depth,
+                        ..TagNode::default()
+                    };
+                    nodes.insert(i, node);
+                    i
+                }
+            };
+            let node = &mut nodes[i];
+            if node.recipes.last() != Some(&recipe) {
+                node.recipes.push(recipe);
+            }
+            nodes = &mut node.children;
+        }
+    }
+}
+
+/// Ends of the non-empty components of a tag, with the components.
+fn components(tag: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut start = 0;
+    tag.split('/').filter_map(move |component| {
+        let end = start + component.len();
+        start = end + 1;
+        (!component.is_empty()).then_some((end, component))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_tree() {
+        assert!(is_within("cuisine/italian", "cuisine"));
+        assert!(is_within("cuisine", "cuisine"));
+        assert!(!is_within("cuisines", "cuisine"));
+        assert!(!is_within("cuisine", "cuisine/italian"));
+
+        let mut tree = TagTree::default();
+        tree.insert("cuisine/italian", 0);
+        tree.insert("cuisine", 0);
+        tree.insert("dessert", 1);
+        tree.insert("cuisine/french/lyon", 1);
+        tree.insert("cuisine/italian", 2);
+
+        let tags: Vec<(&str, usize, usize)> = tree
+            .iter()
+            .map(|n| (n.tag(), n.depth(), n.count()))
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("cuisine", 0, 3),
+                ("cuisine/french", 1, 1),
+                ("cuisine/french/lyon", 2, 1),
+                ("cuisine/italian", 1, 2),
+                ("dessert", 0, 1),
+            ]
+        );
+        let italian = tree.get("cuisine/italian").unwrap();
+        assert_eq!(italian.name(), "italian");
+        assert_eq!(italian.recipes(), [0, 2]);
+        assert_eq!(tree.get("cuisine").unwrap().children().len(), 2);
+        assert!(tree.get("cuisine/greek").is_none());
+        assert!(tree.get("").is_none());
+    }
+}
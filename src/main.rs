@@ -0,0 +1,202 @@
+//! Command-line interface to check, render, and scale recipes, and to make shopping lists.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    str::FromStr,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use down_to_cook::{
+    library,
+    recipe::{amount::Amount, lint::RuleSet, Diagnostic, ParseContext, Recipe, Severity},
+    reference,
+    render::{self, OriginalQuantities},
+    shopping::ShoppingList,
+};
+
+#[derive(Parser)]
+#[command(version, about = "Check, render, and scale Markdown recipes")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Reports the errors, warnings, and hints in recipes.
+    Check {
+        /// Recipe, or directory searched recursively for recipes.
+        path: PathBuf,
+        /// Prints the diagnostics as JSON, one object per line, for editors and other tools.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Renders a recipe.
+    Render {
+        file: PathBuf,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Renders a recipe scaled by a factor.
+    Scale {
+        file: PathBuf,
+        /// Scale factor (e.g., "2" or "1/2").
+        #[arg(long, value_parser = parse_factor)]
+        factor: Amount,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Prints the ingredients of recipes as a Markdown task list.
+    ShoppingList {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct Output {
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Width of text output.
+    #[arg(long, default_value_t = 80)]
+    width: usize,
+    /// Highlights timers in text output.
+    #[arg(long)]
+    color: bool,
+    /// Follows scaled quantities with their original quantity in HTML and text output.
+    #[arg(long)]
+    original: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// schema.org Recipe, as JSON-LD.
+    Json,
+    /// Compact JSON for kitchen displays.
+    Cookview,
+    Html,
+    Latex,
+    /// Canonical Markdown.
+    Markdown,
+    Text,
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Check { path, json } => check(&path, json),
+        Command::Render { file, output } => read(&file).map(|recipe| output.print(&recipe)),
+        Command::Scale {
+            file,
+            factor,
+            output,
+        } => read(&file).map(|recipe| output.print(&recipe.scaled(factor))),
+        Command::ShoppingList { files } => files
+            .iter()
+            .map(|file| read(file))
+            .collect::<Result<Vec<_>, _>>()
+            .map(|recipes| print!("{}", ShoppingList::from_recipes(&recipes).to_markdown())),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+impl Output {
+    fn print(&self, recipe: &Recipe) {
+        let original_quantities = match self.original {
+            true => OriginalQuantities::Changed,
+            false => OriginalQuantities::Hidden,
+        };
+        let output = match self.format {
+            Format::Json => format!("{:#}\n", recipe.to_schema_org_json()),
+            Format::Cookview => format!("{}\n", recipe.to_cookview()),
+            Format::Html => render::html::HtmlRenderer::new()
+                .original_quantities(original_quantities)
+                .render(recipe),
+            Format::Latex => render::latex::render(recipe),
+            Format::Markdown => render::markdown::render(recipe),
+            Format::Text => render::text::TextRenderer::new()
+                .width(self.width)
+                .color(self.color)
+                .original_quantities(original_quantities)
+                .render(recipe),
+        };
+        print!("{}", output);
+    }
+}
+
+fn parse_factor(factor: &str) -> Result<Amount, String> {
+    Amount::from_str(factor)
+        .ok()
+        .filter(|f| *f > Amount::ZERO)
+        .ok_or(format!("invalid scale factor {:?}", factor))
+}
+
+fn read(path: &Path) -> Result<Recipe, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Recipe::from_mdast(&content)
+        .map_err(|e| format!("{}: {}", path.display(), e.to_string().trim_end()))
+}
+
+/// Checks every recipe under `path`, failing if any has errors.
+fn check(path: &Path, json: bool) -> Result<(), String> {
+    let files = match path.is_dir() {
+        true => library::markdown_files(path).map_err(|e| format!("{}: {}", path.display(), e))?,
+        false => vec![path.to_path_buf()],
+    };
+    let (mut errors, mut warnings, mut failed) = (0, 0, 0);
+    for file in &files {
+        let content = fs::read_to_string(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+        let mut ctx = ParseContext::new();
+        let result = Recipe::parse_with(&content, &mut ctx);
+        let mut diagnostics = ctx.into_diagnostics();
+        // Parsing already reports the references it cannot resolve.
+        let rules = RuleSet::default().without("unresolved-ref");
+        match result {
+            Ok(recipe) => diagnostics.extend(
+                recipe
+                    .lint(&rules)
+                    .into_iter()
+                    .chain(reference::lint_doneness(&recipe)),
+            ),
+            Err(e) => diagnostics.push(Diagnostic::new(Severity::Error, e)),
+        }
+        if diagnostics.iter().any(|d| d.severity() == Severity::Error) {
+            failed += 1;
+        }
+        for diagnostic in diagnostics {
+            match diagnostic.severity() {
+                Severity::Error => errors += 1,
+                Severity::Warning => warnings += 1,
+                Severity::Hint => (),
+            }
+            if json {
+                let mut object = diagnostic.to_json();
+                object["file"] = file.display().to_string().into();
+                println!("{}", object);
+            } else {
+                // Messages quote the lines they are about.
+                let message = diagnostic.render(&content);
+                println!("{}: {}", file.display(), message.trim_end());
+            }
+        }
+    }
+    if !json {
+        println!(
+            "checked {} recipe(s): {} error(s), {} warning(s)",
+            files.len(),
+            errors,
+            warnings
+        );
+    }
+    match failed {
+        0 => Ok(()),
+        _ => Err(format!("{} recipe(s) with errors", failed)),
+    }
+}
@@ -0,0 +1,265 @@
+//! Fuzzy string matching, for accepting typos in references and suggesting close names.
+//!
+//! Similarity scores range from 0 (nothing in common) to 1 (equal). [`Matcher`] compares names
+//! case-insensitively with one of the [`Metric`]s, and accepts pairs scoring at least its
+//! threshold.
+
+use std::cmp::Ordering;
+
+/// Levenshtein distance between two strings, counted in characters.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(cur)
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+/// Levenshtein distance scaled to a similarity: 1 minus the distance over the length of the
+/// longest string.
+pub fn normalized_levenshtein(a: &str, b: &str) -> f64 {
+    let len = a.chars().count().max(b.chars().count());
+    if len == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / len as f64
+}
+
+/// Jaro-Winkler similarity, which favors strings sharing a prefix (e.g., "tomato" and
+/// "tomatoes").
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    // Characters match if they are equal and not too far apart.
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0;
+    for (i, ca) in a.iter().enumerate() {
+        let range = i.saturating_sub(window)..(i + window + 1).min(b.len());
+        for j in range {
+            if !b_matched[j] && b[j] == *ca {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+    // Matching characters out of order count as half a transposition each.
+    let a_seq = a.iter().zip(&a_matched).filter(|(_, m)| **m);
+    let b_seq = b.iter().zip(&b_matched).filter(|(_, m)| **m);
+    let transpositions = a_seq.zip(b_seq).filter(|((x, _), (y, _))| x != y).count() / 2;
+    let m = matches as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+/// Similarity of two strings as sets of words, ignoring their order and repeated words (e.g.,
+/// "red onion, diced" and "diced red onion" are equal). The words both strings share are
+/// compared, with normalized Levenshtein similarity, against each string's full set of words,
+/// so that a string whose words are all in the other scores high.
+pub fn token_set_ratio(a: &str, b: &str) -> f64 {
+    let words = |s: &str| {
+        let mut words: Vec<String> = s
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        words.sort();
+        words.dedup();
+        words
+    };
+    let (a, b) = (words(a), words(b));
+    let common: Vec<&str> = a
+        .iter()
+        .filter(|w| b.contains(w))
+        .map(String::as_str)
+        .collect();
+    let with_rest = |words: &[String]| {
+        let rest = words.iter().filter(|w| !common.contains(&w.as_str()));
+        common
+            .iter()
+            .copied()
+            .chain(rest.map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let common_text = common.join(" ");
+    let (a_text, b_text) = (with_rest(&a), with_rest(&b));
+    if common.is_empty() {
+        return normalized_levenshtein(&a_text, &b_text);
+    }
+    normalized_levenshtein(&common_text, &a_text)
+        .max(normalized_levenshtein(&common_text, &b_text))
+        .max(normalized_levenshtein(&a_text, &b_text))
+}
+
+/// How a [`Matcher`] scores pairs of strings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Metric {
+    /// [`normalized_levenshtein`], for typos.
+    Levenshtein,
+    /// [`jaro_winkler`], for typos and different endings.
+    #[default]
+    JaroWinkler,
+    /// [`token_set_ratio`], for reordered or extra words.
+    TokenSet,
+}
+
+impl Metric {
+    pub fn score(self, a: &str, b: &str) -> f64 {
+        match self {
+            Self::Levenshtein => normalized_levenshtein(a, b),
+            Self::JaroWinkler => jaro_winkler(a, b),
+            Self::TokenSet => token_set_ratio(a, b),
+        }
+    }
+}
+
+/// Accepts names similar enough to a query, case-insensitively and ignoring surrounding and
+/// repeated whitespace.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matcher {
+    metric: Metric,
+    threshold: f64,
+}
+
+impl Default for Matcher {
+    fn default() -> Self {
+        Self::new(Metric::default())
+    }
+}
+
+impl Matcher {
+    /// Matcher with a threshold of 0.85.
+    pub fn new(metric: Metric) -> Self {
+        Self {
+            metric,
+            threshold: 0.85,
+        }
+    }
+
+    /// Sets the minimum score of a match, between 0 and 1.
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn score(&self, a: &str, b: &str) -> f64 {
+        self.metric.score(&normalize(a), &normalize(b))
+    }
+
+    pub fn is_match(&self, a: &str, b: &str) -> bool {
+        self.score(a, b) >= self.threshold
+    }
+
+    /// Candidates matching the query, by index and score, from best to worst. Ties keep the order
+    /// of the candidates.
+    pub fn ranked<'a>(
+        &self,
+        query: &str,
+        candidates: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<(usize, f64)> {
+        let query = normalize(query);
+        let mut ranked: Vec<(usize, f64)> = candidates
+            .into_iter()
+            .map(|candidate| self.metric.score(&query, &normalize(candidate)))
+            .enumerate()
+            .filter(|(_, score)| *score >= self.threshold)
+            .collect();
+        ranked.sort_by(|x, y| y.1.partial_cmp(&x.1).unwrap_or(Ordering::Equal));
+        ranked
+    }
+
+    /// The best candidate matching the query, by index and score.
+    pub fn best_match<'a>(
+        &self,
+        query: &str,
+        candidates: impl IntoIterator<Item = &'a str>,
+    ) -> Option<(usize, f64)> {
+        self.ranked(query, candidates).into_iter().next()
+    }
+}
+
+fn normalize(s: &str) -> String {
+    s.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(x: f64, expected: f64) -> bool {
+        (x - expected).abs() < 0.001
+    }
+
+    #[test]
+    fn metrics() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("crème", "creme"), 1);
+        assert!(approx(
+            normalized_levenshtein("kitten", "sitting"),
+            4.0 / 7.0
+        ));
+        assert_eq!(normalized_levenshtein("", ""), 1.0);
+
+        // Reference values.
+        assert!(approx(jaro_winkler("martha", "marhta"), 0.961));
+        assert!(approx(jaro_winkler("dwayne", "duane"), 0.840));
+        assert!(approx(jaro_winkler("dixon", "dicksonx"), 0.813));
+        assert_eq!(jaro_winkler("abc", "xyz"), 0.0);
+        assert_eq!(jaro_winkler("", "abc"), 0.0);
+
+        assert_eq!(token_set_ratio("red onion, diced", "Diced red onion"), 1.0);
+        assert_eq!(token_set_ratio("onion", "red onion"), 1.0);
+        assert!(token_set_ratio("red onion", "green pepper") < 0.5);
+    }
+
+    #[test]
+    fn matcher() {
+        let names = ["Tomatoes", "Potatoes", "Olive oil"];
+        let matcher = Matcher::default();
+        assert!(matcher.is_match("tomatos", "Tomatoes"));
+        assert!(!matcher.is_match("cheese", "Tomatoes"));
+        assert_eq!(matcher.best_match("tomatoe", names).map(|m| m.0), Some(0));
+        assert_eq!(matcher.best_match("basil", names), None);
+
+        let ranked: Vec<usize> = matcher
+            .threshold(0.7)
+            .ranked("potatos", names)
+            .iter()
+            .map(|m| m.0)
+            .collect();
+        assert_eq!(ranked, [1, 0]);
+
+        let words = Matcher::new(Metric::TokenSet);
+        assert!(words.is_match("oil  olive", "Olive oil"));
+        assert!(!Matcher::new(Metric::Levenshtein).is_match("oil olive", "Olive oil"));
+    }
+}
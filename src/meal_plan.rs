@@ -0,0 +1,296 @@
+//! Meals planned over days, and the nutrition they add up to.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, IsoWeek, NaiveDate};
+
+use crate::{
+    random::Rng,
+    recipe::{
+        amount::Amount,
+        metadata::Nutrition,
+        unit::{Mass, QuantityOf},
+        Recipe,
+    },
+};
+
+/// A number of servings of a recipe, referred to by name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Meal {
+    pub recipe: String,
+    pub servings: Amount,
+}
+
+/// Daily goals meals are checked against. Weekly goals are the daily goals times the number of
+/// planned days in the week.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NutritionTargets {
+    /// Energy, in kcal.
+    pub calories: Option<Amount>,
+    pub sodium: Option<QuantityOf<Mass>>,
+}
+
+impl Default for NutritionTargets {
+    /// The reference daily intakes used on nutrition labels: 2000 kcal and 2.3 g of sodium.
+    fn default() -> Self {
+        Self {
+            calories: Some(Amount::from_int(2000)),
+            sodium: Some(QuantityOf::new(Mass::Gram, Amount::new(23, 10))),
+        }
+    }
+}
+
+/// A goal of [`NutritionTargets`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    Calories,
+    Sodium,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MealPlan {
+    days: BTreeMap<NaiveDate, Vec<Meal>>,
+    targets: NutritionTargets,
+}
+
+impl MealPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the goals of [`MealPlan::nutrition_summary`].
+    pub fn targets(mut self, targets: NutritionTargets) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    /// Plans servings of a recipe on a day.
+    pub fn add(&mut self, date: NaiveDate, recipe: &str, servings: Amount) {
+        self.days.entry(date).or_default().push(Meal {
+            recipe: recipe.to_string(),
+            servings,
+        });
+    }
+
+    /// Plans a random recipe among `recipes` for each of `days` days from `start`, with
+    /// `servings` each. Recipes are only repeated once all of them have been planned.
+    pub fn generate(
+        &mut self,
+        start: NaiveDate,
+        days: u64,
+        recipes: &[&str],
+        servings: Amount,
+        rng: &mut impl Rng,
+    ) {
+        let mut bag: Vec<&str> = vec![];
+        for day in 0..days {
+            if bag.is_empty() {
+                bag = recipes.to_vec();
+                rng.shuffle(&mut bag);
+            }
+            let (Some(date), Some(recipe)) = (start.checked_add_days(Days::new(day)), bag.pop())
+            else {
+                return;
+            };
+            self.add(date, recipe, servings);
+        }
+    }
+
+    /// Planned days, in order, with their meals.
+    pub fn days(&self) -> impl Iterator<Item = (NaiveDate, &[Meal])> {
+        self.days
+            .iter()
+            .map(|(date, meals)| (*date, meals.as_slice()))
+    }
+
+    /// Adds up the nutrition facts of the meals of each day and of each week, finding recipes with
+    /// `resolver` (e.g., `|name| library.by_name(name).next().map(|e| e.recipe())`). Meals whose
+    /// recipe is not found or has no nutrition facts per serving are reported rather than counted.
+    pub fn nutrition_summary<'a>(
+        &self,
+        resolver: impl Fn(&str) -> Option<&'a Recipe>,
+    ) -> NutritionSummary {
+        let mut days = vec![];
+        let mut weeks: Vec<WeekNutrition> = vec![];
+        for (date, meals) in self.days() {
+            let mut nutrition = Nutrition::default();
+            let mut unknown = vec![];
+            for meal in meals {
+                let per_serving = resolver(&meal.recipe)
+                    .and_then(|recipe| recipe.metadata().nutrition_per_serving());
+                match per_serving {
+                    Some(facts) => nutrition = nutrition + facts.scale(meal.servings),
+                    None => unknown.push(meal.recipe.clone()),
+                }
+            }
+            let week = date.iso_week();
+            match weeks.last_mut() {
+                Some(last) if last.week == week => {
+                    last.nutrition = last.nutrition.clone() + nutrition.clone();
+                    last.days += 1;
+                }
+                _ => weeks.push(WeekNutrition {
+                    week,
+                    days: 1,
+                    nutrition: nutrition.clone(),
+                    exceeded: vec![],
+                }),
+            }
+            days.push(DayNutrition {
+                date,
+                exceeded: self.exceeded(&nutrition, Amount::ONE),
+                nutrition,
+                unknown,
+            });
+        }
+        for week in &mut weeks {
+            week.exceeded = self.exceeded(&week.nutrition, Amount::from_int(week.days as i64));
+        }
+        NutritionSummary { days, weeks }
+    }
+
+    /// Goals that `nutrition` is over, for a number of days.
+    fn exceeded(&self, nutrition: &Nutrition, days: Amount) -> Vec<Goal> {
+        let mut exceeded = vec![];
+        if let (Some(calories), Some(target)) = (nutrition.calories, self.targets.calories) {
+            if calories > target * days {
+                exceeded.push(Goal::Calories);
+            }
+        }
+        if let (Some(sodium), Some(target)) = (nutrition.sodium, self.targets.sodium) {
+            if sodium.as_grams() > target.as_grams() * days {
+                exceeded.push(Goal::Sodium);
+            }
+        }
+        exceeded
+    }
+}
+
+/// Nutrition facts of a meal plan, per day and per week.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NutritionSummary {
+    pub days: Vec<DayNutrition>,
+    pub weeks: Vec<WeekNutrition>,
+}
+
+impl NutritionSummary {
+    /// Days over at least one goal.
+    pub fn flagged_days(&self) -> impl Iterator<Item = &DayNutrition> {
+        self.days.iter().filter(|day| !day.exceeded.is_empty())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DayNutrition {
+    pub date: NaiveDate,
+    pub nutrition: Nutrition,
+    /// Goals the day is over.
+    pub exceeded: Vec<Goal>,
+    /// Recipes of meals left out of the totals.
+    pub unknown: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeekNutrition {
+    pub week: IsoWeek,
+    /// Planned days in the week.
+    pub days: usize,
+    pub nutrition: Nutrition,
+    /// Goals the week is over, for its planned days.
+    pub exceeded: Vec<Goal>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::SeededRng;
+
+    fn recipe(name: &str, servings: u32, calories: u32, sodium: &str) -> Recipe {
+        let content = format!(
+            "---\nservings: {}\nnutrition:\n  calories: {}\n  sodium: {}\n---\n# {}\n\
+             ## Ingredients\n\n- Water, 1 L\n\n## Instructions\n\n- Boil\n",
+            servings, calories, sodium, name
+        );
+        Recipe::from_mdast(&content).unwrap()
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+    }
+
+    #[test]
+    fn generate() {
+        let recipes = ["Lasagna", "Salad", "Ramen"];
+        let plan = |seed| {
+            let mut plan = MealPlan::new();
+            let mut rng = SeededRng::new(seed);
+            plan.generate(date(1), 7, &recipes, Amount::from_int(2), &mut rng);
+            plan
+        };
+        assert_eq!(plan(1), plan(1));
+        let plan = plan(1);
+        let meals: Vec<&str> = plan
+            .days()
+            .map(|(_, meals)| meals[0].recipe.as_str())
+            .collect();
+        assert_eq!(meals.len(), 7);
+        for week in meals.chunks(3) {
+            let mut sorted = week.to_vec();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(sorted.len(), week.len());
+        }
+        let mut empty = MealPlan::new();
+        empty.generate(date(1), 7, &[], Amount::ONE, &mut SeededRng::new(1));
+        assert!(empty.days().next().is_none());
+    }
+
+    #[test]
+    fn nutrition_summary() {
+        let recipes = [
+            recipe("Lasagna", 4, 3200, "4 g"),
+            recipe("Salad", 2, 600, "0.4 g"),
+            recipe("Ramen", 1, 900, "2 g"),
+        ];
+        let resolver = |name: &str| recipes.iter().find(|r| r.name() == name);
+
+        let mut plan = MealPlan::new();
+        // Friday to Monday, over two weeks.
+        plan.add(date(3), "Lasagna", Amount::from_int(2));
+        plan.add(date(3), "Salad", Amount::ONE);
+        plan.add(date(4), "Ramen", Amount::ONE);
+        plan.add(date(4), "Ramen", Amount::ONE);
+        plan.add(date(5), "Salad", Amount::ONE);
+        plan.add(date(5), "Soup", Amount::ONE);
+        plan.add(date(6), "Lasagna", Amount::ONE);
+
+        let summary = plan.nutrition_summary(resolver);
+        let calories: Vec<String> = summary
+            .days
+            .iter()
+            .map(|d| d.nutrition.calories.unwrap().to_string())
+            .collect();
+        assert_eq!(calories, ["1900", "1800", "300", "800"]);
+        assert_eq!(summary.days[1].exceeded, [Goal::Sodium]);
+        assert_eq!(summary.days[2].unknown, ["Soup"]);
+        let flagged: Vec<NaiveDate> = summary.flagged_days().map(|d| d.date).collect();
+        assert_eq!(flagged, [date(4)]);
+
+        assert_eq!(summary.weeks.len(), 2);
+        assert_eq!(summary.weeks[0].days, 3);
+        assert_eq!(
+            summary.weeks[0].nutrition.calories,
+            Some(Amount::from_int(4000))
+        );
+        assert_eq!(summary.weeks[0].exceeded, []);
+
+        let targets = NutritionTargets {
+            calories: Some(Amount::from_int(1300)),
+            sodium: None,
+        };
+        let summary = plan.targets(targets).nutrition_summary(resolver);
+        let flagged: Vec<NaiveDate> = summary.flagged_days().map(|d| d.date).collect();
+        assert_eq!(flagged, [date(3), date(4)]);
+        assert_eq!(summary.weeks[0].exceeded, [Goal::Calories]);
+    }
+}
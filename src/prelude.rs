@@ -0,0 +1,40 @@
+//! The supported API of the crate, for glob imports:
+//!
+//! ```
+//! use down_to_cook::prelude::*;
+//!
+//! let recipe = Recipe::from_mdast("# Toast\n## Ingredients\n\n- Bread, 2\n\n## Instructions\n\n- Toast the *bread*\n").unwrap();
+//! assert_eq!(recipe.ingredients().iter().count(), 1);
+//! ```
+//!
+//! Items re-exported here follow semantic versioning. Other public items may change along with the
+//! parser's internals.
+
+pub use crate::{
+    cook::{CookSession, Timer},
+    household::{Household, Member},
+    library::{bundle::Bundle, LibraryEntry, RecipeLibrary, TagQuery},
+    meal_plan::{MealPlan, NutritionTargets},
+    random::{Rng, SeededRng},
+    recipe::{
+        amount::{Amount, NumberFormat},
+        equipment::{Equipment, Tool},
+        ingredients::{Ingredient, IngredientGroup, IngredientOptions, Ingredients, RefMatching},
+        instructions::{
+            Conditional, IngredientRef, InstructionGroup, Instructions, Numbering, Step, StepPath,
+            TextElem,
+        },
+        language::Language,
+        metadata::{Category, Metadata, Nutrition},
+        notes::{Note, NoteKind, RichText},
+        scaling::ScalePreset,
+        unit::{
+            Dimension, Distance, Mass, NamedUnit, Quantity, QuantityOf, Temperature, Time, Unit,
+            UnitNames, UnitRegistry, Volume,
+        },
+        Diagnostic, EmptyRefPolicy, ErrorCode, HeadingFormatting, MDError, MDResult, ParseContext,
+        Recipe, RecipeParseOptions, SectionNames, Severity,
+    },
+    render::{html::HtmlRenderer, text::TextRenderer, OriginalQuantities},
+    shopping::{ShoppingItem, ShoppingList},
+};
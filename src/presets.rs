@@ -0,0 +1,111 @@
+//! Preset [`Bundle`]s of tables, so that users can enable sensible defaults rather than building
+//! their own tables from scratch.
+//!
+//! Built-in packs are:
+//! - `eu-metric` and `us-imperial`: preferred units for masses, volumes, distances, and
+//!   temperatures (see [`preferred_unit`]);
+//! - `densities`: densities of common ingredients;
+//! - `allergens`: the allergens of common ingredients.
+
+use std::{fs, path::Path};
+
+use crate::{
+    library::bundle::{Bundle, Conflict, MergePolicy, UNITS},
+    recipe::{
+        unit::{Dimension, Quantity, Unit},
+        MDError, MDResult,
+    },
+};
+
+/// Built-in packs, by name.
+pub const BUILTIN: [(&str, &str); 4] = [
+    ("eu-metric", include_str!("presets/eu-metric.json")),
+    ("us-imperial", include_str!("presets/us-imperial.json")),
+    ("densities", include_str!("presets/densities.json")),
+    ("allergens", include_str!("presets/allergens.json")),
+];
+
+/// Loads a built-in pack by name, or else a bundle from a file.
+pub fn load(name_or_path: &str) -> MDResult<Bundle> {
+    match BUILTIN.iter().find(|(name, _)| *name == name_or_path) {
+        Some((_, json)) => Bundle::from_json(json),
+        None => {
+            let json = fs::read_to_string(Path::new(name_or_path)).map_err(|e| {
+                MDError::new(
+                    &format!("failed to read preset pack {:?}: {}", name_or_path, e),
+                    None,
+                )
+                .with_source(e)
+            })?;
+            Bundle::from_json(&json)
+        }
+    }
+}
+
+/// Loads and merges several packs, earlier packs taking precedence over later ones.
+pub fn load_all(names_or_paths: &[&str]) -> MDResult<(Bundle, Vec<Conflict>)> {
+    let mut bundle = Bundle::new();
+    let mut conflicts = vec![];
+    for name_or_path in names_or_paths {
+        conflicts.extend(bundle.merge(load(name_or_path)?, MergePolicy::KeepOurs));
+    }
+    Ok((bundle, conflicts))
+}
+
+/// Unit that the `units` table of a bundle prefers for the kind of a quantity (e.g., "g" for
+/// masses), for use with [`Recipe::converted`](crate::recipe::Recipe::converted).
+pub fn preferred_unit(bundle: &Bundle, quantity: &Quantity) -> Option<Unit> {
+    let dimension = quantity.unit.dimension();
+    let kind = match dimension {
+        Dimension::Mass => "mass",
+        Dimension::Volume => "volume",
+        Dimension::Length => "distance",
+        Dimension::Temperature => "temperature",
+        _ => return None,
+    };
+    let unit: Unit = bundle.get(UNITS, kind)?.parse().ok()?;
+    (unit.dimension() == dimension).then_some(unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{library::bundle::DENSITIES, recipe::Recipe};
+    use indoc::indoc;
+
+    #[test]
+    fn builtin() -> MDResult<()> {
+        for (name, _) in BUILTIN {
+            assert!(load(name)?.tables().count() > 0);
+        }
+        assert!(load("no-such-pack").is_err());
+
+        let (bundle, conflicts) = load_all(&["eu-metric", "us-imperial", "densities"])?;
+        assert_eq!(conflicts.len(), 4);
+        assert_eq!(bundle.get(UNITS, "mass"), Some("g"));
+        assert_eq!(bundle.get(DENSITIES, "water"), Some("1 g/ml"));
+
+        let recipe = Recipe::from_mdast(indoc! {"
+            # Pancakes
+            ## Ingredients
+
+            - Flour, 1 cup
+            - Milk, 2 lbs
+
+            ## Instructions
+
+            - Whisk
+        "})?;
+        let bundle = load("eu-metric")?;
+        let recipe = recipe.converted(|q| preferred_unit(&bundle, q));
+        let quantities: Vec<String> = recipe
+            .ingredients()
+            .iter()
+            .map(|i| i.ingredient().quantity().unwrap().to_string())
+            .collect();
+        assert_eq!(quantities.len(), 2);
+        assert!(quantities[0].ends_with(" mL"));
+        assert!(quantities[1].ends_with(" g"));
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+//! Reproducible randomness for suggestions (e.g., the recipe of the day, generated menus).
+//!
+//! Random picks take an [`Rng`] rather than drawing from a global source, so that they can be
+//! replayed from a seed: tests pin their results, and devices seeding with the same value (e.g.,
+//! the date, with [`SeededRng::for_day`]) make the same picks. The built-in generator's output is
+//! fixed across platforms and versions of the crate.
+
+use chrono::{Datelike, NaiveDate};
+
+/// Source of random numbers.
+pub trait Rng {
+    /// Next random number, uniformly distributed over all `u64`s.
+    fn next_u64(&mut self) -> u64;
+
+    /// Random index below `n`, uniformly distributed. Panics if `n` is 0.
+    fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "cannot draw below 0");
+        let n = n as u64;
+        // Rejecting the top values that would wrap around unevenly avoids a bias towards small
+        // indices.
+        let limit = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < limit {
+                return (x % n) as usize;
+            }
+        }
+    }
+
+    /// Random item of a slice, or `None` if it is empty.
+    fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T>
+    where
+        Self: Sized,
+    {
+        match items.len() {
+            0 => None,
+            len => items.get(self.below(len)),
+        }
+    }
+
+    /// Shuffles a slice in place, with every order equally likely.
+    fn shuffle<T>(&mut self, items: &mut [T])
+    where
+        Self: Sized,
+    {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// SplitMix64 generator: small, fast, and good enough for suggestions, but not for cryptography.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Generator seeded by a day, which is the same on all devices.
+    pub fn for_day(date: NaiveDate) -> Self {
+        Self::new(date.num_days_from_ce() as u64)
+    }
+}
+
+impl Rng for SeededRng {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        // Reference output of SplitMix64 for seed 0.
+        let mut rng = SeededRng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+
+        let draws = |seed| -> Vec<usize> {
+            let mut rng = SeededRng::new(seed);
+            (0..20).map(|_| rng.below(6)).collect()
+        };
+        assert_eq!(draws(42), draws(42));
+        assert_ne!(draws(42), draws(43));
+        assert!(draws(42).iter().all(|i| *i < 6));
+
+        let day = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        assert_eq!(SeededRng::for_day(day), SeededRng::for_day(day));
+        assert_ne!(
+            SeededRng::for_day(day),
+            SeededRng::for_day(day.succ_opt().unwrap())
+        );
+    }
+
+    #[test]
+    fn choose_and_shuffle() {
+        let mut rng = SeededRng::new(7);
+        assert_eq!(rng.choose::<u8>(&[]), None);
+        assert_eq!(rng.choose(&[1]), Some(&1));
+
+        let mut items: Vec<u32> = (0..10).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..10).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+
+        // Every index comes up.
+        let mut seen = [false; 4];
+        for _ in 0..100 {
+            seen[rng.below(4)] = true;
+        }
+        assert_eq!(seen, [true; 4]);
+    }
+}
@@ -1,65 +1,457 @@
-mod ingredients;
-mod instructions;
+pub mod amount;
+pub mod bread;
+pub mod canning;
+pub mod coffee;
+#[cfg(feature = "cooklang")]
+pub mod cooklang;
+pub mod cookview;
+pub mod density;
+pub mod drinks;
+pub mod equipment;
+pub mod history;
+pub mod ingredients;
+pub mod instructions;
+pub mod language;
+pub mod lint;
 mod md_parser;
-mod metadata;
-mod unit;
+pub mod metadata;
+pub mod notes;
+pub mod rating;
+pub mod reparse;
+pub mod scaling;
+pub mod schema_org;
+pub mod source;
+pub mod unit;
 
-use ingredients::Ingredients;
+use std::collections::HashMap;
+
+use amount::Amount;
+use equipment::Equipment;
+use ingredients::{IngredientOptions, Ingredients, RefMatching};
 use instructions::Instructions;
+use language::Language;
+use lint::{AltQuantities, RuleSet};
 use markdown::{self, mdast::Node};
-use md_parser::{get_heading, get_parse_options, ASTConsumer, MDError, MDResult};
+pub(crate) use md_parser::note_name;
+use md_parser::{get_heading, get_heading_with, get_parse_options, ASTConsumer};
+pub use md_parser::{
+    Diagnostic, EmptyRefPolicy, ErrorCode, HeadingFormatting, MDError, MDResult, MarkdownMessage,
+    ParseContext, RecipeParseOptions, SectionNames, Severity,
+};
 use metadata::Metadata;
+use notes::{Note, NoteKind, RichText};
+use unit::{QuantityOf, Time};
 
+#[derive(Clone, PartialEq, Debug)]
 pub struct Recipe {
     name: String,
     ingredients: Ingredients,
+    equipment: Equipment,
     instructions: Instructions,
+    notes: Vec<Note>,
     metadata: Metadata,
+    /// Image right after the name, overriding the one in the frontmatter.
+    cover: Option<Image>,
+    /// Paragraphs between the name (or cover image) and the first section.
+    description: Vec<Vec<RichText>>,
+    /// Language of the section headings, if known.
+    language: Option<Language>,
+}
+
+/// Image of a recipe or step (e.g., "![Sliced loaf](loaf.jpg)").
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Image {
+    pub url: String,
+    /// Alternative text, empty when unknown.
+    pub alt: String,
 }
 
+const ALT_QUANTITY_TOLERANCE: Amount = Amount::new(1, 10);
+
 impl Recipe {
     pub fn from_mdast(content: &str) -> MDResult<Self> {
+        let mut ctx = ParseContext::new();
+        let result = Self::parse_with(content, &mut ctx);
+        ctx.finish(result)
+    }
+
+    /// Parses a recipe with non-default options (e.g., other section headings, or custom units).
+    pub fn from_mdast_with_options(content: &str, options: &RecipeParseOptions) -> MDResult<Self> {
+        let mut ctx = ParseContext::with_options(options.clone());
+        let result = Self::parse_with(content, &mut ctx);
+        ctx.finish(result)
+    }
+
+    /// Parses a recipe, returning it along with the warnings and hints raised by questionable
+    /// constructs (e.g., empty steps). In strict mode, warnings are errors.
+    pub fn from_mdast_with_diagnostics(
+        content: &str,
+        strict: bool,
+    ) -> MDResult<(Self, Vec<Diagnostic>)> {
+        let mut ctx = ParseContext::new().strict(strict);
+        let result = Self::parse_with(content, &mut ctx);
+        let diagnostics = ctx.take_diagnostics();
+        ctx.finish(result).map(|recipe| (recipe, diagnostics))
+    }
+
+    /// Parses a recipe, carrying on past errors in individual ingredients, steps, and metadata
+    /// entries to report all of them at once (in document order). Errors in the overall structure
+    /// of the recipe (e.g., a missing section) still stop parsing.
+    pub fn parse_all_errors(content: &str) -> Result<Self, Vec<MDError>> {
+        let mut ctx = ParseContext::new();
+        let result = Self::parse_with(content, &mut ctx);
+        let mut errors = ctx.into_errors();
+        match result {
+            Ok(recipe) if errors.is_empty() => Ok(recipe),
+            Ok(_) => Err(errors),
+            Err(e) => {
+                errors.push(e);
+                Err(errors)
+            }
+        }
+    }
+
+    /// Parses a recipe, recording recoverable errors in `ctx`.
+    pub fn parse_with(content: &str, ctx: &mut ParseContext) -> MDResult<Self> {
         let md = markdown::to_mdast(content, &get_parse_options())?;
         match md.children() {
             Some(children) => {
                 let mut ast_cons = ASTConsumer::new(children);
 
                 // Attempt to parse (optional) metadata and recipe name.
-                let first_node = ast_cons.next()?;
+                let first_node = ast_cons.next().map_err(|_| {
+                    MDError::new("empty file", None).with_code(ErrorCode::EmptyFile)
+                })?;
+                let formatting = ctx.heading_formatting_mode();
+                let title = |node: MDResult<&Node>| {
+                    node.and_then(|node| get_heading_with(node, 1, None, formatting))
+                        .map_err(|e| e.with_code(ErrorCode::MissingTitle))
+                };
                 let (metadata, name): (Metadata, String) = match &first_node {
                     Node::Yaml(yaml) => (
-                        Metadata::parse(yaml)?,
-                        get_heading(ast_cons.next()?, 1, None)?,
+                        ctx.within("while parsing frontmatter", |ctx| {
+                            Metadata::parse_with(yaml, ctx)
+                        })?,
+                        title(ast_cons.next())?,
                     ),
-                    Node::Heading(_) => (Metadata::default(), get_heading(first_node, 1, None)?),
+                    Node::Heading(_) => (Metadata::default(), title(Ok(first_node))?),
                     _ => Err(MDError::new(
                         "expected YAML frontmatter of heading",
                         Some(first_node),
-                    ))?,
+                    )
+                    .with_code(ErrorCode::MissingTitle))?,
                 };
 
+                let cover = Self::parse_cover(&mut ast_cons);
+                let description = ctx.within("while parsing description", |_| {
+                    Self::parse_description(&mut ast_cons)
+                })?;
+
                 // Attempt to parse "Ingredients" and "Instructions" sections.
-                get_heading(ast_cons.next()?, 2, Some("Ingredients"))?;
-                let ingredients = Ingredients::parse(ast_cons.consume_to_next_heading(2))?;
-                get_heading(ast_cons.next()?, 2, Some("Instructions"))?;
-                let instructions = Instructions::parse(ast_cons.consume_to_next_heading(2))?;
+                // An optional "Equipment" section may come before or after the ingredients.
+                if let Some(heading) = ast_cons.peek() {
+                    ctx.detect_language(heading);
+                }
+                let sections = ctx.section_names().clone();
+                let mut equipment = Self::parse_equipment(&mut ast_cons, &metadata, ctx)?;
 
-                Ok(Self {
+                // Either may be empty (or a placeholder) in draft recipes.
+                let missing = |e: MDError| e.with_code(ErrorCode::MissingIngredientsSection);
+                let heading = ast_cons.next().map_err(missing)?;
+                get_heading(heading, 2, Some(&sections.ingredients)).map_err(missing)?;
+                let context = format!("while parsing section '{}'", sections.ingredients);
+                let ingredients = ctx.within(&context, |ctx| {
+                    Ingredients::parse_with(ast_cons.consume_to_next_heading(2), ctx)
+                        .map_err(|e| e.or_code(ErrorCode::MalformedIngredient))
+                })?;
+                if ingredients.is_empty() {
+                    ctx.warn(
+                        MDError::new("no ingredients", Some(heading))
+                            .with_code(ErrorCode::NoIngredients),
+                    );
+                }
+                if equipment.is_none() {
+                    equipment = Self::parse_equipment(&mut ast_cons, &metadata, ctx)?;
+                }
+                let missing = |e: MDError| e.with_code(ErrorCode::MissingInstructionsSection);
+                let heading = ast_cons.next().map_err(missing)?;
+                get_heading(heading, 2, Some(&sections.instructions)).map_err(missing)?;
+                let context = format!("while parsing section '{}'", sections.instructions);
+                let instructions = ctx.within(&context, |ctx| {
+                    Instructions::parse_with(ast_cons.consume_to_next_heading(2), ctx)
+                        .map_err(|e| e.or_code(ErrorCode::MalformedStep))
+                })?;
+                if instructions.steps().is_empty() {
+                    ctx.warn(
+                        MDError::new("no instructions", Some(heading))
+                            .with_code(ErrorCode::NoInstructions),
+                    );
+                }
+                let notes = Self::parse_notes(&mut ast_cons, ctx)?;
+
+                let mut recipe = Self {
                     name,
                     ingredients,
+                    equipment: equipment.unwrap_or_default(),
                     instructions,
+                    notes,
                     metadata,
-                })
+                    cover,
+                    description,
+                    language: ctx.section_language(),
+                };
+                for error in recipe.resolve_ingredient_refs(RefMatching::Exact) {
+                    ctx.warn(error);
+                }
+                Ok(recipe)
             }
-            None => Err(MDError::new("empty file", None)),
+            None => Err(MDError::new("empty file", None).with_code(ErrorCode::EmptyFile)),
         }
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ingredients(&self) -> &Ingredients {
+        &self.ingredients
+    }
+
+    pub fn equipment(&self) -> &Equipment {
+        &self.equipment
+    }
+
+    pub fn instructions(&self) -> &Instructions {
+        &self.instructions
+    }
+
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Metadata to update, before writing the recipe back (see
+    /// [`update_frontmatter`](crate::render::markdown::update_frontmatter)).
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Paragraphs of the description, which comes between the name and the first section.
+    pub fn description(&self) -> &[Vec<RichText>] {
+        &self.description
+    }
+
+    /// Text content of the description, with paragraphs separated by blank lines.
+    pub fn description_text(&self) -> Option<String> {
+        let paragraphs: Vec<String> = self
+            .description
+            .iter()
+            .map(|para| para.iter().map(|t| t.to_string()).collect())
+            .collect();
+        (!paragraphs.is_empty()).then(|| paragraphs.join("\n\n"))
+    }
+
+    /// Language of the section headings (e.g., German for "## Zutaten"), detected while parsing
+    /// unless set in the [`RecipeParseOptions`]. Unknown for recipes parsed with custom headings.
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    /// Headings of the recipe's sections, in its language (or else in English).
+    pub fn section_names(&self) -> SectionNames {
+        self.language.unwrap_or(Language::English).section_names()
+    }
+
+    /// Cover image: a leading image (e.g., "![Sliced loaf](loaf.jpg)" right after the name), or
+    /// else the frontmatter's `image`.
+    pub fn image(&self) -> Option<&Image> {
+        self.cover.as_ref().or(self.metadata.image())
+    }
+
+    /// Leading image, if the cover image is not in the frontmatter.
+    pub(crate) fn leading_image(&self) -> Option<&Image> {
+        self.cover.as_ref()
+    }
+
+    /// Parses a paragraph holding just an image, if it comes next.
+    fn parse_cover(ast_cons: &mut ASTConsumer) -> Option<Image> {
+        let Some(Node::Paragraph(para)) = ast_cons.peek() else {
+            return None;
+        };
+        let [Node::Image(image)] = para.children.as_slice() else {
+            return None;
+        };
+        ast_cons.next().ok()?;
+        Some(Image {
+            url: image.url.clone(),
+            alt: image.alt.clone(),
+        })
+    }
+
+    /// Parses the paragraphs and blockquotes coming next, which describe the recipe.
+    fn parse_description(ast_cons: &mut ASTConsumer) -> MDResult<Vec<Vec<RichText>>> {
+        let mut paragraphs = vec![];
+        while let Some(node @ (Node::Paragraph(_) | Node::Blockquote(_))) = ast_cons.peek() {
+            let nodes = match node {
+                Node::Blockquote(quote) => &quote.children[..],
+                _ => std::slice::from_ref(node),
+            };
+            for node in nodes {
+                match node {
+                    Node::Paragraph(para) => paragraphs.push(RichText::parse_all(&para.children)?),
+                    _ => {
+                        return Err(
+                            MDError::new("expected paragraph in description", Some(node))
+                                .with_code(ErrorCode::MalformedDescription),
+                        )
+                    }
+                }
+            }
+            ast_cons.next()?;
+        }
+        Ok(paragraphs)
+    }
+
+    /// Parses the "Equipment" section if it comes next.
+    fn parse_equipment(
+        ast_cons: &mut ASTConsumer,
+        metadata: &Metadata,
+        ctx: &mut ParseContext,
+    ) -> MDResult<Option<Equipment>> {
+        match ast_cons.peek() {
+            Some(node) if get_heading(node, 2, Some(&ctx.section_names().equipment)).is_ok() => {
+                ast_cons.next()?;
+                let nodes = ast_cons.consume_to_next_heading(2);
+                Equipment::parse_with(nodes, metadata.sizes(), ctx).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Parses the optional "Notes" and "Tips" sections following the instructions, in any order.
+    /// Other sections (e.g., "Variations") are skipped with a warning.
+    fn parse_notes(ast_cons: &mut ASTConsumer, ctx: &mut ParseContext) -> MDResult<Vec<Note>> {
+        let mut notes = vec![];
+        let mut seen = vec![];
+        let sections = ctx.section_names().clone();
+        while let Some(heading) = ast_cons.peek() {
+            let kind = NoteKind::ALL
+                .into_iter()
+                .find(|kind| get_heading(heading, 2, Some(sections.note_heading(*kind))).is_ok());
+            let Some(kind) = kind else {
+                let msg = format!(
+                    "expected \"{}\" or \"{}\" section, ignoring section",
+                    sections.notes, sections.tips
+                );
+                ctx.warn(MDError::new(&msg, Some(heading)).with_code(ErrorCode::UnknownSection));
+                ast_cons.next()?;
+                ast_cons.consume_to_next_heading(2);
+                continue;
+            };
+            if seen.contains(&kind) {
+                return Err(MDError::new(
+                    &format!("duplicate \"{}\" section", sections.note_heading(kind)),
+                    Some(heading),
+                )
+                .with_code(ErrorCode::DuplicateSection));
+            }
+            seen.push(kind);
+            ast_cons.next()?;
+            notes.extend(Note::parse_section(
+                kind,
+                ast_cons.consume_to_next_heading(2),
+                ctx,
+            ));
+        }
+        Ok(notes)
+    }
+
+    /// Estimates how long the recipe takes: the total time from the metadata, or else the sum of
+    /// the prep and cook times, or else the sum of the timers in the instructions.
+    pub fn estimated_time(&self) -> Option<QuantityOf<Time>> {
+        let meta = &self.metadata;
+        if let Some(total) = meta.total_time() {
+            return Some(*total);
+        }
+        [meta.prep_time(), meta.cook_time()]
+            .into_iter()
+            .flatten()
+            .copied()
+            .reduce(|sum, time| sum + time)
+            .or_else(|| {
+                self.instructions
+                    .timers()
+                    .map(|(_, time)| time)
+                    .reduce(|sum, time| sum + time)
+            })
+    }
+
+    /// Returns whether the recipe has both ingredients and instructions, as opposed to a draft.
+    pub fn is_complete(&self) -> bool {
+        !self.ingredients.is_empty() && !self.instructions.steps().is_empty()
+    }
+
+    /// Runs the checks that are too costly or too opinionated for parsing, returning a warning
+    /// for each problem found. Currently, this checks that the alternative quantities of each
+    /// ingredient (e.g., "15 mL / 3 tsp / 1 tbsp") agree, within 10%.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        self.lint(&RuleSet::empty().rule(AltQuantities::default()))
+    }
+
+    /// Links every ingredient reference in the instructions to the ingredient it names, and
+    /// returns one error per unresolved or ambiguous reference. Such references are left
+    /// unresolved; parsing already performs an exact resolution pass, whose errors it reports as
+    /// warnings.
+    pub fn resolve_ingredient_refs(&mut self, matching: RefMatching) -> Vec<MDError> {
+        let mut errors = vec![];
+        let ingredients = &self.ingredients;
+        self.instructions
+            .for_each_ref_mut(|r| match ingredients.resolve(r.name(), matching) {
+                Ok(index) => r.set_index(Some(index)),
+                Err(e) => {
+                    r.set_index(None);
+                    errors.push(e.or_position(r.position()));
+                }
+            });
+        errors
+    }
+
+    /// Returns the recipe with, for each ingredient line, the alternative chosen by `choose` (as
+    /// an index in [`IngredientOptions::alternatives`]) as the main ingredient. References in
+    /// steps to a replaced ingredient by its name are renamed after its replacement.
+    pub fn with_alternatives(&self, choose: impl Fn(&IngredientOptions) -> Option<usize>) -> Self {
+        let mut recipe = self.clone();
+        let mut renamed = HashMap::new();
+        for (index, options) in recipe.ingredients.iter_mut().enumerate() {
+            let Some(alternative) = choose(options) else {
+                continue;
+            };
+            if let Some(names) = options.swap_alternative(alternative) {
+                renamed.insert(index, names);
+            }
+        }
+        recipe.instructions.for_each_ref_mut(|r| {
+            let names = r.index().and_then(|index| renamed.get(&index));
+            if let Some((old, new)) = names {
+                if r.name().eq_ignore_ascii_case(old) {
+                    r.set_name(new);
+                }
+            }
+        });
+        recipe
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use amount::NumberFormat;
     use indoc::indoc;
+    use std::str::FromStr;
+    use unit::{Dimension, Mass, Quantity, Unit, UnitRegistry};
 
     #[test]
     fn parse_recipe() -> MDResult<()> {
@@ -76,4 +468,686 @@ pub mod tests {
         Recipe::from_mdast(content)?;
         Ok(())
     }
+
+    #[test]
+    fn validate() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(indoc! {"
+            # Test recipe
+            ## Ingredients
+
+            - Soy sauce, 15 mL / 3 tsp / 1 tbsp
+            - Mirin, 15 mL / 2 tbsp
+            - Onions, 2 / 300 g
+            - Butter, 1 cup / 240 mL | Oil, 1 cup / 2 cup
+
+            ## Instructions
+
+            - Mix
+        "})?;
+        let warnings: Vec<String> = recipe
+            .validate()
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "warning: alternative quantity \"2 tbsp\" of \"Mirin\" disagrees with \"15 mL\"\n",
+                "warning: alternative quantity \"2 cup\" of \"Oil\" disagrees with \"1 cup\"\n",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_alternative_headings() -> MDResult<()> {
+        let atx = indoc! {"
+            # Test recipe
+            ## Ingredients
+
+            - Lemons, 1
+
+            ## Instructions
+
+            - Squeeze the *Lemons*
+        "};
+        let setext = indoc! {"
+            Test
+            recipe
+            ======
+
+            Ingredients
+            -----------
+
+            - Lemons, 1
+
+            ## Instructions ##
+
+            - Squeeze the *Lemons*
+        "};
+        assert_eq!(Recipe::from_mdast(setext)?, Recipe::from_mdast(atx)?);
+
+        let with_frontmatter = format!("---\ntags:\n  - \"#tag\"\n---\n{}", setext);
+        assert_eq!(Recipe::from_mdast(&with_frontmatter)?.name(), "Test recipe");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_crlf_and_bom() -> MDResult<()> {
+        let content = indoc! {"
+            ---
+            tags:
+              - \"#tag\"
+            quantity: 1 L
+            ---
+            # Test recipe
+            ## Ingredients
+
+            - Lemons, 1 (cold)
+            - Milk, 1/2 cup
+
+            ## Instructions
+
+            - Squeeze the *Lemons*
+              and keep stirring
+        "};
+        let unix = Recipe::from_mdast(content)?;
+        let crlf = content.replace('\n', "\r\n");
+        assert_eq!(Recipe::from_mdast(&crlf)?, unix);
+        assert_eq!(Recipe::from_mdast(&format!("\u{feff}{}", crlf))?, unix);
+        assert_eq!(Recipe::from_mdast(&format!("\u{feff}{}", content))?, unix);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_draft() -> MDResult<()> {
+        let content = indoc! {"
+            # Test recipe
+            ## Ingredients
+
+            TBD
+
+            ## Instructions
+        "};
+        let (recipe, diagnostics) = Recipe::from_mdast_with_diagnostics(content, false)?;
+        assert!(!recipe.is_complete());
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "warning: expected ingredient list, ignoring placeholder @ 4:1-4:4\n",
+                "warning: no ingredients @ 2:1-2:15\n",
+                "warning: no instructions @ 6:1-6:16\n",
+            ]
+        );
+        assert!(Recipe::from_mdast_with_diagnostics(content, true).is_err());
+
+        // Only placeholder text stands in for a section, not arbitrary paragraphs.
+        let content = indoc! {"
+            # Test recipe
+            ## Ingredients
+
+            - Flour, 100 g
+
+            ## Instructions
+
+            …
+        "};
+        let (_, diagnostics) = Recipe::from_mdast_with_diagnostics(content, false)?;
+        assert_eq!(
+            diagnostics[0].code(),
+            Some(ErrorCode::InstructionsPlaceholder)
+        );
+        let content = indoc! {"
+            # Test recipe
+            ## Ingredients
+
+            Flour and water, mixed.
+
+            ## Instructions
+        "};
+        assert!(Recipe::from_mdast_with_diagnostics(content, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn cover_image() -> MDResult<()> {
+        let content = indoc! {"
+            ---
+            image: lemons.jpg
+            ---
+            # Lemonade
+
+            ![A glass of lemonade](lemonade.jpg)
+
+            ## Ingredients
+
+            - Lemons, 1
+
+            ## Instructions
+
+            - Squeeze the *Lemons*
+        "};
+        let recipe = Recipe::from_mdast(content)?;
+        let image = recipe.image().unwrap();
+        assert_eq!(image.url, "lemonade.jpg");
+        assert_eq!(image.alt, "A glass of lemonade");
+
+        let recipe =
+            Recipe::from_mdast(&content.replace("![A glass of lemonade](lemonade.jpg)", ""))?;
+        assert_eq!(recipe.image().unwrap().url, "lemons.jpg");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_equipment() -> MDResult<()> {
+        let before = indoc! {"
+            ---
+            size | pan: 24 cm°
+            ---
+            # Test recipe
+            ## Equipment
+
+            - Springform pan, pan
+
+            ## Ingredients
+
+            - Lemons, 1
+
+            ## Instructions
+
+            - Squeeze the *Lemons*
+        "};
+        let recipe = Recipe::from_mdast(before)?;
+        assert_eq!(recipe.equipment().tools()[0].name(), "Springform pan");
+
+        // The section may also follow the ingredients.
+        let after = indoc! {"
+            ---
+            size | pan: 24 cm°
+            ---
+            # Test recipe
+            ## Ingredients
+
+            - Lemons, 1
+
+            ## Equipment
+
+            - Springform pan, pan
+
+            ## Instructions
+
+            - Squeeze the *Lemons*
+        "};
+        assert_eq!(Recipe::from_mdast(after)?, recipe);
+        Ok(())
+    }
+
+    #[test]
+    fn estimated_time() -> MDResult<()> {
+        let content = indoc! {"
+            ---
+            prep_time: 15 min
+            cook_time: 1 h
+            ---
+            # Test recipe
+            ## Ingredients
+
+            - Lemons, 1
+
+            ## Instructions
+
+            - Squeeze the *Lemons* for **1 min**
+                - Wait for **10-20 min**
+        "};
+        let time = |content: &str| Recipe::from_mdast(content).map(|r| r.estimated_time());
+        assert_eq!(time(content)?.unwrap().to_string(), "75 min");
+        let with_total = content.replace("cook_time", "total_time");
+        assert_eq!(time(&with_total)?.unwrap().to_string(), "1 h");
+        let without_meta = content.split_once("# Test").unwrap().1;
+        assert_eq!(
+            time(&format!("# Test{}", without_meta))?
+                .unwrap()
+                .to_string(),
+            "11-21 min"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_notes() -> MDResult<()> {
+        let content = indoc! {"
+            # Test recipe
+            ## Ingredients
+
+            - Lemons, 1
+
+            ## Instructions
+
+            - Squeeze the *Lemons*
+
+            ## Tips
+
+            - Roll the lemons first
+
+            ## Notes
+
+            Keeps for **2 days**.
+        "};
+        let recipe = Recipe::from_mdast(content)?;
+        let notes = recipe.notes();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].kind(), NoteKind::Tip);
+        assert_eq!(notes[1].text(), "Keeps for 2 days.");
+
+        // Other sections are ignored with a warning, which is an error in strict mode.
+        let unknown = content.replace("## Notes", "## Variations");
+        let (recipe, diagnostics) = Recipe::from_mdast_with_diagnostics(&unknown, false)?;
+        assert_eq!(recipe.notes().len(), 1);
+        assert_eq!(diagnostics[0].code(), Some(ErrorCode::UnknownSection));
+        assert!(Recipe::from_mdast_with_diagnostics(&unknown, true).is_err());
+        let before = content.replace("## Tips", "## Variations\n\n- Use limes\n\n## Tips");
+        assert_eq!(Recipe::from_mdast(&before)?.notes().len(), 2);
+        let duplicate = content.replace("## Notes", "## Tips");
+        assert!(Recipe::from_mdast(&duplicate).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_all_errors() {
+        let content = indoc! {"
+            ---
+            tags:
+              - \"no-hash\"
+            ---
+            # Test recipe
+            ## Ingredients
+
+            - Lemons, a few
+            - Milk, 50 mL
+            - Paprika powder, 1 tbsp (optional
+
+            ## Instructions
+
+            - Squeeze the *Lemons*
+            - Wait for <Timer />
+        "};
+        let errors = Recipe::parse_all_errors(content).unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert!(errors[0].message().contains("tag"));
+        assert!(errors[3].message().contains("unsupported"));
+        assert_eq!(
+            Recipe::from_mdast(content).unwrap_err().message(),
+            errors[0].message()
+        );
+
+        // Structural errors are reported after the recoverable ones.
+        let errors = Recipe::parse_all_errors("# Test recipe\n## Ingredients\n- Lemons, a few\n")
+            .unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[1].message().contains("EOF"));
+    }
+
+    #[test]
+    fn error_context() {
+        let content = indoc! {"
+            # Test recipe
+            ## Ingredients
+
+            ### Dough
+            - Flour, 500 g
+            ### Sauce
+            - Tomatoes, a few
+
+            ## Instructions
+
+            - Make the dough
+            - Make the sauce
+              - Wait for <Timer />
+        "};
+        let errors = Recipe::parse_all_errors(content).unwrap_err();
+        assert_eq!(
+            errors[0].context(),
+            [
+                "while parsing ingredient group 'Sauce'",
+                "while parsing section 'Ingredients'"
+            ]
+        );
+        assert_eq!(
+            errors[1].context(),
+            [
+                "while parsing step 1",
+                "while parsing step 2",
+                "while parsing section 'Instructions'"
+            ]
+        );
+        assert!(errors[1].to_string().ends_with(
+            "\n  while parsing step 1\n  while parsing step 2\n  while parsing section 'Instructions'\n"
+        ));
+    }
+
+    #[test]
+    fn parse_diagnostics() -> MDResult<()> {
+        let content = indoc! {"
+            ---
+            tags:
+              - \"#tag\"
+              - \"#tag\"
+            unknown: value
+            ---
+            # Test recipe
+            ## Ingredients
+
+            - Lemons, 1
+
+            ## Instructions
+
+            - Squeeze the *Lemons*
+            -
+        "};
+        let (recipe, diagnostics) = Recipe::from_mdast_with_diagnostics(content, false)?;
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "warning: duplicate tag \"tag\" @ 2:1-2:6\n",
+                "warning: unknown metadata key \"unknown\" @ 5:1-5:15\n",
+                "warning: empty step @ 15:1-15:2\n",
+            ]
+        );
+        assert_eq!(recipe, Recipe::from_mdast(content)?);
+        assert!(recipe.is_complete());
+        assert!(Recipe::from_mdast_with_diagnostics(content, true).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn registered_units() -> MDResult<()> {
+        let content = indoc! {"
+            # Cookies
+            ## Ingredients
+
+            ### Dough (makes 2 batches)
+            - Butter, 2 sticks
+            - Tomatoes, 1 can / 400 g
+            - Flour, 1 bunch
+
+            ## Instructions
+
+            - Mix
+        "};
+        let units = UnitRegistry::new()
+            .register(&["stick", "sticks"], &Quantity::from_str("113 g")?)
+            .register(&["can", "cans"], &Quantity::from_str("400 g")?)
+            .register(&["batch", "batches"], &Quantity::from_str("24 cookies")?);
+        let mut ctx = ParseContext::new().units(units);
+        let result = Recipe::parse_with(content, &mut ctx);
+        let recipe = ctx.finish(result)?;
+        let grams = recipe.converted(|_| Some(Unit::Mass(Mass::Gram)));
+        let quantities: Vec<String> = grams
+            .ingredients()
+            .iter()
+            .map(|o| o.ingredient().quantity().unwrap().to_string())
+            .collect();
+        assert_eq!(quantities, ["226 g", "400 g", "1 bunch"]);
+        let Ingredients::IngredientGroups(groups) = recipe.ingredients() else {
+            panic!("expected ingredient groups");
+        };
+        let yields = groups[0].yields().unwrap();
+        assert_eq!(yields.to_base().to_string(), "48 cookies");
+
+        // Without the registry, they are custom units and counts.
+        let recipe = Recipe::from_mdast(content)?;
+        let butter = recipe.ingredients().iter().next().unwrap().ingredient();
+        assert_eq!(
+            butter.quantity().unwrap().unit.dimension(),
+            Dimension::Other
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_options() -> MDResult<()> {
+        let content = indoc! {"
+            # Crêpes
+            ## What you need
+
+            - Flour, 0,25 kg
+            - Milk, 500 mL
+
+            ## Kit
+
+            - Pan
+
+            ## Method
+
+            -
+            - Cook
+        "};
+        let options = RecipeParseOptions::new()
+            .sections(SectionNames {
+                ingredients: "What you need".to_string(),
+                instructions: "Method".to_string(),
+                equipment: "Kit".to_string(),
+                ..SectionNames::default()
+            })
+            .numbers(NumberFormat::DecimalComma);
+        let recipe = Recipe::from_mdast_with_options(content, &options)?;
+        let flour = recipe.ingredients().iter().next().unwrap().ingredient();
+        assert_eq!(flour.quantity(), Some(&Quantity::from_str("0.25 kg")?));
+        assert_eq!(recipe.equipment().tools().len(), 1);
+        assert_eq!(recipe.instructions().steps().len(), 2);
+
+        // Empty steps are errors in strict mode, and default headings are not accepted anymore.
+        assert!(Recipe::from_mdast_with_options(content, &options.clone().strict(true)).is_err());
+        assert!(Recipe::from_mdast(content).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn description_and_aliases() -> MDResult<()> {
+        let content = indoc! {"
+            ---
+            aliases:
+              - Bolognese
+              - Ragù
+            ---
+            # Ragù alla bolognese
+
+            A slow-cooked *meat* sauce.
+
+            > From my grandmother's notebook.
+
+            ## Ingredients
+
+            - Beef, 500 g
+
+            ## Instructions
+
+            - Simmer for **3 h**
+        "};
+        let recipe = Recipe::from_mdast(content)?;
+        assert_eq!(recipe.metadata().aliases(), ["Bolognese", "Ragù"]);
+        assert_eq!(recipe.description().len(), 2);
+        assert_eq!(
+            recipe.description_text().unwrap(),
+            "A slow-cooked meat sauce.\n\nFrom my grandmother's notebook."
+        );
+        let md = crate::render::markdown::render(&recipe);
+        assert_eq!(Recipe::from_mdast(&md)?, recipe);
+
+        let mut library = crate::library::RecipeLibrary::default();
+        library.insert("ragu.md".into(), recipe);
+        assert_eq!(library.by_name("bolognese").count(), 1);
+
+        // Other content before the first section is still an error.
+        let list = content.replace("> From", "- From");
+        assert!(Recipe::from_mdast(&list).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn localized_sections() -> MDResult<()> {
+        let content = indoc! {"
+            # Pfannkuchen
+            ## Utensilien
+
+            - Pfanne
+
+            ## Zutaten
+
+            - Mehl, 250 g
+            - Eier, 2
+            - Öl, 2 EL
+
+            ## Zubereitung
+
+            - Die *Eier* verquirlen
+
+            ## Tipps
+
+            - Teig ruhen lassen
+        "};
+        let recipe = Recipe::from_mdast(content)?;
+        assert_eq!(recipe.language(), Some(Language::German));
+        assert_eq!(recipe.notes()[0].kind(), NoteKind::Tip);
+        assert_eq!(recipe.equipment().tools().len(), 1);
+        let oil = recipe.ingredients().iter().nth(2).unwrap().ingredient();
+        assert_eq!(oil.quantity(), Some(&Quantity::from_str("2 tbsp")?));
+        let md = crate::render::markdown::render(&recipe);
+        assert!(md.contains("## Zutaten") && md.contains("- Öl, 2 EL"));
+        assert_eq!(Recipe::from_mdast(&md)?, recipe);
+        let english = Recipe::from_mdast("# Water\n## Ingredients\n## Instructions\n")?;
+        assert_eq!(english.language(), Some(Language::English));
+        let french = "# Vinaigrette\n## Ingrédients\n- Huile, 3 c. à s.\n## Préparation\n";
+        let french = Recipe::from_mdast(french)?;
+        let oil = french.ingredients().iter().next().unwrap().ingredient();
+        assert_eq!(oil.quantity(), Some(&Quantity::from_str("3 tbsp")?));
+
+        // Headings of a set language are the only ones accepted.
+        let german = RecipeParseOptions::new().language(Language::German);
+        assert!(Recipe::from_mdast_with_options(content, &german).is_ok());
+        let french = RecipeParseOptions::new().language(Language::French);
+        assert!(Recipe::from_mdast_with_options(content, &french).is_err());
+
+        // Headings cannot mix languages.
+        let mixed = content.replace("## Zubereitung", "## Instructions");
+        assert!(Recipe::from_mdast(&mixed).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_ingredient_refs() -> MDResult<()> {
+        let content = indoc! {"
+            # Test recipe
+            ## Ingredients
+
+            ### Dough
+            - Flour, 500 g
+            - Butter, 50 g | Margarine, 50 g
+
+            ### Topping
+            - Lemons, 2
+            - Lemon zest, 1 tsp
+
+            ## Instructions
+
+            - Mix the *flour* and *margarine*
+                - Add the *lemmons* and the *Sugar*
+        "};
+        let mut recipe = Recipe::from_mdast(content)?;
+        let refs = |recipe: &Recipe| -> Vec<Option<usize>> {
+            let mut refs = vec![];
+            let mut steps: Vec<_> = recipe.instructions().steps().iter().collect();
+            while let Some(step) = steps.pop() {
+                for elem in step.description() {
+                    if let instructions::TextElem::IngredientRef(r) = elem {
+                        refs.push(r.index());
+                    }
+                }
+                steps.extend(step.substeps());
+            }
+            refs
+        };
+        // Exact (case-insensitive) matching happens while parsing, which reports the references
+        // it cannot resolve where they are written.
+        assert_eq!(refs(&recipe), vec![Some(0), Some(1), None, None]);
+        let (_, diagnostics) = Recipe::from_mdast_with_diagnostics(content, false)?;
+        let rendered: Vec<String> = diagnostics.iter().map(|d| d.render(content)).collect();
+        assert_eq!(
+            rendered,
+            vec![
+                indoc! {r#"
+                    warning[E040_UNRESOLVED_REFERENCE]: unresolved ingredient reference "lemmons" (did you mean "lemons"?)
+                      --> 15:15
+                       |
+                    15 |     - Add the *lemmons* and the *Sugar*
+                       |               ^^^^^^^^^
+                "#},
+                indoc! {r#"
+                    warning[E040_UNRESOLVED_REFERENCE]: unresolved ingredient reference "sugar"
+                      --> 15:33
+                       |
+                    15 |     - Add the *lemmons* and the *Sugar*
+                       |                                 ^^^^^^^
+                "#},
+            ]
+        );
+
+        // Fuzzy matching accepts typos, but not unrelated names.
+        let errors = recipe.resolve_ingredient_refs(RefMatching::Fuzzy);
+        assert_eq!(refs(&recipe), vec![Some(0), Some(1), Some(2), None]);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message().contains("sugar"));
+        assert!(!errors[0].message().contains("did you mean"));
+
+        // Unresolved references suggest a close name.
+        let error = recipe
+            .ingredients()
+            .resolve("Lemon zst", RefMatching::Exact)
+            .unwrap_err();
+        assert!(error.message().ends_with("(did you mean \"lemon zest\"?)"));
+
+        // Empty references never resolve, even fuzzily.
+        assert!(recipe
+            .ingredients()
+            .resolve(" ", RefMatching::Fuzzy)
+            .is_err());
+        Ok(())
+    }
+    #[test]
+    fn with_alternatives() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(indoc! {"
+            # Test recipe
+            ## Ingredients
+
+            - Flour, 500 g
+            - Butter, 50 g | Margarine, 50 g | Oil, 40 mL
+
+            ## Instructions
+
+            - Rub the *butter* into the *flour*
+        "})?;
+        let swapped = recipe.with_alternatives(|options| {
+            options
+                .alternatives()
+                .iter()
+                .position(|a| a.name() == "Oil")
+        });
+        let names: Vec<Vec<&str>> = swapped
+            .ingredients()
+            .iter()
+            .map(|options| options.names().collect())
+            .collect();
+        assert_eq!(
+            names,
+            vec![vec!["Flour"], vec!["Oil", "Margarine", "Butter"]]
+        );
+        assert_eq!(
+            swapped.instructions().steps()[0].text(),
+            "Rub the Oil into the flour"
+        );
+        assert_eq!(recipe.with_alternatives(|_| Some(5)), recipe);
+        Ok(())
+    }
 }
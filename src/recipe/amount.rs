@@ -0,0 +1,598 @@
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    fmt,
+    iter::Sum,
+    num::ParseFloatError,
+    ops::{Add, Div, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+/// Exact rational amount, always stored in reduced form with a positive denominator.
+///
+/// Using rationals rather than floats makes conversions like `3 tsp -> 15 mL` exact, and makes
+/// comparison, hashing, and formatting deterministic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Amount {
+    num: i64,
+    den: i64,
+}
+
+/// Largest denominator used when approximating a float with a rational.
+const MAX_APPROX_DENOMINATOR: i64 = 1_000_000;
+
+const VULGAR_FRACTIONS: [(char, i64, i64); 18] = [
+    ('½', 1, 2),
+    ('⅓', 1, 3),
+    ('⅔', 2, 3),
+    ('¼', 1, 4),
+    ('¾', 3, 4),
+    ('⅕', 1, 5),
+    ('⅖', 2, 5),
+    ('⅗', 3, 5),
+    ('⅘', 4, 5),
+    ('⅙', 1, 6),
+    ('⅚', 5, 6),
+    ('⅐', 1, 7),
+    ('⅛', 1, 8),
+    ('⅜', 3, 8),
+    ('⅝', 5, 8),
+    ('⅞', 7, 8),
+    ('⅑', 1, 9),
+    ('⅒', 1, 10),
+];
+
+/// Denominators considered by [`Amount::as_fraction`], in order of preference.
+const DISPLAY_DENOMINATORS: [i64; 4] = [2, 3, 4, 8];
+
+/// Number of decimals amounts are rounded to when displayed without an explicit precision.
+pub const DEFAULT_DISPLAY_PRECISION: usize = 2;
+
+const fn gcd(mut a: i128, mut b: i128) -> i128 {
+    if a < 0 {
+        a = -a;
+    }
+    if b < 0 {
+        b = -b;
+    }
+    while b != 0 {
+        let tmp = a % b;
+        a = b;
+        b = tmp;
+    }
+    a
+}
+
+impl Amount {
+    pub const ZERO: Self = Self::from_int(0);
+    pub const ONE: Self = Self::from_int(1);
+
+    /// Creates the amount `num / den`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `den` is zero, or if the reduced amount does not fit (e.g., `i64::MIN / 1`).
+    pub const fn new(num: i64, den: i64) -> Self {
+        match Self::reduce(num as i128, den as i128) {
+            Some(amount) => amount,
+            None => panic!("invalid amount"),
+        }
+    }
+
+    /// Creates the amount `num`, saturating `i64::MIN` to `-i64::MAX` so that it can be negated.
+    pub const fn from_int(num: i64) -> Self {
+        let num = if num == i64::MIN { -i64::MAX } else { num };
+        Self { num, den: 1 }
+    }
+
+    /// Returns the closest rational with a denominator of at most one million, or `None` if the
+    /// float is not finite or too large.
+    pub fn approximate(value: f64) -> Option<Self> {
+        if !value.is_finite() || value.abs() >= i64::MAX as f64 {
+            return None;
+        }
+        // Continued fraction expansion, stopping before the denominator gets too large.
+        let (mut p0, mut q0, mut p1, mut q1): (i128, i128, i128, i128) = (0, 1, 1, 0);
+        let mut x = value;
+        loop {
+            let a = x.floor();
+            let (p2, q2) = (a as i128 * p1 + p0, a as i128 * q1 + q0);
+            if q2 > MAX_APPROX_DENOMINATOR as i128 || p2.abs() > i64::MAX as i128 {
+                break;
+            }
+            (p0, q0, p1, q1) = (p1, q1, p2, q2);
+            let frac = x - a;
+            if frac.abs() < 1e-12 {
+                break;
+            }
+            x = 1. / frac;
+        }
+        Self::reduce(p1, q1)
+    }
+
+    const fn reduce(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+        let g = gcd(num, den);
+        let sign = if den < 0 { -1 } else { 1 };
+        let (num, den) = (sign * num / g, sign * den / g);
+        // Not `i64::MIN`, so that amounts can always be negated.
+        if num.abs() > i64::MAX as i128 || den > i64::MAX as i128 {
+            None
+        } else {
+            Some(Self {
+                num: num as i64,
+                den: den as i64,
+            })
+        }
+    }
+
+    /// Reduces the result of an arithmetic operation, falling back to an approximation on
+    /// overflow, and saturating to the largest amount if it cannot be approximated either.
+    fn reduce_or_approximate(num: i128, den: i128) -> Self {
+        Self::reduce(num, den)
+            .or_else(|| Self::approximate(num as f64 / den as f64))
+            .unwrap_or_else(|| {
+                // Not `i64::MIN`, so that the amount can still be negated.
+                let max = Self::from_int(i64::MAX);
+                if (num < 0) != (den < 0) {
+                    -max
+                } else {
+                    max
+                }
+            })
+    }
+
+    pub fn numerator(&self) -> i64 {
+        self.num
+    }
+
+    pub fn denominator(&self) -> i64 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn abs(self) -> Self {
+        Self {
+            num: self.num.abs(),
+            den: self.den,
+        }
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.to_f64() as f32
+    }
+
+    /// Rounds to `decimals` decimals (halves rounded away from zero) and formats the result,
+    /// optionally dropping trailing zeros.
+    fn fmt_decimals(&self, decimals: usize, trim: bool) -> String {
+        // Denominators have at most 19 digits, so further decimals are padding which would only
+        // overflow the computation.
+        let (decimals, padding) = (decimals.min(18), decimals.saturating_sub(18));
+        let scale = 10i128.pow(decimals as u32);
+        let scaled = self.num as i128 * scale;
+        let den = self.den as i128;
+        let rounded = (scaled.abs() * 2 + den) / (2 * den) * scaled.signum();
+        let (int, frac) = (rounded.abs() / scale, rounded.abs() % scale);
+        let mut s = if rounded < 0 {
+            format!("-{}", int)
+        } else {
+            int.to_string()
+        };
+        if decimals > 0 {
+            let frac = format!("{:0width$}{}", frac, "0".repeat(padding), width = decimals);
+            let frac = if trim {
+                frac.trim_end_matches('0')
+            } else {
+                &frac
+            };
+            if !frac.is_empty() {
+                s.push('.');
+                s.push_str(frac);
+            }
+        }
+        s
+    }
+
+    /// Returns the amount written as a whole or mixed number (e.g., "1 1/2") if it is a
+    /// non-negative fraction with a common denominator.
+    pub fn as_fraction(&self) -> Option<String> {
+        if self.num < 0 || !DISPLAY_DENOMINATORS.iter().any(|d| d % self.den == 0) {
+            return None;
+        }
+        let (whole, rest) = (self.num / self.den, self.num % self.den);
+        Some(match (whole, rest) {
+            (_, 0) => whole.to_string(),
+            (0, _) => format!("{}/{}", rest, self.den),
+            _ => format!("{} {}/{}", whole, rest, self.den),
+        })
+    }
+
+    /// Formats the amount so that parsing it back yields the exact same value (e.g., "1/3").
+    pub fn to_exact_string(&self) -> String {
+        if self.is_integer() {
+            self.num.to_string()
+        } else {
+            format!("{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl Default for Amount {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl From<i64> for Amount {
+    fn from(value: i64) -> Self {
+        Self::from_int(value)
+    }
+}
+
+impl From<u32> for Amount {
+    fn from(value: u32) -> Self {
+        Self::from_int(value as i64)
+    }
+}
+
+impl PartialOrd for Amount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Amount {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num as i128 * other.den as i128).cmp(&(other.num as i128 * self.den as i128))
+    }
+}
+
+impl Add for Amount {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::reduce_or_approximate(
+            self.num as i128 * rhs.den as i128 + rhs.num as i128 * self.den as i128,
+            self.den as i128 * rhs.den as i128,
+        )
+    }
+}
+
+impl Sub for Amount {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + (-rhs)
+    }
+}
+
+impl Neg for Amount {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl Mul for Amount {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::reduce_or_approximate(
+            self.num as i128 * rhs.num as i128,
+            self.den as i128 * rhs.den as i128,
+        )
+    }
+}
+
+impl Div for Amount {
+    type Output = Self;
+
+    /// # Panics
+    ///
+    /// Panics if `rhs` is zero.
+    fn div(self, rhs: Self) -> Self::Output {
+        assert!(!rhs.is_zero(), "division of amount by zero");
+        Self::reduce_or_approximate(
+            self.num as i128 * rhs.den as i128,
+            self.den as i128 * rhs.num as i128,
+        )
+    }
+}
+
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, a| acc + a)
+    }
+}
+
+/// The formatter's precision (e.g., `{:.1}`) sets the exact number of decimals to display. Without
+/// one, the amount is rounded to [`DEFAULT_DISPLAY_PRECISION`] decimals and trailing zeros are
+/// dropped, so that `15` displays as "15" and `0.126` as "0.13".
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match f.precision() {
+            Some(precision) => self.fmt_decimals(precision, false),
+            None => self.fmt_decimals(DEFAULT_DISPLAY_PRECISION, true),
+        };
+        // Avoid displaying "-0" for tiny negative amounts.
+        f.write_str(if s == "-0" { "0" } else { &s })
+    }
+}
+
+/// Parses a fraction of the form "a/b" (also accepting the unicode fraction slash '⁄') or a single
+/// unicode vulgar fraction (e.g., '½').
+fn parse_fraction(s: &str) -> Option<Amount> {
+    if let Some((num, den)) = s.split_once(['/', '⁄']) {
+        let num = num.trim().parse::<i64>().ok()?;
+        let den = den.trim().parse::<i64>().ok().filter(|den| *den > 0)?;
+        Amount::reduce(num as i128, den as i128)
+    } else {
+        let mut chars = s.chars();
+        let c = chars.next()?;
+        if chars.next().is_some() {
+            return None;
+        }
+        VULGAR_FRACTIONS
+            .iter()
+            .find(|(v, _, _)| *v == c)
+            .map(|&(_, num, den)| Amount::new(num, den))
+    }
+}
+
+/// Parses a decimal number exactly (e.g., "50.111" is 50111/1000).
+fn parse_decimal(s: &str) -> Option<Amount> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    if int.is_empty() && frac.is_empty()
+        || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit())
+        || frac.len() > 18
+    {
+        return None;
+    }
+    let den = 10i128.pow(frac.len() as u32);
+    let num = format!("{}{}", int, frac).parse::<i128>().ok()?;
+    Amount::reduce(if negative { -num } else { num }, den)
+}
+
+/// Parses an amount, which can be a decimal number ("1.5"), a simple fraction ("1/2", "½"), or a
+/// mixed number ("1 1/2", "1 ½", "1½").
+impl FromStr for Amount {
+    type Err = ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(fraction) = parse_fraction(s) {
+            return Ok(fraction);
+        }
+        // Mixed number, with the whole part separated by spaces or directly followed by a vulgar
+        // fraction.
+        let split = s.find(' ').or_else(|| {
+            s.char_indices()
+                .find(|(_, c)| VULGAR_FRACTIONS.iter().any(|(v, _, _)| v == c))
+                .map(|(idx, _)| idx)
+        });
+        if let Some(idx) = split {
+            let (whole, fraction) = s.split_at(idx);
+            if let (Ok(whole), Some(fraction)) =
+                (whole.parse::<u32>(), parse_fraction(fraction.trim()))
+            {
+                return Ok(Amount::from(whole) + fraction);
+            }
+        }
+        // Decimals are parsed as floats first so that invalid input yields the usual error.
+        let value = s.parse::<f64>()?;
+        parse_decimal(s)
+            .or_else(|| Amount::approximate(value))
+            .ok_or_else(|| "invalid".parse::<f64>().unwrap_err())
+    }
+}
+
+/// How numbers are written in a recipe, for parsing amounts with separators.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NumberFormat {
+    /// Decimal point and no thousands separators (e.g., "1.5" or "1500").
+    #[default]
+    Plain,
+    /// Decimal point and comma thousands separators (e.g., "1,500.5").
+    DecimalPoint,
+    /// Decimal comma and point thousands separators (e.g., "1,5" or "1.500,5"), as in most of
+    /// Europe.
+    DecimalComma,
+}
+
+impl NumberFormat {
+    /// Whether the character at byte `pos` of `s` is a separator within a number: between two
+    /// digits, and followed by exactly three digits for thousands separators.
+    pub fn is_separator_at(self, s: &str, pos: usize) -> bool {
+        self.separator_at(s, pos).is_some()
+    }
+
+    /// Rewrites the numbers of a string as plain numbers (e.g., "1.500,5 g" as "1500.5 g"). Other
+    /// text is left as is.
+    pub fn normalize(self, s: &str) -> Cow<'_, str> {
+        if self == Self::Plain {
+            return Cow::Borrowed(s);
+        }
+        let mut normalized = String::with_capacity(s.len());
+        for (pos, c) in s.char_indices() {
+            match self.separator_at(s, pos) {
+                Some(Separator::Decimal) => normalized.push('.'),
+                Some(Separator::Thousands) => (),
+                None => normalized.push(c),
+            }
+        }
+        Cow::Owned(normalized)
+    }
+
+    fn separator_at(self, s: &str, pos: usize) -> Option<Separator> {
+        let bytes = s.as_bytes();
+        let between_digits = pos > 0
+            && bytes[pos - 1].is_ascii_digit()
+            && bytes.get(pos + 1).is_some_and(u8::is_ascii_digit);
+        if !between_digits {
+            return None;
+        }
+        let digits = bytes[pos + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        match (self, bytes[pos]) {
+            (Self::DecimalPoint, b',') | (Self::DecimalComma, b'.') if digits == 3 => {
+                Some(Separator::Thousands)
+            }
+            (Self::DecimalComma, b',') => Some(Separator::Decimal),
+            _ => None,
+        }
+    }
+}
+
+enum Separator {
+    Decimal,
+    Thousands,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_formats() {
+        let normalize = |format: NumberFormat, s| format.normalize(s).into_owned();
+        assert_eq!(normalize(NumberFormat::Plain, "1,5 kg"), "1,5 kg");
+        assert_eq!(normalize(NumberFormat::DecimalComma, "1,5 kg"), "1.5 kg");
+        assert_eq!(
+            normalize(NumberFormat::DecimalComma, "1.500,25 g"),
+            "1500.25 g"
+        );
+        assert_eq!(
+            normalize(NumberFormat::DecimalComma, "0.5-1,5 L"),
+            "0.5-1.5 L"
+        );
+        assert_eq!(
+            normalize(NumberFormat::DecimalPoint, "1,500.5 g"),
+            "1500.5 g"
+        );
+        assert_eq!(normalize(NumberFormat::DecimalPoint, "1,5 g"), "1,5 g");
+        assert_eq!(
+            normalize(NumberFormat::DecimalPoint, "2,000,000"),
+            "2000000"
+        );
+        assert_eq!(
+            normalize(NumberFormat::DecimalComma, "salt, 1 tsp"),
+            "salt, 1 tsp"
+        );
+        assert!(NumberFormat::DecimalComma.is_separator_at("1,5", 1));
+        assert!(!NumberFormat::DecimalPoint.is_separator_at("1,5", 1));
+        assert!(!NumberFormat::DecimalComma.is_separator_at("1, 5", 1));
+    }
+
+    #[test]
+    fn parse_amount() -> Result<(), ParseFloatError> {
+        assert_eq!(Amount::from_str("15")?, Amount::from_int(15));
+        assert_eq!(Amount::from_str(" 50.111 ")?, Amount::new(50111, 1000));
+        assert_eq!(Amount::from_str("-0.5")?, Amount::new(-1, 2));
+        assert_eq!(Amount::from_str(".25")?, Amount::new(1, 4));
+        assert_eq!(Amount::from_str("1e3")?, Amount::from_int(1000));
+        assert_eq!(Amount::from_str("1 1/2")?, Amount::new(3, 2));
+        assert_eq!(Amount::from_str("2⅓")?, Amount::new(7, 3));
+        assert!(Amount::from_str("inf").is_err());
+        assert!(Amount::from_str("1/0").is_err());
+        assert!(Amount::from_str("1/-2").is_err());
+        assert_eq!(
+            Amount::from_str("1/5000000000")?,
+            Amount::new(1, 5_000_000_000)
+        );
+        assert_eq!(Amount::from_str("20/10")?, Amount::from_int(2));
+        // Amounts can always be negated.
+        assert!(Amount::from_str(&i64::MIN.to_string()).is_err());
+        assert!(Amount::from_str(&format!("{}/1", i64::MIN)).is_err());
+        let max = Amount::from_int(i64::MAX);
+        assert_eq!(-Amount::from(i64::MIN), max);
+        assert_eq!(Amount::from(i64::MIN).abs(), max);
+        assert!(Amount::from_str("1,5").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn arithmetic_is_exact() {
+        let third = Amount::new(1, 3);
+        assert_eq!(third + third + third, Amount::ONE);
+        assert_eq!(Amount::new(1, 10) * Amount::from_int(3), Amount::new(3, 10));
+        assert_eq!(
+            Amount::from_int(3) * Amount::from_int(5),
+            Amount::from_int(15)
+        );
+        assert_eq!(Amount::ONE / Amount::new(-2, 4), Amount::from_int(-2));
+        assert!(Amount::new(1, 3) < Amount::new(1, 2));
+        assert_eq!(
+            [Amount::new(1, 4), Amount::new(3, 4)]
+                .into_iter()
+                .sum::<Amount>(),
+            Amount::ONE
+        );
+    }
+
+    #[test]
+    fn approximate() {
+        assert_eq!(Amount::approximate(0.5), Some(Amount::new(1, 2)));
+        assert_eq!(Amount::approximate(1. / 3.), Some(Amount::new(1, 3)));
+        assert_eq!(Amount::approximate(3.785), Some(Amount::new(757, 200)));
+        assert_eq!(Amount::approximate(f64::NAN), None);
+
+        // Overflowing results are approximated, or saturate if they are too large.
+        let max = Amount::from_int(i64::MAX);
+        assert_eq!(
+            Amount::new(i64::MAX, 3) + Amount::new(1, 2),
+            Amount::approximate(i64::MAX as f64 / 3. + 0.5).unwrap()
+        );
+        assert_eq!(max + Amount::ONE, max);
+        assert_eq!(max * Amount::from_int(-2), -max);
+        assert_eq!(-max - max, -max);
+    }
+
+    #[test]
+    fn display_amount() {
+        assert_eq!(Amount::from_int(15).to_string(), "15");
+        assert_eq!(Amount::new(1, 3).to_string(), "0.33");
+        assert_eq!(Amount::new(2, 3).to_string(), "0.67");
+        assert_eq!(Amount::new(1, 8).to_string(), "0.13");
+        assert_eq!(Amount::new(-1, 1000).to_string(), "0");
+        assert_eq!(format!("{:.3}", Amount::new(5, 2)), "2.500");
+        assert_eq!(format!("{:.0}", Amount::new(5, 2)), "3");
+        assert_eq!(
+            format!("{:.40}", Amount::new(1, 4)),
+            format!("0.25{}", "0".repeat(38))
+        );
+        assert_eq!(format!("{:.40}", Amount::from_int(i64::MAX)).len(), 19 + 41);
+        assert_eq!(Amount::new(1, 3).to_exact_string(), "1/3");
+    }
+
+    #[test]
+    fn amount_as_fraction() {
+        assert_eq!(Amount::new(3, 2).as_fraction().unwrap(), "1 1/2");
+        assert_eq!(Amount::new(1, 4).as_fraction().unwrap(), "1/4");
+        assert_eq!(Amount::from_int(3).as_fraction().unwrap(), "3");
+        assert!(Amount::new(1, 10).as_fraction().is_none());
+        assert!(Amount::new(-1, 2).as_fraction().is_none());
+    }
+}
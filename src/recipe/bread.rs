@@ -0,0 +1,308 @@
+//! Helpers for bread and other fermented doughs, based on baker's percentages: the weight of each
+//! ingredient relative to the total weight of flour.
+//!
+//! Ingredients are classified by name: flours contain "flour", liquids "water" or "milk", and
+//! starters "starter" or "levain". Starters are assumed to be at 100% hydration (equal weights of
+//! flour and water). Volumes of liquids are taken as weights (1 mL = 1 g).
+
+use std::{str::FromStr, time::Duration};
+
+use super::{
+    amount::Amount,
+    instructions::StepPath,
+    md_parser::{MDError, MDResult},
+    unit::{Quantity, QuantityOf, Time, Unit},
+    Recipe,
+};
+
+const FLOURS: [&str; 1] = ["flour"];
+const LIQUIDS: [&str; 2] = ["water", "milk"];
+const STARTERS: [&str; 2] = ["starter", "levain"];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum DoughPart {
+    Flour,
+    Liquid,
+    Starter,
+}
+
+impl DoughPart {
+    fn of(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let any = |words: &[&str]| words.iter().any(|w| name.contains(w));
+        if any(&STARTERS) {
+            Some(Self::Starter)
+        } else if any(&FLOURS) {
+            Some(Self::Flour)
+        } else if any(&LIQUIDS) {
+            Some(Self::Liquid)
+        } else {
+            None
+        }
+    }
+}
+
+/// Weight of an ingredient, either in grams or in baker's percent.
+fn weight(quantity: &Quantity) -> Option<(Amount, bool)> {
+    match quantity.unit {
+        Unit::Mass(_) | Unit::Volume(_) => Some((quantity.to_base().amount, false)),
+        Unit::Percent(_) => Some((quantity.amount, true)),
+        _ => None,
+    }
+}
+
+impl Recipe {
+    /// Weights of the ingredients given by weight (or volume, or baker's percentage), in grams or
+    /// in percent. Returns `None` if grams and percents are mixed.
+    fn weights(&self) -> Option<Vec<(&str, Amount)>> {
+        let mut in_percent = None;
+        let mut weights = vec![];
+        for options in self.ingredients.iter() {
+            let ingredient = options.ingredient();
+            let Some((amount, is_percent)) = ingredient.quantity().and_then(weight) else {
+                continue;
+            };
+            if *in_percent.get_or_insert(is_percent) != is_percent {
+                return None;
+            }
+            weights.push((ingredient.name(), amount));
+        }
+        Some(weights)
+    }
+
+    /// Total weights of flour and of liquids, including those of starters.
+    fn flour_and_liquids(&self) -> Option<(Amount, Amount)> {
+        let (mut flour, mut liquids) = (Amount::ZERO, Amount::ZERO);
+        for (name, weight) in self.weights()? {
+            match DoughPart::of(name) {
+                Some(DoughPart::Flour) => flour = flour + weight,
+                Some(DoughPart::Liquid) => liquids = liquids + weight,
+                Some(DoughPart::Starter) => {
+                    flour = flour + weight / Amount::from_int(2);
+                    liquids = liquids + weight / Amount::from_int(2);
+                }
+                None => {}
+            }
+        }
+        Some((flour, liquids))
+    }
+
+    /// Hydration of the dough (weight of liquids over weight of flour), in percent.
+    pub fn hydration(&self) -> Option<Amount> {
+        let (flour, liquids) = self.flour_and_liquids()?;
+        (!flour.is_zero()).then(|| liquids / flour * Amount::from_int(100))
+    }
+
+    /// Baker's percentage of every ingredient given by weight, relative to the flours (not
+    /// counting the flour in starters, by convention).
+    pub fn bakers_percentages(&self) -> Vec<(&str, Amount)> {
+        let Some(weights) = self.weights() else {
+            return vec![];
+        };
+        let flour: Amount = weights
+            .iter()
+            .filter(|(name, _)| DoughPart::of(name) == Some(DoughPart::Flour))
+            .map(|(_, weight)| *weight)
+            .sum();
+        if flour.is_zero() {
+            return vec![];
+        }
+        weights
+            .into_iter()
+            .map(|(name, weight)| (name, weight / flour * Amount::from_int(100)))
+            .collect()
+    }
+
+    /// Returns the recipe with baker's percentages (e.g., "Water, 75%") converted to weights, for
+    /// the given total weight of flour.
+    pub fn with_flour_weight(&self, flour: &Quantity) -> Self {
+        let mut recipe = self.clone();
+        let percent = Amount::new(1, 100);
+        recipe.ingredients.map_quantities(|q| match q.unit {
+            Unit::Percent(_) => Quantity {
+                unit: flour.unit.clone(),
+                amount: q.amount * percent * flour.amount,
+                max_amount: q.max_amount.map(|max| max * percent * flour.amount),
+                approximation: q.approximation,
+            },
+            _ => q.clone(),
+        });
+        recipe
+    }
+
+    /// Lays the timers of the instructions end to end (e.g., "Proof for **2 days**"), to plan a
+    /// bake spanning several days.
+    pub fn timeline(&self) -> Vec<TimelineEntry> {
+        let mut start = Duration::ZERO;
+        self.instructions
+            .timers()
+            .map(|(path, time)| {
+                let entry = TimelineEntry { path, time, start };
+                start = start.saturating_add(time.to_duration());
+                entry
+            })
+            .collect()
+    }
+}
+
+/// A timer of [`Recipe::timeline`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimelineEntry {
+    pub path: StepPath,
+    pub time: QuantityOf<Time>,
+    /// Time elapsed since the start of the recipe.
+    pub start: Duration,
+}
+
+impl TimelineEntry {
+    /// Day the timer starts on, starting from 1.
+    pub fn day(&self) -> u64 {
+        self.start.as_secs() / 86400 + 1
+    }
+
+    pub fn end(&self) -> Duration {
+        self.start.saturating_add(self.time.to_duration())
+    }
+}
+
+/// Proportions of a levain feed by weight, written "seed:flour:water" (e.g., "1:5:5").
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LevainRatio {
+    pub seed: Amount,
+    pub flour: Amount,
+    pub water: Amount,
+}
+
+impl FromStr for LevainRatio {
+    type Err = MDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<Amount> = s
+            .split(':')
+            .map(|part| Amount::from_str(part.trim()))
+            .collect::<Result<_, _>>()?;
+        match parts[..] {
+            [seed, flour, water] if !(seed + flour + water).is_zero() => {
+                Ok(Self { seed, flour, water })
+            }
+            _ => Err(MDError::new(
+                &format!("expected levain ratio as \"seed:flour:water\", got {:?}", s),
+                None,
+            )),
+        }
+    }
+}
+
+/// Weights, in grams, of a levain feed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LevainFeed {
+    pub seed: Amount,
+    pub flour: Amount,
+    pub water: Amount,
+}
+
+impl LevainFeed {
+    pub fn total(&self) -> Amount {
+        self.seed + self.flour + self.water
+    }
+}
+
+/// Computes the successive feeds producing `target` grams of levain, each feed being seeded with
+/// the whole output of the previous one. Feeds are returned in order; the first one is seeded
+/// with starter.
+pub fn levain_build(target: Amount, ratios: &[LevainRatio]) -> Vec<LevainFeed> {
+    let mut feeds = vec![];
+    let mut total = target;
+    for ratio in ratios.iter().rev() {
+        let part = total / (ratio.seed + ratio.flour + ratio.water);
+        let feed = LevainFeed {
+            seed: ratio.seed * part,
+            flour: ratio.flour * part,
+            water: ratio.water * part,
+        };
+        total = feed.seed;
+        feeds.push(feed);
+    }
+    feeds.reverse();
+    feeds
+}
+
+/// Parses levain ratios separated by commas (e.g., "1:2:2, 1:5:5").
+pub fn parse_levain_ratios(s: &str) -> MDResult<Vec<LevainRatio>> {
+    s.split(',').map(LevainRatio::from_str).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn hydration() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(indoc! {"
+            # Country loaf
+            ## Ingredients
+
+            - Bread flour, 900 g
+            - Whole wheat flour, 100 g
+            - Water, 700 mL
+            - Levain, 200 g
+            - Salt, 20 g
+
+            ## Instructions
+
+            - Bulk ferment for **4 h**
+            - Retard for **1 day**
+            - Bake for **45 min**
+        "})?;
+        assert_eq!(
+            recipe.hydration(),
+            Some(Amount::from_int(800) / Amount::from_int(11))
+        );
+        let percentages = recipe.bakers_percentages();
+        assert_eq!(percentages[2], ("Water", Amount::from_int(70)));
+        assert_eq!(percentages[4], ("Salt", Amount::from_int(2)));
+
+        let timeline = recipe.timeline();
+        assert_eq!(timeline[1].day(), 1);
+        assert_eq!(timeline[2].day(), 2);
+        assert_eq!(timeline[2].start, Duration::from_secs(28 * 3600));
+        Ok(())
+    }
+
+    #[test]
+    fn bakers_percentages() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(indoc! {"
+            # Baguette
+            ## Ingredients
+
+            - Flour, 100%
+            - Water, 68%
+            - Salt, 2%
+
+            ## Instructions
+
+            - Mix
+        "})?;
+        assert_eq!(recipe.hydration(), Some(Amount::from_int(68)));
+        let recipe = recipe.with_flour_weight(&Quantity::from_str("500 g")?);
+        let water = recipe.ingredients().get(1).unwrap().ingredient();
+        assert_eq!(water.quantity().unwrap().to_string(), "340 g");
+        assert_eq!(recipe.hydration(), Some(Amount::from_int(68)));
+        Ok(())
+    }
+
+    #[test]
+    fn levain() -> MDResult<()> {
+        let feeds = levain_build(
+            Amount::from_int(220),
+            &parse_levain_ratios("1:5:5, 1:10:10")?,
+        );
+        assert_eq!(feeds.len(), 2);
+        assert_eq!(feeds[1].total(), Amount::from_int(220));
+        assert_eq!(feeds[1].seed, Amount::new(220, 21));
+        assert_eq!(feeds[0].total(), feeds[1].seed);
+        assert!(LevainRatio::from_str("1:5").is_err());
+        Ok(())
+    }
+}
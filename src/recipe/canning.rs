@@ -0,0 +1,466 @@
+//! Processing information for canned and preserved recipes, from a mapping such as
+//!
+//! ```yaml
+//! canning:
+//!   method: pressure
+//!   headspace: 1 in
+//!   processing:
+//!     - jar: 500 mL
+//!       altitude: 0-1000 ft
+//!       time: 25 min
+//!       pressure: 10 psi
+//!     - jar: 500 mL
+//!       altitude: 1000+ ft
+//!       time: 25 min
+//!       pressure: 15 psi
+//! ```
+//!
+//! Processing times and pressures are safety-critical, so entries are never interpolated: a lookup
+//! either finds an entry covering the jar and altitude or fails.
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use super::{
+    amount::Amount,
+    md_parser::{MDError, MDResult},
+    metadata::Metadata,
+    unit::{Distance, Quantity, QuantityOf, Time, Unit},
+};
+
+pub(super) const CANNING: &str = "canning";
+/// Tags marking a recipe as one that must come with processing information.
+const CANNING_TAGS: [&str; 2] = ["canning", "preserving"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Canning {
+    method: CanningMethod,
+    /// Space to leave between the food and the rim of the jar.
+    headspace: Option<QuantityOf<Distance>>,
+    processing: Vec<Processing>,
+}
+
+impl Canning {
+    pub(super) fn parse(value: &saphyr::Yaml<'_>) -> MDResult<Self> {
+        let mapping = value.as_mapping().ok_or(MDError::new(
+            &format!("expected mapping under {:?}", CANNING),
+            None,
+        ))?;
+        let (mut method, mut headspace, mut processing) = (None, None, vec![]);
+        for (key, value) in mapping {
+            let key = key
+                .as_str()
+                .ok_or(MDError::new("expected string key", None))?;
+            match key {
+                "method" => {
+                    method = Some(CanningMethod::from_str(&Metadata::scalar_str(value, key)?)?)
+                }
+                "headspace" => {
+                    let value = Metadata::scalar_str(value, key)?;
+                    headspace = Some(QuantityOf::from_str(&value).map_err(|e| {
+                        MDError::new(&format!("failed to parse headspace: {}", e), None)
+                    })?);
+                }
+                "processing" => {
+                    processing = value
+                        .as_sequence()
+                        .ok_or(MDError::new(
+                            "expected list of processing entries under \"processing\"",
+                            None,
+                        ))?
+                        .iter()
+                        .map(Processing::parse)
+                        .collect::<MDResult<_>>()?;
+                }
+                _ => {
+                    return Err(MDError::new(
+                        &format!("unknown canning key {:?}", key),
+                        None,
+                    ))
+                }
+            }
+        }
+        Ok(Self {
+            method: method.ok_or(MDError::new("missing canning method", None))?,
+            headspace,
+            processing,
+        })
+    }
+
+    /// Problems making the processing information unsafe to follow.
+    pub(super) fn warnings(&self) -> Vec<MDError> {
+        let mut warnings = vec![];
+        if self.processing.is_empty() {
+            warnings.push(MDError::new("canning recipe has no processing times", None));
+        }
+        for entry in &self.processing {
+            match (self.method, entry.pressure) {
+                (CanningMethod::Pressure, None) => warnings.push(MDError::new(
+                    &format!("missing pressure for {}", entry),
+                    None,
+                )),
+                (CanningMethod::WaterBath | CanningMethod::Steam, Some(_)) => {
+                    warnings.push(MDError::new(
+                        &format!("pressure given for {} canning in {}", self.method, entry),
+                        None,
+                    ))
+                }
+                _ => {}
+            }
+        }
+        warnings
+    }
+
+    /// Whether tags mark a recipe as canned or preserved (e.g., "#canning" or "#preserving/jam").
+    pub(super) fn is_tagged(tags: &[String]) -> bool {
+        tags.iter().any(|tag| {
+            let tag = tag.to_lowercase();
+            CANNING_TAGS.iter().any(|t| {
+                tag.strip_prefix(t)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+        })
+    }
+
+    pub fn method(&self) -> CanningMethod {
+        self.method
+    }
+
+    pub fn headspace(&self) -> Option<&QuantityOf<Distance>> {
+        self.headspace.as_ref()
+    }
+
+    pub fn processing(&self) -> &[Processing] {
+        &self.processing
+    }
+
+    /// Finds the processing entry for a jar at an altitude. Since larger jars need longer
+    /// processing, the smallest listed jar at least as large as `jar` is used. When several
+    /// entries match (e.g., at 1000 ft, with "0-1000 ft" and "1000+ ft" ranges), the safest is
+    /// used: the longest, then at the highest pressure.
+    pub fn lookup(&self, jar: &Quantity, altitude: Altitude) -> Option<&Processing> {
+        let candidates: Vec<&Processing> = self
+            .processing
+            .iter()
+            .filter(|p| p.altitude.contains(altitude))
+            .filter(|p| {
+                matches!(
+                    p.jar.compare(jar),
+                    Some(Ordering::Greater | Ordering::Equal)
+                )
+            })
+            .collect();
+        let smallest = candidates
+            .iter()
+            .min_by(|a, b| a.jar.compare(&b.jar).unwrap_or(Ordering::Equal))?;
+        candidates
+            .iter()
+            .filter(|p| p.jar.compare(&smallest.jar) == Some(Ordering::Equal))
+            .max_by_key(|p| (p.time.as_seconds(), p.pressure))
+            .copied()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CanningMethod {
+    WaterBath,
+    Pressure,
+    Steam,
+}
+
+impl FromStr for CanningMethod {
+    type Err = MDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "water bath" | "water-bath" | "boiling water" => Ok(Self::WaterBath),
+            "pressure" => Ok(Self::Pressure),
+            "steam" => Ok(Self::Steam),
+            _ => Err(MDError::new(
+                &format!("unknown canning method {:?}", s),
+                None,
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CanningMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WaterBath => write!(f, "water bath"),
+            Self::Pressure => write!(f, "pressure"),
+            Self::Steam => write!(f, "steam"),
+        }
+    }
+}
+
+/// Processing of a jar size at a range of altitudes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Processing {
+    pub jar: Quantity,
+    pub altitude: AltitudeRange,
+    pub time: QuantityOf<Time>,
+    /// Gauge pressure, in psi.
+    pub pressure: Option<Amount>,
+}
+
+const PSI: &str = "psi";
+
+impl Processing {
+    fn parse(value: &saphyr::Yaml<'_>) -> MDResult<Self> {
+        let mapping = value
+            .as_mapping()
+            .ok_or(MDError::new("expected mapping for processing entry", None))?;
+        let (mut jar, mut altitude, mut time, mut pressure) = (None, None, None, None);
+        for (key, value) in mapping {
+            let key = key
+                .as_str()
+                .ok_or(MDError::new("expected string key", None))?;
+            let value = Metadata::scalar_str(value, key)?;
+            let invalid = |e: &dyn fmt::Display| {
+                MDError::new(&format!("failed to parse {}: {}", key, e), None)
+            };
+            match key {
+                "jar" => {
+                    let quantity = Quantity::from_str(&value).map_err(|e| invalid(&e))?;
+                    if !matches!(quantity.unit, Unit::Volume(_)) {
+                        return Err(MDError::new(
+                            &format!("expected volume for jar, got {:?}", value),
+                            None,
+                        ));
+                    }
+                    jar = Some(quantity);
+                }
+                "altitude" => altitude = Some(AltitudeRange::from_str(&value)?),
+                "time" => time = Some(QuantityOf::from_str(&value).map_err(|e| invalid(&e))?),
+                "pressure" => {
+                    let psi = value.strip_suffix(PSI).unwrap_or(&value).trim();
+                    pressure = Some(Amount::from_str(psi).map_err(|e| invalid(&e))?);
+                }
+                _ => {
+                    return Err(MDError::new(
+                        &format!("unknown processing key {:?}", key),
+                        None,
+                    ))
+                }
+            }
+        }
+        let missing = |key| MDError::new(&format!("missing {:?} in processing entry", key), None);
+        Ok(Self {
+            jar: jar.ok_or_else(|| missing("jar"))?,
+            altitude: altitude.unwrap_or_default(),
+            time: time.ok_or_else(|| missing("time"))?,
+            pressure,
+        })
+    }
+}
+
+impl fmt::Display for Processing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} jars at {}: {}", self.jar, self.altitude, self.time)?;
+        if let Some(pressure) = self.pressure {
+            write!(f, " at {} {}", pressure, PSI)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AltitudeUnit {
+    #[default]
+    Meter,
+    Foot,
+}
+
+/// Meters in a foot.
+const FOOT: Amount = Amount::new(3048, 10000);
+
+impl AltitudeUnit {
+    fn to_meters(self, value: Amount) -> Amount {
+        match self {
+            Self::Meter => value,
+            Self::Foot => value * FOOT,
+        }
+    }
+}
+
+impl FromStr for AltitudeUnit {
+    type Err = MDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "m" => Ok(Self::Meter),
+            "ft" => Ok(Self::Foot),
+            _ => Err(MDError::new(
+                &format!("unknown altitude unit {:?}", s),
+                None,
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AltitudeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Meter => write!(f, "m"),
+            Self::Foot => write!(f, "ft"),
+        }
+    }
+}
+
+/// An altitude above sea level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Altitude {
+    pub value: Amount,
+    pub unit: AltitudeUnit,
+}
+
+impl FromStr for Altitude {
+    type Err = MDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| c.is_alphabetic()).unwrap_or(s.len());
+        Ok(Self {
+            value: Amount::from_str(s[..split].trim())?,
+            unit: AltitudeUnit::from_str(&s[split..])?,
+        })
+    }
+}
+
+/// Range of altitudes, written "0-1000 ft", or "1000+ ft" when it has no upper bound. Both bounds
+/// are inclusive. The default range covers all altitudes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct AltitudeRange {
+    pub min: Amount,
+    pub max: Option<Amount>,
+    pub unit: AltitudeUnit,
+}
+
+impl AltitudeRange {
+    pub fn contains(&self, altitude: Altitude) -> bool {
+        let meters = altitude.unit.to_meters(altitude.value);
+        self.unit.to_meters(self.min) <= meters
+            && self
+                .max
+                .is_none_or(|max| meters <= self.unit.to_meters(max))
+    }
+}
+
+impl FromStr for AltitudeRange {
+    type Err = MDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| c.is_alphabetic()).unwrap_or(s.len());
+        let (bounds, unit) = (s[..split].trim(), AltitudeUnit::from_str(&s[split..])?);
+        let (min, max) = match bounds.strip_suffix('+') {
+            Some(min) => (Amount::from_str(min.trim())?, None),
+            None => {
+                let (min, max) = bounds.split_once('-').ok_or(MDError::new(
+                    &format!("expected altitude range, got {:?}", s),
+                    None,
+                ))?;
+                let (min, max) = (Amount::from_str(min.trim())?, Amount::from_str(max.trim())?);
+                if max < min {
+                    return Err(MDError::new(&format!("empty altitude range {:?}", s), None));
+                }
+                (min, Some(max))
+            }
+        };
+        Ok(Self { min, max, unit })
+    }
+}
+
+impl fmt::Display for AltitudeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) => write!(f, "{}-{} {}", self.min, max, self.unit),
+            None => write!(f, "{}+ {}", self.min, self.unit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::Recipe;
+    use indoc::indoc;
+
+    const JAM: &str = indoc! {"
+        ---
+        tags:
+          - \"#preserving/jam\"
+        canning:
+          method: water bath
+          headspace: 0.5 in
+          processing:
+            - jar: 250 mL
+              altitude: 0-1000 ft
+              time: 5 min
+            - jar: 500 mL
+              altitude: 0-1000 ft
+              time: 10 min
+            - jar: 500 mL
+              altitude: 1000+ ft
+              time: 15 min
+        ---
+        # Strawberry jam
+        ## Ingredients
+
+        - Strawberries, 1 kg
+        - Sugar, 750 g
+
+        ## Instructions
+
+        - Boil
+    "};
+
+    #[test]
+    fn lookup() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(JAM)?;
+        let canning = recipe.metadata().canning().unwrap();
+        assert_eq!(canning.method(), CanningMethod::WaterBath);
+        assert_eq!(canning.processing().len(), 3);
+
+        let time = |jar: &str, altitude: &str| {
+            let jar = Quantity::from_str(jar).unwrap();
+            let altitude = Altitude::from_str(altitude).unwrap();
+            canning.lookup(&jar, altitude).map(|p| p.time.to_string())
+        };
+        assert_eq!(time("250 mL", "100 m").as_deref(), Some("5 min"));
+        assert_eq!(time("300 mL", "100 m").as_deref(), Some("10 min"));
+        assert_eq!(time("250 mL", "1500 ft").as_deref(), Some("15 min"));
+        // Both ranges include 1000 ft, where the longer time is the safe one.
+        assert_eq!(time("500 mL", "1000 ft").as_deref(), Some("15 min"));
+        assert_eq!(time("500 mL", "999 ft").as_deref(), Some("10 min"));
+        assert_eq!(time("1 L", "0 m"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn warnings() -> MDResult<()> {
+        let warnings = |md: &str| -> MDResult<Vec<String>> {
+            let (_, diagnostics) = Recipe::from_mdast_with_diagnostics(md, false)?;
+            Ok(diagnostics
+                .iter()
+                .map(|d| d.error().message().to_string())
+                .collect())
+        };
+        assert!(warnings(JAM)?.is_empty());
+        let pressure = JAM.replace("water bath", "pressure");
+        assert_eq!(warnings(&pressure)?.len(), 3);
+
+        let (start, end) = (JAM.find("canning:").unwrap(), JAM.find("---\n#").unwrap());
+        let missing = format!("{}{}", &JAM[..start], &JAM[end..]);
+        assert_eq!(
+            warnings(&missing)?,
+            vec!["recipe tagged for canning has no \"canning\" processing information"]
+        );
+        assert!(warnings(&missing.replace("#preserving/jam", "#jam"))?.is_empty());
+
+        assert!(AltitudeRange::from_str("1000-0 ft").is_err());
+        assert!(Canning::is_tagged(&["Canning/Pickles".to_string()]));
+        assert!(!Canning::is_tagged(&["canningjars".to_string()]));
+        Ok(())
+    }
+}
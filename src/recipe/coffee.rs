@@ -0,0 +1,146 @@
+//! Brew ratios for coffee, written `dose:yield` with an optional brew time in the metadata (e.g.,
+//! `ratio: 1:2 in 25-30 s` for an espresso yielding twice its dose of ground coffee).
+
+use std::{fmt, str::FromStr};
+
+use super::{
+    amount::Amount,
+    md_parser::MDError,
+    unit::{Mass, QuantityOf, Time},
+};
+
+pub(super) const RATIO: &str = "ratio";
+const TIME_SEPARATOR: &str = " in ";
+
+/// Weight of the output (e.g., espresso in the cup) relative to the dose of ground coffee.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BrewRatio {
+    pub dose: Amount,
+    pub output: Amount,
+    pub time: Option<QuantityOf<Time>>,
+}
+
+impl BrewRatio {
+    /// Weight of output per unit of dose (e.g., 2 for "1:2").
+    pub fn ratio(&self) -> Amount {
+        self.output / self.dose
+    }
+
+    /// Computes the output for a dose. The brew time does not depend on the dose.
+    pub fn for_dose(&self, dose: QuantityOf<Mass>) -> Brew {
+        Brew {
+            dose,
+            output: dose.scale(self.ratio()),
+            time: self.time,
+        }
+    }
+
+    /// Computes the dose for a desired output.
+    pub fn for_output(&self, output: QuantityOf<Mass>) -> Brew {
+        Brew {
+            dose: output.scale(self.dose / self.output),
+            output,
+            time: self.time,
+        }
+    }
+}
+
+impl FromStr for BrewRatio {
+    type Err = MDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            MDError::new(
+                &format!(
+                    "expected brew ratio as \"dose:yield [in time]\", got {:?}",
+                    s
+                ),
+                None,
+            )
+        };
+        let (ratio, time) = match s.split_once(TIME_SEPARATOR) {
+            Some((ratio, time)) => (
+                ratio,
+                Some(QuantityOf::from_str(time.trim()).map_err(|e| {
+                    MDError::new(&format!("failed to parse brew time: {}", e), None)
+                })?),
+            ),
+            None => (s, None),
+        };
+        let (dose, output) = ratio.split_once(':').ok_or_else(invalid)?;
+        let (dose, output) = (
+            Amount::from_str(dose.trim())?,
+            Amount::from_str(output.trim())?,
+        );
+        if dose <= Amount::ZERO || output <= Amount::ZERO {
+            return Err(invalid());
+        }
+        Ok(Self { dose, output, time })
+    }
+}
+
+impl fmt::Display for BrewRatio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.dose, self.output)?;
+        if let Some(time) = self.time {
+            write!(f, "{}{}", TIME_SEPARATOR, time)?;
+        }
+        Ok(())
+    }
+}
+
+/// Weights of a brew, as computed from a [`BrewRatio`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Brew {
+    pub dose: QuantityOf<Mass>,
+    pub output: QuantityOf<Mass>,
+    pub time: Option<QuantityOf<Time>>,
+}
+
+impl fmt::Display for Brew {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in, {} out", self.dose, self.output)?;
+        if let Some(time) = self.time {
+            write!(f, "{}{}", TIME_SEPARATOR, time)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::{MDResult, Recipe};
+    use indoc::indoc;
+
+    #[test]
+    fn ratio() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(indoc! {"
+            ---
+            ratio: 1:2 in 25-30 s
+            ---
+            # Espresso
+            ## Ingredients
+
+            - Coffee beans, 18 g
+
+            ## Instructions
+
+            - Pull the shot
+        "})?;
+        let ratio = recipe.metadata().ratio().unwrap();
+        assert_eq!(ratio.to_string(), "1:2 in 25-30 s");
+        assert_eq!(ratio.ratio(), Amount::from_int(2));
+
+        let brew = ratio.for_dose(QuantityOf::new(Mass::Gram, Amount::from_int(18)));
+        assert_eq!(brew.to_string(), "18 g in, 36 g out in 25-30 s");
+        let brew = ratio.for_output(QuantityOf::new(Mass::Gram, Amount::from_int(40)));
+        assert_eq!(brew.dose.to_string(), "20 g");
+
+        assert_eq!(BrewRatio::from_str("1:2.5")?.ratio(), Amount::new(5, 2));
+        assert!(BrewRatio::from_str("1:0").is_err());
+        assert!(BrewRatio::from_str("2").is_err());
+        assert!(BrewRatio::from_str("1:2 in forever").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,429 @@
+//! Import of [Cooklang](https://cooklang.org) recipes, in which ingredients, cookware, and timers
+//! are marked up within the steps:
+//!
+//! ```text
+//! >> servings: 2
+//!
+//! Boil @water{2%L} in a #large pot{} with @salt.
+//! Add @pasta{200%g} and cook for ~{10%minutes}.
+//! ```
+//!
+//! Each paragraph is a step, and sections (`== Sauce ==`) become steps whose substeps are the
+//! steps of the section. Notes (`> ...`) become [`Note`]s. Metadata is given either as a YAML
+//! frontmatter or as `>> key: value` lines, and is parsed like that of Markdown recipes except
+//! that tags need no '#' prefix. The ingredient list is made of the ingredients used in the steps,
+//! in order of first use; quantities of an ingredient used several times are added up.
+
+use std::str::FromStr;
+
+use markdown::mdast::Yaml;
+
+use super::{
+    equipment::{Equipment, Tool},
+    ingredients::{IngredientOptions, Ingredients, RefMatching},
+    instructions::{IngredientRef, Instructions, Step, TextElem},
+    md_parser::{ErrorCode, MDError, MDResult, ParseContext},
+    metadata::{Metadata, TagPrefix},
+    notes::{Note, NoteKind, RichText},
+    unit::{Quantity, QuantityOf},
+    Recipe,
+};
+
+const TITLE: &str = "title";
+/// Ingredient modifiers (e.g., "@?salt" for an optional ingredient), which are ignored.
+const MODIFIERS: [char; 4] = ['?', '-', '+', '&'];
+
+/// Parses a Cooklang recipe. Cooklang recipes are usually named after their file, so `name` is
+/// used unless the metadata has a `title`.
+pub fn parse(content: &str, name: &str) -> MDResult<Recipe> {
+    let mut ctx = ParseContext::new().tag_prefix(TagPrefix::Optional);
+    let result = parse_with(content, name, &mut ctx);
+    ctx.finish(result)
+}
+
+/// Parses a Cooklang recipe, recording errors in individual metadata entries and quantities in
+/// `ctx` rather than failing.
+pub fn parse_with(content: &str, name: &str, ctx: &mut ParseContext) -> MDResult<Recipe> {
+    let content = strip_comments(content);
+    let (mut yaml, body) = match content.strip_prefix("---\n") {
+        Some(rest) => {
+            let end = rest
+                .find("\n---")
+                .ok_or(MDError::new("unterminated frontmatter", None))?;
+            let body = rest[end + "\n---".len()..].trim_start_matches('-');
+            (rest[..end].to_string(), body)
+        }
+        None => (String::new(), &content[..]),
+    };
+
+    let mut builder = Builder::default();
+    let mut paragraph = String::new();
+    for line in body.lines().map(str::trim) {
+        if line.is_empty() {
+            builder.step(&std::mem::take(&mut paragraph), ctx);
+        } else if let Some(entry) = line.strip_prefix(">>") {
+            yaml.push_str(entry.trim());
+            yaml.push('\n');
+        } else if let Some(note) = line.strip_prefix('>') {
+            builder.notes.push(Note::new(
+                NoteKind::Note,
+                vec![RichText::Text(note.trim().to_string())],
+            ));
+        } else if line.starts_with('=') {
+            builder.step(&std::mem::take(&mut paragraph), ctx);
+            builder.section(line.trim_matches(|c: char| c == '=' || c.is_whitespace()));
+        } else {
+            if !paragraph.is_empty() {
+                paragraph.push(' ');
+            }
+            paragraph.push_str(line);
+        }
+    }
+    builder.step(&paragraph, ctx);
+    builder.section("");
+
+    // The title is not a metadata key of Markdown recipes, which take it from their heading.
+    let mut name = name.to_string();
+    let yaml: Vec<&str> = yaml
+        .lines()
+        .filter(
+            |line| match line.strip_prefix(TITLE).and_then(|l| l.strip_prefix(':')) {
+                Some(title) => {
+                    name = title.trim().trim_matches(['"', '\'']).to_string();
+                    false
+                }
+                None => true,
+            },
+        )
+        .collect();
+    let metadata = match yaml.is_empty() {
+        true => Metadata::default(),
+        false => Metadata::parse_with(
+            &Yaml {
+                value: yaml.join("\n"),
+                position: None,
+            },
+            ctx,
+        )?,
+    };
+
+    let ingredients = builder
+        .ingredients
+        .into_iter()
+        .filter_map(|(name, quantity, notes)| {
+            let mut text = name;
+            if let Some(quantity) = quantity {
+                text.push_str(&format!(", {}", quantity));
+            }
+            if !notes.is_empty() {
+                text.push_str(&format!(" ({})", notes.join(", ")));
+            }
+            let options = IngredientOptions::from_str(&text)
+                .map_err(|e| e.or_code(ErrorCode::MalformedIngredient));
+            ctx.recover(options)
+        })
+        .collect();
+    let mut recipe = Recipe {
+        name,
+        ingredients: Ingredients::IngredientList(ingredients),
+        equipment: Equipment::new(builder.tools),
+        instructions: Instructions::new(builder.steps),
+        notes: builder.notes,
+        metadata,
+        cover: None,
+        description: vec![],
+        language: None,
+    };
+    recipe.resolve_ingredient_refs(RefMatching::Exact);
+    Ok(recipe)
+}
+
+/// Removes line comments (`-- ...`) and block comments (`[- ... -]`).
+fn strip_comments(content: &str) -> String {
+    let mut stripped = String::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("[-") {
+        stripped.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find("-]")
+            .map_or("", |end| &rest[start + end + 2..]);
+    }
+    stripped.push_str(rest);
+    stripped
+        .lines()
+        .map(|line| match line.find("--") {
+            // Keep frontmatter delimiters.
+            Some(idx) if !line.trim().chars().all(|c| c == '-') => line[..idx].trim_end(),
+            _ => line,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Names cannot contain the separators of ingredient and tool lines.
+fn sanitize(name: &str) -> String {
+    name.replace([',', '/', '|', '(', ')'], " ")
+        .trim()
+        .to_string()
+}
+
+#[derive(Default)]
+struct Builder {
+    /// Ingredients with their total quantity and notes.
+    ingredients: Vec<(String, Option<Quantity>, Vec<String>)>,
+    tools: Vec<Tool>,
+    steps: Vec<Step>,
+    notes: Vec<Note>,
+    /// Steps of the current section, if any.
+    section: Option<(String, Vec<Step>)>,
+}
+
+impl Builder {
+    /// Starts a section, closing the current one.
+    fn section(&mut self, name: &str) {
+        if let Some((name, steps)) = self.section.take() {
+            match name.is_empty() {
+                true => self.steps.extend(steps),
+                false => self.steps.push(Step::from_text(&name, steps)),
+            }
+        }
+        self.section = Some((name.to_string(), vec![]));
+    }
+
+    fn step(&mut self, text: &str, ctx: &mut ParseContext) {
+        if text.is_empty() {
+            return;
+        }
+        let mut description = vec![];
+        let mut plain = String::new();
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            rest = &rest[c.len_utf8()..];
+            let elem = match c {
+                '@' => self.ingredient(&mut rest, ctx),
+                '#' => self.tool(&mut rest),
+                '~' => Self::timer(&mut rest, ctx),
+                _ => None,
+            };
+            match elem {
+                Some(elem) => {
+                    if !plain.is_empty() {
+                        description.push(TextElem::Text(std::mem::take(&mut plain)));
+                    }
+                    description.extend(elem);
+                }
+                None => plain.push(c),
+            }
+        }
+        if !plain.is_empty() {
+            description.push(TextElem::Text(plain));
+        }
+        let step = Step::new(description, vec![]);
+        match &mut self.section {
+            Some((_, steps)) => steps.push(step),
+            None => self.steps.push(step),
+        }
+    }
+
+    /// Parses an ingredient following '@' and records it. Returns `None`, leaving `rest`
+    /// unchanged, if there is no ingredient.
+    fn ingredient(&mut self, rest: &mut &str, ctx: &mut ParseContext) -> Option<Option<TextElem>> {
+        let mut after = rest.trim_start_matches(MODIFIERS);
+        let (name, amount) = component(&mut after)?;
+        let name = sanitize(&name);
+        let quantity = amount.and_then(|amount| {
+            ctx.recover(Quantity::from_str(&amount).map_err(|e| {
+                MDError::new(
+                    &format!("failed to parse quantity of {:?}: {}", name, e),
+                    None,
+                )
+                .with_code(ErrorCode::InvalidQuantity)
+            }))
+        });
+        let note = after
+            .strip_prefix('(')
+            .and_then(|r| r.split_once(')'))
+            .map(|(note, rest)| {
+                after = rest;
+                sanitize(note)
+            });
+        *rest = after;
+
+        let existing = self
+            .ingredients
+            .iter_mut()
+            .find(|(n, _, _)| n.eq_ignore_ascii_case(&name));
+        match existing {
+            Some((_, total, notes)) => {
+                match (&total, quantity) {
+                    (Some(q), Some(quantity)) => match q.checked_add(&quantity) {
+                        Some(sum) => *total = Some(sum),
+                        None => ctx.warn(MDError::new(
+                            &format!("cannot add up quantities of {:?}", name),
+                            None,
+                        )),
+                    },
+                    (None, quantity) => *total = quantity,
+                    (_, None) => {}
+                }
+                notes.extend(note);
+            }
+            None => self
+                .ingredients
+                .push((name.clone(), quantity, note.into_iter().collect())),
+        }
+        Some(Some(TextElem::IngredientRef(IngredientRef::new(&name))))
+    }
+
+    /// Parses a tool following '#' and records it. Its name is kept in the step.
+    fn tool(&mut self, rest: &mut &str) -> Option<Option<TextElem>> {
+        let mut after = *rest;
+        let (name, _) = component(&mut after)?;
+        let name = sanitize(&name);
+        let tool = Tool::from_str(&name, &Default::default()).ok()?;
+        if !self
+            .tools
+            .iter()
+            .any(|t| t.name().eq_ignore_ascii_case(&name))
+        {
+            self.tools.push(tool);
+        }
+        *rest = after;
+        Some(Some(TextElem::Text(name)))
+    }
+
+    /// Parses a timer following '~'. Its name, if any, is dropped. Returns `Some(None)` if the
+    /// timer is invalid.
+    fn timer(rest: &mut &str, ctx: &mut ParseContext) -> Option<Option<TextElem>> {
+        let mut after = *rest;
+        let (_, amount) = component(&mut after)?;
+        let amount = amount?;
+        *rest = after;
+        let time = ctx.recover(QuantityOf::from_str(&amount).map_err(|e| {
+            MDError::new(&format!("failed to parse timer: {}", e), None)
+                .with_code(ErrorCode::InvalidTimer)
+        }));
+        Some(time.map(TextElem::Timer))
+    }
+}
+
+/// Splits the component at the start of `rest` (e.g., "large pot{}" or "salt") into its name and
+/// amount (e.g., "2 tsp" from "{2%tsp}"), advancing `rest` past it. Multi-word names must be
+/// followed by braces.
+fn component(rest: &mut &str) -> Option<(String, Option<String>)> {
+    let word_end = rest
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(rest.len());
+    let brace = rest
+        .find(['{', '@', '#', '~', '\n'])
+        .filter(|&idx| rest[idx..].starts_with('{'));
+    let (name, after) = match brace.and_then(|idx| Some((idx, rest[idx..].find('}')? + idx))) {
+        Some((open, close)) => {
+            let amount = rest[open + 1..close].trim().trim_start_matches('=');
+            let amount = (!amount.is_empty()).then(|| amount.replace('%', " "));
+            let name = (rest[..open].trim().to_string(), amount);
+            (name, close + 1)
+        }
+        None if word_end > 0 => ((rest[..word_end].to_string(), None), word_end),
+        None => return None,
+    };
+    *rest = &rest[after..];
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn parse_cooklang() -> MDResult<()> {
+        let recipe = parse(
+            indoc! {"
+                >> title: Pasta al pomodoro
+                >> servings: 2
+                >> tags: [pasta, quick]
+
+                -- Family recipe
+                Boil @water{2%L} in a #large pot{} with @salt{1%tsp}.
+                Add @pasta{200%g} and cook for ~{10%minutes}.
+
+                == Sauce ==
+                Fry @garlic{2}(sliced) in @olive oil{2%tbsp}. [- not too long -]
+                Add @tomatoes{400%g} and @salt{0.5%tsp}, then simmer for ~sauce{15%min}.
+
+                > Save some pasta water.
+            "},
+            "pasta",
+        )?;
+        assert_eq!(recipe.name(), "Pasta al pomodoro");
+        assert_eq!(recipe.metadata().tags(), ["pasta", "quick"]);
+        assert_eq!(recipe.equipment().tools()[0].name(), "large pot");
+        assert_eq!(recipe.notes()[0].text(), "Save some pasta water.");
+
+        let ingredients: Vec<String> = recipe
+            .ingredients()
+            .iter()
+            .map(|i| {
+                let i = i.ingredient();
+                format!(
+                    "{}: {:?} {:?}",
+                    i.name(),
+                    i.quantity().map(|q| q.to_string()),
+                    i.info()
+                )
+            })
+            .collect();
+        assert_eq!(
+            ingredients,
+            vec![
+                "water: Some(\"2 L\") None",
+                "salt: Some(\"1.5 tsp\") None",
+                "pasta: Some(\"200 g\") None",
+                "garlic: Some(\"2\") Some(\"sliced\")",
+                "olive oil: Some(\"2 tbsp\") None",
+                "tomatoes: Some(\"400 g\") None",
+            ]
+        );
+
+        let steps = recipe.instructions().steps();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(
+            steps[0].text(),
+            "Boil water in a large pot with salt. Add pasta and cook for 10 min."
+        );
+        assert_eq!(steps[1].text(), "Sauce");
+        assert_eq!(
+            steps[1].substeps()[0].text(),
+            "Fry garlic in olive oil. Add tomatoes and salt, then simmer for 15 min."
+        );
+        let timers: Vec<String> = recipe
+            .instructions()
+            .timers()
+            .map(|(path, time)| format!("{}: {}", path, time))
+            .collect();
+        assert_eq!(timers, vec!["1: 10 min", "2.1: 15 min"]);
+        let refs = steps[1].substeps()[0].description();
+        assert!(matches!(&refs[1], TextElem::IngredientRef(r) if r.index() == Some(3)));
+        Ok(())
+    }
+
+    #[test]
+    fn frontmatter() -> MDResult<()> {
+        let recipe = parse(
+            indoc! {"
+                ---
+                title: Toast
+                servings: 1
+                ---
+                Toast the @bread{1%slice}, ~{2%min}.
+            "},
+            "toast",
+        )?;
+        assert_eq!(recipe.name(), "Toast");
+        assert_eq!(recipe.metadata().servings().unwrap().to_string(), "1");
+        assert_eq!(recipe.ingredients().iter().count(), 1);
+        assert!(parse("Wait for ~{a while}.", "wait").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+//! Compact JSON "cook view" of a recipe, for kitchen displays and other small clients that only
+//! walk through the steps: steps are flattened, and come with their timers and the ingredients
+//! they use.
+//!
+//! The payload is versioned by its `v` field. Fields are only ever added within a version, so
+//! clients must ignore fields they do not know. Optional fields and empty lists are left out
+//! rather than written as `null` or `[]`:
+//!
+//! ```json
+//! {
+//!   "v": 1,
+//!   "name": "Lemonade",
+//!   "servings": "4",
+//!   "ingredients": [{ "name": "Lemons", "qty": "3" }, { "name": "Sugar", "qty": "100 g" }],
+//!   "steps": [
+//!     { "id": "1", "text": "Squeeze the Lemons", "ingredients": [0] },
+//!     { "id": "1.1", "text": "Stir for 2-3 min", "timers": [{ "secs": 120, "max_secs": 180 }] },
+//!     { "id": "2", "text": "Serve", "group": "Serving" }
+//!   ]
+//! }
+//! ```
+
+use serde_json::{json, Map, Value};
+
+use super::{
+    amount::Amount,
+    instructions::{FlatStep, TextElem},
+    unit::{QuantityOf, Time},
+    Recipe,
+};
+
+/// Version of the cook view payload.
+pub const COOKVIEW_VERSION: u64 = 1;
+
+impl Recipe {
+    /// Returns the recipe as a cook view payload, at scale 1 for scale-dependent text. Scale the
+    /// recipe first for other scales.
+    pub fn to_cookview(&self) -> Value {
+        let mut json = Map::new();
+        json.insert("v".into(), COOKVIEW_VERSION.into());
+        json.insert("name".into(), self.name.clone().into());
+        if let Some(servings) = self.metadata.servings() {
+            json.insert("servings".into(), servings.amount.to_string().into());
+        }
+        let ingredients: Vec<Value> = self
+            .ingredients
+            .iter()
+            .map(|options| {
+                let ingredient = options.ingredient();
+                let mut json = Map::new();
+                json.insert("name".into(), ingredient.name().into());
+                if let Some(quantity) = ingredient.quantity() {
+                    json.insert("qty".into(), quantity.to_string().into());
+                }
+                json.into()
+            })
+            .collect();
+        if !ingredients.is_empty() {
+            json.insert("ingredients".into(), ingredients.into());
+        }
+        let groups: Vec<(&str, usize)> = self
+            .instructions
+            .groups()
+            .map(|(group, steps)| (group.name(), steps.len()))
+            .collect();
+        let steps: Vec<Value> = self
+            .instructions
+            .flatten()
+            .iter()
+            .map(|flat| {
+                let mut json = step(flat);
+                if let Some(name) = group_of(&groups, flat.path.0[0]) {
+                    json["group"] = name.into();
+                }
+                json
+            })
+            .collect();
+        if !steps.is_empty() {
+            json.insert("steps".into(), steps.into());
+        }
+        json.into()
+    }
+}
+
+/// Name of the group of a top-level step, given the groups' names and numbers of steps.
+fn group_of<'a>(groups: &[(&'a str, usize)], index: usize) -> Option<&'a str> {
+    let mut first = 0;
+    for (name, len) in groups {
+        if index < first + len {
+            return Some(name);
+        }
+        first += len;
+    }
+    None
+}
+
+fn step(flat: &FlatStep) -> Value {
+    let mut json = Map::new();
+    json.insert("id".into(), flat.path.to_string().into());
+    let elems = flat.step.resolved(Amount::ONE);
+    let text: String = elems.iter().map(|e| e.to_string()).collect();
+    json.insert("text".into(), text.into());
+    let mut ingredients: Vec<usize> = vec![];
+    let mut timers = vec![];
+    for elem in elems {
+        match elem {
+            TextElem::IngredientRef(r) => {
+                if let Some(index) = r.index().filter(|i| !ingredients.contains(i)) {
+                    ingredients.push(index);
+                }
+            }
+            TextElem::Timer(time) => timers.push(timer(time)),
+            _ => (),
+        }
+    }
+    if !ingredients.is_empty() {
+        json.insert("ingredients".into(), ingredients.into());
+    }
+    if !timers.is_empty() {
+        json.insert("timers".into(), timers.into());
+    }
+    json.into()
+}
+
+/// A timer, in whole seconds.
+fn timer(time: &QuantityOf<Time>) -> Value {
+    let secs = |amount: Amount| {
+        QuantityOf::new(time.unit, amount)
+            .to_duration()
+            .as_secs_f64()
+            .round() as u64
+    };
+    match time.max_amount {
+        Some(max) => json!({ "secs": secs(time.amount), "max_secs": secs(max) }),
+        None => json!({ "secs": secs(time.amount) }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn cookview() {
+        let recipe = Recipe::from_mdast(indoc! {"
+            ---
+            servings: 4
+            ---
+            # Lemonade
+            ## Ingredients
+
+            - Lemons, 3
+            - Sugar, 100 g | Honey, 80 g
+            - Ice
+
+            ## Instructions
+
+            - Squeeze the *Lemons*, then add the *sugar* {1x: right away | 2x: in two goes}
+                - Stir for **2-3 min**, rest **1 h**, and squeeze the *lemons* again
+            - Serve
+        "})
+        .unwrap();
+        assert_eq!(
+            recipe.to_cookview(),
+            json!({
+                "v": 1,
+                "name": "Lemonade",
+                "servings": "4",
+                "ingredients": [
+                    { "name": "Lemons", "qty": "3" },
+                    { "name": "Sugar", "qty": "100 g" },
+                    { "name": "Ice" },
+                ],
+                "steps": [
+                    {
+                        "id": "1",
+                        "text": "Squeeze the Lemons, then add the sugar right away",
+                        "ingredients": [0, 1],
+                    },
+                    {
+                        "id": "1.1",
+                        "text": "Stir for 2-3 min, rest 1 h, and squeeze the lemons again",
+                        "ingredients": [0],
+                        "timers": [{ "secs": 120, "max_secs": 180 }, { "secs": 3600 }],
+                    },
+                    { "id": "2", "text": "Serve" },
+                ],
+            })
+        );
+    }
+}
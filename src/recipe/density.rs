@@ -0,0 +1,243 @@
+//! Densities of ingredients, to convert their quantities between masses and volumes (e.g., "2
+//! cup" of flour to "254.4 g").
+//!
+//! Densities are in grams per milliliter. Built-in densities of common ingredients come from the
+//! `densities` preset pack, tables can be loaded from any bundle (e.g., one curated alongside a
+//! library), and recipes may set their own in the metadata:
+//!
+//! ```yaml
+//! densities:
+//!   flour: 0.55 g/mL
+//!   ground almonds: 100 g / 250 mL
+//! ```
+
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+use super::{
+    amount::Amount,
+    md_parser::{MDError, MDResult},
+    metadata::Metadata,
+    unit::{Dimension, Quantity, Unit},
+    Recipe,
+};
+use crate::{
+    library::bundle::{Bundle, DENSITIES as DENSITIES_TABLE},
+    presets,
+};
+
+pub(super) const DENSITIES: &str = "densities";
+
+/// Densities of ingredients in g/mL, by lowercase name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DensityTable {
+    densities: BTreeMap<String, Amount>,
+}
+
+impl DensityTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Densities of the built-in `densities` preset pack.
+    pub fn builtin() -> Self {
+        presets::load("densities")
+            .and_then(|bundle| Self::from_bundle(&bundle))
+            .expect("built-in densities are valid")
+    }
+
+    /// Densities of the `densities` table of a bundle, with values like "0.53 g/ml".
+    pub fn from_bundle(bundle: &Bundle) -> MDResult<Self> {
+        let mut this = Self::new();
+        for (name, value) in bundle.table(DENSITIES_TABLE).into_iter().flatten() {
+            this.insert(name, parse_density(name, value)?);
+        }
+        Ok(this)
+    }
+
+    /// Densities of a bundle file (see [`DensityTable::from_bundle`]).
+    pub fn load(path: &Path) -> MDResult<Self> {
+        Self::from_bundle(&presets::load(&path.to_string_lossy())?)
+    }
+
+    /// Sets the density of an ingredient, in g/mL.
+    pub fn insert(&mut self, name: &str, grams_per_ml: Amount) {
+        self.densities.insert(name.to_lowercase(), grams_per_ml);
+    }
+
+    /// Adds the densities of `other`, which take precedence over ours.
+    pub fn extend(&mut self, other: &Self) {
+        self.densities
+            .extend(other.densities.iter().map(|(k, v)| (k.clone(), *v)));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.densities.is_empty()
+    }
+
+    /// Densities by lowercase name, in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Amount)> {
+        self.densities.iter().map(|(k, v)| (k.as_str(), *v))
+    }
+
+    /// Density of an ingredient, in g/mL. Names are compared case-insensitively, and fall back to
+    /// their last words (e.g., "Brown sugar" gets the density of "sugar" unless "brown sugar" has
+    /// its own).
+    pub fn get(&self, name: &str) -> Option<Amount> {
+        let name = name.trim().to_lowercase();
+        let mut rest = name.as_str();
+        loop {
+            if let Some(density) = self.densities.get(rest) {
+                return Some(*density);
+            }
+            rest = rest.split_once(' ')?.1.trim_start();
+        }
+    }
+
+    /// Converts a quantity of an ingredient to a unit, through the ingredient's density if the
+    /// units are a mass and a volume.
+    pub fn convert(&self, name: &str, quantity: &Quantity, unit: &Unit) -> Option<Quantity> {
+        quantity
+            .convert_to(unit)
+            .or_else(|| quantity.convert_with_density(unit, self.get(name)?))
+    }
+
+    pub(super) fn parse(value: &saphyr::Yaml<'_>) -> MDResult<Self> {
+        let mapping = value.as_mapping().ok_or(MDError::new(
+            &format!("expected mapping under {:?}", DENSITIES),
+            None,
+        ))?;
+        let mut this = Self::new();
+        for (key, value) in mapping {
+            let key = key
+                .as_str()
+                .ok_or(MDError::new("expected string key", None))?;
+            let value = Metadata::scalar_str(value, key)?;
+            this.insert(key, parse_density(key, &value)?);
+        }
+        Ok(this)
+    }
+}
+
+/// Parses a density written as a mass per volume (e.g., "0.53 g/mL" or "100 g / 250 mL"), or as
+/// a number of grams per milliliter.
+fn parse_density(name: &str, value: &str) -> MDResult<Amount> {
+    let invalid = || MDError::new(&format!("invalid density of {:?}: {:?}", name, value), None);
+    if let Ok(density) = Amount::from_str(value) {
+        return Ok(density);
+    }
+    let (mass, volume) = value.rsplit_once('/').ok_or_else(invalid)?;
+    let mass = Quantity::from_str(mass.trim()).map_err(|_| invalid())?;
+    let volume = match Unit::from_str(volume.trim()) {
+        Ok(unit) => Quantity::new(&unit, Amount::ONE),
+        Err(_) => Quantity::from_str(volume.trim()).map_err(|_| invalid())?,
+    };
+    if mass.unit.dimension() != Dimension::Mass
+        || volume.unit.dimension() != Dimension::Volume
+        || mass.is_range()
+        || volume.is_range()
+        || volume.amount.is_zero()
+    {
+        return Err(invalid());
+    }
+    Ok(mass.to_base().amount / volume.to_base().amount)
+}
+
+impl Recipe {
+    /// Returns the recipe with ingredient quantities converted to the unit chosen by `unit_for`,
+    /// as [`Recipe::converted`] does, but also between masses and volumes for ingredients with a
+    /// known density. The recipe's own densities take precedence over `densities`.
+    pub fn converted_with_densities(
+        &self,
+        densities: &DensityTable,
+        unit_for: impl Fn(&Quantity) -> Option<Unit>,
+    ) -> Self {
+        let mut table = densities.clone();
+        table.extend(self.metadata.densities());
+        let mut recipe = self.clone();
+        recipe.ingredients.map_ingredient_quantities(|name, q| {
+            unit_for(q)
+                .and_then(|unit| table.convert(name, q, &unit))
+                .unwrap_or_else(|| q.clone())
+        });
+        recipe
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::unit::{Mass, Volume};
+    use indoc::indoc;
+
+    #[test]
+    fn densities() -> MDResult<()> {
+        assert_eq!(parse_density("x", "0.5")?, Amount::new(1, 2));
+        assert_eq!(parse_density("x", "0.53 g/ml")?, Amount::new(53, 100));
+        assert_eq!(parse_density("x", "100 g / 250 mL")?, Amount::new(2, 5));
+        assert_eq!(parse_density("x", "1 kg/L")?, Amount::ONE);
+        assert!(parse_density("x", "1 g/cm").is_err());
+        assert!(parse_density("x", "1 g/0 mL").is_err());
+
+        let table = DensityTable::builtin();
+        assert_eq!(table.get("Water"), Some(Amount::ONE));
+        assert_eq!(table.get("brown sugar"), Some(Amount::new(83, 100)));
+        assert_eq!(table.get("Caster Sugar"), table.get("sugar"));
+        assert_eq!(table.get("gravel"), None);
+
+        let flour = Quantity::from_str("2 cup")?;
+        let grams = Unit::Mass(Mass::Gram);
+        assert_eq!(
+            table.convert("Flour", &flour, &grams).unwrap().to_string(),
+            "254.4 g"
+        );
+        let butter = Quantity::from_str("91 g")?;
+        let ml = Unit::Volume(Volume::Milliliter);
+        assert_eq!(
+            table.convert("Butter", &butter, &ml).unwrap().to_string(),
+            "100 mL"
+        );
+        assert!(table.convert("gravel", &flour, &grams).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn converted_with_densities() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(indoc! {"
+            ---
+            densities:
+              flour: 0.5 g/mL
+            ---
+            # Bread
+            ## Ingredients
+
+            - Flour, 2 cup
+            - Sugar, 1 tbsp
+            - Water, 300 mL
+            - Eggs, 2
+
+            ## Instructions
+
+            - Mix
+        "})?;
+        let grams = recipe.converted_with_densities(&DensityTable::builtin(), |q| {
+            (q.unit.dimension() == Dimension::Volume).then_some(Unit::Mass(Mass::Gram))
+        });
+        let quantities: Vec<String> = grams
+            .ingredients()
+            .iter()
+            .map(|o| o.ingredient().quantity().unwrap().to_string())
+            .collect();
+        assert_eq!(quantities, ["240 g", "12.75 g", "300 g", "2"]);
+        assert_eq!(
+            grams
+                .ingredients()
+                .iter()
+                .next()
+                .unwrap()
+                .ingredient()
+                .original_quantity(),
+            Some(&Quantity::from_str("2 cup")?)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+//! Helpers for drinks and cocktails, whose ingredients are often given in parts (e.g., "Gin, 2
+//! parts") rather than in absolute quantities.
+
+use std::str::FromStr;
+
+use super::{
+    amount::Amount,
+    ingredients::Ingredient,
+    unit::{Quantity, Unit},
+    Recipe,
+};
+
+impl Recipe {
+    /// Total number of parts among the ingredients (not counting alternatives).
+    pub fn total_parts(&self) -> Amount {
+        self.ingredients
+            .iter()
+            .filter_map(|options| options.ingredient().quantity())
+            .filter(|q| matches!(q.unit, Unit::Part(_)))
+            .map(|q| q.amount)
+            .sum()
+    }
+
+    /// Returns the recipe with quantities in parts converted to multiples of `part` (e.g., "30
+    /// mL").
+    pub fn with_part_size(&self, part: &Quantity) -> Self {
+        let mut recipe = self.clone();
+        recipe.ingredients.map_quantities(|q| match q.unit {
+            Unit::Part(_) => Quantity {
+                unit: part.unit.clone(),
+                amount: q.amount * part.amount,
+                max_amount: q.max_amount.map(|max| max * part.amount),
+                approximation: q.approximation.or(part.approximation),
+            },
+            _ => q.clone(),
+        });
+        recipe
+    }
+
+    /// Returns the recipe with quantities in parts converted so that they add up to `total` (e.g.,
+    /// the capacity of a glass), or `None` if no ingredient is given in parts.
+    pub fn with_total(&self, total: &Quantity) -> Option<Self> {
+        let parts = self.total_parts();
+        (!parts.is_zero()).then(|| self.with_part_size(&total.clone().scale(Amount::ONE / parts)))
+    }
+
+    /// Estimates the alcohol by volume of the drink, in percent, given the ABV of each ingredient
+    /// (`None` for non-alcoholic ones). Only ingredients measured in volume or in parts count, and
+    /// both cannot be mixed. Returns `None` if there is nothing to measure.
+    pub fn estimated_abv(&self, abv: impl Fn(&Ingredient) -> Option<Amount>) -> Option<Amount> {
+        let mut in_parts = None;
+        let (mut volume, mut alcohol) = (Amount::ZERO, Amount::ZERO);
+        for options in self.ingredients.iter() {
+            let ingredient = options.ingredient();
+            let Some(quantity) = ingredient.quantity().filter(|q| !q.is_to_taste()) else {
+                continue;
+            };
+            let amount = match quantity.unit {
+                Unit::Part(_) => quantity.amount,
+                Unit::Volume(_) => quantity.to_base().amount,
+                _ => continue,
+            };
+            let is_part = matches!(quantity.unit, Unit::Part(_));
+            if *in_parts.get_or_insert(is_part) != is_part {
+                return None;
+            }
+            volume = volume + amount;
+            alcohol = alcohol + amount * abv(ingredient).unwrap_or(Amount::ZERO);
+        }
+        (!volume.is_zero()).then(|| alcohol / volume)
+    }
+}
+
+/// Reads an ingredient's ABV from its additional information (e.g., "Rum, 2 parts (40% ABV)").
+pub fn abv_from_info(ingredient: &Ingredient) -> Option<Amount> {
+    ingredient
+        .info()?
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .find_map(|word| Amount::from_str(word.strip_suffix('%')?).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::MDResult;
+    use indoc::indoc;
+
+    #[test]
+    fn parts() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(indoc! {"
+            # Negroni
+            ## Ingredients
+
+            - Gin, 1 part (40% ABV)
+            - Campari, 1 part (25%)
+            - Vermouth, 1 part (15%)
+            - Orange peel, 1
+
+            ## Instructions
+
+            - Stir with ice
+        "})?;
+        assert_eq!(recipe.total_parts(), Amount::from_int(3));
+
+        let quantity = |recipe: &Recipe, i: usize| {
+            let options = recipe.ingredients().get(i).unwrap();
+            options.ingredient().quantity().unwrap().to_string()
+        };
+        let part = Quantity::from_str("30 mL")?;
+        assert_eq!(quantity(&recipe.with_part_size(&part), 0), "30 mL");
+        let glass = recipe.with_total(&Quantity::from_str("12 cL")?).unwrap();
+        assert_eq!(quantity(&glass, 1), "4 cL");
+        assert_eq!(quantity(&glass, 3), "1");
+
+        assert_eq!(
+            recipe.estimated_abv(abv_from_info),
+            Some(Amount::new(80, 3))
+        );
+        assert_eq!(glass.estimated_abv(abv_from_info), Some(Amount::new(80, 3)));
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use std::{collections::HashMap, str::FromStr};
+
+use super::{
+    md_parser::{
+        expect_children, get_text_from_paragraph, ErrorCode, MDError, MDResult, ParseContext,
+    },
+    metadata::SizeInfo,
+};
+use markdown::mdast::Node;
+
+/// Tools needed for a recipe, listed in an optional "Equipment" section.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Equipment {
+    tools: Vec<Tool>,
+}
+
+impl Equipment {
+    pub fn new(tools: Vec<Tool>) -> Self {
+        Self { tools }
+    }
+
+    /// Parses the equipment list. Tools may reference the sizes defined in the metadata, which
+    /// must then be given in `sizes`.
+    pub fn parse_with(
+        nodes: &[Node],
+        sizes: &HashMap<String, SizeInfo>,
+        ctx: &mut ParseContext,
+    ) -> MDResult<Self> {
+        match nodes {
+            [] => Ok(Self::default()),
+            [Node::List(list)] => Ok(Self {
+                tools: list
+                    .children
+                    .iter()
+                    .filter_map(|item| {
+                        let tool = Tool::parse(item, sizes)
+                            .map_err(|e| e.or_code(ErrorCode::MalformedEquipment));
+                        ctx.recover(tool)
+                    })
+                    .collect(),
+            }),
+            [node] => Err(MDError::new("equipment must be list", Some(node))
+                .with_code(ErrorCode::MalformedEquipment)),
+            _ => Err(
+                MDError::new("expected single list node for equipment", None)
+                    .with_code(ErrorCode::MalformedEquipment),
+            ),
+        }
+    }
+
+    pub fn tools(&self) -> &[Tool] {
+        &self.tools
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+}
+
+/// A tool, written "name" or "name, size" (e.g., "Springform pan, 24 cm°"). The size may also be
+/// the name of a `size | <name>` metadata entry (e.g., "Springform pan, pan").
+#[derive(Clone, PartialEq, Debug)]
+pub struct Tool {
+    name: String,
+    size: Option<ToolSize>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ToolSize {
+    Inline(SizeInfo),
+    /// Name of a size defined in the metadata.
+    Ref(String),
+}
+
+impl Tool {
+    fn parse(node: &Node, sizes: &HashMap<String, SizeInfo>) -> MDResult<Self> {
+        match node {
+            Node::ListItem(item) => {
+                expect_children(node, 1)?;
+                Self::from_str(get_text_from_paragraph(&item.children[0])?, sizes)
+                    .map_err(|e| MDError::new(e.message(), Some(node)))
+            }
+            _ => Err(MDError::new("expected list item", Some(node))),
+        }
+    }
+
+    pub(super) fn from_str(text: &str, sizes: &HashMap<String, SizeInfo>) -> MDResult<Self> {
+        let (name, size) = match text.split_once(',') {
+            Some((name, size)) => (name.trim(), Some(size.trim())),
+            None => (text.trim(), None),
+        };
+        if name.is_empty() {
+            return Err(MDError::new("tool name cannot be empty", None));
+        }
+        let size = match size {
+            None => None,
+            Some(size) if sizes.contains_key(size) => Some(ToolSize::Ref(size.to_string())),
+            Some(size) => Some(ToolSize::Inline(SizeInfo::from_str(size).map_err(
+                |_| {
+                    MDError::new(
+                        &format!(
+                            "{:?} is neither a size nor a size defined in metadata",
+                            size
+                        ),
+                        None,
+                    )
+                },
+            )?)),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            size,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> Option<&ToolSize> {
+        self.size.as_ref()
+    }
+
+    /// Returns the size of the tool, looking up references in the metadata's sizes.
+    pub fn resolve_size<'a>(
+        &'a self,
+        sizes: &'a HashMap<String, SizeInfo>,
+    ) -> Option<&'a SizeInfo> {
+        match self.size.as_ref()? {
+            ToolSize::Inline(size) => Some(size),
+            ToolSize::Ref(name) => sizes.get(name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn parse_equipment() -> MDResult<()> {
+        let content = indoc! {"
+            - Whisk
+            - Springform pan, pan
+            - Baking dish, 20 cm
+        "};
+        let sizes = HashMap::from([("pan".to_string(), SizeInfo::from_str("24 cm°")?)]);
+        let mdast = markdown::to_mdast(content, &markdown::ParseOptions::default()).unwrap();
+        let equipment =
+            Equipment::parse_with(mdast.children().unwrap(), &sizes, &mut ParseContext::new())?;
+        let tools = equipment.tools();
+        assert_eq!(tools.len(), 3);
+        assert_eq!(tools[0].size(), None);
+        assert_eq!(tools[1].size(), Some(&ToolSize::Ref("pan".to_string())));
+        assert_eq!(tools[1].resolve_size(&sizes).unwrap().to_string(), "24 cm°");
+        assert_eq!(tools[2].resolve_size(&sizes).unwrap().to_string(), "20 cm");
+
+        assert!(Tool::from_str("Pot, large", &sizes).is_err());
+        assert!(Tool::from_str(" , 20 cm", &sizes).is_err());
+        Ok(())
+    }
+}
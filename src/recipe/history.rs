@@ -0,0 +1,122 @@
+//! When a recipe was written and cooked, from the frontmatter:
+//!
+//! ```yaml
+//! created: 2023-11-04
+//! last_cooked: 2024-05-01
+//! cook_log:
+//!   - 2024-03-12
+//!   - 2024-05-01
+//! ```
+//!
+//! Dates are written as "YYYY-MM-DD". [`Recipe::record_cooked`] keeps the cook log and the last
+//! cooked date up to date, so that they can be written back.
+
+use chrono::NaiveDate;
+
+use super::{
+    md_parser::{MDError, MDResult},
+    metadata::{Metadata, DATE_FORMAT},
+    Recipe,
+};
+
+pub(super) const CREATED: &str = "created";
+pub(super) const LAST_COOKED: &str = "last_cooked";
+pub(super) const COOK_LOG: &str = "cook_log";
+
+pub(super) fn parse_date(value: &saphyr::Yaml<'_>, key: &str) -> MDResult<NaiveDate> {
+    let value = Metadata::scalar_str(value, key)?;
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT).map_err(|e| {
+        MDError::new(
+            &format!(
+                "invalid date {:?} under {:?}: expected YYYY-MM-DD",
+                value, key
+            ),
+            None,
+        )
+        .with_source(e)
+    })
+}
+
+/// Parses a list of dates (or a single one), in chronological order.
+pub(super) fn parse_log(value: &saphyr::Yaml<'_>) -> MDResult<Vec<NaiveDate>> {
+    let mut dates = match value.as_sequence() {
+        Some(seq) => seq
+            .iter()
+            .map(|date| parse_date(date, COOK_LOG))
+            .collect::<MDResult<Vec<_>>>()?,
+        None => vec![parse_date(value, COOK_LOG)?],
+    };
+    dates.sort();
+    Ok(dates)
+}
+
+impl Recipe {
+    /// Records that the recipe was cooked on a date, in its cook log and its last cooked date,
+    /// whichever it keeps (a cook log if it keeps neither).
+    pub fn record_cooked(&mut self, date: NaiveDate) {
+        let metadata = &mut self.metadata;
+        if !metadata.cook_log.is_empty() || metadata.last_cooked.is_none() {
+            let i = metadata.cook_log.partition_point(|d| *d <= date);
+            metadata.cook_log.insert(i, date);
+        }
+        if let Some(last_cooked) = &mut metadata.last_cooked {
+            *last_cooked = date.max(*last_cooked);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::markdown::render;
+    use indoc::indoc;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn record_cooked() -> MDResult<()> {
+        let body = "# Flan\n## Ingredients\n\n- Eggs, 4\n\n## Instructions\n\n- Bake\n";
+        let mut recipe = Recipe::from_mdast(&format!(
+            indoc! {"
+                ---
+                created: 2023-11-04
+                cook_log:
+                  - 2024-05-01
+                  - 2024-03-12
+                ---
+                {}"},
+            body
+        ))?;
+        let metadata = recipe.metadata();
+        assert_eq!(metadata.created(), Some(date("2023-11-04")));
+        assert_eq!(
+            metadata.cook_log(),
+            [date("2024-03-12"), date("2024-05-01")]
+        );
+        assert_eq!(metadata.last_cooked(), Some(date("2024-05-01")));
+
+        recipe.record_cooked(date("2024-04-20"));
+        assert_eq!(recipe.metadata().cook_log().len(), 3);
+        assert_eq!(recipe.metadata().last_cooked(), Some(date("2024-05-01")));
+        let written = render(&recipe);
+        assert!(written.contains("cook_log:\n  - 2024-03-12\n  - 2024-04-20\n  - 2024-05-01\n"));
+        assert_eq!(Recipe::from_mdast(&written)?, recipe);
+
+        // Only the last cooked date is kept, if that is all the recipe has.
+        let mut recipe =
+            Recipe::from_mdast(&format!("---\nlast_cooked: 2024-01-02\n---\n{}", body))?;
+        recipe.record_cooked(date("2024-06-01"));
+        assert!(recipe.metadata().cook_log().is_empty());
+        assert!(render(&recipe).contains("last_cooked: 2024-06-01\n"));
+
+        let mut recipe = Recipe::from_mdast(body)?;
+        assert_eq!(recipe.metadata().last_cooked(), None);
+        recipe.record_cooked(date("2024-06-01"));
+        assert_eq!(recipe.metadata().cook_log(), [date("2024-06-01")]);
+
+        assert!(Recipe::from_mdast(&format!("---\ncreated: yesterday\n---\n{}", body)).is_err());
+        Ok(())
+    }
+}
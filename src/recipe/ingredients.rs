@@ -1,21 +1,52 @@
+use std::ops::Range;
 use std::str::FromStr;
 use std::vec;
 
-use super::md_parser::{expect_children, get_heading, get_text_from_paragraph, MDError, MDResult};
-use super::unit::Quantity;
-use markdown::{self, mdast::Node};
+use super::amount::{Amount, NumberFormat};
+use super::language::Language;
+use super::md_parser::{
+    expect_children, find_wikilink, get_heading_with, get_text_from_paragraph, is_placeholder,
+    shift_position, ErrorCode, MDError, MDResult, ParseContext,
+};
+use super::unit::{Quantity, Unit, UnitRegistry};
+use crate::matching::{levenshtein, Matcher};
+use markdown::{
+    self,
+    mdast::Node,
+    unist::{Point, Position},
+};
 
+#[derive(Clone, PartialEq, Debug)]
 pub enum Ingredients {
     IngredientList(Vec<IngredientOptions>),
     IngredientGroups(Vec<IngredientGroup>),
 }
 
 impl Ingredients {
-    pub fn parse(nodes: &[Node]) -> MDResult<Self> {
+    #[cfg(test)]
+    pub(crate) fn parse(nodes: &[Node]) -> MDResult<Self> {
+        let mut ctx = ParseContext::new();
+        let result = Self::parse_with(nodes, &mut ctx);
+        ctx.finish(result)
+    }
+
+    /// Parses the ingredients section, recording errors in individual lines or groups in `ctx`
+    /// rather than failing.
+    pub(crate) fn parse_with(nodes: &[Node], ctx: &mut ParseContext) -> MDResult<Self> {
         match nodes.len() {
             0 => Ok(Self::IngredientList(vec![])),
+            1 if is_placeholder(&nodes[0]) => {
+                ctx.warn(
+                    MDError::new(
+                        "expected ingredient list, ignoring placeholder",
+                        Some(&nodes[0]),
+                    )
+                    .with_code(ErrorCode::IngredientsPlaceholder),
+                );
+                Ok(Self::IngredientList(vec![]))
+            }
             1 => Ok(Self::IngredientList(Self::parse_ingredient_list(
-                &nodes[0],
+                &nodes[0], ctx,
             )?)),
             _ => {
                 // We expect sequences of the following form:
@@ -24,43 +55,315 @@ impl Ingredients {
                 Ok(Self::IngredientGroups(
                     nodes
                         .chunks(2)
-                        .map(|group| -> MDResult<IngredientGroup> {
-                            if group.len() == 1 {
+                        .filter_map(|group| {
+                            let group = if group.len() == 1 {
                                 Err(MDError::new("malformed ingredient group", Some(&group[0])))
                             } else {
-                                IngredientGroup::parse(&group[0], &group[1])
-                            }
+                                IngredientGroup::parse(&group[0], &group[1], ctx)
+                            };
+                            ctx.recover(
+                                group.map_err(|e| e.or_code(ErrorCode::MalformedIngredient)),
+                            )
                         })
-                        .collect::<MDResult<Vec<IngredientGroup>>>()?,
+                        .collect(),
                 ))
             }
         }
     }
 
-    fn parse_ingredient_list(node: &Node) -> MDResult<Vec<IngredientOptions>> {
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Iterates over all ingredient lines, across groups, in document order. Positions in this
+    /// iteration are the indices stored by resolved ingredient references.
+    pub fn iter(&self) -> impl Iterator<Item = &IngredientOptions> {
+        let (list, groups): (&[IngredientOptions], &[IngredientGroup]) = match self {
+            Self::IngredientList(list) => (list, &[]),
+            Self::IngredientGroups(groups) => (&[], groups),
+        };
+        list.iter()
+            .chain(groups.iter().flat_map(|g| g.ingredients.iter()))
+    }
+
+    pub fn get(&self, index: usize) -> Option<&IngredientOptions> {
+        self.iter().nth(index)
+    }
+
+    /// Mutable counterpart of [`Ingredients::iter`].
+    pub(super) fn iter_mut(&mut self) -> impl Iterator<Item = &mut IngredientOptions> {
+        let (list, groups): (&mut [IngredientOptions], &mut [IngredientGroup]) = match self {
+            Self::IngredientList(list) => (list, &mut []),
+            Self::IngredientGroups(groups) => (&mut [], groups),
+        };
+        list.iter_mut()
+            .chain(groups.iter_mut().flat_map(|g| g.ingredients.iter_mut()))
+    }
+
+    /// Replaces every quantity (including alternative quantities, those of alternative
+    /// ingredients, and the yields of groups). Ingredients keep the quantity they had before
+    /// being first changed as their original quantity.
+    pub(super) fn map_quantities(&mut self, mut f: impl FnMut(&Quantity) -> Quantity) {
+        match self {
+            Self::IngredientList(list) => map_line_quantities(list, &mut f),
+            Self::IngredientGroups(groups) => {
+                for group in groups {
+                    group.map_quantities(&mut f);
+                }
+            }
+        }
+    }
+
+    /// Replaces the quantities of every ingredient (including alternative ingredients) as
+    /// [`Ingredients::map_quantities`] does, given the ingredient's name. The yields of groups are
+    /// left as they are.
+    pub(super) fn map_ingredient_quantities(
+        &mut self,
+        mut f: impl FnMut(&str, &Quantity) -> Quantity,
+    ) {
+        for options in self.iter_mut() {
+            let alternatives = options.alternatives.iter_mut().flatten();
+            for ingredient in std::iter::once(&mut options.ingredient).chain(alternatives) {
+                let name = ingredient.name.clone();
+                ingredient.map_quantities(&mut |q| f(&name, q));
+            }
+        }
+    }
+
+    /// Group with the given name, compared case-insensitively.
+    pub(super) fn group_mut(&mut self, name: &str) -> Option<&mut IngredientGroup> {
+        match self {
+            Self::IngredientList(_) => None,
+            Self::IngredientGroups(groups) => groups
+                .iter_mut()
+                .find(|g| g.name.to_lowercase() == name.to_lowercase()),
+        }
+    }
+
+    /// Finds the index of the ingredient line a reference points to. Names are compared
+    /// case-insensitively against ingredients and their alternatives; with
+    /// [`RefMatching::Fuzzy`], near-misses (typos, plurals) are accepted when no exact match
+    /// exists.
+    pub fn resolve(&self, name: &str, matching: RefMatching) -> MDResult<usize> {
+        let name = name.trim().to_lowercase();
+        if name.is_empty() {
+            return Err(MDError::new("empty ingredient reference", None)
+                .with_code(ErrorCode::EmptyReference));
+        }
+        let names: Vec<(usize, String)> = self
+            .iter()
+            .enumerate()
+            .flat_map(|(i, opts)| opts.names().map(move |n| (i, n.to_lowercase())))
+            .collect();
+        let mut candidates: Vec<usize> = names
+            .iter()
+            .filter_map(|(i, n)| (*n == name).then_some(*i))
+            .collect();
+        if candidates.is_empty() && matching == RefMatching::Fuzzy {
+            let max_distance = if name.chars().count() < 5 { 1 } else { 2 };
+            let best = names
+                .iter()
+                .map(|(i, n)| (*i, levenshtein(n, &name)))
+                .filter(|(_, d)| *d <= max_distance)
+                .min_by_key(|(_, d)| *d)
+                .map(|(_, d)| d);
+            candidates = names
+                .iter()
+                .filter_map(|(i, n)| (Some(levenshtein(n, &name)) == best).then_some(*i))
+                .collect();
+        }
+        candidates.dedup();
+        match candidates[..] {
+            [index] => Ok(index),
+            [] => {
+                let suggestion = Matcher::default()
+                    .best_match(&name, names.iter().map(|(_, n)| n.as_str()))
+                    .map(|(i, _)| format!(" (did you mean \"{}\"?)", names[i].1))
+                    .unwrap_or_default();
+                Err(MDError::new(
+                    &format!("unresolved ingredient reference \"{}\"{}", name, suggestion),
+                    None,
+                )
+                .with_code(ErrorCode::UnresolvedReference))
+            }
+            _ => Err(MDError::new(
+                &format!("ambiguous ingredient reference \"{}\"", name),
+                None,
+            )
+            .with_code(ErrorCode::AmbiguousReference)),
+        }
+    }
+
+    fn parse_ingredient_list(
+        node: &Node,
+        ctx: &mut ParseContext,
+    ) -> MDResult<Vec<IngredientOptions>> {
         match node {
             Node::List(list) => Ok(list
                 .children
                 .iter()
-                .map(|n| IngredientOptions::parse(n))
-                .collect::<MDResult<Vec<IngredientOptions>>>()?),
+                .filter_map(|item| {
+                    let options = IngredientOptions::parse(item, ctx.number_format())
+                        .map_err(|e| e.or_code(ErrorCode::MalformedIngredient));
+                    let mut options = ctx.recover(options)?;
+                    options.resolve_units(ctx.unit_registry(), ctx.section_language());
+                    Some(options)
+                })
+                .collect()),
             _ => Err(MDError::new("ingredients must be list", Some(node))),
         }
     }
 }
 
+/// How ingredient references in instructions are matched against ingredient names.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RefMatching {
+    Exact,
+    Fuzzy,
+}
+
+/// Replaces a custom unit by a registered unit, or else by a unit of the language.
+fn resolve_unit(quantity: Quantity, units: &UnitRegistry, language: Option<Language>) -> Quantity {
+    let quantity = units.resolve(quantity);
+    match language {
+        Some(language) => language.resolve(quantity),
+        None => quantity,
+    }
+}
+
+/// Replaces the quantities of ingredient lines, as [`Ingredients::map_quantities`] does.
+fn map_line_quantities(lines: &mut [IngredientOptions], f: &mut impl FnMut(&Quantity) -> Quantity) {
+    for options in lines {
+        let alternatives = options.alternatives.iter_mut().flatten();
+        for ingredient in std::iter::once(&mut options.ingredient).chain(alternatives) {
+            ingredient.map_quantities(f);
+        }
+    }
+}
+
+/// How a link to another recipe is written.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LinkStyle {
+    /// Markdown link (e.g., "[Pizza dough](dough.md)").
+    #[default]
+    Markdown,
+    /// Obsidian wikilink (e.g., "[[Pizza dough]]").
+    Wiki,
+}
+
+/// A component of a recipe (e.g., "### Sauce"). Its heading may say how much the component makes
+/// (e.g., "### Sauce (makes 200 mL)") and link to the recipe it comes from (e.g., "### [[Tomato
+/// sauce|Sauce]]" or "### [Sauce](sauce.md)").
+#[derive(Clone, PartialEq, Debug)]
 pub struct IngredientGroup {
     name: String,
+    yields: Option<Quantity>,
+    sub_recipe: Option<String>,
+    link_style: LinkStyle,
     ingredients: Vec<IngredientOptions>,
 }
 
 impl IngredientGroup {
-    fn parse(heading: &Node, list: &Node) -> MDResult<Self> {
+    fn parse(heading: &Node, list: &Node, ctx: &mut ParseContext) -> MDResult<Self> {
+        let heading_text = get_heading_with(heading, 3, None, ctx.heading_formatting_mode())?;
+        let (mut name, yields) = Self::split_yields(&heading_text, ctx.number_format());
+        let mut sub_recipe = heading
+            .children()
+            .into_iter()
+            .flatten()
+            .find_map(|node| match node {
+                Node::Link(link) => Some(link.url.clone()),
+                _ => None,
+            });
+        let mut link_style = LinkStyle::Markdown;
+        if let Some((before, link, after)) = find_wikilink(&name) {
+            sub_recipe = Some(link.target.to_string());
+            link_style = LinkStyle::Wiki;
+            name = format!("{}{}{}", before, link.label(), after)
+                .trim()
+                .to_string();
+        }
+        let yields = yields.map(|q| resolve_unit(q, ctx.unit_registry(), ctx.section_language()));
+        let ingredients = ctx.within(
+            &format!("while parsing ingredient group '{}'", name),
+            |ctx| Ingredients::parse_ingredient_list(list, ctx),
+        )?;
         Ok(Self {
-            name: get_heading(heading, 3, None)?,
-            ingredients: Ingredients::parse_ingredient_list(list)?,
+            name,
+            yields,
+            sub_recipe,
+            link_style,
+            ingredients,
         })
     }
+
+    /// Splits a trailing quantity in parentheses, optionally after "makes", off a heading.
+    /// Parentheses holding anything else (e.g., "(optional)") are part of the name.
+    fn split_yields(heading: &str, numbers: NumberFormat) -> (String, Option<Quantity>) {
+        let parsed = heading
+            .strip_suffix(')')
+            .and_then(|rest| rest.rsplit_once(" ("))
+            .and_then(|(name, inner)| {
+                let inner = inner.trim();
+                let quantity = match inner.get(..6) {
+                    Some(prefix) if prefix.eq_ignore_ascii_case("makes ") => &inner[6..],
+                    _ => inner,
+                };
+                let quantity = Quantity::from_str_with(quantity.trim(), numbers).ok()?;
+                (!quantity.is_to_taste()).then(|| (name.trim_end().to_string(), quantity))
+            });
+        match parsed {
+            Some((name, quantity)) => (name, Some(quantity)),
+            None => (heading.to_string(), None),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How much of the component the group makes.
+    pub fn yields(&self) -> Option<&Quantity> {
+        self.yields.as_ref()
+    }
+
+    /// The recipe the component comes from, as written in the heading's link.
+    pub fn sub_recipe(&self) -> Option<&str> {
+        self.sub_recipe.as_deref()
+    }
+
+    /// How the heading's link is written, to write it back the same way.
+    pub fn link_style(&self) -> LinkStyle {
+        self.link_style
+    }
+
+    /// Factor to scale the group by to make a quantity of the component (e.g., 2 for "400 mL" of
+    /// a group making 200 mL), as [`Recipe::batches_for`](crate::recipe::Recipe::batches_for)
+    /// does for whole recipes.
+    pub fn batches_for(&self, quantity: Option<&Quantity>) -> Option<Amount> {
+        let Some(quantity) = quantity.filter(|q| !q.is_to_taste()) else {
+            return Some(Amount::ONE);
+        };
+        if let Unit::Nominal(_) = quantity.unit {
+            return Some(quantity.amount);
+        }
+        let yields = self
+            .yields
+            .as_ref()?
+            .convert_to(&quantity.unit)
+            .filter(|y| y.amount > Amount::ZERO)?;
+        Some(quantity.amount / yields.amount)
+    }
+
+    /// Replaces the quantities of the group's ingredients and its yield.
+    pub(super) fn map_quantities(&mut self, f: &mut impl FnMut(&Quantity) -> Quantity) {
+        map_line_quantities(&mut self.ingredients, f);
+        self.yields = self.yields.as_ref().map(&mut *f);
+    }
+
+    pub fn ingredients(&self) -> &[IngredientOptions] {
+        &self.ingredients
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -69,109 +372,412 @@ pub struct Ingredient {
     quantity: Option<Quantity>,
     alt_quantities: Option<Vec<Quantity>>,
     info: Option<String>,
+    original: Option<Quantity>,
+    sub_recipe: Option<String>,
+    link_style: LinkStyle,
 }
 
-const INFO_FORBIDDEN_CHARS: [char; 3] = ['|', '(', ')'];
-const FORBIDDEN_CHARS: [char; 5] = [',', '|', '/', '(', ')'];
-
 impl Ingredient {
-    fn from_str(text: &str) -> MDResult<Self> {
-        let mut text = text.trim();
-
-        // Determine whether there is additional info between '(' and ')' at the end.
-        let info: Option<String> = if text.ends_with(")") {
-            Some(
-                text.find("(")
-                    .ok_or_else(|| MDError::new("found closing parenthesis but no opening", None))
-                    .map(|idx| {
-                        let info = text[idx + 1..text.len() - 1].trim().to_string();
-                        text = &text[..idx];
-                        info
-                    })?,
-            )
-        } else {
-            None
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn quantity(&self) -> Option<&Quantity> {
+        self.quantity.as_ref()
+    }
+
+    pub fn alt_quantities(&self) -> &[Quantity] {
+        self.alt_quantities.as_deref().unwrap_or_default()
+    }
+
+    pub fn info(&self) -> Option<&str> {
+        self.info.as_deref()
+    }
+
+    /// Returns an error for each alternative quantity which disagrees with the main quantity
+    /// beyond a relative tolerance (e.g., "15 mL / 2 tbsp"). Quantities in incompatible units
+    /// cannot be compared, and are not reported.
+    pub fn inconsistent_alt_quantities(&self, tolerance: Amount) -> Vec<MDError> {
+        let Some(quantity) = &self.quantity else {
+            return vec![];
         };
-        if let Some(info_text) = &info {
-            if INFO_FORBIDDEN_CHARS.iter().any(|c| info_text.contains(*c)) {
-                Err(MDError::new(
-                    &format!("additiona info contains forbidden character: {}", info_text),
+        self.alt_quantities()
+            .iter()
+            .filter(|alt| quantity.convert_to(&alt.unit).is_some())
+            .filter(|alt| !quantity.approx_eq(alt, tolerance))
+            .map(|alt| {
+                MDError::new(
+                    &format!(
+                        "alternative quantity \"{}\" of {:?} disagrees with \"{}\"",
+                        alt, self.name, quantity
+                    ),
                     None,
-                ))?;
+                )
+                .with_code(ErrorCode::AltQuantityMismatch)
+            })
+            .collect()
+    }
+
+    /// Target of the link naming an ingredient made from another recipe (e.g., "dough.md" for
+    /// "[Pizza dough](dough.md), 1"), which [`RecipeLibrary::resolve_link`] resolves.
+    ///
+    /// [`RecipeLibrary::resolve_link`]: crate::library::RecipeLibrary::resolve_link
+    pub fn sub_recipe(&self) -> Option<&str> {
+        self.sub_recipe.as_deref()
+    }
+
+    /// How the link naming the ingredient is written, to write it back the same way.
+    pub fn link_style(&self) -> LinkStyle {
+        self.link_style
+    }
+
+    /// Quantity as written in the recipe, if the quantity has since been scaled or converted.
+    pub fn original_quantity(&self) -> Option<&Quantity> {
+        self.original.as_ref()
+    }
+
+    /// Replaces the quantity and alternative quantities, keeping the quantity as written.
+    fn map_quantities(&mut self, f: &mut impl FnMut(&Quantity) -> Quantity) {
+        let original = self.quantity.clone();
+        let quantities = self
+            .quantity
+            .iter_mut()
+            .chain(self.alt_quantities.iter_mut().flatten());
+        for quantity in quantities {
+            *quantity = f(quantity);
+        }
+        if self.original.is_none() && self.quantity != original {
+            self.original = original;
+        }
+    }
+}
+
+/// Scanner for a single ingredient line, which follows the grammar
+///
+/// ```text
+/// line       := ingredient ('|' ingredient)*
+/// ingredient := name [',' quantity ('/' quantity)*] ['(' info ')']
+/// ```
+///
+/// Slashes directly between two digits denote a fraction (e.g., "1/2 cup / 120 mL") rather than
+/// an alternative quantity, and so do commas in numbers (e.g., "1,5 kg") rather than the end of
+/// the quantity when numbers are written with separators. Errors are located by their byte range
+/// within the line.
+struct LineParser<'a> {
+    line: &'a str,
+    pos: usize,
+    numbers: NumberFormat,
+}
+
+impl<'a> LineParser<'a> {
+    fn new(line: &'a str, numbers: NumberFormat) -> Self {
+        Self {
+            line,
+            pos: 0,
+            numbers,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.line[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) {
+        if let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn is_fraction_slash(&self) -> bool {
+        let bytes = self.line.as_bytes();
+        self.pos > 0
+            && bytes[self.pos - 1].is_ascii_digit()
+            && bytes.get(self.pos + 1).is_some_and(|b| b.is_ascii_digit())
+    }
+
+    /// Advances until `stop` matches the next character (or the end of the line), and returns the
+    /// trimmed text consumed along with its span.
+    fn take_until(&mut self, stop: impl Fn(&Self, char) -> bool) -> (&'a str, Range<usize>) {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if stop(self, c) {
+                break;
             }
+            self.bump();
+        }
+        let raw = &self.line[start..self.pos];
+        let trimmed_start = start + (raw.len() - raw.trim_start().len());
+        let trimmed_end = (start + raw.trim_end().len()).max(trimmed_start);
+        (
+            &self.line[trimmed_start..trimmed_end],
+            trimmed_start..trimmed_end,
+        )
+    }
+
+    fn error_at_next(&self, msg: &str) -> MDError {
+        let len = self.peek().map_or(0, char::len_utf8);
+        MDError::in_line(msg, self.pos..self.pos + len)
+    }
+
+    fn line(mut self) -> MDResult<Vec<Ingredient>> {
+        let mut ingredients = vec![self.ingredient()?];
+        while self.peek() == Some('|') {
+            self.bump();
+            ingredients.push(self.ingredient()?);
+        }
+        Ok(ingredients)
+    }
+
+    fn ingredient(&mut self) -> MDResult<Ingredient> {
+        let (name, name_span) = self.take_until(|_, c| matches!(c, ',' | '|' | '/' | '(' | ')'));
+        match self.peek() {
+            Some('/') => return Err(self.error_at_next("name contains forbidden character '/'")),
+            Some(')') => return Err(self.error_at_next("found closing parenthesis but no opening")),
+            _ => {}
+        }
+        if name.is_empty() {
+            return Err(MDError::in_line("name cannot be empty", name_span));
         }
 
-        // Determine whether there is an optional quantity specfied after a ','.
         let (mut quantity, mut alt_quantities): (Option<Quantity>, Option<Vec<Quantity>>) =
             (None, None);
-        if let Some(idx) = text.find(",") {
+        if self.peek() == Some(',') {
             // We expect at least one quantity, and possibly many alteratives.
-            for (i, s) in text[idx + 1..].split('/').enumerate() {
-                if FORBIDDEN_CHARS.iter().any(|c| s.contains(*c)) {
-                    Err(MDError::new(
-                        &format!("quantity contains forbidden character: {}", s),
-                        None,
-                    ))?;
-                }
-                let quant = Quantity::from_str(s.trim())
-                    .map_err(|e| MDError::new(&format!("failed to parse quantity: {}", e), None))?;
-                if i == 0 {
+            self.bump();
+            loop {
+                let (text, span) = self.take_until(|p, c| {
+                    matches!(c, '|' | '(' | ')')
+                        || (c == ',' && !p.numbers.is_separator_at(p.line, p.pos))
+                        || (c == '/' && !p.is_fraction_slash())
+                });
+                let quant = Quantity::from_str_with(text, self.numbers).map_err(|e| {
+                    MDError::in_line(&format!("failed to parse quantity: {}", e), span)
+                        .with_code(ErrorCode::InvalidQuantity)
+                })?;
+                if quantity.is_none() {
                     quantity = Some(quant);
-                } else if i == 1 {
-                    alt_quantities = Some(vec![quant]);
                 } else {
-                    alt_quantities.as_mut().unwrap().push(quant);
+                    alt_quantities.get_or_insert_with(Vec::new).push(quant);
+                }
+                if self.peek() != Some('/') {
+                    break;
                 }
+                self.bump();
+            }
+            if matches!(self.peek(), Some(',' | ')')) {
+                return Err(self.error_at_next("quantity contains forbidden character"));
             }
-            text = &text[..idx];
         }
 
-        let name = text.trim().to_string();
-        if name.is_empty() {
-            Err(MDError::new(
-                &format!("name cannot be empty: {}", name),
-                None,
-            ))
-        } else if FORBIDDEN_CHARS.iter().any(|c| name.contains(*c)) {
-            Err(MDError::new("name contains forbidden character", None))
-        } else {
-            Ok(Self {
-                name,
-                quantity,
-                alt_quantities,
-                info,
-            })
+        let mut info = None;
+        if self.peek() == Some('(') {
+            let open = self.pos;
+            self.bump();
+            let (text, _) = self.take_until(|_, c| matches!(c, '|' | '(' | ')'));
+            match self.peek() {
+                Some(')') => {
+                    info = Some(text.to_string());
+                    self.bump();
+                }
+                Some(_) => {
+                    return Err(self.error_at_next("additional info contains forbidden character"))
+                }
+                None => {
+                    return Err(MDError::in_line(
+                        "found opening parenthesis but no closing",
+                        open..self.line.len(),
+                    ))
+                }
+            }
+            let (trailing, span) = self.take_until(|_, c| c == '|');
+            if !trailing.is_empty() {
+                return Err(MDError::in_line(
+                    "unexpected text after additional info",
+                    span,
+                ));
+            }
         }
+
+        Ok(Ingredient {
+            name: name.to_string(),
+            quantity,
+            alt_quantities,
+            info,
+            original: None,
+            sub_recipe: None,
+            link_style: LinkStyle::Markdown,
+        })
     }
 }
 
-#[derive(Clone, PartialEq, Debug)]
+impl FromStr for Ingredient {
+    type Err = MDError;
+
+    fn from_str(text: &str) -> MDResult<Self> {
+        let mut parser = LineParser::new(text, NumberFormat::Plain);
+        let ingredient = parser.ingredient()?;
+        match parser.peek() {
+            Some(_) => Err(parser.error_at_next("name contains forbidden character '|'")),
+            None => Ok(ingredient),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct IngredientOptions {
     ingredient: Ingredient,
     alternatives: Option<Vec<Ingredient>>,
+    /// Where the line is written, to locate warnings about it.
+    position: Option<Position>,
+}
+
+/// Lines are equal whatever their position, as recipes are.
+impl PartialEq for IngredientOptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.ingredient == other.ingredient && self.alternatives == other.alternatives
+    }
 }
 
 impl IngredientOptions {
-    fn parse(node: &Node) -> MDResult<Self> {
+    pub fn ingredient(&self) -> &Ingredient {
+        &self.ingredient
+    }
+
+    /// Where the line is written in the document, if it was parsed from one.
+    pub fn position(&self) -> Option<&Position> {
+        self.position.as_ref()
+    }
+
+    /// Moves the line's position from text starting at `start` of the document.
+    pub(super) fn shift(&mut self, start: &Point) {
+        if let Some(position) = &self.position {
+            self.position = Some(shift_position(position, start));
+        }
+    }
+
+    /// Replaces custom units by the registered units of the same name, as written, or else by
+    /// the units of that name in the recipe's language.
+    fn resolve_units(&mut self, units: &UnitRegistry, language: Option<Language>) {
+        let alternatives = self.alternatives.iter_mut().flatten();
+        for ingredient in std::iter::once(&mut self.ingredient).chain(alternatives) {
+            let quantities = ingredient
+                .quantity
+                .iter_mut()
+                .chain(ingredient.alt_quantities.iter_mut().flatten());
+            for quantity in quantities {
+                *quantity = resolve_unit(quantity.clone(), units, language);
+            }
+        }
+    }
+
+    pub fn alternatives(&self) -> &[Ingredient] {
+        self.alternatives.as_deref().unwrap_or_default()
+    }
+
+    /// Names of the ingredient and of its alternatives.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(&self.ingredient)
+            .chain(self.alternatives())
+            .map(|i| i.name())
+    }
+
+    /// Makes the alternative at `index` the main ingredient, and the main ingredient its
+    /// alternative. Returns the names of the former and new main ingredients, or `None` if there
+    /// is no such alternative.
+    pub(super) fn swap_alternative(&mut self, index: usize) -> Option<(String, String)> {
+        let alternative = self.alternatives.as_mut()?.get_mut(index)?;
+        std::mem::swap(&mut self.ingredient, alternative);
+        Some((alternative.name.clone(), self.ingredient.name.clone()))
+    }
+
+    fn parse(node: &Node, numbers: NumberFormat) -> MDResult<Self> {
         match node {
-            Node::ListItem(item) => expect_children(node, 1)
-                .and_then(|_| Self::from_str(get_text_from_paragraph(&item.children[0])?)),
+            Node::ListItem(item) => {
+                expect_children(node, 1)?;
+                let para = &item.children[0];
+                let options = match Self::parse_sub_recipe(para, numbers) {
+                    Some(options) => options,
+                    None => {
+                        let text = get_text_from_paragraph(para)?;
+                        Self::parse_wikilink(text, numbers)
+                            .unwrap_or_else(|| Self::parse_line(text, numbers))
+                            .map_err(|e| e.locate_in(&para.children().unwrap()[0]))
+                    }
+                };
+                options.map(|options| Self {
+                    position: node.position().cloned(),
+                    ..options
+                })
+            }
             _ => Err(MDError::new("expected list item", Some(node))),
         }
     }
 
-    fn from_str(text: &str) -> MDResult<Self> {
-        let idx = text.find('|').or(Some(text.len())).unwrap();
-        let ingredient = Ingredient::from_str(&text[..idx])?;
-        let mut alternatives: Vec<Ingredient> = vec![];
-        if idx != text.len() {
-            for s in text[idx + 1..].split('|') {
-                alternatives.push(Ingredient::from_str(s)?);
+    /// Parses a line whose main ingredient is a link to another recipe (e.g., "[Pizza
+    /// dough](dough.md), 1"), or returns `None` if the line does not start with a link.
+    fn parse_sub_recipe(para: &Node, numbers: NumberFormat) -> Option<MDResult<Self>> {
+        let children = para.children()?;
+        let Some(Node::Link(link)) = children.first() else {
+            return None;
+        };
+        let name = match link.children.as_slice() {
+            [Node::Text(text)] => &text.value,
+            _ => {
+                return Some(Err(MDError::new(
+                    "expected recipe link to be text",
+                    Some(&children[0]),
+                )))
             }
-        }
+        };
+        let rest = match &children[1..] {
+            [] => "",
+            [Node::Text(text)] => &text.value,
+            _ => {
+                return Some(Err(MDError::new(
+                    "expected text after recipe link",
+                    Some(&children[1]),
+                )))
+            }
+        };
+        let options = Self::parse_line(&format!("{}{}", name, rest), numbers).map(|mut options| {
+            options.ingredient.sub_recipe = Some(link.url.clone());
+            options
+        });
+        Some(options.map_err(|e| {
+            let error = MDError::new(e.message(), Some(para));
+            match e.code() {
+                Some(code) => error.with_code(code),
+                None => error,
+            }
+        }))
+    }
+
+    /// Parses a line whose main ingredient is a wikilink to another recipe (e.g., "[[Pizza
+    /// dough|Dough]], 1"), or returns `None` if the line does not start with a wikilink. The
+    /// ingredient is named after the alias, or else after the note the link points to.
+    fn parse_wikilink(text: &str, numbers: NumberFormat) -> Option<MDResult<Self>> {
+        let ("", link, rest) = find_wikilink(text)? else {
+            return None;
+        };
+        Some(
+            Self::parse_line(&format!("{}{}", link.label(), rest), numbers).map(|mut options| {
+                options.ingredient.sub_recipe = Some(link.target.to_string());
+                options.ingredient.link_style = LinkStyle::Wiki;
+                options
+            }),
+        )
+    }
+
+    pub(super) fn from_str(text: &str) -> MDResult<Self> {
+        Self::parse_line(text, NumberFormat::Plain)
+    }
+
+    fn parse_line(text: &str, numbers: NumberFormat) -> MDResult<Self> {
+        let mut ingredients = LineParser::new(text, numbers).line()?.into_iter();
         Ok(Self {
-            ingredient,
-            alternatives: (!alternatives.is_empty()).then_some(alternatives),
+            ingredient: ingredients.next().unwrap(),
+            alternatives: Some(ingredients.collect::<Vec<_>>()).filter(|alts| !alts.is_empty()),
+            position: None,
         })
     }
 }
@@ -179,25 +785,38 @@ impl IngredientOptions {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::recipe::unit::{Nominal, Unit, Volume};
+    use crate::recipe::{
+        amount::Amount,
+        md_parser::get_parse_options,
+        unit::{Approximation, Nominal, Unit, Volume},
+    };
     use indoc::indoc;
+    use markdown::message::Place;
 
     // Some quantities
     const ONE_NOMINAL: Quantity = Quantity {
         unit: Unit::Nominal(Nominal),
-        amount: 1.,
+        amount: Amount::from_int(1),
+        max_amount: None,
+        approximation: None,
     };
     const FIFTEEN_ML: Quantity = Quantity {
         unit: Unit::Volume(Volume::Milliliter),
-        amount: 15.,
+        amount: Amount::from_int(15),
+        max_amount: None,
+        approximation: None,
     };
     const THREE_TSP: Quantity = Quantity {
         unit: Unit::Volume(Volume::Teaspoon),
-        amount: 3.,
+        amount: Amount::from_int(3),
+        max_amount: None,
+        approximation: None,
     };
     const ONE_TBSP: Quantity = Quantity {
         unit: Unit::Volume(Volume::Tablespoon),
-        amount: 1.,
+        amount: Amount::from_int(1),
+        max_amount: None,
+        approximation: None,
     };
     const NAME: &str = "name";
 
@@ -207,6 +826,9 @@ mod tests {
             quantity: quantity.cloned(),
             alt_quantities: None,
             info: info.map(|s| s.to_string()),
+            original: None,
+            sub_recipe: None,
+            link_style: LinkStyle::Markdown,
         }
     }
 
@@ -221,7 +843,9 @@ mod tests {
         // "Special units": none, nominal, and custom.
         let one_custom = Quantity {
             unit: Unit::Custom("bunch".to_string()),
-            amount: 1.,
+            amount: Amount::from_int(1),
+            max_amount: None,
+            approximation: None,
         };
         assert_eq!(Ingredient::from_str("name")?, simple_ingredient(None, None));
         assert_eq!(
@@ -263,6 +887,9 @@ mod tests {
             quantity: Some(FIFTEEN_ML),
             alt_quantities: Some(vec![THREE_TSP, ONE_TBSP]),
             info: None,
+            original: None,
+            sub_recipe: None,
+            link_style: LinkStyle::Markdown,
         };
         assert_eq!(
             Ingredient::from_str("name, 15mL / 3 tsp / 1tbsp")?,
@@ -272,6 +899,62 @@ mod tests {
             Ingredient::from_str("name, 15mL  /  3 tsp/1tbsp")?,
             ingr_with_alts
         );
+
+        // Ranges of quantities.
+        let two_to_three_tbsp = Quantity::new_range(
+            &Unit::Volume(Volume::Tablespoon),
+            Amount::from_int(2),
+            Amount::from_int(3),
+        );
+        assert_eq!(
+            Ingredient::from_str("name, 2-3 tbsp")?,
+            simple_ingredient(Some(&two_to_three_tbsp), None)
+        );
+
+        // Approximate quantities.
+        assert_eq!(
+            Ingredient::from_str("name, to taste")?,
+            simple_ingredient(Some(&Quantity::to_taste()), None)
+        );
+        assert_eq!(
+            Ingredient::from_str("name, about 15 mL (info)")?,
+            simple_ingredient(
+                Some(&Quantity {
+                    approximation: Some(Approximation::About),
+                    ..FIFTEEN_ML
+                }),
+                Some("info")
+            )
+        );
+
+        // Slashes between digits denote fractions rather than alternatives.
+        let half_tbsp = Quantity {
+            unit: Unit::Volume(Volume::Tablespoon),
+            amount: Amount::new(1, 2),
+            max_amount: None,
+            approximation: None,
+        };
+        assert_eq!(
+            Ingredient::from_str("name, 1/2 tbsp")?,
+            simple_ingredient(Some(&half_tbsp), None)
+        );
+        assert_eq!(
+            Ingredient::from_str("name, 1/2tbsp/ 1 1/2 tsp")?,
+            Ingredient {
+                name: NAME.to_string(),
+                quantity: Some(half_tbsp),
+                alt_quantities: Some(vec![Quantity {
+                    unit: Unit::Volume(Volume::Teaspoon),
+                    amount: Amount::new(3, 2),
+                    max_amount: None,
+                    approximation: None,
+                }]),
+                info: None,
+                original: None,
+                sub_recipe: None,
+                link_style: LinkStyle::Markdown,
+            }
+        );
         Ok(())
     }
 
@@ -294,6 +977,37 @@ mod tests {
         assert!(Ingredient::from_str("name, 15mL ((info))").is_err());
     }
 
+    #[test]
+    fn parse_failure_spans() {
+        let span = |text: &str| IngredientOptions::from_str(text).unwrap_err().span();
+        assert_eq!(span("  , 15mL"), Some(2..2));
+        assert_eq!(span("name, a15 mL"), Some(6..12));
+        assert_eq!(span("name, 15mL / x3 tsp"), Some(13..19));
+        assert_eq!(span("name, 15mL, 3 tsp"), Some(10..11));
+        assert_eq!(span("name, 15mL (info"), Some(11..16));
+        assert_eq!(span("name (info) more"), Some(12..16));
+        // Spans of alternatives are relative to the whole line.
+        assert_eq!(span("name | other, a15mL"), Some(14..19));
+    }
+
+    #[test]
+    fn parse_failure_places() {
+        let content = indoc! {"
+            - Lemons, 1
+            - Milk, a50 mL (cold)
+        "};
+        let mdast = markdown::to_mdast(content, &markdown::ParseOptions::default()).unwrap();
+        let err = Ingredients::parse(mdast.children().unwrap()).unwrap_err();
+        match err.place() {
+            Some(Place::Position(pos)) => {
+                assert_eq!((pos.start.line, pos.start.column), (2, 9));
+                assert_eq!((pos.end.line, pos.end.column), (2, 15));
+                assert_eq!(&content[pos.start.offset..pos.end.offset], "a50 mL");
+            }
+            place => panic!("unexpected place {:?}", place),
+        }
+    }
+
     #[test]
     fn parse_ingredient_options() -> MDResult<()> {
         let ingr = simple_ingredient(Some(&FIFTEEN_ML), Some("info"));
@@ -306,7 +1020,8 @@ mod tests {
             IngredientOptions::from_str("name, 15ml (info)")?,
             IngredientOptions {
                 ingredient: ingr.clone(),
-                alternatives: None
+                alternatives: None,
+                position: None,
             }
         );
 
@@ -315,7 +1030,8 @@ mod tests {
             IngredientOptions::from_str("name, 15ml (info)|name (info)    |   name, 1")?,
             IngredientOptions {
                 ingredient: ingr,
-                alternatives: Some(alts.clone())
+                alternatives: Some(alts.clone()),
+                position: None,
             }
         );
         Ok(())
@@ -343,6 +1059,91 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_number_formats() -> MDResult<()> {
+        let content = indoc! {"
+        ### Dough (makes 1,2 kg)
+        - Flour, 1,5 kg / 1.500,5 g (sifted) | Semolina, 0,5-1 kg
+        - Water, 2,5 dL
+        "};
+        let mdast = markdown::to_mdast(content, &markdown::ParseOptions::default()).unwrap();
+        let nodes = mdast.children().unwrap();
+        let mut ctx = ParseContext::new().numbers(NumberFormat::DecimalComma);
+        let result = Ingredients::parse_with(nodes, &mut ctx);
+        let ingredients = ctx.finish(result)?;
+        let Ingredients::IngredientGroups(groups) = &ingredients else {
+            panic!("expected ingredient groups");
+        };
+        assert_eq!(groups[0].name(), "Dough");
+        assert_eq!(groups[0].yields().unwrap().to_string(), "1.2 kg");
+        let flour = &groups[0].ingredients()[0];
+        assert_eq!(flour.ingredient().quantity().unwrap().to_string(), "1.5 kg");
+        assert_eq!(
+            flour.ingredient().alt_quantities()[0].to_string(),
+            "1500.5 g"
+        );
+        assert_eq!(flour.ingredient().info(), Some("sifted"));
+        assert_eq!(
+            flour.alternatives()[0].quantity().unwrap().to_string(),
+            "0.5-1 kg"
+        );
+        // "dL" is not a known unit, but the decimal comma still belongs to the number.
+        let water = groups[0].ingredients()[1].ingredient();
+        assert_eq!(water.quantity().unwrap().amount, Amount::new(5, 2));
+
+        // Without a number format, commas end quantities.
+        assert!(Ingredients::parse(nodes).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_sub_recipes() -> MDResult<()> {
+        let content = indoc! {"
+        - [Pizza dough](../bases/dough.md), 1
+        - [Tomato sauce](sauce.md), 200 g (homemade) | Passata, 200 g
+        - [Basil](basil.md)
+        "};
+        let mdast = markdown::to_mdast(content, &markdown::ParseOptions::default()).unwrap();
+        let ingredients = Ingredients::parse(mdast.children().unwrap())?;
+        let options: Vec<&IngredientOptions> = ingredients.iter().collect();
+        let dough = options[0].ingredient();
+        assert_eq!(dough.name(), "Pizza dough");
+        assert_eq!(dough.quantity(), Some(&ONE_NOMINAL));
+        assert_eq!(dough.sub_recipe(), Some("../bases/dough.md"));
+        let sauce = options[1].ingredient();
+        assert_eq!(sauce.info(), Some("homemade"));
+        assert_eq!(sauce.sub_recipe(), Some("sauce.md"));
+        assert_eq!(options[1].alternatives()[0].sub_recipe(), None);
+        assert_eq!(options[2].ingredient().sub_recipe(), Some("basil.md"));
+
+        let content = indoc! {"
+        - [[Pizza dough|Dough]], 1
+        - [[bases/Tomato sauce#Quick]], 200 g | Passata, 200 g
+        - Basil, 1 bunch (see [[Pesto]])
+        "};
+        let mdast = markdown::to_mdast(content, &get_parse_options()).unwrap();
+        let ingredients = Ingredients::parse(mdast.children().unwrap())?;
+        let options: Vec<&IngredientOptions> = ingredients.iter().collect();
+        let dough = options[0].ingredient();
+        assert_eq!(dough.name(), "Dough");
+        assert_eq!(dough.quantity(), Some(&ONE_NOMINAL));
+        assert_eq!(dough.sub_recipe(), Some("Pizza dough"));
+        let sauce = options[1].ingredient();
+        assert_eq!(sauce.name(), "Tomato sauce");
+        assert_eq!(sauce.sub_recipe(), Some("bases/Tomato sauce#Quick"));
+        assert_eq!(options[1].alternatives()[0].name(), "Passata");
+        assert_eq!(options[2].ingredient().sub_recipe(), None);
+
+        // Link text must be plain.
+        let mdast = markdown::to_mdast(
+            "- [*Dough*](dough.md), 1\n",
+            &markdown::ParseOptions::default(),
+        )
+        .unwrap();
+        assert!(Ingredients::parse(mdast.children().unwrap()).is_err());
+        Ok(())
+    }
+
     #[test]
     fn parse_ingredient_groups() -> MDResult<()> {
         let content = indoc! {"
@@ -360,4 +1161,42 @@ mod tests {
         Ingredients::parse(mdast.children().unwrap())?;
         Ok(())
     }
+
+    #[test]
+    fn parse_group_metadata() -> MDResult<()> {
+        let content = indoc! {"
+        ### Sauce (makes 200 mL)
+        - Tomatoes, 400 g
+        ### [[Pizza dough|Dough]] (2)
+        - Flour, 500 g
+        ### [Topping](topping.md)
+        - Basil
+        ### Garnish (optional)
+        - Parsley
+        "};
+        let mdast = markdown::to_mdast(content, &get_parse_options()).unwrap();
+        let Ingredients::IngredientGroups(groups) = Ingredients::parse(mdast.children().unwrap())?
+        else {
+            panic!("expected ingredient groups");
+        };
+        let sauce = &groups[0];
+        assert_eq!(sauce.name(), "Sauce");
+        assert_eq!(sauce.yields().unwrap().to_string(), "200 mL");
+        assert_eq!(sauce.sub_recipe(), None);
+        let dough = &groups[1];
+        assert_eq!(dough.name(), "Dough");
+        assert_eq!(dough.yields(), Some(&Quantity::from_str("2").unwrap()));
+        assert_eq!(dough.sub_recipe(), Some("Pizza dough"));
+        assert_eq!(groups[2].name(), "Topping");
+        assert_eq!(groups[2].sub_recipe(), Some("topping.md"));
+        assert_eq!(groups[3].name(), "Garnish (optional)");
+        assert_eq!(groups[3].yields(), None);
+
+        // Groups scale to how much of the component is needed.
+        let wanted = Quantity::from_str("0.6 L").unwrap();
+        assert_eq!(sauce.batches_for(Some(&wanted)), Some(Amount::from_int(3)));
+        assert_eq!(groups[3].batches_for(Some(&wanted)), None);
+        assert_eq!(groups[3].batches_for(None), Some(Amount::ONE));
+        Ok(())
+    }
 }
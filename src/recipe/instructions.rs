@@ -1,51 +1,411 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use super::{
-    md_parser::{MDError, MDResult},
-    unit::{QuantityOf, Time},
+    amount::Amount,
+    md_parser::{
+        find_wikilink, get_heading_with, get_parse_options, is_placeholder, normalize_newlines,
+        shift_position, EmptyRefPolicy, ErrorCode, MDError, MDResult, ParseContext,
+    },
+    notes::RichText,
+    unit::{QuantityOf, Temperature, Time},
+};
+use markdown::{
+    mdast::Node,
+    unist::{Point, Position},
 };
-use markdown::mdast::Node;
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct Instructions {
     steps: Vec<Step>,
+    numbering: Numbering,
+    groups: Vec<InstructionGroup>,
+}
+
+/// A phase of the instructions, under its own heading (e.g., "### Dough"). Its steps are
+/// consecutive top-level steps of [`Instructions::steps`], so that step paths run on across
+/// groups.
+#[derive(Clone, PartialEq, Debug)]
+pub struct InstructionGroup {
+    name: String,
+    /// Number of top-level steps.
+    len: usize,
+    numbering: Numbering,
+}
+
+impl InstructionGroup {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How the steps of the group are marked.
+    pub fn numbering(&self) -> Numbering {
+        self.numbering
+    }
+}
+
+/// How a list of steps is marked in Markdown, kept so that rewriting a recipe keeps its lists.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Numbering {
+    /// Bullet list (e.g., "- Boil").
+    #[default]
+    Bullets,
+    /// Ordered list, numbered from `start` (e.g., "1. Boil").
+    Numbers { start: u32 },
 }
 
 impl Instructions {
-    pub fn parse(nodes: &[Node]) -> MDResult<Self> {
+    #[cfg(test)]
+    pub(crate) fn parse(nodes: &[Node]) -> MDResult<Self> {
+        let mut ctx = ParseContext::new();
+        let result = Self::parse_with(nodes, &mut ctx);
+        ctx.finish(result)
+    }
+
+    /// Parses the instructions section, recording errors in individual steps in `ctx` rather than
+    /// failing.
+    pub(crate) fn parse_with(nodes: &[Node], ctx: &mut ParseContext) -> MDResult<Self> {
         match nodes.len() {
-            0 => Ok(Self { steps: vec![] }),
-            1 => Ok(Self {
-                steps: Step::parse_step_list(&nodes[0])?,
-            }),
-            _ => Err(MDError::new("expected single list node for steps", None)),
+            0 => Ok(Self::new(vec![])),
+            1 if is_placeholder(&nodes[0]) => {
+                ctx.warn(
+                    MDError::new("expected step list, ignoring placeholder", Some(&nodes[0]))
+                        .with_code(ErrorCode::InstructionsPlaceholder),
+                );
+                Ok(Self::new(vec![]))
+            }
+            1 => {
+                let (steps, numbering) = Step::parse_step_list(&nodes[0], ctx)?;
+                Ok(Self {
+                    steps,
+                    numbering,
+                    groups: vec![],
+                })
+            }
+            _ => {
+                // As for ingredient groups, we expect sequences of the following form:
+                // - heading at depth 3 (defining the group's name)
+                // - list of steps
+                let mut instructions = Self::new(vec![]);
+                for group in nodes.chunks(2) {
+                    let group = match group {
+                        [heading, list] => Self::parse_group(heading, list, ctx),
+                        _ => Err(MDError::new("malformed instruction group", Some(&group[0]))),
+                    };
+                    let group = group.map_err(|e| e.or_code(ErrorCode::MalformedStep));
+                    if let Some((group, steps)) = ctx.recover(group) {
+                        instructions.steps.extend(steps);
+                        instructions.groups.push(group);
+                    }
+                }
+                Ok(instructions)
+            }
+        }
+    }
+
+    fn parse_group(
+        heading: &Node,
+        list: &Node,
+        ctx: &mut ParseContext,
+    ) -> MDResult<(InstructionGroup, Vec<Step>)> {
+        let name = get_heading_with(heading, 3, None, ctx.heading_formatting_mode())?;
+        let (steps, numbering) = ctx.within(
+            &format!("while parsing instruction group '{}'", name),
+            |ctx| Step::parse_step_list(list, ctx),
+        )?;
+        let group = InstructionGroup {
+            name,
+            len: steps.len(),
+            numbering,
+        };
+        Ok((group, steps))
+    }
+
+    pub(super) fn new(steps: Vec<Step>) -> Self {
+        Self {
+            steps,
+            numbering: Numbering::default(),
+            groups: vec![],
+        }
+    }
+
+    /// Top-level steps, across groups.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// How the top-level steps are marked, when they are not grouped.
+    pub fn numbering(&self) -> Numbering {
+        self.numbering
+    }
+
+    /// Groups with their top-level steps, in order. Empty if the steps are not grouped.
+    pub fn groups(&self) -> impl Iterator<Item = (&InstructionGroup, &[Step])> {
+        let mut rest = self.steps.as_slice();
+        self.groups.iter().map(move |group| {
+            let (steps, after) = rest.split_at(group.len);
+            rest = after;
+            (group, steps)
+        })
+    }
+
+    pub(super) fn for_each_ref_mut(&mut self, mut f: impl FnMut(&mut IngredientRef)) {
+        for step in &mut self.steps {
+            step.for_each_ref_mut(&mut f);
+        }
+    }
+
+    /// Replaces scale-dependent text with its text at `scale`.
+    pub(super) fn resolve_conditionals(&mut self, scale: Amount) {
+        fn resolve(steps: &mut [Step], scale: Amount) {
+            for step in steps {
+                step.description = step.resolved(scale).into_iter().cloned().collect();
+                resolve(&mut step.substeps, scale);
+            }
         }
+        resolve(&mut self.steps, scale);
+    }
+
+    /// Returns every step and substep in reading order, each with its position (e.g., "3.2").
+    pub fn flatten(&self) -> Vec<FlatStep<'_>> {
+        fn collect<'a>(steps: &'a [Step], path: &mut Vec<usize>, flat: &mut Vec<FlatStep<'a>>) {
+            for (i, step) in steps.iter().enumerate() {
+                path.push(i);
+                flat.push(FlatStep {
+                    path: StepPath(path.clone()),
+                    step,
+                });
+                collect(&step.substeps, path, flat);
+                path.pop();
+            }
+        }
+        let mut flat = vec![];
+        collect(&self.steps, &mut vec![], &mut flat);
+        flat
+    }
+
+    /// Returns every timer in the instructions, in reading order, along with the step it is in.
+    pub fn timers(&self) -> impl Iterator<Item = (StepPath, QuantityOf<Time>)> + '_ {
+        self.flatten()
+            .into_iter()
+            .flat_map(|FlatStep { path, step }| {
+                let path = path.clone();
+                step.resolved(Amount::ONE)
+                    .into_iter()
+                    .filter_map(move |elem| match elem {
+                        TextElem::Timer(time) => Some((path.clone(), *time)),
+                        _ => None,
+                    })
+            })
+    }
+
+    /// Returns every temperature in the instructions, in reading order, along with the step it is
+    /// in.
+    pub fn temperatures(&self) -> impl Iterator<Item = (StepPath, QuantityOf<Temperature>)> + '_ {
+        self.flatten()
+            .into_iter()
+            .flat_map(|FlatStep { path, step }| {
+                let path = path.clone();
+                step.resolved(Amount::ONE)
+                    .into_iter()
+                    .filter_map(move |elem| match elem {
+                        TextElem::Temperature(temperature) => Some((path.clone(), *temperature)),
+                        _ => None,
+                    })
+            })
+    }
+
+    /// Returns the first oven temperature, i.e., the first temperature in a step about the oven
+    /// or baking (e.g., "Preheat the oven to **200°C**"), along with that step, so that the oven
+    /// can be preheated ahead of time.
+    pub fn preheat(&self) -> Option<(StepPath, QuantityOf<Temperature>)> {
+        const OVEN_WORDS: [&str; 5] = ["oven", "preheat", "bake", "roast", "broil"];
+        let steps = self.flatten();
+        self.temperatures().find(|(path, _)| {
+            let text = steps
+                .iter()
+                .find(|flat| flat.path == *path)
+                .map(|flat| flat.step.text().to_lowercase())
+                .unwrap_or_default();
+            OVEN_WORDS.iter().any(|word| text.contains(word))
+        })
+    }
+
+    /// Returns every ingredient reference in the instructions, in reading order and in every
+    /// branch of scale-dependent text, along with the step it is in.
+    pub fn refs(&self) -> Vec<(StepPath, &IngredientRef)> {
+        fn visit<'a>(
+            elems: &'a [TextElem],
+            path: &StepPath,
+            refs: &mut Vec<(StepPath, &'a IngredientRef)>,
+        ) {
+            for elem in elems {
+                match elem {
+                    TextElem::IngredientRef(r) => refs.push((path.clone(), r)),
+                    TextElem::Conditional(conditional) => {
+                        for (_, branch) in &conditional.branches {
+                            visit(branch, path, refs);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        let mut refs = vec![];
+        for FlatStep { path, step } in self.flatten() {
+            visit(&step.description, &path, &mut refs);
+        }
+        refs
+    }
+
+    /// Returns every link in the instructions (both [`TextElem::Link`] and
+    /// [`TextElem::WikiLink`]), in reading order, along with the step it is in.
+    pub fn links(&self) -> impl Iterator<Item = (StepPath, &TextElem)> + '_ {
+        self.flatten()
+            .into_iter()
+            .flat_map(|FlatStep { path, step }| {
+                step.resolved(Amount::ONE)
+                    .into_iter()
+                    .filter(|elem| {
+                        matches!(elem, TextElem::Link { .. } | TextElem::WikiLink { .. })
+                    })
+                    .map(move |elem| (path.clone(), elem))
+            })
+    }
+}
+
+/// A step of [`Instructions::flatten`], without its substeps.
+#[derive(Clone, Debug)]
+pub struct FlatStep<'a> {
+    pub path: StepPath,
+    pub step: &'a Step,
+}
+
+impl FlatStep<'_> {
+    /// Nesting level, 0 for top-level steps.
+    pub fn depth(&self) -> usize {
+        self.path.0.len() - 1
     }
 }
 
-#[derive(Clone, PartialEq)]
+/// Position of a step, as indices through the steps and substeps (e.g., `[1, 0]` is the first
+/// substep of the second step). Displayed 1-based, as "2.1".
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StepPath(pub Vec<usize>);
+
+impl fmt::Display for StepPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, idx) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{}", idx + 1)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub struct Step {
     description: Vec<TextElem>,
     substeps: Vec<Step>,
+    substep_numbering: Numbering,
 }
 
 impl Step {
-    fn parse(node: &Node) -> MDResult<Self> {
+    pub(super) fn new(description: Vec<TextElem>, substeps: Vec<Step>) -> Self {
+        Self {
+            description,
+            substeps,
+            substep_numbering: Numbering::default(),
+        }
+    }
+
+    /// Step made of plain text, without ingredient references or timers.
+    pub(super) fn from_text(text: &str, substeps: Vec<Step>) -> Self {
+        Self::new(vec![TextElem::Text(text.to_string())], substeps)
+    }
+
+    pub fn description(&self) -> &[TextElem] {
+        &self.description
+    }
+
+    pub fn substeps(&self) -> &[Step] {
+        &self.substeps
+    }
+
+    /// How the substeps are marked.
+    pub fn substep_numbering(&self) -> Numbering {
+        self.substep_numbering
+    }
+
+    /// Description of the step at a scale, with scale-dependent text replaced by the text for
+    /// that scale. Recipes are at scale 1 until [`Recipe::scaled`](super::Recipe::scaled),
+    /// which resolves scale-dependent text.
+    pub fn resolved(&self, scale: Amount) -> Vec<&TextElem> {
+        fn resolve<'a>(elems: &'a [TextElem], scale: Amount, resolved: &mut Vec<&'a TextElem>) {
+            for elem in elems {
+                match elem {
+                    TextElem::Conditional(conditional) => {
+                        resolve(conditional.resolve(scale), scale, resolved)
+                    }
+                    _ => resolved.push(elem),
+                }
+            }
+        }
+        let mut resolved = vec![];
+        resolve(&self.description, scale, &mut resolved);
+        resolved
+    }
+
+    /// Text content of the step's description (excluding substeps).
+    pub fn text(&self) -> String {
+        self.description.iter().map(|e| e.to_string()).collect()
+    }
+
+    fn for_each_ref_mut(&mut self, f: &mut impl FnMut(&mut IngredientRef)) {
+        fn visit(elems: &mut [TextElem], f: &mut impl FnMut(&mut IngredientRef)) {
+            for elem in elems {
+                match elem {
+                    TextElem::IngredientRef(r) => f(r),
+                    TextElem::Conditional(conditional) => {
+                        for (_, branch) in &mut conditional.branches {
+                            visit(branch, f);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        visit(&mut self.description, f);
+        for substep in &mut self.substeps {
+            substep.for_each_ref_mut(f);
+        }
+    }
+
+    fn parse(node: &Node, ctx: &mut ParseContext) -> MDResult<Self> {
         match node {
             Node::ListItem(item) => match item.children.len() {
-                0 => Ok(Self {
-                    description: vec![],
-                    substeps: vec![],
-                }),
-                1 => Ok(Self {
-                    description: Self::parse_description(&item.children[0])?,
-                    substeps: vec![],
-                }),
-
-                2 => Ok(Self {
-                    description: Self::parse_description(&item.children[0])?,
-                    substeps: Self::parse_step_list(&item.children[1])?,
-                }),
+                0 => {
+                    ctx.warn(
+                        MDError::new("empty step", Some(node)).with_code(ErrorCode::EmptyStep),
+                    );
+                    Ok(Self::new(vec![], vec![]))
+                }
+                1 => Ok(Self::new(
+                    Self::parse_description(&item.children[0], ctx)?,
+                    vec![],
+                )),
+
+                2 => {
+                    let description = Self::parse_description(&item.children[0], ctx)?;
+                    let (substeps, substep_numbering) =
+                        Self::parse_step_list(&item.children[1], ctx)?;
+                    Ok(Self {
+                        description,
+                        substeps,
+                        substep_numbering,
+                    })
+                }
                 _ => Err(MDError::new(
                     "too many children to list item, expected at most 2",
                     None,
@@ -55,84 +415,415 @@ impl Step {
         }
     }
 
-    fn parse_description(node: &Node) -> MDResult<Vec<TextElem>> {
+    fn parse_description(node: &Node, ctx: &mut ParseContext) -> MDResult<Vec<TextElem>> {
         match node {
             Node::Paragraph(para) => Ok(para
                 .children
                 .iter()
-                .map(|n| TextElem::parse(n))
-                .collect::<MDResult<Vec<TextElem>>>()?),
+                .filter_map(|elem| {
+                    let elem =
+                        TextElem::parse(elem, ctx).map_err(|e| e.or_code(ErrorCode::MalformedStep));
+                    ctx.recover(elem).flatten()
+                })
+                .flat_map(TextElem::split_links)
+                .collect()),
             _ => Err(MDError::new("expected paragraph", Some(node))),
         }
     }
 
-    fn parse_step_list(node: &Node) -> MDResult<Vec<Step>> {
+    /// Parses a bullet or ordered list of steps.
+    fn parse_step_list(node: &Node, ctx: &mut ParseContext) -> MDResult<(Vec<Step>, Numbering)> {
         match node {
-            Node::List(list) => Ok(list
-                .children
-                .iter()
-                .map(|n| Step::parse(n))
-                .collect::<MDResult<Vec<Step>>>()?),
+            Node::List(list) => {
+                let steps = list
+                    .children
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, item)| {
+                        let step = ctx.within(&format!("while parsing step {}", i + 1), |ctx| {
+                            Step::parse(item, ctx)
+                        });
+                        ctx.recover(step.map_err(|e| e.or_code(ErrorCode::MalformedStep)))
+                    })
+                    .collect();
+                let numbering = match list.ordered {
+                    true => Numbering::Numbers {
+                        start: list.start.unwrap_or(1),
+                    },
+                    false => Numbering::Bullets,
+                };
+                Ok((steps, numbering))
+            }
             _ => Err(MDError::new("expected list", Some(node))),
         }
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum TextElem {
     Text(String),
-    IngredientRef(String),
+    IngredientRef(IngredientRef),
     Timer(QuantityOf<Time>),
+    /// Temperature written as strong or emphasized text (e.g., "**180°C**").
+    Temperature(QuantityOf<Temperature>),
+    Conditional(Conditional),
+    /// Link written in Markdown (e.g., "see [this technique](https://example.org/fold)").
+    Link {
+        text: String,
+        url: String,
+    },
+    /// Link to another recipe or note, written as an Obsidian wikilink (e.g., "[[Pizza dough|the
+    /// dough]]"), shown as its alias if it has one and as its target otherwise.
+    WikiLink {
+        target: String,
+        alias: Option<String>,
+    },
+    /// Image illustrating the step (e.g., "![Folded dough](fold.jpg)"), whose text content is its
+    /// alternative text.
+    Image {
+        url: String,
+        alt: String,
+    },
+    /// Formatting that is neither a reference nor a timer, kept as written: inline code (e.g.,
+    /// "`00` flour"), and emphasis or strong text holding more than plain text (e.g., "*chopped
+    /// **red** onion*").
+    Formatted(RichText),
+}
+
+impl fmt::Display for TextElem {
+    /// Writes the text content, at scale 1 for scale-dependent text.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) => write!(f, "{}", text),
+            Self::IngredientRef(r) => write!(f, "{}", r.name),
+            Self::Timer(quantity) => write!(f, "{}", quantity),
+            Self::Temperature(quantity) => write!(f, "{}", quantity),
+            Self::Conditional(conditional) => conditional
+                .resolve(Amount::ONE)
+                .iter()
+                .try_for_each(|elem| write!(f, "{}", elem)),
+            Self::Link { text, .. } => write!(f, "{}", text),
+            Self::WikiLink { target, alias } => write!(f, "{}", alias.as_ref().unwrap_or(target)),
+            Self::Image { alt, .. } => write!(f, "{}", alt),
+            Self::Formatted(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// Text of a step that depends on the scale of the recipe, written with a branch per scale factor
+/// (e.g., "{1x: bake for **30 min** | 2x: bake for **45 min**}"), because times and the handling
+/// of pans rarely scale linearly.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Conditional {
+    branches: Vec<(Amount, Vec<TextElem>)>,
+}
+
+impl Conditional {
+    /// Branches by increasing scale factor.
+    pub fn branches(&self) -> &[(Amount, Vec<TextElem>)] {
+        &self.branches
+    }
+
+    /// Text at a scale, which is that of the largest factor up to the scale, or that of the
+    /// smallest factor for smaller scales.
+    pub fn resolve(&self, scale: Amount) -> &[TextElem] {
+        self.branches
+            .iter()
+            .rev()
+            .find(|(factor, _)| *factor <= scale)
+            .unwrap_or(&self.branches[0])
+            .1
+            .as_slice()
+    }
+
+    /// Parses the content of braces (e.g., "1x: bake for **30 min** | 2x: bake for **45 min**"),
+    /// or returns `None` if it does not start with a scale factor.
+    fn parse(content: &str, node: &Node, ctx: &mut ParseContext) -> MDResult<Option<Self>> {
+        let first = content.split('|').next().unwrap_or_default();
+        if !Self::looks_like_branch(first) {
+            return Ok(None);
+        }
+        let mut branches = vec![];
+        for part in content.split('|') {
+            let (factor, text) = match Self::branch_start(part) {
+                Some(branch) => branch,
+                None if Self::looks_like_branch(part) => {
+                    return Err(MDError::new(
+                        "expected positive scale factor (e.g., \"2x:\")",
+                        Some(node),
+                    ))
+                }
+                None => {
+                    return Err(MDError::new(
+                        "expected scale factor (e.g., \"2x:\") before scale-dependent text",
+                        Some(node),
+                    ))
+                }
+            };
+            // The branch is parsed on its own, so its places are moved to the document.
+            let text = text.trim_end();
+            let offset = text.as_ptr() as usize - content.as_ptr() as usize;
+            let md = markdown::to_mdast(text, &get_parse_options())?;
+            let elems = match (md.children().map(Vec::as_slice), node.position()) {
+                (None | Some([]), _) => vec![],
+                (Some([paragraph @ Node::Paragraph(_)]), Some(pos)) => {
+                    let start = Self::point_in(&pos.start, &content[..offset]);
+                    let mut elems =
+                        ctx.shifted(&start, |ctx| Step::parse_description(paragraph, ctx))?;
+                    for elem in &mut elems {
+                        if let TextElem::IngredientRef(r) = elem {
+                            r.shift(&start);
+                        }
+                    }
+                    elems
+                }
+                (Some([paragraph @ Node::Paragraph(_)]), None) => {
+                    Step::parse_description(paragraph, ctx)?
+                }
+                (Some(_), _) => {
+                    return Err(MDError::new(
+                        "expected inline text in scale-dependent text",
+                        Some(node),
+                    ))
+                }
+            };
+            branches.push((factor, elems));
+        }
+        Self::new(branches, node).map(Some)
+    }
+
+    /// Point in the document after `before`, the content of braces starting at `brace`.
+    fn point_in(brace: &Point, before: &str) -> Point {
+        let offset = brace.offset + 1 + before.len();
+        match before.rsplit_once('\n') {
+            Some((lines, last)) => Point::new(
+                brace.line + lines.matches('\n').count() + 1,
+                last.len() + 1,
+                offset,
+            ),
+            None => Point::new(brace.line, brace.column + 1 + before.len(), offset),
+        }
+    }
+
+    fn new(mut branches: Vec<(Amount, Vec<TextElem>)>, node: &Node) -> MDResult<Self> {
+        branches.sort_by_key(|(factor, _)| *factor);
+        for pair in branches.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                return Err(MDError::new(
+                    &format!("duplicate scale factor {}x", pair[0].0),
+                    Some(node),
+                ));
+            }
+        }
+        Ok(Self { branches })
+    }
+
+    /// Whether text starts the way a branch does (e.g., "2x:"), whether or not its factor is valid
+    /// (e.g., "0x:" or "x:").
+    fn looks_like_branch(text: &str) -> bool {
+        text.split_once(':')
+            .and_then(|(factor, _)| factor.trim().strip_suffix(['x', '×']))
+            .is_some_and(|factor| !factor.chars().any(char::is_alphabetic))
+    }
+
+    /// Parses the scale factor starting a branch (e.g., "2x:"), returning the text after it.
+    fn branch_start(text: &str) -> Option<(Amount, &str)> {
+        let (factor, after) = text.split_once(':')?;
+        let factor = factor.trim().strip_suffix(['x', '×'])?;
+        let factor = Amount::from_str(factor.trim())
+            .ok()
+            .filter(|f| *f > Amount::ZERO)?;
+        Some((factor, after.trim_start()))
+    }
+}
+
+/// Reference to an ingredient from within a step (e.g., "Squeeze the *lemons*").
+#[derive(Clone, Debug)]
+pub struct IngredientRef {
+    name: String,
+    index: Option<usize>,
+    /// Where the reference is written, to locate errors resolving it.
+    position: Option<Position>,
+}
+
+/// References are equal whatever their position, as recipes are.
+impl PartialEq for IngredientRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.index == other.index
+    }
+}
+
+impl IngredientRef {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            index: None,
+            position: None,
+        }
+    }
+
+    fn at(node: &Node, name: &str) -> Self {
+        Self {
+            position: node.position().cloned(),
+            ..Self::new(name)
+        }
+    }
+
+    /// Where the reference is written in the document, if it was parsed from one.
+    pub fn position(&self) -> Option<&Position> {
+        self.position.as_ref()
+    }
+
+    /// Moves the reference's position from text starting at `start` of the document.
+    pub(super) fn shift(&mut self, start: &Point) {
+        if let Some(position) = &self.position {
+            self.position = Some(shift_position(position, start));
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Index of the referenced line in [`Ingredients::iter`](super::ingredients::Ingredients::iter),
+    /// once resolved.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub(super) fn set_index(&mut self, index: Option<usize>) {
+        self.index = index;
+    }
+
+    pub(super) fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
 }
 
 impl TextElem {
-    fn parse(node: &Node) -> MDResult<Self> {
+    /// Parses an inline element of a step, which may be dropped altogether (`Ok(None)`).
+    fn parse(node: &Node, ctx: &mut ParseContext) -> MDResult<Option<Self>> {
         match node {
-            Node::Text(text) => Ok(Self::Text(text.value.clone())),
-            Node::Emphasis(emphasis) => match emphasis.children.len() {
-                0 => Ok(Self::IngredientRef(String::new())),
-                1 => match &emphasis.children[0] {
-                    Node::Text(text) => Ok(Self::IngredientRef(text.value.clone())),
-                    _ => Err(MDError::new(
-                        "expected ingrdient ref to be text",
-                        Some(&emphasis.children[0]),
-                    )),
+            Node::Text(text) => Ok(Some(Self::Text(normalize_newlines(&text.value)))),
+            Node::Emphasis(emphasis) => match emphasis.children.as_slice() {
+                [] => Self::empty_ref(node, "**", ctx),
+                [Node::Text(text)] => match QuantityOf::<Temperature>::from_str(&text.value) {
+                    Ok(temperature) => Ok(Some(Self::Temperature(temperature))),
+                    Err(_) => Ok(Some(Self::IngredientRef(IngredientRef::at(
+                        node,
+                        &text.value,
+                    )))),
                 },
-                _ => Err(MDError::new("expected single children", Some(node))),
+                _ => Ok(Some(Self::Formatted(RichText::parse(node)?))),
             },
-            Node::Strong(strong) => match strong.children.len() {
-                0 => Ok(Self::IngredientRef(String::new())),
-                1 => match &strong.children[0] {
-                    Node::Text(text) => match QuantityOf::<Time>::from_str(&text.value[..]) {
-                        Ok(quantity) => Ok(Self::Timer(quantity)),
-                        Err(_) => Err(MDError::new(
-                            &format!("expected time information but got \"{}\"", &text.value),
+            Node::Strong(strong) => match strong.children.as_slice() {
+                [] => Self::empty_ref(node, "****", ctx),
+                [Node::Text(text)] => {
+                    if let Ok(quantity) = QuantityOf::<Time>::from_str(&text.value[..]) {
+                        return Ok(Some(Self::Timer(quantity)));
+                    }
+                    if let Ok(quantity) = QuantityOf::<Temperature>::from_str(&text.value[..]) {
+                        return Ok(Some(Self::Temperature(quantity)));
+                    }
+                    // Strong text is reserved for timers and temperatures; anything else is kept
+                    // as formatted text, so that it is written back as such.
+                    ctx.warn(
+                        MDError::new(
+                            &format!(
+                                "expected time or temperature information but got \"{}\"",
+                                &text.value
+                            ),
                             Some(&strong.children[0]),
-                        )),
-                    },
-                    _ => Err(MDError::new(
-                        "expected ingrdient ref to be text",
-                        Some(&strong.children[0]),
-                    )),
-                },
-                _ => Err(MDError::new("expected single children", Some(node))),
+                        )
+                        .with_code(ErrorCode::NotATimer),
+                    );
+                    Ok(Some(Self::Formatted(RichText::parse(node)?)))
+                }
+                _ => Ok(Some(Self::Formatted(RichText::parse(node)?))),
+            },
+            Node::InlineCode(code) => Ok(Some(Self::Formatted(RichText::Code(code.value.clone())))),
+            Node::Link(link) => match link.children.as_slice() {
+                [] => Ok(Some(Self::Link {
+                    text: String::new(),
+                    url: link.url.clone(),
+                })),
+                [Node::Text(text)] => Ok(Some(Self::Link {
+                    text: normalize_newlines(&text.value),
+                    url: link.url.clone(),
+                })),
+                _ => Err(MDError::new("expected link text to be plain", Some(node))),
             },
+            Node::Image(image) => Ok(Some(Self::Image {
+                url: image.url.clone(),
+                alt: image.alt.clone(),
+            })),
+            // Braces are MDX expressions, which may hold scale-dependent text.
+            Node::MdxTextExpression(expression) => {
+                let conditional = Conditional::parse(&expression.value, node, ctx)
+                    .map_err(|e| e.or_code(ErrorCode::InvalidScaleBranch))?;
+                match conditional {
+                    Some(conditional) => Ok(Some(Self::Conditional(conditional))),
+                    None => Err(MDError::new("unsupported element in step", Some(node))),
+                }
+            }
             _ => Err(MDError::new("unsupported element in step", Some(node))),
         }
     }
+
+    /// Splits the wikilinks out of text (e.g., "Knead the [[Pizza dough]]").
+    fn split_links(self) -> Vec<Self> {
+        let Self::Text(mut text) = self else {
+            return vec![self];
+        };
+        let mut elems = vec![];
+        while let Some((before, link, after)) = find_wikilink(&text) {
+            if !before.is_empty() {
+                elems.push(Self::Text(before.to_string()));
+            }
+            elems.push(Self::WikiLink {
+                target: link.target.to_string(),
+                alias: link.alias.map(str::to_string),
+            });
+            text = after.to_string();
+        }
+        if !text.is_empty() || elems.is_empty() {
+            elems.push(Self::Text(text));
+        }
+        elems
+    }
+
+    fn empty_ref(node: &Node, markers: &str, ctx: &mut ParseContext) -> MDResult<Option<Self>> {
+        let err = MDError::new("empty ingredient reference", Some(node))
+            .with_code(ErrorCode::EmptyReference);
+        match ctx.empty_ref_policy() {
+            EmptyRefPolicy::Error => Err(err),
+            EmptyRefPolicy::Warn => {
+                ctx.warn(err);
+                Ok(None)
+            }
+            EmptyRefPolicy::PlainText => Ok(Some(Self::Text(markers.to_string()))),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
 
-    use crate::recipe::{instructions::Instructions, md_parser::MDResult};
+    use crate::recipe::{
+        amount::Amount,
+        instructions::{Instructions, Numbering, TextElem},
+        md_parser::{get_parse_options, EmptyRefPolicy, ErrorCode, MDResult, ParseContext},
+        notes::RichText,
+    };
+    use markdown::mdast::{Emphasis, Node};
 
     #[test]
     fn parse_step() -> MDResult<()> {
         let content = indoc! {"
         - Top
             - Nested with *emphasis* and **10 minutes**
+            - Simmer for **30-40 minutes**
             - Nested at the same width
                 - Double-nested
         "};
@@ -140,4 +831,369 @@ mod tests {
         Instructions::parse(mdast.children().unwrap())?;
         Ok(())
     }
+
+    #[test]
+    fn timers() -> MDResult<()> {
+        let content = indoc! {"
+        - Boil for **10 min**
+        - Top
+            - Nested
+                - Simmer for **30-40 minutes**, then rest for **5 min**
+        "};
+        let mdast = markdown::to_mdast(content, &markdown::ParseOptions::default()).unwrap();
+        let instructions = Instructions::parse(mdast.children().unwrap())?;
+        let timers: Vec<String> = instructions
+            .timers()
+            .map(|(path, time)| format!("{}: {}", path, time))
+            .collect();
+        assert_eq!(
+            timers,
+            vec!["1: 10 min", "2.1.1: 30-40 min", "2.1.1: 5 min"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn temperatures() -> MDResult<()> {
+        let content = indoc! {"
+        - Melt the *butter* at *40°C*
+        - Shape the dough
+            - Bake at **200-220 °C** for **25 min**, then at **350°F**
+        "};
+        let mdast = markdown::to_mdast(content, &markdown::ParseOptions::default()).unwrap();
+        let instructions = Instructions::parse(mdast.children().unwrap())?;
+        let temperatures: Vec<String> = instructions
+            .temperatures()
+            .map(|(path, temperature)| format!("{}: {}", path, temperature))
+            .collect();
+        assert_eq!(
+            temperatures,
+            vec!["1: 40 °C", "2.1: 200-220 °C", "2.1: 350 °F"]
+        );
+        let (path, preheat) = instructions.preheat().unwrap();
+        assert_eq!(path.to_string(), "2.1");
+        assert_eq!(preheat.to_string(), "200-220 °C");
+        assert_eq!(instructions.steps()[0].text(), "Melt the butter at 40 °C");
+        Ok(())
+    }
+
+    #[test]
+    fn flatten() -> MDResult<()> {
+        let content = indoc! {"
+        - Prepare
+            - Peel
+            - Chop
+                - Finely
+        - Cook
+        "};
+        let mdast = markdown::to_mdast(content, &markdown::ParseOptions::default()).unwrap();
+        let instructions = Instructions::parse(mdast.children().unwrap())?;
+        let flat: Vec<String> = instructions
+            .flatten()
+            .iter()
+            .map(|s| format!("{}{} {}", "  ".repeat(s.depth()), s.path, s.step.text()))
+            .collect();
+        assert_eq!(
+            flat,
+            vec![
+                "1 Prepare",
+                "  1.1 Peel",
+                "  1.2 Chop",
+                "    1.2.1 Finely",
+                "2 Cook"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn groups() -> MDResult<()> {
+        let content = indoc! {"
+        ### Dough
+        1. Knead
+        2. Rest
+            - Covered
+
+        ### Topping
+        - Spread the sauce
+        "};
+        let mdast = markdown::to_mdast(content, &markdown::ParseOptions::default()).unwrap();
+        let instructions = Instructions::parse(mdast.children().unwrap())?;
+        assert_eq!(instructions.steps().len(), 3);
+        let groups: Vec<(&str, Numbering, usize)> = instructions
+            .groups()
+            .map(|(group, steps)| (group.name(), group.numbering(), steps.len()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("Dough", Numbering::Numbers { start: 1 }, 2),
+                ("Topping", Numbering::Bullets, 1)
+            ]
+        );
+        // Paths run on across groups.
+        let paths: Vec<String> = instructions
+            .flatten()
+            .iter()
+            .map(|s| s.path.to_string())
+            .collect();
+        assert_eq!(paths, vec!["1", "2", "2.1", "3"]);
+
+        // A group without steps is reported, and the others are kept.
+        let content = indoc! {"
+        ### Dough
+        - Knead
+
+        ### Topping
+        "};
+        let mdast = markdown::to_mdast(content, &markdown::ParseOptions::default()).unwrap();
+        let mut ctx = ParseContext::new();
+        let instructions = Instructions::parse_with(mdast.children().unwrap(), &mut ctx)?;
+        assert_eq!(instructions.groups().count(), 1);
+        assert_eq!(ctx.errors().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_step_warnings() -> MDResult<()> {
+        let content = indoc! {"
+        - Stir for **a while**
+        -
+        "};
+        let mdast = markdown::to_mdast(content, &markdown::ParseOptions::default()).unwrap();
+        let nodes = mdast.children().unwrap();
+
+        let mut ctx = ParseContext::new();
+        let instructions = Instructions::parse_with(nodes, &mut ctx)?;
+        assert_eq!(ctx.diagnostics().len(), 2);
+        assert_eq!(
+            instructions.steps()[0].description()[1],
+            TextElem::Formatted(RichText::Strong(vec![RichText::Text(
+                "a while".to_string()
+            )]))
+        );
+
+        // In strict mode, warnings are errors.
+        let mut ctx = ParseContext::new().strict(true);
+        Instructions::parse_with(nodes, &mut ctx)?;
+        assert!(ctx.diagnostics().is_empty());
+        assert_eq!(ctx.errors().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn conditionals() -> MDResult<()> {
+        let content = indoc! {"
+        - Pour into {1x: one pan | 2x: two pans}, then {0.5x: bake for **20 min** | 1x: bake for **30 min** | 2x: bake *them* for **45 min**}
+        "};
+        let mdast = markdown::to_mdast(content, &get_parse_options()).unwrap();
+        let instructions = Instructions::parse(mdast.children().unwrap())?;
+        let step = &instructions.steps()[0];
+        assert_eq!(step.description().len(), 4);
+        assert_eq!(step.text(), "Pour into one pan, then bake for 30 min");
+        let TextElem::Conditional(conditional) = &step.description()[3] else {
+            panic!("expected conditional text");
+        };
+        let factors: Vec<String> = conditional
+            .branches()
+            .iter()
+            .map(|(factor, _)| factor.to_string())
+            .collect();
+        assert_eq!(factors, vec!["0.5", "1", "2"]);
+
+        let text = |scale: Amount| -> String {
+            step.resolved(scale).iter().map(|e| e.to_string()).collect()
+        };
+        assert_eq!(
+            text(Amount::new(1, 4)),
+            "Pour into one pan, then bake for 20 min"
+        );
+        assert_eq!(
+            text(Amount::new(3, 2)),
+            "Pour into one pan, then bake for 30 min"
+        );
+        assert_eq!(
+            text(Amount::from_int(3)),
+            "Pour into two pans, then bake them for 45 min"
+        );
+
+        let timers: Vec<String> = instructions.timers().map(|(_, t)| t.to_string()).collect();
+        assert_eq!(timers, vec!["30 min"]);
+
+        for invalid in [
+            "- Bake {at most}",
+            "- Bake {1x: 30 min | twice: 45 min}",
+            "- Bake {1x: 30 min | 1x: 45 min}",
+        ] {
+            let mdast = markdown::to_mdast(invalid, &get_parse_options()).unwrap();
+            assert!(Instructions::parse(mdast.children().unwrap()).is_err());
+        }
+        for invalid in ["- Bake {0x: a | 2x: b}", "- Bake {x: a | 2x: b}"] {
+            let mdast = markdown::to_mdast(invalid, &get_parse_options()).unwrap();
+            let error = Instructions::parse(mdast.children().unwrap()).unwrap_err();
+            assert_eq!(error.code(), Some(ErrorCode::InvalidScaleBranch));
+        }
+
+        // Diagnostics within a branch are placed in the document.
+        let content = "- Boil\n- Then {1x: bake **nope** | 2x: b}\n";
+        let mdast = markdown::to_mdast(content, &get_parse_options()).unwrap();
+        let mut ctx = ParseContext::new();
+        Instructions::parse_with(mdast.children().unwrap(), &mut ctx)?;
+        let diagnostics = ctx.into_diagnostics();
+        assert_eq!(diagnostics[0].code(), Some(ErrorCode::NotATimer));
+        assert!(diagnostics[0].render(content).contains(
+            " --> 2:20\n  |\n2 | - Then {1x: bake **nope** | 2x: b}\n  |                    ^^^^\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn images() -> MDResult<()> {
+        let content = indoc! {"
+        - Fold the dough ![Folded dough](img/fold%201.jpg) in three
+        "};
+        let mdast = markdown::to_mdast(content, &get_parse_options()).unwrap();
+        let instructions = Instructions::parse(mdast.children().unwrap())?;
+        let step = &instructions.steps()[0];
+        assert_eq!(
+            step.description()[1],
+            TextElem::Image {
+                url: "img/fold%201.jpg".to_string(),
+                alt: "Folded dough".to_string(),
+            }
+        );
+        assert_eq!(step.text(), "Fold the dough Folded dough in three");
+        Ok(())
+    }
+
+    #[test]
+    fn links() -> MDResult<()> {
+        let content = indoc! {"
+        - Fold the dough, see [this technique](https://example.org/fold)
+            - Then shape it like [[Baguette|a baguette]]
+        - Rest
+        "};
+        let mdast = markdown::to_mdast(content, &get_parse_options()).unwrap();
+        let instructions = Instructions::parse(mdast.children().unwrap())?;
+        assert_eq!(
+            instructions.steps()[0].text(),
+            "Fold the dough, see this technique"
+        );
+        let links: Vec<(String, &TextElem)> = instructions
+            .links()
+            .map(|(path, link)| (path.to_string(), link))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                (
+                    "1".to_string(),
+                    &TextElem::Link {
+                        text: "this technique".to_string(),
+                        url: "https://example.org/fold".to_string(),
+                    }
+                ),
+                (
+                    "1.1".to_string(),
+                    &TextElem::WikiLink {
+                        target: "Baguette".to_string(),
+                        alias: Some("a baguette".to_string()),
+                    }
+                ),
+            ]
+        );
+
+        let mdast = markdown::to_mdast("- See [*this*](fold.md)", &get_parse_options()).unwrap();
+        assert!(Instructions::parse(mdast.children().unwrap()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn formatting() -> MDResult<()> {
+        let content = indoc! {"
+        - Add the `00` flour and *chopped **red** onion*, then **stir `well`**
+        "};
+        let mdast = markdown::to_mdast(content, &get_parse_options()).unwrap();
+        let instructions = Instructions::parse(mdast.children().unwrap())?;
+        let step = &instructions.steps()[0];
+        let text = |s: &str| RichText::Text(s.to_string());
+        assert_eq!(
+            step.description(),
+            [
+                TextElem::Text("Add the ".to_string()),
+                TextElem::Formatted(RichText::Code("00".to_string())),
+                TextElem::Text(" flour and ".to_string()),
+                TextElem::Formatted(RichText::Emphasis(vec![
+                    text("chopped "),
+                    RichText::Strong(vec![text("red")]),
+                    text(" onion"),
+                ])),
+                TextElem::Text(", then ".to_string()),
+                TextElem::Formatted(RichText::Strong(vec![
+                    text("stir "),
+                    RichText::Code("well".to_string()),
+                ])),
+            ]
+        );
+        assert_eq!(
+            step.text(),
+            "Add the 00 flour and chopped red onion, then stir well"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn wikilinks() -> MDResult<()> {
+        let content = indoc! {"
+        - Roll out the [[Pizza dough|dough]], then spread [[Tomato sauce]] on top
+        - Keep [[brackets] as text
+        "};
+        let mdast = markdown::to_mdast(content, &get_parse_options()).unwrap();
+        let instructions = Instructions::parse(mdast.children().unwrap())?;
+        let step = &instructions.steps()[0];
+        assert_eq!(
+            step.description(),
+            [
+                TextElem::Text("Roll out the ".to_string()),
+                TextElem::WikiLink {
+                    target: "Pizza dough".to_string(),
+                    alias: Some("dough".to_string()),
+                },
+                TextElem::Text(", then spread ".to_string()),
+                TextElem::WikiLink {
+                    target: "Tomato sauce".to_string(),
+                    alias: None,
+                },
+                TextElem::Text(" on top".to_string()),
+            ]
+        );
+        assert_eq!(
+            step.text(),
+            "Roll out the dough, then spread Tomato sauce on top"
+        );
+        assert_eq!(
+            instructions.steps()[1].description(),
+            [TextElem::Text("Keep [[brackets] as text".to_string())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn empty_ref_policy() {
+        let empty = Node::Emphasis(Emphasis {
+            children: vec![],
+            position: None,
+        });
+        let parse = |policy| {
+            let mut ctx = ParseContext::new().empty_refs(policy);
+            let elem = TextElem::parse(&empty, &mut ctx);
+            (elem, ctx.diagnostics().len())
+        };
+        assert!(parse(EmptyRefPolicy::Error).0.is_err());
+        assert!(matches!(parse(EmptyRefPolicy::Warn), (Ok(None), 1)));
+        assert!(matches!(
+            parse(EmptyRefPolicy::PlainText),
+            (Ok(Some(TextElem::Text(text))), 0) if text == "**"
+        ));
+    }
 }
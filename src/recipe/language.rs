@@ -0,0 +1,343 @@
+//! Languages of recipes, whose section headings the parser recognizes (e.g., "## Zutaten" rather
+//! than "## Ingredients" in German recipes).
+//!
+//! Unless told otherwise, the parser detects the language from the first section's heading.
+//! Units then parse from their names in the language too (e.g., "2 EL" of oil in German), and
+//! recipes are written back with them.
+
+use std::{fmt, str::FromStr};
+
+use super::{
+    md_parser::SectionNames,
+    unit::{Quantity, Unit, UnitNames, Volume},
+};
+
+const GERMAN_UNITS: &[(Unit, UnitNames)] = &[
+    (
+        Unit::Volume(Volume::Tablespoon),
+        UnitNames::new("EL", "Esslöffel", "Esslöffel", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Teaspoon),
+        UnitNames::new("TL", "Teelöffel", "Teelöffel", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Cup),
+        UnitNames::new("Tasse", "Tasse", "Tassen", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Drop),
+        UnitNames::new("Tropfen", "Tropfen", "Tropfen", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Dash),
+        UnitNames::new("Spritzer", "Spritzer", "Spritzer", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Pinch),
+        UnitNames::new("Prise", "Prise", "Prisen", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Handful),
+        UnitNames::new("Handvoll", "Handvoll", "Handvoll", &[]),
+    ),
+];
+
+const FRENCH_UNITS: &[(Unit, UnitNames)] = &[
+    (
+        Unit::Volume(Volume::Tablespoon),
+        UnitNames::new(
+            "c. à s.",
+            "cuillère à soupe",
+            "cuillères à soupe",
+            &["c.à.s.", "càs"],
+        ),
+    ),
+    (
+        Unit::Volume(Volume::Teaspoon),
+        UnitNames::new(
+            "c. à c.",
+            "cuillère à café",
+            "cuillères à café",
+            &["c.à.c.", "càc"],
+        ),
+    ),
+    (
+        Unit::Volume(Volume::Cup),
+        UnitNames::new("tasse", "tasse", "tasses", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Drop),
+        UnitNames::new("goutte", "goutte", "gouttes", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Dash),
+        UnitNames::new("trait", "trait", "traits", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Pinch),
+        UnitNames::new("pincée", "pincée", "pincées", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Handful),
+        UnitNames::new("poignée", "poignée", "poignées", &[]),
+    ),
+];
+
+const SPANISH_UNITS: &[(Unit, UnitNames)] = &[
+    (
+        Unit::Volume(Volume::Tablespoon),
+        UnitNames::new("cda.", "cucharada", "cucharadas", &["cda"]),
+    ),
+    (
+        Unit::Volume(Volume::Teaspoon),
+        UnitNames::new("cdta.", "cucharadita", "cucharaditas", &["cdta"]),
+    ),
+    (
+        Unit::Volume(Volume::Cup),
+        UnitNames::new("taza", "taza", "tazas", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Pinch),
+        UnitNames::new("pizca", "pizca", "pizcas", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Handful),
+        UnitNames::new("puñado", "puñado", "puñados", &[]),
+    ),
+];
+
+const ITALIAN_UNITS: &[(Unit, UnitNames)] = &[
+    (
+        Unit::Volume(Volume::Tablespoon),
+        UnitNames::new("cucchiaio", "cucchiaio", "cucchiai", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Teaspoon),
+        UnitNames::new("cucchiaino", "cucchiaino", "cucchiaini", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Cup),
+        UnitNames::new("tazza", "tazza", "tazze", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Pinch),
+        UnitNames::new("pizzico", "pizzico", "pizzichi", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Handful),
+        UnitNames::new("manciata", "manciata", "manciate", &[]),
+    ),
+];
+
+const DUTCH_UNITS: &[(Unit, UnitNames)] = &[
+    (
+        Unit::Volume(Volume::Tablespoon),
+        UnitNames::new("el", "eetlepel", "eetlepels", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Teaspoon),
+        UnitNames::new("tl", "theelepel", "theelepels", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Cup),
+        UnitNames::new("kopje", "kopje", "kopjes", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Pinch),
+        UnitNames::new("snufje", "snufje", "snufjes", &[]),
+    ),
+    (
+        Unit::Volume(Volume::Handful),
+        UnitNames::new("handvol", "handvol", "handvollen", &[]),
+    ),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+    Italian,
+    Dutch,
+}
+
+impl Language {
+    /// All languages, in the order detection tries them.
+    pub const ALL: [Language; 6] = [
+        Language::English,
+        Language::German,
+        Language::French,
+        Language::Spanish,
+        Language::Italian,
+        Language::Dutch,
+    ];
+
+    /// ISO 639-1 code (e.g., "de").
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::German => "de",
+            Self::French => "fr",
+            Self::Spanish => "es",
+            Self::Italian => "it",
+            Self::Dutch => "nl",
+        }
+    }
+
+    /// Name of the language in English.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::German => "German",
+            Self::French => "French",
+            Self::Spanish => "Spanish",
+            Self::Italian => "Italian",
+            Self::Dutch => "Dutch",
+        }
+    }
+
+    /// Section headings of recipes in this language.
+    pub fn section_names(self) -> SectionNames {
+        let [ingredients, instructions, equipment, notes, tips] = match self {
+            Self::English => ["Ingredients", "Instructions", "Equipment", "Notes", "Tips"],
+            Self::German => ["Zutaten", "Zubereitung", "Utensilien", "Notizen", "Tipps"],
+            Self::French => [
+                "Ingrédients",
+                "Préparation",
+                "Ustensiles",
+                "Notes",
+                "Astuces",
+            ],
+            Self::Spanish => [
+                "Ingredientes",
+                "Preparación",
+                "Utensilios",
+                "Notas",
+                "Consejos",
+            ],
+            Self::Italian => [
+                "Ingredienti",
+                "Preparazione",
+                "Utensili",
+                "Note",
+                "Consigli",
+            ],
+            Self::Dutch => [
+                "Ingrediënten",
+                "Bereiding",
+                "Benodigdheden",
+                "Notities",
+                "Tips",
+            ],
+        };
+        SectionNames {
+            ingredients: ingredients.to_string(),
+            instructions: instructions.to_string(),
+            equipment: equipment.to_string(),
+            notes: notes.to_string(),
+            tips: tips.to_string(),
+        }
+    }
+
+    /// Units named differently in this language, with their names in it. Other units go by
+    /// their English names.
+    pub fn units(self) -> &'static [(Unit, UnitNames)] {
+        match self {
+            Self::English => &[],
+            Self::German => GERMAN_UNITS,
+            Self::French => FRENCH_UNITS,
+            Self::Spanish => SPANISH_UNITS,
+            Self::Italian => ITALIAN_UNITS,
+            Self::Dutch => DUTCH_UNITS,
+        }
+    }
+
+    /// Unit named so in this language, case-insensitively.
+    pub fn unit(self, name: &str) -> Option<Unit> {
+        let name = name.trim().to_lowercase();
+        self.units()
+            .iter()
+            .find(|(_, names)| names.all().any(|n| n.to_lowercase() == name))
+            .map(|(unit, _)| unit.clone())
+    }
+
+    /// Names of a unit in this language, if it has its own.
+    pub fn names_of(self, unit: &Unit) -> Option<UnitNames> {
+        self.units()
+            .iter()
+            .find(|(u, _)| u == unit)
+            .map(|(_, names)| *names)
+    }
+
+    /// Replaces custom units by the units of the same name in this language, as
+    /// [`UnitRegistry::resolve`](super::unit::UnitRegistry::resolve) does for registered units.
+    pub fn resolve(self, quantity: Quantity) -> Quantity {
+        let unit = match &quantity.unit {
+            Unit::Custom(name) | Unit::Count(name) => self.unit(name),
+            _ => None,
+        };
+        match unit {
+            Some(unit) => Quantity { unit, ..quantity },
+            None => quantity,
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = ();
+
+    /// Parses a language from its code or English name, case-insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|lang| lang.code().eq_ignore_ascii_case(s) || lang.name().eq_ignore_ascii_case(s))
+            .ok_or(())
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::amount::NumberFormat;
+
+    #[test]
+    fn languages() {
+        assert_eq!(Language::from_str("de"), Ok(Language::German));
+        assert_eq!(Language::from_str("French"), Ok(Language::French));
+        assert_eq!(Language::from_str("klingon"), Err(()));
+        assert_eq!(Language::Dutch.to_string(), "nl");
+        assert_eq!(Language::German.section_names().ingredients, "Zutaten");
+        assert_eq!(SectionNames::default(), Language::English.section_names());
+    }
+
+    #[test]
+    fn localized_units() -> Result<(), std::num::ParseFloatError> {
+        let tbsp = Unit::Volume(Volume::Tablespoon);
+        assert_eq!(Unit::from_str_in("EL", Language::German), tbsp);
+        assert_eq!(Unit::from_str_in("c. à s.", Language::French), tbsp);
+        assert_eq!(Unit::from_str_in("tbsp", Language::French), tbsp);
+        assert_eq!(Unit::from_str_in("EL", Language::English), Unit::from("EL"));
+        assert_eq!(tbsp.names_in(Language::German).unwrap().symbol, "EL");
+        assert_eq!(tbsp.names_in(Language::English).unwrap().symbol, "tbsp");
+
+        let numbers = NumberFormat::DecimalComma;
+        let oil = Quantity::from_str_in("1,5 EL", numbers, Language::German)?;
+        assert_eq!(oil, Quantity::from_str("1.5 tbsp")?);
+        assert_eq!(oil.display_in(Language::German).to_string(), "1.5 EL");
+        assert_eq!(oil.display_in(Language::French).to_string(), "1.5 c. à s.");
+        assert_eq!(oil.display_in(Language::English).to_string(), "1.5 tbsp");
+        let salt = Quantity::from_str_in("~2 Prisen", numbers, Language::German)?;
+        assert_eq!(salt.display_in(Language::German).to_string(), "~2 Prise");
+        let flour = Quantity::from_str("250 g")?;
+        assert_eq!(flour.display_in(Language::German).to_string(), "250 g");
+        Ok(())
+    }
+}
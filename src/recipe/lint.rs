@@ -0,0 +1,412 @@
+//! Checks of recipes beyond what parsing requires, as rules which a [`RuleSet`] gathers.
+//!
+//! Rules implement [`RecipeLint`], so that applications may add their own to the built-in ones.
+//! [`Recipe::lint`] runs a set of rules, and returns their diagnostics in the order of the rules.
+
+use std::fmt;
+
+use super::{
+    amount::Amount,
+    ingredients::RefMatching,
+    md_parser::{Diagnostic, ErrorCode, MDError, Severity},
+    unit::{QuantityOf, Time},
+    Recipe, ALT_QUANTITY_TOLERANCE,
+};
+
+/// A check of a recipe.
+pub trait RecipeLint {
+    /// Name of the rule, in kebab case (e.g., "long-step").
+    fn name(&self) -> &str;
+
+    fn check(&self, recipe: &Recipe) -> Vec<Diagnostic>;
+}
+
+/// Rules to check recipes with. The default set holds the built-in rules, with their default
+/// settings.
+pub struct RuleSet {
+    rules: Vec<Box<dyn RecipeLint>>,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::empty()
+            .rule(AltQuantities::default())
+            .rule(LongSteps::default())
+            .rule(TimerUnits::default())
+            .rule(UnusedIngredients)
+            .rule(UnresolvedRefs::default())
+    }
+}
+
+impl RuleSet {
+    /// Set without any rule.
+    pub fn empty() -> Self {
+        Self { rules: vec![] }
+    }
+
+    /// Adds a rule, replacing the rule of the same name if there is one (e.g., to change its
+    /// settings).
+    pub fn rule(mut self, rule: impl RecipeLint + 'static) -> Self {
+        let rule: Box<dyn RecipeLint> = Box::new(rule);
+        match self.rules.iter().position(|r| r.name() == rule.name()) {
+            Some(i) => self.rules[i] = rule,
+            None => self.rules.push(rule),
+        }
+        self
+    }
+
+    /// Removes a rule by its name.
+    pub fn without(mut self, name: &str) -> Self {
+        self.rules.retain(|r| r.name() != name);
+        self
+    }
+
+    /// Names of the rules, in the order they run in.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|r| r.name())
+    }
+}
+
+impl fmt::Debug for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl Recipe {
+    /// Checks the recipe against a set of rules.
+    pub fn lint(&self, rules: &RuleSet) -> Vec<Diagnostic> {
+        rules.rules.iter().flat_map(|r| r.check(self)).collect()
+    }
+}
+
+/// Alternative quantities of an ingredient (e.g., "15 mL / 3 tsp / 1 tbsp") which disagree with
+/// its main quantity beyond a relative tolerance (10% by default), as [`Recipe::validate`] reports.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AltQuantities {
+    pub tolerance: Amount,
+}
+
+impl Default for AltQuantities {
+    fn default() -> Self {
+        Self {
+            tolerance: ALT_QUANTITY_TOLERANCE,
+        }
+    }
+}
+
+impl RecipeLint for AltQuantities {
+    fn name(&self) -> &str {
+        "alt-quantities"
+    }
+
+    fn check(&self, recipe: &Recipe) -> Vec<Diagnostic> {
+        recipe
+            .ingredients()
+            .iter()
+            .flat_map(|options| std::iter::once(options.ingredient()).chain(options.alternatives()))
+            .flat_map(|ingredient| ingredient.inconsistent_alt_quantities(self.tolerance))
+            .map(|e| Diagnostic::new(Severity::Warning, e))
+            .collect()
+    }
+}
+
+/// Steps of more than some number of sentences (3 by default), which are easier to follow when
+/// split. Substeps count as steps of their own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LongSteps {
+    pub max_sentences: usize,
+}
+
+impl Default for LongSteps {
+    fn default() -> Self {
+        Self { max_sentences: 3 }
+    }
+}
+
+impl RecipeLint for LongSteps {
+    fn name(&self) -> &str {
+        "long-step"
+    }
+
+    fn check(&self, recipe: &Recipe) -> Vec<Diagnostic> {
+        recipe
+            .instructions()
+            .flatten()
+            .into_iter()
+            .filter_map(|flat| {
+                let sentences = sentences(&flat.step.text());
+                (sentences > self.max_sentences).then(|| {
+                    let msg = format!(
+                        "step {} has {} sentences, more than {}",
+                        flat.path, sentences, self.max_sentences
+                    );
+                    let error = MDError::new(&msg, None).with_code(ErrorCode::LongStep);
+                    Diagnostic::new(Severity::Hint, error)
+                })
+            })
+            .collect()
+    }
+}
+
+/// Number of sentences in a text, ended by '.', '!' or '?' followed by a space, or by the end of
+/// the text.
+fn sentences(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_sentence = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|next| next.is_whitespace()) {
+            count += usize::from(in_sentence);
+            in_sentence = false;
+        } else if !c.is_whitespace() {
+            in_sentence = true;
+        }
+    }
+    count + usize::from(in_sentence)
+}
+
+/// Timers that are likely written in the wrong unit: those of no time at all, longer than some
+/// maximum (3 days by default, e.g., "**20 h**" for 20 minutes), or of many seconds (e.g.,
+/// "**900 s**" rather than "**15 min**").
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimerUnits {
+    pub max: QuantityOf<Time>,
+}
+
+impl Default for TimerUnits {
+    fn default() -> Self {
+        Self {
+            max: QuantityOf {
+                unit: Time::Day,
+                amount: Amount::from_int(3),
+                max_amount: None,
+            },
+        }
+    }
+}
+
+impl RecipeLint for TimerUnits {
+    fn name(&self) -> &str {
+        "timer-units"
+    }
+
+    fn check(&self, recipe: &Recipe) -> Vec<Diagnostic> {
+        const MANY_SECONDS: i64 = 600;
+        recipe
+            .instructions()
+            .timers()
+            .filter_map(|(path, timer)| {
+                let longest = QuantityOf {
+                    amount: timer.max_amount.unwrap_or(timer.amount),
+                    ..timer
+                };
+                let (severity, problem) = if longest.as_seconds().is_zero() {
+                    (Severity::Warning, "takes no time")
+                } else if longest.as_seconds() > self.max.as_seconds() {
+                    (Severity::Warning, "is suspiciously long")
+                } else if timer.unit == Time::Second
+                    && longest.as_seconds() >= Amount::from_int(MANY_SECONDS)
+                {
+                    (Severity::Hint, "would read better in minutes")
+                } else {
+                    return None;
+                };
+                let msg = format!("timer \"{}\" in step {} {}", timer, path, problem);
+                let error = MDError::new(&msg, None).with_code(ErrorCode::SuspiciousTimer);
+                Some(Diagnostic::new(severity, error))
+            })
+            .collect()
+    }
+}
+
+/// Ingredients which no step refers to (e.g., "- Lemons, 2" without "*lemons*" in any step),
+/// which are often forgotten in the steps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UnusedIngredients;
+
+impl RecipeLint for UnusedIngredients {
+    fn name(&self) -> &str {
+        "unused-ingredient"
+    }
+
+    fn check(&self, recipe: &Recipe) -> Vec<Diagnostic> {
+        let refs = recipe.instructions().refs();
+        recipe
+            .ingredients()
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !refs.iter().any(|(_, r)| r.index() == Some(*i)))
+            .map(|(_, options)| {
+                let msg = format!(
+                    "ingredient \"{}\" is not referenced in any step",
+                    options.ingredient().name()
+                );
+                let error = MDError::new(&msg, None)
+                    .with_code(ErrorCode::UnusedIngredient)
+                    .or_position(options.position());
+                Diagnostic::new(Severity::Warning, error)
+            })
+            .collect()
+    }
+}
+
+/// References in steps to ingredients which are not in the ingredients (e.g., "*lemon*" with
+/// "Limes" as the only ingredient), matched exactly by default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnresolvedRefs {
+    pub matching: RefMatching,
+}
+
+impl Default for UnresolvedRefs {
+    fn default() -> Self {
+        Self {
+            matching: RefMatching::Exact,
+        }
+    }
+}
+
+impl RecipeLint for UnresolvedRefs {
+    fn name(&self) -> &str {
+        "unresolved-ref"
+    }
+
+    fn check(&self, recipe: &Recipe) -> Vec<Diagnostic> {
+        recipe
+            .instructions()
+            .refs()
+            .into_iter()
+            .filter_map(|(path, r)| {
+                let error = recipe
+                    .ingredients()
+                    .resolve(r.name(), self.matching)
+                    .err()?;
+                let error = error
+                    .or_position(r.position())
+                    .with_context(&format!("in step {}", path));
+                Some(Diagnostic::new(Severity::Warning, error))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::MDResult;
+    use indoc::indoc;
+    use std::str::FromStr;
+
+    fn messages(diagnostics: &[Diagnostic]) -> Vec<String> {
+        diagnostics
+            .iter()
+            .map(|d| format!("{}: {}", d.severity(), d.error().message()))
+            .collect()
+    }
+
+    #[test]
+    fn sentence_count() {
+        assert_eq!(sentences(""), 0);
+        assert_eq!(sentences("Mix"), 1);
+        assert_eq!(sentences("Mix. Rest for 1.5 h! Serve?"), 3);
+        assert_eq!(sentences("Mix...  Then bake."), 2);
+    }
+
+    #[test]
+    fn lint_recipe() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(indoc! {"
+            # Bread
+            ## Ingredients
+
+            - Flour, 500 g
+            - Water, 15 mL / 2 tbsp
+
+            ## Instructions
+
+            - Mix the *flour* and the *water*. Knead. Shape. Rest.
+                - Rest for **900 s**
+            - Bake for **100 h**
+            - Cool for **0 min**
+        "})?;
+        assert_eq!(
+            messages(&recipe.lint(&RuleSet::default())),
+            vec![
+                "warning: alternative quantity \"2 tbsp\" of \"Water\" disagrees with \"15 mL\"",
+                "hint: step 1 has 4 sentences, more than 3",
+                "hint: timer \"900 s\" in step 1.1 would read better in minutes",
+                "warning: timer \"100 h\" in step 2 is suspiciously long",
+                "warning: timer \"0 min\" in step 3 takes no time",
+            ]
+        );
+
+        let rules = RuleSet::default()
+            .without("alt-quantities")
+            .rule(LongSteps { max_sentences: 4 })
+            .rule(TimerUnits {
+                max: QuantityOf::from_str("5 d").unwrap(),
+            });
+        assert_eq!(
+            rules.names().collect::<Vec<_>>(),
+            vec![
+                "long-step",
+                "timer-units",
+                "unused-ingredient",
+                "unresolved-ref"
+            ]
+        );
+        assert_eq!(recipe.lint(&rules).len(), 2);
+        assert!(recipe.lint(&RuleSet::empty()).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn ingredient_refs() -> MDResult<()> {
+        let source = indoc! {"
+            # Lemonade
+            ## Ingredients
+
+            - Lemons, 4 | Limes, 6
+            - Sugar, 100 g
+            - Water, 1 L
+            - Mint
+
+            ## Instructions
+
+            - Squeeze the *limes*
+            - Dissolve the *suger* in the *water*
+            - Add {1x: the *ice* | 2x: more *ice*}
+        "};
+        let recipe = Recipe::from_mdast(source)?;
+        let rules = RuleSet::empty()
+            .rule(UnusedIngredients)
+            .rule(UnresolvedRefs::default());
+        let diagnostics = recipe.lint(&rules);
+        assert_eq!(
+            messages(&diagnostics),
+            vec![
+                "warning: ingredient \"Sugar\" is not referenced in any step",
+                "warning: ingredient \"Mint\" is not referenced in any step",
+                "warning: unresolved ingredient reference \"suger\" (did you mean \"sugar\"?)",
+                "warning: unresolved ingredient reference \"ice\"",
+                "warning: unresolved ingredient reference \"ice\"",
+            ]
+        );
+        assert_eq!(diagnostics[2].error().context(), ["in step 2"]);
+        // Unused ingredients are placed on their line, unresolved references on themselves.
+        let places: Vec<_> = diagnostics
+            .iter()
+            .map(|d| d.render(source).lines().nth(1).unwrap().trim().to_string())
+            .collect();
+        assert_eq!(
+            places,
+            ["--> 5:1", "--> 7:1", "--> 12:16", "--> 13:16", "--> 13:33"]
+        );
+
+        // Typos are accepted with fuzzy matching, but the steps still need fixing.
+        let rules = RuleSet::empty().rule(UnresolvedRefs {
+            matching: RefMatching::Fuzzy,
+        });
+        assert_eq!(recipe.lint(&rules).len(), 2);
+        Ok(())
+    }
+}
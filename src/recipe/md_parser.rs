@@ -1,29 +1,268 @@
+use super::{
+    amount::NumberFormat,
+    language::Language,
+    metadata::{KeyAliases, TagNormalization, TagPrefix},
+    notes::NoteKind,
+    unit::UnitRegistry,
+};
 use markdown::{
     self,
     mdast::Node,
     message::{self, Place},
+    unist::{Point, Position},
 };
+use serde_json::{json, Value};
 use std::{
+    error::Error,
     fmt::{self, Display},
     num::ParseFloatError,
+    ops::Range,
+    str::FromStr,
 };
 
 #[derive(Debug)]
 pub struct MDError {
-    msg: String,
+    // Boxed strings and slices keep results small, as errors are passed around in every parser.
+    msg: Box<str>,
     place: Option<Place>,
+    span: Option<Range<usize>>,
+    code: Option<ErrorCode>,
+    details: Option<Box<Details>>,
+}
+
+/// Parts of an error that most errors do without, kept apart so that results stay small.
+#[derive(Debug, Default)]
+struct Details {
+    context: Vec<String>,
+    source: Option<Box<dyn Error + Send + Sync>>,
 }
 
 impl MDError {
     pub fn new(msg: &str, node: Option<&Node>) -> Self {
         Self {
-            msg: msg.to_string(),
-            place: node.and_then(|n| {
-                n.position()
-                    .and_then(|pos| Some(Place::Position(pos.clone())))
-            }),
+            msg: msg.into(),
+            place: node.and_then(|n| n.position().map(|pos| Place::Position(pos.clone()))),
+            span: None,
+            code: None,
+            details: None,
+        }
+    }
+
+    /// Creates an error located at a byte range within a single line of text (e.g., an ingredient
+    /// line), independently of the Markdown node the line comes from.
+    pub fn in_line(msg: &str, span: Range<usize>) -> Self {
+        Self {
+            msg: msg.into(),
+            place: None,
+            span: Some(span),
+            code: None,
+            details: None,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
+
+    /// Byte range of the offending token within its line, when known.
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+
+    pub fn place(&self) -> Option<&Place> {
+        self.place.as_ref()
+    }
+
+    /// Stable code of the error, for tools to act on (see [`ErrorCode`]). Errors from outside the
+    /// parser and the lints may have none.
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.code
+    }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Gives the error a code unless it already has a more specific one.
+    pub(crate) fn or_code(mut self, code: ErrorCode) -> Self {
+        self.code.get_or_insert(code);
+        self
+    }
+
+    /// Describes what was being parsed when the error occurred (e.g., "while parsing ingredient
+    /// group 'Sauce'"). Contexts accumulate as the error goes up through enclosing parsers.
+    pub fn with_context(mut self, context: &str) -> Self {
+        self.add_context(context);
+        self
+    }
+
+    fn add_context(&mut self, context: &str) {
+        self.details_mut().context.push(context.to_string());
+    }
+
+    /// Contexts of the error, innermost first.
+    pub fn context(&self) -> &[String] {
+        self.details.as_ref().map_or(&[], |d| &d.context)
+    }
+
+    /// Sets the lower-level error this error comes from (e.g., a YAML syntax error), which
+    /// [`Error::source`] returns.
+    pub fn with_source(mut self, source: impl Error + Send + Sync + 'static) -> Self {
+        self.details_mut().source = Some(Box::new(source));
+        self
+    }
+
+    fn details_mut(&mut self) -> &mut Details {
+        self.details.get_or_insert_with(Box::default)
+    }
+
+    /// Locates an in-line error within the document, given the text node the line was read from.
+    /// The error's place then covers exactly the offending token. Errors without a span, or text
+    /// nodes without a position, are left as-is.
+    ///
+    /// Offsets in the text only match offsets in the source if the text was written as-is. When
+    /// it was not (e.g., "\*" decoded to "*", or "&amp;" to "&"), the place covers the whole text
+    /// node instead.
+    pub(crate) fn locate_in(mut self, text_node: &Node) -> Self {
+        if let (Some(span), Node::Text(text), Some(pos)) =
+            (&self.span, text_node, text_node.position())
+        {
+            if pos.end.offset - pos.start.offset != text.value.len() {
+                self.place = Some(Place::Position(pos.clone()));
+                return self;
+            }
+            let point_at = |offset: usize| {
+                let mut point = pos.start.clone();
+                for c in text.value[..offset].chars() {
+                    if c == '\n' {
+                        point.line += 1;
+                        point.column = 1;
+                    } else {
+                        point.column += c.len_utf8();
+                    }
+                }
+                Point::new(point.line, point.column, pos.start.offset + offset)
+            };
+            self.place = Some(Place::Position(Position {
+                start: point_at(span.start),
+                end: point_at(span.end),
+            }));
+        }
+        self
+    }
+
+    /// Moves the error's place from text starting at `start` of a document (e.g., a section parsed
+    /// on its own) to the document.
+    pub(crate) fn shifted(mut self, start: &Point) -> Self {
+        self.shift(start);
+        self
+    }
+
+    fn shift(&mut self, start: &Point) {
+        self.place = match self.place.take() {
+            Some(Place::Position(pos)) => Some(Place::Position(shift_position(&pos, start))),
+            Some(Place::Point(point)) => Some(Place::Point(shift_point(&point, start))),
+            None => None,
+        };
+    }
+
+    /// Places the error at a position of the document, if it has none yet.
+    pub(crate) fn or_position(mut self, position: Option<&Position>) -> Self {
+        self.place_at(position);
+        self
+    }
+
+    fn place_at(&mut self, position: Option<&Position>) {
+        if self.place.is_none() {
+            self.place = position.map(|pos| Place::Position(pos.clone()));
         }
     }
+
+    /// Renders the error for people to read, with the line of the Markdown it comes from and
+    /// carets under its place, as compilers do:
+    ///
+    /// ```text
+    /// error[E040_UNRESOLVED_REFERENCE]: unresolved ingredient reference "suger" (did you mean "sugar"?)
+    ///   --> 12:16
+    ///    |
+    /// 12 | - Dissolve the *suger* in the *water*
+    ///    |                ^^^^^^^
+    /// ```
+    ///
+    /// Errors without a place are rendered with their message only. Lines and columns are those of
+    /// the place, as in [`MDError::to_json`]: columns count bytes from the start of the line, as
+    /// markdown-rs does, while carets are aligned on the characters of the line.
+    pub fn render(&self, source: &str) -> String {
+        self.render_as(Severity::Error, source)
+    }
+
+    fn render_as(&self, severity: Severity, source: &str) -> String {
+        let mut out = match self.code {
+            Some(code) => format!("{}[{}]: {}\n", severity, code, self.msg),
+            None => format!("{}: {}\n", severity, self.msg),
+        };
+        let (start, end) = match &self.place {
+            Some(Place::Position(pos)) => (&pos.start, pos.end.offset),
+            Some(Place::Point(point)) => (point, point.offset),
+            None => (&Point::new(0, 0, 0), 0),
+        };
+        let (line, column, start) = (start.line, start.column, start.offset);
+        if line > 0 && start <= source.len() {
+            let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = source[start..]
+                .find('\n')
+                .map_or(source.len(), |i| start + i);
+            let text = source[line_start..line_end].trim_end_matches('\r');
+            let indent = source[line_start..start].chars().count();
+            let width = source[start..end.clamp(start, line_end)]
+                .chars()
+                .count()
+                .max(1);
+            let gutter = " ".repeat(line.to_string().len());
+            out.push_str(&format!("{}--> {}:{}\n", gutter, line, column));
+            out.push_str(&format!("{} |\n", gutter));
+            out.push_str(&format!("{} | {}\n", line, text));
+            out.push_str(&format!(
+                "{} | {}{}\n",
+                gutter,
+                " ".repeat(indent),
+                "^".repeat(width)
+            ));
+        }
+        for context in self.context() {
+            out.push_str(&format!("  = {}\n", context));
+        }
+        out
+    }
+
+    /// Serializes the error for tools to read (see [`Diagnostic::to_json`]).
+    pub fn to_json(&self) -> Value {
+        self.json_as(Severity::Error)
+    }
+
+    fn json_as(&self, severity: Severity) -> Value {
+        let point = |point: &Point| json!({ "line": point.line, "column": point.column, "offset": point.offset });
+        let range = match &self.place {
+            Some(Place::Position(pos)) => {
+                json!({ "start": point(&pos.start), "end": point(&pos.end) })
+            }
+            Some(Place::Point(p)) => json!({ "start": point(p), "end": point(p) }),
+            None => Value::Null,
+        };
+        let span = match &self.span {
+            Some(span) => json!({ "start": span.start, "end": span.end }),
+            None => Value::Null,
+        };
+        json!({
+            "severity": severity.to_string(),
+            "code": self.code.map(|code| code.as_str()),
+            "message": self.msg,
+            "range": range,
+            "span": span,
+            "context": self.context(),
+        })
+    }
 }
 
 pub type MDResult<T> = Result<T, MDError>;
@@ -32,29 +271,668 @@ impl From<message::Message> for MDError {
     fn from(value: message::Message) -> Self {
         let msg = format!("{} ({}:{})", value.reason, value.source, value.rule_id);
         Self {
-            msg,
-            place: value.place.and_then(|p| Some(*p)),
+            msg: msg.into(),
+            place: value.place.clone().map(|p| *p),
+            span: None,
+            code: Some(ErrorCode::MarkdownSyntax),
+            details: None,
         }
+        .with_source(MarkdownMessage(value))
     }
 }
 
 impl From<ParseFloatError> for MDError {
     fn from(value: ParseFloatError) -> Self {
-        MDError::new(&format!("{}", value), None)
+        MDError::new(&format!("{}", value), None).with_source(value)
+    }
+}
+
+impl Error for MDError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        let source = self.details.as_ref()?.source.as_ref()?;
+        Some(source.as_ref())
+    }
+}
+
+/// A message of the Markdown parser, as the source of an [`MDError`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarkdownMessage(pub message::Message);
+
+impl Display for MarkdownMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
     }
 }
 
+impl Error for MarkdownMessage {}
+
 impl Display for MDError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.msg)?;
         if let Some(place) = &self.place {
             write!(f, " @ {}", place)?;
+        } else if let Some(span) = &self.span {
+            write!(f, " @ {}..{}", span.start, span.end)?;
+        }
+        writeln!(f)?;
+        for context in self.context() {
+            writeln!(f, "  {}", context)?;
         }
-        write!(f, "\n")?;
         Ok(())
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Hint,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Hint => write!(f, "hint"),
+        }
+    }
+}
+
+/// Stable code of a kind of error, warning, or hint, which stays the same across releases even
+/// when messages change (e.g., "E040_UNRESOLVED_REFERENCE"). Codes are grouped by area: the
+/// document's structure (E00x), the frontmatter (E01x), ingredients (E02x), instructions (E03x),
+/// ingredient references (E04x), and lints (E05x). Codes of removed kinds are not reused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorCode {
+    MissingIngredientsSection,
+    MissingInstructionsSection,
+    MissingTitle,
+    EmptyFile,
+    MarkdownSyntax,
+    DuplicateSection,
+    UnknownSection,
+    MalformedDescription,
+    MalformedNote,
+    InvalidFrontmatter,
+    InvalidMetadata,
+    UnknownMetadataKey,
+    DeprecatedMetadataKey,
+    DuplicateTag,
+    InconsistentMetadata,
+    MalformedIngredient,
+    InvalidQuantity,
+    NoIngredients,
+    IngredientsPlaceholder,
+    MalformedEquipment,
+    MalformedStep,
+    InvalidScaleBranch,
+    NoInstructions,
+    InstructionsPlaceholder,
+    EmptyStep,
+    NotATimer,
+    InvalidTimer,
+    UnresolvedReference,
+    AmbiguousReference,
+    EmptyReference,
+    UnusedIngredient,
+    AltQuantityMismatch,
+    LongStep,
+    SuspiciousTimer,
+    MissingDonenessTemperature,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 35] = [
+        Self::MissingIngredientsSection,
+        Self::MissingInstructionsSection,
+        Self::MissingTitle,
+        Self::EmptyFile,
+        Self::MarkdownSyntax,
+        Self::DuplicateSection,
+        Self::UnknownSection,
+        Self::MalformedDescription,
+        Self::MalformedNote,
+        Self::InvalidFrontmatter,
+        Self::InvalidMetadata,
+        Self::UnknownMetadataKey,
+        Self::DeprecatedMetadataKey,
+        Self::DuplicateTag,
+        Self::InconsistentMetadata,
+        Self::MalformedIngredient,
+        Self::InvalidQuantity,
+        Self::NoIngredients,
+        Self::IngredientsPlaceholder,
+        Self::MalformedEquipment,
+        Self::MalformedStep,
+        Self::InvalidScaleBranch,
+        Self::NoInstructions,
+        Self::InstructionsPlaceholder,
+        Self::EmptyStep,
+        Self::NotATimer,
+        Self::InvalidTimer,
+        Self::UnresolvedReference,
+        Self::AmbiguousReference,
+        Self::EmptyReference,
+        Self::UnusedIngredient,
+        Self::AltQuantityMismatch,
+        Self::LongStep,
+        Self::SuspiciousTimer,
+        Self::MissingDonenessTemperature,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MissingIngredientsSection => "E001_MISSING_INGREDIENTS_SECTION",
+            Self::MissingInstructionsSection => "E002_MISSING_INSTRUCTIONS_SECTION",
+            Self::MissingTitle => "E003_MISSING_TITLE",
+            Self::EmptyFile => "E004_EMPTY_FILE",
+            Self::MarkdownSyntax => "E005_MARKDOWN_SYNTAX",
+            Self::DuplicateSection => "E006_DUPLICATE_SECTION",
+            Self::UnknownSection => "E007_UNKNOWN_SECTION",
+            Self::MalformedDescription => "E008_MALFORMED_DESCRIPTION",
+            Self::MalformedNote => "E009_MALFORMED_NOTE",
+            Self::InvalidFrontmatter => "E010_INVALID_FRONTMATTER",
+            Self::InvalidMetadata => "E011_INVALID_METADATA",
+            Self::UnknownMetadataKey => "E012_UNKNOWN_METADATA_KEY",
+            Self::DeprecatedMetadataKey => "E013_DEPRECATED_METADATA_KEY",
+            Self::DuplicateTag => "E014_DUPLICATE_TAG",
+            Self::InconsistentMetadata => "E015_INCONSISTENT_METADATA",
+            Self::MalformedIngredient => "E020_MALFORMED_INGREDIENT",
+            Self::InvalidQuantity => "E021_INVALID_QUANTITY",
+            Self::NoIngredients => "E022_NO_INGREDIENTS",
+            Self::IngredientsPlaceholder => "E023_INGREDIENTS_PLACEHOLDER",
+            Self::MalformedEquipment => "E024_MALFORMED_EQUIPMENT",
+            Self::MalformedStep => "E030_MALFORMED_STEP",
+            Self::InvalidScaleBranch => "E031_INVALID_SCALE_BRANCH",
+            Self::NoInstructions => "E032_NO_INSTRUCTIONS",
+            Self::InstructionsPlaceholder => "E033_INSTRUCTIONS_PLACEHOLDER",
+            Self::EmptyStep => "E034_EMPTY_STEP",
+            Self::NotATimer => "E035_NOT_A_TIMER",
+            Self::InvalidTimer => "E036_INVALID_TIMER",
+            Self::UnresolvedReference => "E040_UNRESOLVED_REFERENCE",
+            Self::AmbiguousReference => "E041_AMBIGUOUS_REFERENCE",
+            Self::EmptyReference => "E042_EMPTY_REFERENCE",
+            Self::UnusedIngredient => "E043_UNUSED_INGREDIENT",
+            Self::AltQuantityMismatch => "E050_ALT_QUANTITY_MISMATCH",
+            Self::LongStep => "E051_LONG_STEP",
+            Self::SuspiciousTimer => "E052_SUSPICIOUS_TIMER",
+            Self::MissingDonenessTemperature => "E053_MISSING_DONENESS_TEMPERATURE",
+        }
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = MDError;
+
+    /// Parses a full code (e.g., "E040_UNRESOLVED_REFERENCE") or its number only (e.g., "E040").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|code| {
+                let full = code.as_str();
+                s == full || full.split('_').next() == Some(s)
+            })
+            .ok_or_else(|| MDError::new(&format!("unknown error code {:?}", s), None))
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An issue found while parsing, which only prevents producing a recipe if it is an error.
+#[derive(Debug)]
+pub struct Diagnostic {
+    severity: Severity,
+    error: MDError,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, error: MDError) -> Self {
+        Self { severity, error }
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn error(&self) -> &MDError {
+        &self.error
+    }
+
+    pub fn into_error(self) -> MDError {
+        self.error
+    }
+
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.error.code
+    }
+
+    /// Serializes the diagnostic for tools to read (e.g., editor plugins), as an object with its
+    /// `severity`, `code` (or null), `message`, `context` (innermost first), the `range` it covers
+    /// in the document (or null), with 1-based lines and byte columns and 0-based byte offsets, and
+    /// the byte `span` within its line (or null).
+    pub fn to_json(&self) -> Value {
+        self.error.json_as(self.severity)
+    }
+
+    /// Renders the diagnostic with the line of `source` it comes from (see [`MDError::render`]).
+    pub fn render(&self, source: &str) -> String {
+        self.error.render_as(self.severity, source)
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.error)
+    }
+}
+
+/// What to do with emphasis that has no content (e.g., `**`), which would otherwise be an ingredient
+/// reference without a name.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmptyRefPolicy {
+    Error,
+    /// Warns and drops the reference.
+    #[default]
+    Warn,
+    /// Keeps the emphasis markers as plain text.
+    PlainText,
+}
+
+/// Headings of the sections of a recipe.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionNames {
+    pub ingredients: String,
+    pub instructions: String,
+    pub equipment: String,
+    pub notes: String,
+    pub tips: String,
+}
+
+impl SectionNames {
+    /// Heading of the section holding notes of a kind.
+    pub fn note_heading(&self, kind: NoteKind) -> &str {
+        match kind {
+            NoteKind::Note => &self.notes,
+            NoteKind::Tip => &self.tips,
+        }
+    }
+}
+
+impl Default for SectionNames {
+    fn default() -> Self {
+        Language::English.section_names()
+    }
+}
+
+/// Options of the recipe parser, which the parsers of every section read from their
+/// [`ParseContext`].
+///
+/// Section headings are detected from the first section by default, among the built-in
+/// [`Language`]s; setting the language or the headings turns detection off.
+#[derive(Clone, Debug)]
+pub struct RecipeParseOptions {
+    strict: bool,
+    empty_refs: EmptyRefPolicy,
+    heading_formatting: HeadingFormatting,
+    key_aliases: KeyAliases,
+    tag_prefix: TagPrefix,
+    tag_normalization: Option<TagNormalization>,
+    units: UnitRegistry,
+    numbers: NumberFormat,
+    sections: SectionNames,
+    language: Option<Language>,
+    detect_language: bool,
+}
+
+impl Default for RecipeParseOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            empty_refs: EmptyRefPolicy::default(),
+            heading_formatting: HeadingFormatting::default(),
+            key_aliases: KeyAliases::default(),
+            tag_prefix: TagPrefix::default(),
+            tag_normalization: None,
+            units: UnitRegistry::default(),
+            numbers: NumberFormat::default(),
+            sections: SectionNames::default(),
+            language: Some(Language::English),
+            detect_language: true,
+        }
+    }
+}
+
+impl RecipeParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Promotes warnings to errors.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn empty_refs(mut self, policy: EmptyRefPolicy) -> Self {
+        self.empty_refs = policy;
+        self
+    }
+
+    /// How formatting in the title and group names is kept.
+    pub fn heading_formatting(mut self, formatting: HeadingFormatting) -> Self {
+        self.heading_formatting = formatting;
+        self
+    }
+
+    /// Alternative names accepted for the frontmatter keys.
+    pub fn key_aliases(mut self, aliases: KeyAliases) -> Self {
+        self.key_aliases = aliases;
+        self
+    }
+
+    /// Whether tags in the frontmatter must start with '#'.
+    pub fn tag_prefix(mut self, prefix: TagPrefix) -> Self {
+        self.tag_prefix = prefix;
+        self
+    }
+
+    /// Normalizes tags in the frontmatter (none are by default).
+    pub fn tag_normalization(mut self, normalization: TagNormalization) -> Self {
+        self.tag_normalization = Some(normalization);
+        self
+    }
+
+    /// Custom units that ingredient quantities may be in.
+    pub fn units(mut self, units: UnitRegistry) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// How numbers are written in ingredient quantities (e.g., "1,5 kg" with decimal commas).
+    pub fn numbers(mut self, format: NumberFormat) -> Self {
+        self.numbers = format;
+        self
+    }
+
+    /// Expected headings of the sections (e.g., "Method" rather than "Instructions"), in no
+    /// particular language.
+    pub fn sections(mut self, names: SectionNames) -> Self {
+        self.sections = names;
+        self.language = None;
+        self.detect_language = false;
+        self
+    }
+
+    /// Expects the section headings of a language.
+    pub fn language(mut self, language: Language) -> Self {
+        self.sections = language.section_names();
+        self.language = Some(language);
+        self.detect_language = false;
+        self
+    }
+}
+
+/// State shared by the parsers of a single recipe. Errors in self-contained elements (an
+/// ingredient line, a step, a metadata entry) are recorded here so that parsing can carry on and
+/// report every problem at once. Questionable but parseable constructs are recorded as warnings
+/// or hints; in strict mode, warnings are errors too.
+///
+/// The context holds the [`RecipeParseOptions`], which its builder methods set too.
+#[derive(Debug, Default)]
+pub struct ParseContext {
+    errors: Vec<MDError>,
+    diagnostics: Vec<Diagnostic>,
+    options: RecipeParseOptions,
+    /// Language detected from the first section, with its headings.
+    detected: Option<(Language, SectionNames)>,
+}
+
+impl ParseContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: RecipeParseOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    pub fn options(&self) -> &RecipeParseOptions {
+        &self.options
+    }
+
+    /// See [`RecipeParseOptions::strict`].
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.options.strict = strict;
+        self
+    }
+
+    /// See [`RecipeParseOptions::empty_refs`].
+    pub fn empty_refs(mut self, policy: EmptyRefPolicy) -> Self {
+        self.options.empty_refs = policy;
+        self
+    }
+
+    pub(crate) fn empty_ref_policy(&self) -> EmptyRefPolicy {
+        self.options.empty_refs
+    }
+
+    /// See [`RecipeParseOptions::heading_formatting`].
+    pub fn heading_formatting(mut self, formatting: HeadingFormatting) -> Self {
+        self.options.heading_formatting = formatting;
+        self
+    }
+
+    pub(crate) fn heading_formatting_mode(&self) -> HeadingFormatting {
+        self.options.heading_formatting
+    }
+
+    /// See [`RecipeParseOptions::key_aliases`].
+    pub fn key_aliases(mut self, aliases: KeyAliases) -> Self {
+        self.options.key_aliases = aliases;
+        self
+    }
+
+    pub(crate) fn metadata_key_aliases(&self) -> &KeyAliases {
+        &self.options.key_aliases
+    }
+
+    /// See [`RecipeParseOptions::tag_prefix`].
+    pub fn tag_prefix(mut self, prefix: TagPrefix) -> Self {
+        self.options.tag_prefix = prefix;
+        self
+    }
+
+    pub(crate) fn tag_prefix_mode(&self) -> TagPrefix {
+        self.options.tag_prefix
+    }
+
+    /// See [`RecipeParseOptions::tag_normalization`].
+    pub fn tag_normalization(mut self, normalization: TagNormalization) -> Self {
+        self.options.tag_normalization = Some(normalization);
+        self
+    }
+
+    pub(crate) fn tag_normalizer(&self) -> Option<&TagNormalization> {
+        self.options.tag_normalization.as_ref()
+    }
+
+    /// See [`RecipeParseOptions::units`].
+    pub fn units(mut self, units: UnitRegistry) -> Self {
+        self.options.units = units;
+        self
+    }
+
+    pub(crate) fn unit_registry(&self) -> &UnitRegistry {
+        &self.options.units
+    }
+
+    /// See [`RecipeParseOptions::numbers`].
+    pub fn numbers(mut self, format: NumberFormat) -> Self {
+        self.options.numbers = format;
+        self
+    }
+
+    pub(crate) fn number_format(&self) -> NumberFormat {
+        self.options.numbers
+    }
+
+    /// See [`RecipeParseOptions::sections`].
+    pub fn sections(mut self, names: SectionNames) -> Self {
+        self.options = self.options.sections(names);
+        self
+    }
+
+    /// See [`RecipeParseOptions::language`].
+    pub fn language(mut self, language: Language) -> Self {
+        self.options = self.options.language(language);
+        self
+    }
+
+    pub(crate) fn section_names(&self) -> &SectionNames {
+        match &self.detected {
+            Some((_, names)) => names,
+            None => &self.options.sections,
+        }
+    }
+
+    /// Language of the section headings, unless they were set without one.
+    pub(crate) fn section_language(&self) -> Option<Language> {
+        match &self.detected {
+            Some((language, _)) => Some(*language),
+            None => self.options.language,
+        }
+    }
+
+    /// Detects the language of the section headings from the first section's heading, which is
+    /// the ingredients' or the equipment's. Headings stay the same when detection is off or finds
+    /// no language.
+    pub(super) fn detect_language(&mut self, heading: &Node) {
+        if !self.options.detect_language {
+            return;
+        }
+        self.detected = Language::ALL.into_iter().find_map(|language| {
+            let names = language.section_names();
+            let matches = |name: &str| get_heading(heading, 2, Some(name)).is_ok();
+            (matches(&names.ingredients) || matches(&names.equipment)).then_some((language, names))
+        });
+    }
+
+    /// Records the error of a failed sub-parse, if any, and returns its value otherwise.
+    pub(crate) fn recover<T>(&mut self, result: MDResult<T>) -> Option<T> {
+        result.map_err(|e| self.errors.push(e)).ok()
+    }
+
+    /// Runs a sub-parse, adding `context` to the errors it records or returns. Warnings and hints
+    /// are left as-is.
+    pub(crate) fn within<T>(
+        &mut self,
+        context: &str,
+        parse: impl FnOnce(&mut Self) -> MDResult<T>,
+    ) -> MDResult<T> {
+        let errors = self.errors.len();
+        let result = parse(self);
+        for error in self.errors.iter_mut().skip(errors) {
+            error.add_context(context);
+        }
+        result.map_err(|e| e.with_context(context))
+    }
+
+    /// Runs a sub-parse of text starting at `start` of the document (e.g., text re-parsed on its
+    /// own), moving the places of the errors and diagnostics it records or returns to the
+    /// document.
+    pub(crate) fn shifted<T>(
+        &mut self,
+        start: &Point,
+        parse: impl FnOnce(&mut Self) -> MDResult<T>,
+    ) -> MDResult<T> {
+        let (errors, diagnostics) = (self.errors.len(), self.diagnostics.len());
+        let result = parse(self);
+        for error in self.errors.iter_mut().skip(errors) {
+            error.shift(start);
+        }
+        for diagnostic in self.diagnostics.iter_mut().skip(diagnostics) {
+            diagnostic.error.shift(start);
+        }
+        result.map_err(|e| e.shifted(start))
+    }
+
+    /// Runs a parse of something at `position` of the document which cannot place its own errors
+    /// (e.g., a frontmatter entry), placing the errors and diagnostics it records or returns there
+    /// unless they already have a place.
+    pub(crate) fn placed<T>(
+        &mut self,
+        position: Option<&Position>,
+        parse: impl FnOnce(&mut Self) -> MDResult<T>,
+    ) -> MDResult<T> {
+        let (errors, diagnostics) = (self.errors.len(), self.diagnostics.len());
+        let result = parse(self);
+        for error in self.errors.iter_mut().skip(errors) {
+            error.place_at(position);
+        }
+        for diagnostic in self.diagnostics.iter_mut().skip(diagnostics) {
+            diagnostic.error.place_at(position);
+        }
+        result.map_err(|e| e.or_position(position))
+    }
+
+    pub(crate) fn warn(&mut self, warning: MDError) {
+        if self.options.strict {
+            self.errors.push(warning);
+        } else {
+            self.diagnostics
+                .push(Diagnostic::new(Severity::Warning, warning));
+        }
+    }
+
+    pub(crate) fn hint(&mut self, hint: MDError) {
+        self.diagnostics.push(Diagnostic::new(Severity::Hint, hint));
+    }
+
+    /// Non-fatal diagnostics (warnings and hints) recorded so far.
+    #[cfg(test)]
+    pub(crate) fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub(crate) fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn errors(&self) -> &[MDError] {
+        &self.errors
+    }
+
+    pub(crate) fn into_errors(self) -> Vec<MDError> {
+        self.errors
+    }
+
+    /// Returns all diagnostics, errors first.
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.errors
+            .into_iter()
+            .map(|e| Diagnostic::new(Severity::Error, e))
+            .chain(self.diagnostics)
+            .collect()
+    }
+
+    /// Returns the first error encountered during parsing, or the parsing result.
+    pub(crate) fn finish<T>(self, result: MDResult<T>) -> MDResult<T> {
+        match self.errors.into_iter().next() {
+            Some(e) => Err(e),
+            None => result,
+        }
+    }
+}
+
 pub struct ASTConsumer<'a> {
     idx: usize,
     nodes: &'a [Node],
@@ -75,6 +953,10 @@ impl<'a> ASTConsumer<'a> {
         }
     }
 
+    pub fn peek(&self) -> Option<&'a Node> {
+        self.nodes.get(self.idx)
+    }
+
     pub fn consume_to_next_heading(&mut self, depth: u8) -> &[Node] {
         if self.idx == self.nodes.len() {
             &[]
@@ -95,6 +977,7 @@ impl<'a> ASTConsumer<'a> {
         }
     }
 
+    #[allow(dead_code)]
     pub fn get_remaining(&'a self) -> &'a [Node] {
         if self.idx == self.nodes.len() {
             &[]
@@ -124,41 +1007,63 @@ pub fn expect_children(node: &Node, num: usize) -> MDResult<()> {
     }
 }
 
+/// How inline formatting in headings (e.g., `## Dough *for the base*`) is turned into text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeadingFormatting {
+    /// Keeps only the text content.
+    #[default]
+    Strip,
+    /// Keeps the Markdown markup of the formatting.
+    Preserve,
+}
+
 pub fn get_heading(node: &Node, depth: u8, name: Option<&str>) -> MDResult<String> {
+    get_heading_with(node, depth, name, HeadingFormatting::Strip)
+}
+
+/// Returns the text of a heading at the given depth. When `name` is given, the heading's text
+/// content must be that name, optionally followed by a qualifier (e.g., "Ingredients *for 4*").
+pub fn get_heading_with(
+    node: &Node,
+    depth: u8,
+    name: Option<&str>,
+    formatting: HeadingFormatting,
+) -> MDResult<String> {
     // Check that the heading is what we expect.
     if let Node::Heading(heading) = &node {
-        // We expect a single Node::Text children at the correct depth.
         if heading.depth != depth {
-            Err(MDError::new(
+            return Err(MDError::new(
                 &format!(
                     "expected heading at depth {}, but got {}",
                     depth, heading.depth
                 ),
                 Some(node),
-            ))
-        } else if let Err(e) = expect_children(node, 1) {
-            Err(e)
-        } else if let Node::Text(text) = &heading.children[0] {
-            if let Some(requested_name) = name {
-                if text.value != requested_name {
-                    Err(MDError::new(
-                        &format!(
-                            "expected heading \"{}\", but got \"{}\"",
-                            requested_name, text.value
-                        ),
-                        Some(&heading.children[0]),
-                    ))
-                } else {
-                    Ok(text.value.clone())
-                }
-            } else {
-                Ok(text.value.clone())
-            }
-        } else {
-            Err(MDError::new(
+            ));
+        }
+        let plain = inline_text(&heading.children, HeadingFormatting::Strip)?;
+        if plain.is_empty() {
+            return Err(MDError::new(
                 "expected heading to have text child",
                 Some(node),
-            ))
+            ));
+        }
+        if let Some(requested_name) = name {
+            let qualified = plain
+                .strip_prefix(requested_name)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace));
+            if !qualified {
+                return Err(MDError::new(
+                    &format!(
+                        "expected heading \"{}\", but got \"{}\"",
+                        requested_name, plain
+                    ),
+                    Some(node),
+                ));
+            }
+        }
+        match formatting {
+            HeadingFormatting::Strip => Ok(plain),
+            HeadingFormatting::Preserve => inline_text(&heading.children, formatting),
         }
     } else {
         Err(MDError::new(
@@ -168,7 +1073,74 @@ pub fn get_heading(node: &Node, depth: u8, name: Option<&str>) -> MDResult<Strin
     }
 }
 
-pub fn get_text_from_paragraph<'a>(node: &'a Node) -> MDResult<&'a str> {
+/// Concatenates inline nodes into single-line text, trimmed.
+fn inline_text(nodes: &[Node], formatting: HeadingFormatting) -> MDResult<String> {
+    fn push(node: &Node, formatting: HeadingFormatting, out: &mut String) -> MDResult<()> {
+        let preserve = formatting == HeadingFormatting::Preserve;
+        let (markers, children) = match node {
+            Node::Text(text) => {
+                out.push_str(&text.value);
+                return Ok(());
+            }
+            Node::InlineCode(code) => {
+                match preserve {
+                    true => out.push_str(&format!("`{}`", code.value)),
+                    false => out.push_str(&code.value),
+                }
+                return Ok(());
+            }
+            Node::Break(_) => {
+                out.push(' ');
+                return Ok(());
+            }
+            Node::Emphasis(emphasis) => ("*", &emphasis.children),
+            Node::Strong(strong) => ("**", &strong.children),
+            Node::Delete(delete) => ("~~", &delete.children),
+            Node::Link(link) => {
+                if preserve {
+                    out.push('[');
+                }
+                for child in &link.children {
+                    push(child, formatting, out)?;
+                }
+                if preserve {
+                    out.push_str(&format!("]({})", link.url));
+                }
+                return Ok(());
+            }
+            _ => {
+                return Err(MDError::new(
+                    "unsupported inline element in heading",
+                    Some(node),
+                ))
+            }
+        };
+        if preserve {
+            out.push_str(markers);
+        }
+        for child in children {
+            push(child, formatting, out)?;
+        }
+        if preserve {
+            out.push_str(markers);
+        }
+        Ok(())
+    }
+
+    let mut out = String::new();
+    for node in nodes {
+        push(node, formatting, &mut out)?;
+    }
+    // Setext headings may span several lines.
+    Ok(out
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+pub fn get_text_from_paragraph(node: &Node) -> MDResult<&str> {
     if let Node::Paragraph(para) = &node {
         if let Err(e) = expect_children(node, 1) {
             Err(e)
@@ -185,8 +1157,330 @@ pub fn get_text_from_paragraph<'a>(node: &'a Node) -> MDResult<&'a str> {
     }
 }
 
+/// Moves a point of text starting at `start` of a document (e.g., a section parsed on its own) to
+/// the document.
+pub(crate) fn shift_point(point: &Point, start: &Point) -> Point {
+    let column = match point.line {
+        1 => start.column + point.column - 1,
+        _ => point.column,
+    };
+    Point::new(
+        start.line + point.line - 1,
+        column,
+        start.offset + point.offset,
+    )
+}
+
+/// Moves a position as [`shift_point`] does.
+pub(crate) fn shift_position(position: &Position, start: &Point) -> Position {
+    Position {
+        start: shift_point(&position.start, start),
+        end: shift_point(&position.end, start),
+    }
+}
+
+/// Texts a draft section may hold in place of its content (e.g., "TBD").
+const PLACEHOLDERS: [&str; 5] = ["TBD", "TBA", "TODO", "...", "…"];
+
+/// Whether the node is a paragraph of placeholder text (e.g., "TBD" or "…"), compared without
+/// case.
+pub fn is_placeholder(node: &Node) -> bool {
+    get_text_from_paragraph(node).is_ok_and(|text| {
+        PLACEHOLDERS
+            .iter()
+            .any(|placeholder| placeholder.eq_ignore_ascii_case(text.trim()))
+    })
+}
+
+/// Converts Windows (and old Mac) line endings in text content to `\n`, so that files parse
+/// identically regardless of the platform they were written on.
+pub fn normalize_newlines(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Obsidian-style link to another note (e.g., "[[Pizza dough|the dough]]"), which Markdown leaves
+/// as plain text.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WikiLink<'a> {
+    pub target: &'a str,
+    pub alias: Option<&'a str>,
+}
+
+impl<'a> WikiLink<'a> {
+    /// Text the link is shown as: its alias, or else the name of the note it points to (e.g.,
+    /// "Tomato sauce" for "[[bases/Tomato sauce#Quick]]").
+    pub fn label(&self) -> &'a str {
+        self.alias.unwrap_or_else(|| note_name(self.target))
+    }
+}
+
+/// Name of the note a wikilink target points to, without its folder and heading.
+pub(crate) fn note_name(target: &str) -> &str {
+    let note = target.split('#').next().unwrap_or_default();
+    note.rsplit('/').next().unwrap_or_default()
+}
+
+/// Finds the first wikilink in text, returning the text before it, the link, and the text after
+/// it. Brackets which do not form a link with a non-empty target (e.g., "[[]]") are not links.
+pub fn find_wikilink(text: &str) -> Option<(&str, WikiLink<'_>, &str)> {
+    let mut from = 0;
+    while let Some(start) = text[from..].find("[[").map(|i| from + i) {
+        let inner_start = start + 2;
+        if let Some(len) = text[inner_start..].find("]]") {
+            let inner = &text[inner_start..inner_start + len];
+            let (target, alias) = match inner.split_once('|') {
+                Some((target, alias)) => (target.trim(), Some(alias.trim())),
+                None => (inner.trim(), None),
+            };
+            if !target.is_empty() && alias != Some("") && !inner.contains(['[', ']', '\n']) {
+                let link = WikiLink { target, alias };
+                return Some((&text[..start], link, &text[inner_start + len + 2..]));
+            }
+        }
+        from = start + 1;
+    }
+    None
+}
+
 pub fn get_parse_options() -> markdown::ParseOptions {
     let mut options = markdown::ParseOptions::mdx();
     options.constructs.frontmatter = true;
     options
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heading(content: &str) -> Node {
+        let md = markdown::to_mdast(content, &get_parse_options()).unwrap();
+        md.children().unwrap()[0].clone()
+    }
+
+    #[test]
+    fn heading_with_formatting() -> MDResult<()> {
+        let node = heading("## Ingredients *for the `base`*");
+        assert_eq!(
+            get_heading(&node, 2, Some("Ingredients"))?,
+            "Ingredients for the base"
+        );
+        assert_eq!(
+            get_heading_with(&node, 2, None, HeadingFormatting::Preserve)?,
+            "Ingredients *for the `base`*"
+        );
+        assert_eq!(
+            get_heading(&heading("# [Pie](https://pie.org) **deluxe**"), 1, None)?,
+            "Pie deluxe"
+        );
+
+        // The requested name must be a whole word.
+        assert!(get_heading(&heading("## Ingredientsss"), 2, Some("Ingredients")).is_err());
+        assert!(get_heading(&heading("## *Ingredients*"), 2, Some("Ingredients")).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn render_errors() {
+        let source = "# Lemonade\n\n## Ingredients\n\n- Lemons, 4\n- Sugar, 2 bananas\n";
+        let md = markdown::to_mdast(source, &get_parse_options()).unwrap();
+        let list = &md.children().unwrap()[2];
+        let sugar = &list.children().unwrap()[1];
+        let text = &sugar.children().unwrap()[0].children().unwrap()[0];
+
+        let error = MDError::in_line("unknown unit \"bananas\"", 9..16)
+            .locate_in(text)
+            .with_context("while parsing ingredients");
+        assert_eq!(
+            Diagnostic::new(Severity::Warning, error).render(source),
+            indoc::indoc! {r#"
+                warning: unknown unit "bananas"
+                 --> 6:12
+                  |
+                6 | - Sugar, 2 bananas
+                  |            ^^^^^^^
+                  = while parsing ingredients
+            "#}
+        );
+
+        // Offsets after an escaped character do not match the source, so the whole text is
+        // underlined rather than the wrong characters.
+        let escaped = "- Sug\\*ar, 2 bananas\n";
+        let escaped_md = markdown::to_mdast(escaped, &get_parse_options()).unwrap();
+        let item = &escaped_md.children().unwrap()[0].children().unwrap()[0];
+        let text = &item.children().unwrap()[0].children().unwrap()[0];
+        let error = MDError::in_line("unknown unit \"bananas\"", 10..17).locate_in(text);
+        assert_eq!(
+            error.render(escaped),
+            indoc::indoc! {r#"
+                error: unknown unit "bananas"
+                 --> 1:3
+                  |
+                1 | - Sug\*ar, 2 bananas
+                  |   ^^^^^^^^^^^^^^^^^^
+            "#}
+        );
+
+        // Nodes spanning several lines are underlined up to the end of their first line.
+        let error = MDError::new("expected a single ingredient", Some(list));
+        assert_eq!(
+            error.render(source),
+            indoc::indoc! {"
+                error: expected a single ingredient
+                 --> 5:1
+                  |
+                5 | - Lemons, 4
+                  | ^^^^^^^^^^^
+            "}
+        );
+        let error = MDError::new("no place", None);
+        assert_eq!(error.render(source), "error: no place\n");
+
+        // Columns count bytes, as in JSON, but carets are under the characters.
+        let accented = "- Crème, 2 bananas\n";
+        let accented_md = markdown::to_mdast(accented, &get_parse_options()).unwrap();
+        let item = &accented_md.children().unwrap()[0].children().unwrap()[0];
+        let text = &item.children().unwrap()[0].children().unwrap()[0];
+        let error = MDError::in_line("unknown unit \"bananas\"", 10..17).locate_in(text);
+        assert_eq!(error.to_json()["range"]["start"]["column"], 13);
+        assert_eq!(
+            error.render(accented),
+            indoc::indoc! {r#"
+                error: unknown unit "bananas"
+                 --> 1:13
+                  |
+                1 | - Crème, 2 bananas
+                  |            ^^^^^^^
+            "#}
+        );
+    }
+
+    #[test]
+    fn error_codes() -> MDResult<()> {
+        use crate::recipe::Recipe;
+
+        let code = |md: &str| Recipe::from_mdast(md).err().and_then(|e| e.code());
+        assert_eq!(code(""), Some(ErrorCode::EmptyFile));
+        assert_eq!(
+            code("# Toast\n## Instructions\n\n- Toast\n"),
+            Some(ErrorCode::MissingIngredientsSection)
+        );
+        assert_eq!(
+            code("# Toast\n## Ingredients\n\n- Bread\n"),
+            Some(ErrorCode::MissingInstructionsSection)
+        );
+        assert_eq!(
+            code("# Toast\n## Ingredients\n\n- Bread, two slices\n## Instructions\n\n- Toast\n"),
+            Some(ErrorCode::InvalidQuantity)
+        );
+
+        let source = indoc::indoc! {"
+            ---
+            servings: many
+            colour: brown
+            ---
+            # Toast
+            ## Ingredients
+
+            - Bread, 2 (sliced) thick
+            - Butter
+
+            ## Instructions
+
+            - Toast the *bread* for **a while**
+        "};
+        let mut ctx = ParseContext::new();
+        let _ = crate::recipe::Recipe::parse_with(source, &mut ctx);
+        let codes: Vec<_> = ctx.into_diagnostics().iter().map(|d| d.code()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                Some(ErrorCode::InvalidMetadata),
+                Some(ErrorCode::MalformedIngredient),
+                Some(ErrorCode::UnknownMetadataKey),
+                Some(ErrorCode::NotATimer),
+                // The bread line is dropped, so its reference is unresolved.
+                Some(ErrorCode::UnresolvedReference),
+            ]
+        );
+
+        let error = MDError::in_line("unknown unit \"bananas\"", 9..16)
+            .locate_in(&heading("- Bread, 2 bananas"))
+            .with_code(ErrorCode::InvalidQuantity)
+            .with_context("while parsing ingredients");
+        let diagnostic = Diagnostic::new(Severity::Warning, error);
+        assert!(diagnostic
+            .render("")
+            .starts_with("warning[E021_INVALID_QUANTITY]: unknown unit"));
+        assert_eq!(
+            diagnostic.to_json(),
+            json!({
+                "severity": "warning",
+                "code": "E021_INVALID_QUANTITY",
+                "message": "unknown unit \"bananas\"",
+                "range": null,
+                "span": { "start": 9, "end": 16 },
+                "context": ["while parsing ingredients"],
+            })
+        );
+        let json = Recipe::from_mdast("# Toast\n").unwrap_err().to_json();
+        assert_eq!(json["code"], "E001_MISSING_INGREDIENTS_SECTION");
+        assert_eq!(json["range"], Value::Null);
+
+        assert_eq!(ErrorCode::from_str("E040")?, ErrorCode::UnresolvedReference);
+        for code in ErrorCode::ALL {
+            assert_eq!(ErrorCode::from_str(code.as_str())?, code);
+            let number = &code.as_str()[..4];
+            let same = ErrorCode::ALL
+                .iter()
+                .filter(|c| c.as_str().starts_with(number));
+            assert_eq!(same.count(), 1, "{}", code);
+        }
+        assert!(ErrorCode::from_str("E999").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn error_sources() {
+        use crate::recipe::Recipe;
+
+        fn is_error<E: Error + Send + Sync + 'static>(_: &E) {}
+        let body = "# Toast\n## Ingredients\n\n- Bread\n\n## Instructions\n\n- Toast\n";
+
+        let error = MDError::from("1,5".parse::<f64>().unwrap_err());
+        is_error(&error);
+        assert!(error.source().unwrap().is::<ParseFloatError>());
+
+        let error = Recipe::from_mdast(&format!("---\ntags: [toast\n---\n{}", body)).unwrap_err();
+        assert!(error.source().unwrap().is::<saphyr::ScanError>());
+
+        // Sources chain down to the lowest-level error.
+        let error =
+            Recipe::from_mdast(&format!("---\nprep_time: 1..5 min\n---\n{}", body)).unwrap_err();
+        let quantity = error.source().unwrap();
+        assert!(quantity.is::<crate::recipe::unit::ParseQuantityOfError>());
+        assert!(quantity.source().unwrap().is::<ParseFloatError>());
+
+        let error = Recipe::from_mdast(&body.replace("- Toast", "- Toast {1x: a")).unwrap_err();
+        let message = error.source().unwrap().downcast_ref::<MarkdownMessage>();
+        assert!(message.is_some());
+        assert_eq!(error.code(), Some(ErrorCode::MarkdownSyntax));
+
+        assert!(MDError::new("no source", None).source().is_none());
+    }
+
+    #[test]
+    fn wikilinks() {
+        let link = |target, alias| WikiLink { target, alias };
+        assert_eq!(
+            find_wikilink("Knead the [[Pizza dough|dough]] well"),
+            Some(("Knead the ", link("Pizza dough", Some("dough")), " well"))
+        );
+        assert_eq!(
+            find_wikilink("[[[Sauce]]]"),
+            Some(("[", link("Sauce", None), "]"))
+        );
+        for text in ["[[]]", "[[|alias]]", "[[Sauce|]]", "[[Sauce]", "[Sauce]"] {
+            assert_eq!(find_wikilink(text), None, "{:?}", text);
+        }
+    }
+}
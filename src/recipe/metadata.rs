@@ -1,69 +1,529 @@
 use super::unit::Unit;
 use crate::recipe::{
-    md_parser::{MDError, MDResult},
-    unit::{Distance, Nominal, Quantity, QuantityOf},
+    amount::Amount,
+    canning::{self, Canning},
+    coffee::{self, BrewRatio},
+    density::{self, DensityTable},
+    equipment::Tool,
+    history,
+    md_parser::{ErrorCode, MDError, MDResult, ParseContext},
+    rating::{self, Difficulty, Rating},
+    scaling::{self, ScalePreset},
+    source::{self, Url},
+    unit::{Distance, Mass, Nominal, Quantity, QuantityOf, Time},
+    Image,
 };
-use markdown::mdast::Yaml;
-use saphyr::LoadableYamlNode;
-use std::{collections::HashMap, str::FromStr};
+use chrono::NaiveDate;
+use markdown::{mdast::Yaml, unist::Position};
+use saphyr::{LoadableYamlNode, Scalar};
+use std::{borrow::Cow, collections::HashMap, fmt, ops::Add, str::FromStr};
 
+#[derive(Clone, PartialEq, Debug, Default)]
 pub struct Metadata {
-    tags: Vec<String>,
-    quantity: Quantity,
-    sizes: HashMap<String, SizeInfo>,
-    others: HashMap<String, String>,
+    pub(super) tags: Vec<String>,
+    /// Other names of the recipe (e.g., "Bolognese" for "Ragù alla bolognese").
+    pub(super) aliases: Vec<String>,
+    pub(super) category: Option<Category>,
+    /// How many people the recipe serves (e.g., "4" or "4-6").
+    pub(super) servings: Option<QuantityOf<Nominal>>,
+    /// How much the recipe makes (e.g., "1.2 L" or "12 cookies").
+    pub(super) yield_quantity: Option<Quantity>,
+    pub(super) prep_time: Option<QuantityOf<Time>>,
+    pub(super) cook_time: Option<QuantityOf<Time>>,
+    pub(super) total_time: Option<QuantityOf<Time>>,
+    pub(super) sizes: HashMap<String, SizeInfo>,
+    /// Glass a drink is served in, whose size may refer to one of the sizes.
+    glass: Option<Tool>,
+    /// Nutrition facts for the whole recipe.
+    pub(super) nutrition: Option<Nutrition>,
+    /// Processing times for canned and preserved recipes.
+    canning: Option<Canning>,
+    /// Brew ratio of a coffee recipe.
+    ratio: Option<BrewRatio>,
+    /// Scale factors suggested by the author.
+    scales: Vec<ScalePreset>,
+    /// Densities of the recipe's ingredients, overriding other densities.
+    densities: DensityTable,
+    /// Cover image, without alternative text.
+    pub(super) image: Option<Image>,
+    /// Where the recipe comes from (e.g., a book), other than its URL.
+    pub(super) source: Option<String>,
+    pub(super) author: Option<String>,
+    pub(super) url: Option<Url>,
+    pub(super) rating: Option<Rating>,
+    pub(super) difficulty: Option<Difficulty>,
+    pub(super) created: Option<NaiveDate>,
+    /// Last date the recipe was cooked, as written (see [`Metadata::last_cooked`]).
+    pub(super) last_cooked: Option<NaiveDate>,
+    /// Dates the recipe was cooked, in chronological order.
+    pub(super) cook_log: Vec<NaiveDate>,
+    others: HashMap<String, MetaValue>,
 }
 
 const TAGS: &str = "tags";
+const ALIASES: &str = "aliases";
+const SERVINGS: &str = "servings";
+const YIELD: &str = "yield";
+/// Former key for either servings or yield, depending on its unit.
 const QUANTITY: &str = "quantity";
-const SIZE_PREFIX: &str = "size | ";
+pub(super) const SIZE_PREFIX: &str = "size | ";
+const NUTRITION: &str = "nutrition";
+pub(super) const PREP_TIME: &str = "prep_time";
+pub(super) const COOK_TIME: &str = "cook_time";
+pub(super) const TOTAL_TIME: &str = "total_time";
+const CATEGORY: &str = "category";
+const GLASS: &str = "glass";
+const CANNING: &str = canning::CANNING;
+const RATIO: &str = coffee::RATIO;
+const SCALES: &str = scaling::SCALES;
+const IMAGE: &str = "image";
+const DENSITIES: &str = density::DENSITIES;
+const SOURCE: &str = source::SOURCE;
+const AUTHOR: &str = source::AUTHOR;
+const URL: &str = source::URL;
+const RATING: &str = rating::RATING;
+const DIFFICULTY: &str = rating::DIFFICULTY;
+const CREATED: &str = history::CREATED;
+const LAST_COOKED: &str = history::LAST_COOKED;
+const COOK_LOG: &str = history::COOK_LOG;
+const KEYS: [&str; 24] = [
+    TAGS,
+    ALIASES,
+    CATEGORY,
+    SERVINGS,
+    YIELD,
+    QUANTITY,
+    NUTRITION,
+    PREP_TIME,
+    COOK_TIME,
+    TOTAL_TIME,
+    GLASS,
+    CANNING,
+    RATIO,
+    SCALES,
+    IMAGE,
+    DENSITIES,
+    SOURCE,
+    AUTHOR,
+    URL,
+    RATING,
+    DIFFICULTY,
+    CREATED,
+    LAST_COOKED,
+    COOK_LOG,
+];
 
 impl Metadata {
-    pub fn parse(yaml: &Yaml) -> MDResult<Self> {
-        let metadata =
-            saphyr::Yaml::load_from_str(&yaml.value).map_err(|e| MDError::new(e.info(), None))?;
+    #[cfg(test)]
+    pub(crate) fn parse(yaml: &Yaml) -> MDResult<Self> {
+        let mut ctx = ParseContext::new();
+        let result = Self::parse_with(yaml, &mut ctx);
+        ctx.finish(result)
+    }
+
+    /// Parses the frontmatter, recording errors in individual entries in `ctx` rather than
+    /// failing. Errors in an entry are placed on the line of its key, and other errors on the
+    /// whole frontmatter.
+    pub(crate) fn parse_with(yaml: &Yaml, ctx: &mut ParseContext) -> MDResult<Self> {
+        ctx.placed(yaml.position.as_ref(), |ctx| Self::parse_entries(yaml, ctx))
+    }
+
+    fn parse_entries(yaml: &Yaml, ctx: &mut ParseContext) -> MDResult<Self> {
+        let invalid = |e: MDError| e.with_code(ErrorCode::InvalidFrontmatter);
+        let metadata = saphyr::Yaml::load_from_str(&yaml.value)
+            .map_err(|e| invalid(MDError::new(e.info(), None).with_source(e)))?;
         let mapping = (metadata.len() == 1)
             .then(|| &metadata[0])
             .ok_or(MDError::new(
                 "expected single YAML document in frontmatter",
                 None,
-            ))?
+            ))
+            .map_err(invalid)?
             .as_mapping()
             .ok_or(MDError::new(
                 "expected top-level element to be mapping",
                 None,
-            ))?;
+            ))
+            .map_err(invalid)?;
 
         let mut this = Self::default();
+        // Parsed last, as it may refer to sizes defined after it.
+        let mut glass = None;
+        // A URL as the source, unless there is a URL of its own.
+        let mut source_url = None;
 
         for (key, value) in mapping {
-            let key: &str = key
-                .as_str()
-                .ok_or(MDError::new("expected string key", None))?;
-            match key {
-                TAGS => Self::parse_tags(value, &mut this.tags)?,
-                QUANTITY => Self::parse_quantity(value, &mut this.quantity)?,
-                _ => {
-                    if key.starts_with(SIZE_PREFIX) {
-                        Self::parse_size(&key[SIZE_PREFIX.len()..], value, &mut this.sizes)?;
-                    } else {
-                        Self::parse_others(&key, value, &mut this.others)?;
-                    }
+            let position = key.as_str().and_then(|key| key_position(yaml, key));
+            let entry = ctx.placed(position.as_ref(), |ctx| {
+                key.as_str()
+                    .ok_or(MDError::new("expected string key", None))
+                    .map(|key| ctx.metadata_key_aliases().canonical(key))
+                    .and_then(|key| match &*key {
+                        TAGS => Self::parse_tags(value, ctx.tag_prefix_mode(), &mut this.tags).map(
+                            |_| {
+                                if ctx.tag_normalizer().is_some() {
+                                    this.tags = Self::normalize_tags(&this.tags, ctx);
+                                    return;
+                                }
+                                for (i, tag) in this.tags.iter().enumerate() {
+                                    if this.tags[..i].contains(tag) {
+                                        ctx.warn(
+                                            MDError::new(&format!("duplicate tag {:?}", tag), None)
+                                                .with_code(ErrorCode::DuplicateTag),
+                                        );
+                                    }
+                                }
+                            },
+                        ),
+                        ALIASES => Self::parse_aliases(value).map(|aliases| this.aliases = aliases),
+                        CATEGORY => Self::scalar_str(value, CATEGORY)
+                            .and_then(|category| Category::from_str(&category))
+                            .map(|category| this.category = Some(category)),
+                        SERVINGS => Self::parse_servings(value, &mut this.servings),
+                        YIELD => Self::parse_yield(value, &mut this.yield_quantity),
+                        QUANTITY => {
+                            ctx.hint(
+                                MDError::new(
+                                    &format!(
+                                        "{:?} is deprecated, use {:?} or {:?}",
+                                        key, SERVINGS, YIELD
+                                    ),
+                                    None,
+                                )
+                                .with_code(ErrorCode::DeprecatedMetadataKey),
+                            );
+                            Self::parse_quantity(
+                                value,
+                                &mut this.servings,
+                                &mut this.yield_quantity,
+                            )
+                        }
+                        NUTRITION => Nutrition::parse(value, ctx).map(|n| this.nutrition = Some(n)),
+                        PREP_TIME => Self::parse_time(value, &key, &mut this.prep_time),
+                        COOK_TIME => Self::parse_time(value, &key, &mut this.cook_time),
+                        TOTAL_TIME => Self::parse_time(value, &key, &mut this.total_time),
+                        GLASS => Self::scalar_str(value, GLASS)
+                            .map(|g| glass = Some((g, position.clone()))),
+                        CANNING => Canning::parse(value).map(|c| {
+                            for warning in c.warnings() {
+                                ctx.warn(warning.with_code(ErrorCode::InconsistentMetadata));
+                            }
+                            this.canning = Some(c);
+                        }),
+                        RATIO => Self::scalar_str(value, RATIO)
+                            .and_then(|ratio| BrewRatio::from_str(&ratio))
+                            .map(|ratio| this.ratio = Some(ratio)),
+                        SCALES => ScalePreset::parse_all(value).map(|scales| this.scales = scales),
+                        DENSITIES => DensityTable::parse(value).map(|d| this.densities = d),
+                        SOURCE => {
+                            Self::scalar_str(value, SOURCE).map(|source| {
+                                match Url::from_str(&source) {
+                                    Ok(url) => source_url = Some(url),
+                                    Err(_) => this.source = Some(source.trim().to_string()),
+                                }
+                            })
+                        }
+                        AUTHOR => Self::scalar_str(value, AUTHOR)
+                            .map(|author| this.author = Some(author.trim().to_string())),
+                        URL => Self::scalar_str(value, URL)
+                            .and_then(|url| Url::from_str(&url))
+                            .map(|url| this.url = Some(url)),
+                        RATING => Self::scalar_str(value, RATING)
+                            .and_then(|rating| Rating::from_str(&rating))
+                            .map(|rating| this.rating = Some(rating)),
+                        DIFFICULTY => Self::scalar_str(value, DIFFICULTY)
+                            .and_then(|difficulty| Difficulty::from_str(&difficulty))
+                            .map(|difficulty| this.difficulty = Some(difficulty)),
+                        CREATED => {
+                            history::parse_date(value, CREATED).map(|d| this.created = Some(d))
+                        }
+                        LAST_COOKED => history::parse_date(value, LAST_COOKED)
+                            .map(|d| this.last_cooked = Some(d)),
+                        COOK_LOG => history::parse_log(value).map(|log| this.cook_log = log),
+                        IMAGE => Self::scalar_str(value, IMAGE).map(|url| {
+                            this.image = Some(Image {
+                                url: url.to_string(),
+                                alt: String::new(),
+                            })
+                        }),
+                        _ => {
+                            if let Some(name) = key.strip_prefix(SIZE_PREFIX) {
+                                Self::parse_size(name, value, &mut this.sizes)
+                            } else {
+                                ctx.warn(
+                                    MDError::new(&format!("unknown metadata key {:?}", key), None)
+                                        .with_code(ErrorCode::UnknownMetadataKey),
+                                );
+                                Self::parse_others(&key, value, &mut this.others)
+                            }
+                        }
+                    })
+            });
+            ctx.recover(entry.map_err(|e| e.or_code(ErrorCode::InvalidMetadata)));
+        }
+        if this.url.is_none() {
+            this.url = source_url;
+        }
+        if let Some((glass, position)) = glass {
+            let glass = Tool::from_str(&glass, &this.sizes).map_err(|e| {
+                e.or_code(ErrorCode::InvalidMetadata)
+                    .or_position(position.as_ref())
+            });
+            this.glass = ctx.recover(glass);
+        }
+        for preset in &this.scales {
+            for name in preset.sizes().keys() {
+                if !this.sizes.contains_key(name) {
+                    ctx.warn(
+                        MDError::new(
+                            &format!(
+                                "scale preset {} overrides unknown size {:?}",
+                                preset.factor(),
+                                name
+                            ),
+                            None,
+                        )
+                        .with_code(ErrorCode::InconsistentMetadata),
+                    );
                 }
             }
         }
+        if this.canning.is_none() && Canning::is_tagged(&this.tags) {
+            ctx.warn(
+                MDError::new(
+                    &format!(
+                        "recipe tagged for canning has no {:?} processing information",
+                        CANNING
+                    ),
+                    None,
+                )
+                .with_code(ErrorCode::InconsistentMetadata),
+            );
+        }
 
         Ok(this)
     }
 
-    fn get_tag(tag: &str) -> MDResult<&str> {
-        if !tag.starts_with("#") {
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    pub fn category(&self) -> Option<Category> {
+        self.category
+    }
+
+    pub fn servings(&self) -> Option<&QuantityOf<Nominal>> {
+        self.servings.as_ref()
+    }
+
+    pub fn yield_quantity(&self) -> Option<&Quantity> {
+        self.yield_quantity.as_ref()
+    }
+
+    pub fn prep_time(&self) -> Option<&QuantityOf<Time>> {
+        self.prep_time.as_ref()
+    }
+
+    pub fn cook_time(&self) -> Option<&QuantityOf<Time>> {
+        self.cook_time.as_ref()
+    }
+
+    pub fn total_time(&self) -> Option<&QuantityOf<Time>> {
+        self.total_time.as_ref()
+    }
+
+    pub fn sizes(&self) -> &HashMap<String, SizeInfo> {
+        &self.sizes
+    }
+
+    pub fn glass(&self) -> Option<&Tool> {
+        self.glass.as_ref()
+    }
+
+    pub fn image(&self) -> Option<&Image> {
+        self.image.as_ref()
+    }
+
+    /// Where the recipe comes from (e.g., "Salt, Fat, Acid, Heat"), other than its URL.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn url(&self) -> Option<&Url> {
+        self.url.as_ref()
+    }
+
+    pub fn rating(&self) -> Option<Rating> {
+        self.rating
+    }
+
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        self.difficulty
+    }
+
+    pub fn created(&self) -> Option<NaiveDate> {
+        self.created
+    }
+
+    /// Last date the recipe was cooked, from either its last cooked date or its cook log.
+    pub fn last_cooked(&self) -> Option<NaiveDate> {
+        self.last_cooked.max(self.cook_log.last().copied())
+    }
+
+    /// Last cooked date as written in the frontmatter, to write it back.
+    pub(crate) fn last_cooked_entry(&self) -> Option<NaiveDate> {
+        self.last_cooked
+    }
+
+    /// Dates the recipe was cooked, in chronological order.
+    pub fn cook_log(&self) -> &[NaiveDate] {
+        &self.cook_log
+    }
+
+    pub fn nutrition(&self) -> Option<&Nutrition> {
+        self.nutrition.as_ref()
+    }
+
+    /// Returns the nutrition facts for a single serving, or `None` if either the nutrition facts
+    /// or the number of servings is missing. For a range of servings, the lower bound is used.
+    pub fn nutrition_per_serving(&self) -> Option<Nutrition> {
+        let servings = self.servings.as_ref()?.amount;
+        if servings.is_zero() {
+            return None;
+        }
+        Some(
+            self.nutrition
+                .as_ref()?
+                .scale(Amount::from_int(1) / servings),
+        )
+    }
+
+    pub fn canning(&self) -> Option<&Canning> {
+        self.canning.as_ref()
+    }
+
+    pub fn ratio(&self) -> Option<&BrewRatio> {
+        self.ratio.as_ref()
+    }
+
+    pub fn scales(&self) -> &[ScalePreset] {
+        &self.scales
+    }
+
+    pub fn densities(&self) -> &DensityTable {
+        &self.densities
+    }
+
+    pub fn others(&self) -> &HashMap<String, MetaValue> {
+        &self.others
+    }
+
+    /// Value of a frontmatter entry without dedicated support, by its key as written.
+    pub fn other(&self, key: &str) -> Option<&MetaValue> {
+        self.others.get(key)
+    }
+
+    /// Adds a tag, with or without its leading '#', unless the recipe already has it.
+    pub fn add_tag(&mut self, tag: &str) -> MDResult<()> {
+        let tag = Self::get_tag(tag.trim(), TagPrefix::Optional)?;
+        if !self.tags.iter().any(|t| t == tag) {
+            self.tags.push(tag.to_string());
+        }
+        Ok(())
+    }
+
+    /// Removes a tag, with or without its leading '#'. Returns whether the recipe had it.
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let tag = tag.trim();
+        let tag = tag.strip_prefix('#').unwrap_or(tag);
+        let len = self.tags.len();
+        self.tags.retain(|t| t != tag);
+        self.tags.len() != len
+    }
+
+    pub fn set_category(&mut self, category: Option<Category>) {
+        self.category = category;
+    }
+
+    pub fn set_servings(&mut self, servings: Option<QuantityOf<Nominal>>) {
+        self.servings = servings;
+    }
+
+    pub fn set_yield(&mut self, quantity: Option<Quantity>) {
+        self.yield_quantity = quantity;
+    }
+
+    pub fn set_prep_time(&mut self, time: Option<QuantityOf<Time>>) {
+        self.prep_time = time;
+    }
+
+    pub fn set_cook_time(&mut self, time: Option<QuantityOf<Time>>) {
+        self.cook_time = time;
+    }
+
+    pub fn set_total_time(&mut self, time: Option<QuantityOf<Time>>) {
+        self.total_time = time;
+    }
+
+    pub fn set_source(&mut self, source: Option<&str>) {
+        self.source = source.map(|source| source.trim().to_string());
+    }
+
+    pub fn set_author(&mut self, author: Option<&str>) {
+        self.author = author.map(|author| author.trim().to_string());
+    }
+
+    pub fn set_url(&mut self, url: Option<Url>) {
+        self.url = url;
+    }
+
+    pub fn set_rating(&mut self, rating: Option<Rating>) {
+        self.rating = rating;
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Option<Difficulty>) {
+        self.difficulty = difficulty;
+    }
+
+    pub fn set_created(&mut self, date: Option<NaiveDate>) {
+        self.created = date;
+    }
+
+    /// Sets a frontmatter entry without dedicated support. Keys with dedicated support (e.g.,
+    /// "servings" or its aliases) are refused, as they have setters of their own.
+    pub fn set_other(&mut self, key: &str, value: MetaValue) -> MDResult<()> {
+        let canonical = KeyAliases::default().canonical(key);
+        if KEYS.contains(&&*canonical) || canonical.starts_with(SIZE_PREFIX) {
             return Err(MDError::new(
-                &format!("tag must start with '#' character"),
+                &format!("{:?} is not an arbitrary metadata key", key),
                 None,
             ));
         }
-        let no_hash = &tag["#".len()..];
+        self.others.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    /// Removes a frontmatter entry without dedicated support, returning its value.
+    pub fn remove_other(&mut self, key: &str) -> Option<MetaValue> {
+        self.others.remove(key)
+    }
+
+    fn get_tag(tag: &str, prefix: TagPrefix) -> MDResult<&str> {
+        let no_hash = match (tag.strip_prefix('#'), prefix) {
+            (Some(no_hash), _) => no_hash,
+            (None, TagPrefix::Optional) => tag,
+            (None, TagPrefix::Required) => {
+                return Err(MDError::new("tag must start with '#' character", None))
+            }
+        };
         if no_hash
             .chars()
             .any(|c| !c.is_alphanumeric() && c != '/' && c != '-' && c != '_')
@@ -76,7 +536,11 @@ impl Metadata {
         Ok(no_hash)
     }
 
-    fn parse_tags(value: &saphyr::Yaml<'_>, tags: &mut Vec<String>) -> MDResult<()> {
+    fn parse_tags(
+        value: &saphyr::Yaml<'_>,
+        prefix: TagPrefix,
+        tags: &mut Vec<String>,
+    ) -> MDResult<()> {
         let value = value.as_sequence().ok_or(MDError::new(
             &format!("expected sequence under {:?}", TAGS),
             None,
@@ -85,21 +549,123 @@ impl Metadata {
             let s_tag = tag
                 .as_str()
                 .ok_or(MDError::new("expected string tag", None))?;
-            Self::get_tag(s_tag).inspect(|t| tags.push(t.to_string()))?;
+            Self::get_tag(s_tag, prefix).inspect(|t| tags.push(t.to_string()))?;
         }
         Ok(())
     }
 
-    fn parse_quantity(value: &saphyr::Yaml<'_>, quantity: &mut Quantity) -> MDResult<()> {
-        let value = value.as_str().ok_or(MDError::new(
-            &format!("expected string under {:?}", QUANTITY),
-            None,
-        ))?;
-        *quantity = Quantity::from_str(value)?;
+    /// Normalizes tags as the context says, dropping and warning about duplicates.
+    fn normalize_tags(tags: &[String], ctx: &mut ParseContext) -> Vec<String> {
+        let Some(normalization) = ctx.tag_normalizer() else {
+            return tags.to_vec();
+        };
+        let normalized: Vec<String> = tags.iter().map(|t| normalization.normalize(t)).collect();
+        let mut unique: Vec<String> = vec![];
+        for (tag, normal) in tags.iter().zip(normalized) {
+            if !unique.contains(&normal) {
+                unique.push(normal);
+            } else if *tag == normal {
+                ctx.warn(
+                    MDError::new(&format!("duplicate tag {:?}", tag), None)
+                        .with_code(ErrorCode::DuplicateTag),
+                );
+            } else {
+                ctx.warn(
+                    MDError::new(
+                        &format!("duplicate tag {:?} (normalized to {:?})", tag, normal),
+                        None,
+                    )
+                    .with_code(ErrorCode::DuplicateTag),
+                );
+            }
+        }
+        unique
+    }
+
+    /// Parses a list of aliases, or a single one.
+    fn parse_aliases(value: &saphyr::Yaml<'_>) -> MDResult<Vec<String>> {
+        let aliases = match value.as_sequence() {
+            Some(aliases) => aliases
+                .iter()
+                .map(|alias| Self::scalar_str(alias, ALIASES))
+                .collect::<MDResult<Vec<_>>>()?,
+            None => vec![Self::scalar_str(value, ALIASES)?],
+        };
+        Ok(aliases
+            .into_iter()
+            .map(|alias| alias.trim().to_string())
+            .filter(|alias| !alias.is_empty())
+            .collect())
+    }
+
+    /// Returns scalar values as strings, since YAML parses e.g. `servings: 4` as an integer.
+    pub(super) fn scalar_str<'a>(value: &'a saphyr::Yaml<'_>, key: &str) -> MDResult<Cow<'a, str>> {
+        if let Some(s) = value.as_str() {
+            Ok(Cow::Borrowed(s))
+        } else if let Some(i) = value.as_integer() {
+            Ok(Cow::Owned(i.to_string()))
+        } else if let Some(f) = value.as_floating_point() {
+            Ok(Cow::Owned(f.to_string()))
+        } else {
+            Err(MDError::new(
+                &format!("expected string under {:?}", key),
+                None,
+            ))
+        }
+    }
+
+    fn parse_servings(
+        value: &saphyr::Yaml<'_>,
+        servings: &mut Option<QuantityOf<Nominal>>,
+    ) -> MDResult<()> {
+        let value = Self::scalar_str(value, SERVINGS)?;
+        *servings = Some(QuantityOf::from_str(&value).map_err(|e| {
+            MDError::new(&format!("failed to parse servings: {}", e), None).with_source(e)
+        })?);
+        Ok(())
+    }
+
+    fn parse_yield(
+        value: &saphyr::Yaml<'_>,
+        yield_quantity: &mut Option<Quantity>,
+    ) -> MDResult<()> {
+        *yield_quantity = Some(Quantity::from_str(&Self::scalar_str(value, YIELD)?)?);
+        Ok(())
+    }
+
+    /// Parses the legacy quantity key, as servings for nominal quantities and as yield otherwise.
+    fn parse_quantity(
+        value: &saphyr::Yaml<'_>,
+        servings: &mut Option<QuantityOf<Nominal>>,
+        yield_quantity: &mut Option<Quantity>,
+    ) -> MDResult<()> {
+        let quantity = Quantity::from_str(&Self::scalar_str(value, QUANTITY)?)?;
+        match (&quantity.unit, quantity.approximation) {
+            (Unit::Nominal(unit), None) => {
+                *servings = Some(QuantityOf {
+                    unit: *unit,
+                    amount: quantity.amount,
+                    max_amount: quantity.max_amount,
+                })
+            }
+            _ => *yield_quantity = Some(quantity),
+        }
         Ok(())
     }
 
-    fn parse_size(
+    pub(super) fn parse_time(
+        value: &saphyr::Yaml<'_>,
+        key: &str,
+        time: &mut Option<QuantityOf<Time>>,
+    ) -> MDResult<()> {
+        let value = Self::scalar_str(value, key)?;
+        *time = Some(QuantityOf::from_str(&value).map_err(|e| {
+            MDError::new(&format!("failed to parse {}: {}", key, e), None).with_source(e)
+        })?);
+        Ok(())
+    }
+
+    pub(super) fn parse_size(
         key: &str,
         value: &saphyr::Yaml<'_>,
         sizes: &mut HashMap<String, SizeInfo>,
@@ -118,13 +684,11 @@ impl Metadata {
     fn parse_others(
         key: &str,
         value: &saphyr::Yaml<'_>,
-        others: &mut HashMap<String, String>,
+        others: &mut HashMap<String, MetaValue>,
     ) -> MDResult<()> {
-        let value = value.as_str().ok_or(MDError::new(
-            "for unknown keys, only string values are supported",
-            None,
-        ))?;
-        if let Some(_) = others.insert(key.to_string(), value.to_string()) {
+        let value = MetaValue::from_yaml(value)
+            .map_err(|e| MDError::new(&format!("under {:?}: {}", key, e.message()), None))?;
+        if others.insert(key.to_string(), value).is_some() {
             return Err(MDError::new(
                 &format!("duplicate metadata key {:?}", key),
                 None,
@@ -134,23 +698,480 @@ impl Metadata {
     }
 }
 
-impl Default for Metadata {
+/// Position of the line defining a top-level key of the frontmatter (e.g., "created: 2024-13-45"),
+/// or `None` if the frontmatter was not read from a document or the key is not found.
+fn key_position(yaml: &Yaml, key: &str) -> Option<Position> {
+    let start = &yaml.position.as_ref()?.start;
+    // The content starts on the line after the opening "---".
+    let mut offset = start.offset + "---\n".len();
+    for (index, line) in yaml.value.split('\n').enumerate() {
+        let unquoted = line.strip_prefix(['"', '\'']).unwrap_or(line);
+        let defines_key = unquoted
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start_matches(['"', '\'', ' ']).starts_with(':'));
+        if defines_key {
+            let (number, len) = (start.line + 1 + index, line.trim_end().len());
+            return Some(Position::new(
+                number,
+                1,
+                offset,
+                number,
+                len + 1,
+                offset + len,
+            ));
+        }
+        offset += line.len() + 1;
+    }
+    None
+}
+
+/// Course of a meal a recipe belongs to, from a fixed vocabulary (unlike tags).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Category {
+    Breakfast,
+    Starter,
+    Main,
+    Side,
+    Dessert,
+    Snack,
+    Drink,
+}
+
+impl Category {
+    pub const ALL: [Category; 7] = [
+        Self::Breakfast,
+        Self::Starter,
+        Self::Main,
+        Self::Side,
+        Self::Dessert,
+        Self::Snack,
+        Self::Drink,
+    ];
+}
+
+impl FromStr for Category {
+    type Err = MDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.trim().to_lowercase()[..] {
+            "breakfast" | "brunch" => Ok(Self::Breakfast),
+            "starter" | "appetizer" => Ok(Self::Starter),
+            "main" | "main course" => Ok(Self::Main),
+            "side" | "side dish" => Ok(Self::Side),
+            "dessert" => Ok(Self::Dessert),
+            "snack" => Ok(Self::Snack),
+            "drink" | "beverage" => Ok(Self::Drink),
+            _ => Err(MDError::new(
+                &format!(
+                    "unknown category {:?}, expected one of: {}",
+                    s,
+                    Self::ALL.map(|c| c.to_string()).join(", ")
+                ),
+                None,
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Breakfast => "breakfast",
+            Self::Starter => "starter",
+            Self::Main => "main",
+            Self::Side => "side",
+            Self::Dessert => "dessert",
+            Self::Snack => "snack",
+            Self::Drink => "drink",
+        })
+    }
+}
+
+/// Whether tags must be written with a leading '#' (e.g., "#dessert"). Obsidian's properties UI
+/// writes tags without it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TagPrefix {
+    #[default]
+    Required,
+    /// Accepts both "#tag" and "tag".
+    Optional,
+}
+
+/// Normalization of frontmatter tags, which parsing opts into with
+/// [`RecipeParseOptions::tag_normalization`](super::RecipeParseOptions::tag_normalization). Tags
+/// are lowercased, aliases are replaced by their tag (e.g., "veggie" by "vegetarian"), and
+/// duplicates are dropped with a warning (e.g., "Vegan" after "vegan").
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagNormalization {
+    aliases: HashMap<String, String>,
+}
+
+impl TagNormalization {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an alias for a tag, both with or without their leading '#'.
+    pub fn alias(mut self, alias: &str, tag: &str) -> Self {
+        let strip = |t: &str| t.trim().trim_start_matches('#').to_lowercase();
+        self.aliases.insert(strip(alias), strip(tag));
+        self
+    }
+
+    /// Normal form of a tag, without its leading '#'.
+    pub fn normalize(&self, tag: &str) -> String {
+        let tag = tag.to_lowercase();
+        self.aliases.get(&tag).cloned().unwrap_or(tag)
+    }
+}
+
+/// Alternative spellings of the frontmatter keys, as written by other tools or people. Keys are
+/// matched case-insensitively (e.g., "Tags" or "TAGS"), and stored under their canonical name.
+#[derive(Clone, Debug)]
+pub struct KeyAliases {
+    aliases: HashMap<String, String>,
+}
+
+impl Default for KeyAliases {
     fn default() -> Self {
+        Self::empty()
+            .alias("tag", TAGS)
+            .alias("alias", ALIASES)
+            .alias("course", CATEGORY)
+            .alias("serving", SERVINGS)
+            .alias("serves", SERVINGS)
+            .alias("makes", YIELD)
+            .alias("prep", PREP_TIME)
+            .alias("cook", COOK_TIME)
+            .alias("time", TOTAL_TIME)
+    }
+}
+
+impl KeyAliases {
+    /// Aliases without any of the default ones, which only ignore casing.
+    pub fn empty() -> Self {
         Self {
-            tags: vec![],
-            quantity: Quantity::new(&Unit::Nominal(Nominal {}), 1.),
-            sizes: HashMap::new(),
-            others: HashMap::new(),
+            aliases: HashMap::new(),
+        }
+    }
+
+    /// Adds an alias for a canonical key.
+    pub fn alias(mut self, alias: &str, canonical: &str) -> Self {
+        self.aliases
+            .insert(alias.to_lowercase(), canonical.to_string());
+        self
+    }
+
+    /// Returns the canonical name of a key, or the key itself if it isn't a known one.
+    pub fn canonical<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        let lower = key.to_lowercase();
+        if let Some(canonical) = self.aliases.get(&lower) {
+            Cow::Owned(canonical.clone())
+        } else if KEYS.contains(&&lower[..]) {
+            Cow::Owned(lower)
+        } else if lower.starts_with(SIZE_PREFIX) && !key.starts_with(SIZE_PREFIX) {
+            // Only the prefix is case-insensitive, not the name of the size.
+            Cow::Owned(format!("{}{}", SIZE_PREFIX, &key[SIZE_PREFIX.len()..]))
+        } else {
+            Cow::Borrowed(key)
         }
     }
 }
 
+pub(super) const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Value of a frontmatter entry without dedicated support (e.g., an Obsidian property). Strings
+/// formatted as dates (`YYYY-MM-DD`) are parsed as such. Mappings keep their entries in document
+/// order.
+#[derive(Clone, PartialEq, Debug)]
+pub enum MetaValue {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Date(NaiveDate),
+    List(Vec<MetaValue>),
+    Map(Vec<(String, MetaValue)>),
+}
+
+impl MetaValue {
+    fn from_yaml(value: &saphyr::Yaml<'_>) -> MDResult<Self> {
+        match value {
+            saphyr::Yaml::Value(scalar) => Ok(match scalar {
+                Scalar::Null => Self::Null,
+                Scalar::Boolean(b) => Self::Bool(*b),
+                Scalar::Integer(i) => Self::Integer(*i),
+                Scalar::FloatingPoint(f) => Self::Float(f.into_inner()),
+                Scalar::String(s) => match NaiveDate::parse_from_str(s, DATE_FORMAT) {
+                    Ok(date) => Self::Date(date),
+                    Err(_) => Self::String(s.to_string()),
+                },
+            }),
+            saphyr::Yaml::Sequence(seq) => Ok(Self::List(
+                seq.iter().map(Self::from_yaml).collect::<MDResult<_>>()?,
+            )),
+            saphyr::Yaml::Mapping(map) => Ok(Self::Map(
+                map.iter()
+                    .map(|(key, value)| {
+                        let key = match Self::from_yaml(key)? {
+                            Self::List(_) | Self::Map(_) => {
+                                return Err(MDError::new("mapping keys must be scalars", None))
+                            }
+                            key => key.to_string(),
+                        };
+                        let value = Self::from_yaml(value).map_err(|e| {
+                            MDError::new(&format!("under {:?}: {}", key, e.message()), None)
+                        })?;
+                        Ok((key, value))
+                    })
+                    .collect::<MDResult<_>>()?,
+            )),
+            saphyr::Yaml::Tagged(_, value) => Self::from_yaml(value),
+            _ => Err(MDError::new("unsupported value", None)),
+        }
+    }
+
+    /// Returns the value under `key` in a mapping.
+    pub fn get(&self, key: &str) -> Option<&MetaValue> {
+        match self {
+            Self::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Self::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns integers and floats as a number.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Integer(i) => Some(*i as f64),
+            Self::Float(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub fn as_date(&self) -> Option<NaiveDate> {
+        match self {
+            Self::Date(date) => Some(*date),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[MetaValue]> {
+        match self {
+            Self::List(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MetaValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => Ok(()),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Integer(i) => write!(f, "{}", i),
+            Self::Float(x) => write!(f, "{}", x),
+            Self::String(s) => write!(f, "{}", s),
+            Self::Date(date) => write!(f, "{}", date.format(DATE_FORMAT)),
+            Self::List(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                Ok(())
+            }
+            Self::Map(entries) => {
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Nutrition facts, from a mapping such as `nutrition: {calories: 1200, protein: 48 g}`.
+/// Macronutrients without a unit are in grams. Calories may also be written as "kcal" or
+/// "energy".
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Nutrition {
+    /// Energy, in kcal.
+    pub calories: Option<Amount>,
+    pub protein: Option<QuantityOf<Mass>>,
+    pub fat: Option<QuantityOf<Mass>>,
+    pub carbs: Option<QuantityOf<Mass>>,
+    pub sodium: Option<QuantityOf<Mass>>,
+    /// Other facts given as masses (e.g., fiber or sugar), in the order they are written.
+    pub others: Vec<(String, QuantityOf<Mass>)>,
+}
+
+const KCAL: &str = "kcal";
+
+impl Nutrition {
+    /// Parses the facts, warning about those which are neither known nor masses (e.g., "vitamin
+    /// C: 20%"), which are dropped.
+    fn parse(value: &saphyr::Yaml<'_>, ctx: &mut ParseContext) -> MDResult<Self> {
+        let mapping = value.as_mapping().ok_or(MDError::new(
+            &format!("expected mapping under {:?}", NUTRITION),
+            None,
+        ))?;
+        let mut this = Self::default();
+        for (key, value) in mapping {
+            let key = key
+                .as_str()
+                .ok_or(MDError::new("expected string key", None))?;
+            let value = Metadata::scalar_str(value, key)?;
+            match key {
+                "calories" | KCAL | "energy" => {
+                    let calories = value.strip_suffix(KCAL).unwrap_or(&value);
+                    this.calories = Some(Amount::from_str(calories).map_err(|_| {
+                        MDError::new(&format!("invalid calories {:?}", value), None)
+                    })?);
+                }
+                "protein" => this.protein = Some(Self::parse_mass(key, &value)?),
+                "fat" => this.fat = Some(Self::parse_mass(key, &value)?),
+                "carbs" => this.carbs = Some(Self::parse_mass(key, &value)?),
+                "sodium" => this.sodium = Some(Self::parse_mass(key, &value)?),
+                _ => match Self::parse_mass(key, &value) {
+                    Ok(mass) => this.others.push((key.to_string(), mass)),
+                    Err(_) => ctx.warn(
+                        MDError::new(
+                            &format!(
+                                "unknown nutrition fact {:?} is not a mass, ignoring it",
+                                key
+                            ),
+                            None,
+                        )
+                        .with_code(ErrorCode::InvalidMetadata),
+                    ),
+                },
+            }
+        }
+        Ok(this)
+    }
+
+    fn parse_mass(key: &str, value: &str) -> MDResult<QuantityOf<Mass>> {
+        match Amount::from_str(value) {
+            Ok(amount) => Ok(QuantityOf::new(Mass::Gram, amount)),
+            Err(_) => QuantityOf::from_str(value).map_err(|e| {
+                MDError::new(&format!("failed to parse {}: {}", key, e), None).with_source(e)
+            }),
+        }
+    }
+
+    /// Multiplies every fact by a factor.
+    pub fn scale(&self, factor: Amount) -> Self {
+        Self {
+            calories: self.calories.map(|c| c * factor),
+            protein: self.protein.map(|q| q.scale(factor)),
+            fat: self.fat.map(|q| q.scale(factor)),
+            carbs: self.carbs.map(|q| q.scale(factor)),
+            sodium: self.sodium.map(|q| q.scale(factor)),
+            others: self
+                .others
+                .iter()
+                .map(|(name, q)| (name.clone(), q.scale(factor)))
+                .collect(),
+        }
+    }
+}
+
+/// Adds the facts of two recipes or meals. A fact known for only one of them is kept as is.
+impl Add for Nutrition {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        fn add<T: Add<Output = T>>(a: Option<T>, b: Option<T>) -> Option<T> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            }
+        }
+        Self {
+            calories: add(self.calories, other.calories),
+            protein: add(self.protein, other.protein),
+            fat: add(self.fat, other.fat),
+            carbs: add(self.carbs, other.carbs),
+            sodium: add(self.sodium, other.sodium),
+            others: {
+                let mut others = self.others;
+                for (name, mass) in other.others {
+                    match others.iter_mut().find(|(n, _)| *n == name) {
+                        Some((_, sum)) => *sum = *sum + mass,
+                        None => others.push((name, mass)),
+                    }
+                }
+                others
+            },
+        }
+    }
+}
+
+impl fmt::Display for Nutrition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut facts = vec![];
+        if let Some(calories) = self.calories {
+            facts.push(format!("{} {}", calories, KCAL));
+        }
+        for (name, mass) in [
+            ("protein", &self.protein),
+            ("fat", &self.fat),
+            ("carbs", &self.carbs),
+            ("sodium", &self.sodium),
+        ] {
+            if let Some(mass) = mass {
+                facts.push(format!("{} {}", mass, name));
+            }
+        }
+        for (name, mass) in &self.others {
+            facts.push(format!("{} {}", mass, name));
+        }
+        f.write_str(&facts.join(", "))
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct SizeInfo {
     quantity: QuantityOf<Distance>,
     unit_mod: Option<UnitMod>,
 }
 
+impl SizeInfo {
+    pub fn quantity(&self) -> &QuantityOf<Distance> {
+        &self.quantity
+    }
+
+    pub fn unit_mod(&self) -> Option<UnitMod> {
+        self.unit_mod
+    }
+}
+
 impl FromStr for SizeInfo {
     type Err = MDError;
 
@@ -161,13 +1182,24 @@ impl FromStr for SizeInfo {
             UnitMod::RadialDistance
         });
         Ok(Self {
-            quantity: QuantityOf::from_str(info_s)
-                .map_err(|e| MDError::new(&format!("failed to parse quantity: {}", e), None))?,
+            quantity: QuantityOf::from_str(info_s).map_err(|e| {
+                MDError::new(&format!("failed to parse quantity: {}", e), None).with_source(e)
+            })?,
             unit_mod,
         })
     }
 }
 
+impl fmt::Display for SizeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.quantity.fmt(f)?;
+        match self.unit_mod {
+            Some(UnitMod::RadialDistance) => write!(f, "°"),
+            None => Ok(()),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum UnitMod {
     RadialDistance,
@@ -175,51 +1207,77 @@ pub enum UnitMod {
 
 #[cfg(test)]
 pub mod tests {
+    use chrono::NaiveDate;
     use indoc::indoc;
     use markdown::mdast::Node;
     use saphyr::LoadableYamlNode;
-    use std::collections::HashMap;
+    use std::{collections::HashMap, str::FromStr};
 
     use crate::recipe::{
-        md_parser::{get_parse_options, MDResult},
-        metadata::{SizeInfo, UnitMod},
-        unit::{Distance, Quantity, QuantityOf, Unit, Volume},
+        amount::Amount,
+        md_parser::{get_parse_options, MDResult, ParseContext},
+        metadata::{
+            Category, KeyAliases, MetaValue, Nutrition, SizeInfo, TagNormalization, TagPrefix,
+            UnitMod, SERVINGS,
+        },
+        unit::{Distance, Mass, Nominal, Quantity, QuantityOf, Unit, Volume},
     };
 
     use super::Metadata;
 
     fn to_yaml(s: &str) -> saphyr::Yaml<'_> {
         let metadata = saphyr::Yaml::load_from_str(s).unwrap();
-        return metadata[0].clone();
+        metadata[0].clone()
     }
 
     #[test]
     fn parse_tags() -> MDResult<()> {
         // Basic case.
         let mut tags: Vec<String> = vec![];
-        Metadata::parse_tags(&to_yaml("- \"#tag1\"\n- \"#tag2\"\n- \"#tag3\""), &mut tags)?;
+        Metadata::parse_tags(
+            &to_yaml("- \"#tag1\"\n- \"#tag2\"\n- \"#tag3\""),
+            TagPrefix::Required,
+            &mut tags,
+        )?;
         assert_eq!(tags, vec!["tag1", "tag2", "tag3"]);
 
         // There is no de-duplication.
         tags.clear();
-        Metadata::parse_tags(&to_yaml("- \"#tag\"\n- \"#tag\""), &mut tags)?;
+        Metadata::parse_tags(
+            &to_yaml("- \"#tag\"\n- \"#tag\""),
+            TagPrefix::Required,
+            &mut tags,
+        )?;
         assert_eq!(tags, vec!["tag", "tag"]);
 
         // Special characters and random spaces.
         tags.clear();
-        Metadata::parse_tags(&to_yaml("-   \"#t/a/g\"  \n- \"#t-a_g\" "), &mut tags)?;
+        Metadata::parse_tags(
+            &to_yaml("-   \"#t/a/g\"  \n- \"#t-a_g\" "),
+            TagPrefix::Required,
+            &mut tags,
+        )?;
         assert_eq!(tags, vec!["t/a/g", "t-a_g"]);
+
+        // Property-style tags, without '#'.
+        tags.clear();
+        Metadata::parse_tags(
+            &to_yaml("- tag1\n- \"#tag2\""),
+            TagPrefix::Optional,
+            &mut tags,
+        )?;
+        assert_eq!(tags, vec!["tag1", "tag2"]);
         Ok(())
     }
 
     #[test]
     fn parse_tags_failure() {
         // Missing #.
-        assert!(Metadata::get_tag("- \"tag\"").is_err());
+        assert!(Metadata::get_tag("- \"tag\"", TagPrefix::Required).is_err());
         // Double #.
-        assert!(Metadata::get_tag("- \"##tag\"").is_err());
+        assert!(Metadata::get_tag("- \"##tag\"", TagPrefix::Required).is_err());
         // Invalid character.
-        assert!(Metadata::get_tag("- \"#tag.1\"").is_err());
+        assert!(Metadata::get_tag("- \"#tag.1\"", TagPrefix::Required).is_err());
     }
 
     #[test]
@@ -228,7 +1286,8 @@ pub mod tests {
         let ten_cm = SizeInfo {
             quantity: QuantityOf {
                 unit: Distance::Centimeter,
-                amount: 10.,
+                amount: Amount::from_int(10),
+                max_amount: None,
             },
             unit_mod: None,
         };
@@ -256,6 +1315,13 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn display_size() -> MDResult<()> {
+        assert_eq!(SizeInfo::from_str("10cm")?.to_string(), "10 cm");
+        assert_eq!(SizeInfo::from_str("10.5 in °")?.to_string(), "10.5 in°");
+        Ok(())
+    }
+
     #[test]
     fn parse_size_failures() {
         // Only distance-typed units are supported.
@@ -264,20 +1330,62 @@ pub mod tests {
 
     #[test]
     fn parse_others() -> MDResult<()> {
-        let mut others: HashMap<String, String> = HashMap::new();
+        let mut others: HashMap<String, MetaValue> = HashMap::new();
 
         // Basic case.
         Metadata::parse_others("key", &to_yaml("value"), &mut others)?;
-        assert_eq!(*others.get("key").unwrap(), "value");
+        assert_eq!(others["key"], MetaValue::String("value".to_string()));
+
+        // Typed values.
+        Metadata::parse_others("rating", &to_yaml("4"), &mut others)?;
+        assert_eq!(others["rating"], MetaValue::Integer(4));
+        Metadata::parse_others("done", &to_yaml("true"), &mut others)?;
+        assert_eq!(others["done"], MetaValue::Bool(true));
+        Metadata::parse_others("created", &to_yaml("2024-05-01"), &mut others)?;
+        assert_eq!(
+            others["created"],
+            MetaValue::Date(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap())
+        );
+        Metadata::parse_others("aliases", &to_yaml("- a\n- 1.5"), &mut others)?;
+        assert_eq!(others["aliases"].to_string(), "a, 1.5");
+
+        // Typed accessors.
+        assert_eq!(others["rating"].as_integer(), Some(4));
+        assert_eq!(others["rating"].as_number(), Some(4.0));
+        assert_eq!(
+            others["aliases"].as_list().unwrap()[1].as_number(),
+            Some(1.5)
+        );
+        assert_eq!(others["done"].as_bool(), Some(true));
+        assert_eq!(
+            others["created"].as_date(),
+            NaiveDate::from_ymd_opt(2024, 5, 1)
+        );
+        assert_eq!(others["key"].as_bool(), None);
+        Metadata::parse_others(
+            "nutrition",
+            &to_yaml("{kcal: 300, protein: 12, extra: {fiber: 4}}"),
+            &mut others,
+        )?;
+        let nutrition = &others["nutrition"];
+        assert_eq!(nutrition.get("kcal"), Some(&MetaValue::Integer(300)));
+        assert_eq!(
+            nutrition.get("extra").and_then(|extra| extra.get("fiber")),
+            Some(&MetaValue::Integer(4))
+        );
+        assert_eq!(
+            nutrition.to_string(),
+            "kcal: 300, protein: 12, extra: fiber: 4"
+        );
 
         Ok(())
     }
 
     #[test]
     fn parse_others_failures() {
-        // The value must be a string.
+        // Mapping keys must be scalars.
         assert!(
-            Metadata::parse_others("key", &to_yaml("- value1\n- value2"), &mut HashMap::new())
+            Metadata::parse_others("key", &to_yaml("? [a, b]\n: value"), &mut HashMap::new())
                 .is_err()
         );
     }
@@ -300,25 +1408,309 @@ pub mod tests {
             let meta = Metadata::parse(yaml)?;
             assert_eq!(meta.tags, vec!["tag1", "tag2"]);
             assert_eq!(
-                meta.quantity,
-                Quantity {
+                meta.yield_quantity,
+                Some(Quantity {
                     unit: Unit::Volume(Volume::Milliliter),
-                    amount: 150.
-                }
+                    amount: Amount::from_int(150),
+                    max_amount: None,
+                    approximation: None,
+                })
             );
+            assert_eq!(meta.servings, None);
             let size = SizeInfo {
                 quantity: QuantityOf {
                     unit: Distance::Centimeter,
-                    amount: 10.,
+                    amount: Amount::from_int(10),
+                    max_amount: None,
                 },
                 unit_mod: None,
             };
             assert_eq!(*meta.sizes.get("pan").unwrap(), size);
             assert_eq!(*meta.sizes.get("whatever").unwrap(), size);
-            assert_eq!(*meta.others.get("random").unwrap(), "something");
+            assert_eq!(meta.others["random"].as_str(), Some("something"));
+            assert_eq!(meta.other("random"), meta.others.get("random"));
         } else {
             panic!("should be YAML!");
         }
         Ok(())
     }
+
+    #[test]
+    fn parse_source() -> MDResult<()> {
+        let parse = |content: &str| -> MDResult<Metadata> {
+            let md = markdown::to_mdast(content, &get_parse_options())?;
+            let Node::Yaml(yaml) = &md.children().unwrap()[0] else {
+                panic!("should be YAML!");
+            };
+            Metadata::parse(yaml)
+        };
+        let meta =
+            parse("---\nsource: The Book, p. 12\nauthor: Ada\nurl: https://example.com/x\n---\n")?;
+        assert_eq!(meta.source(), Some("The Book, p. 12"));
+        assert_eq!(meta.author(), Some("Ada"));
+        assert_eq!(meta.url().unwrap().host(), "example.com");
+        assert!(meta.others().is_empty());
+
+        // A URL as the source is the URL, unless there is one already.
+        let meta = parse("---\nsource: https://example.com/x\n---\n")?;
+        assert_eq!(meta.source(), None);
+        assert_eq!(meta.url().unwrap().as_str(), "https://example.com/x");
+        let meta = parse("---\nsource: https://a.com\nurl: https://b.com\n---\n")?;
+        assert_eq!(meta.url().unwrap().host(), "b.com");
+
+        assert!(parse("---\nurl: example.com\n---\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_servings_and_yield() -> MDResult<()> {
+        let mut servings = None;
+        Metadata::parse_servings(&to_yaml("4"), &mut servings)?;
+        assert_eq!(
+            servings,
+            Some(QuantityOf::new(Nominal, Amount::from_int(4)))
+        );
+        Metadata::parse_servings(&to_yaml("4-6"), &mut servings)?;
+        assert!(servings.unwrap().is_range());
+        assert!(Metadata::parse_servings(&to_yaml("4 L"), &mut servings).is_err());
+
+        let mut yield_quantity = None;
+        Metadata::parse_yield(&to_yaml("12 cookies"), &mut yield_quantity)?;
+        assert_eq!(
+            yield_quantity,
+            Some(Quantity::new(
+                &Unit::Custom("cookies".to_string()),
+                Amount::from_int(12)
+            ))
+        );
+
+        // The legacy key is split between both.
+        let (mut servings, mut yield_quantity) = (None, None);
+        Metadata::parse_quantity(&to_yaml("2"), &mut servings, &mut yield_quantity)?;
+        assert_eq!(
+            servings,
+            Some(QuantityOf::new(Nominal, Amount::from_int(2)))
+        );
+        assert_eq!(yield_quantity, None);
+        Ok(())
+    }
+
+    #[test]
+    fn key_aliases() -> MDResult<()> {
+        let content = indoc! {"
+            ---
+            Tags:
+              - \"#tag\"
+            SERVES: 4
+            Size | Pan: 10cm
+            Source: somewhere
+            ---
+        "};
+        let md = markdown::to_mdast(content, &get_parse_options())?;
+        let Node::Yaml(yaml) = &md.children().unwrap()[0] else {
+            panic!("should be YAML!");
+        };
+        let meta = Metadata::parse(yaml)?;
+        assert_eq!(meta.tags, vec!["tag"]);
+        assert!(meta.servings.is_some());
+        assert!(meta.sizes.contains_key("Pan"));
+        assert_eq!(meta.source(), Some("somewhere"));
+
+        // Custom aliases.
+        let aliases = KeyAliases::empty().alias("portions", SERVINGS);
+        assert_eq!(aliases.canonical("Portions"), SERVINGS);
+        assert_eq!(aliases.canonical("serves"), "serves");
+        let mut ctx = ParseContext::new().key_aliases(aliases);
+        let meta = Metadata::parse_with(yaml, &mut ctx)?;
+        assert!(meta.servings.is_none());
+        assert!(meta.others.contains_key("SERVES"));
+        Ok(())
+    }
+
+    #[test]
+    fn place_errors() -> MDResult<()> {
+        let content = indoc! {"
+            ---
+            servings: 4
+            created: 2024-13-45
+            colour: red
+            \"tags\": vegan
+            ---
+        "};
+        let md = markdown::to_mdast(content, &get_parse_options())?;
+        let Node::Yaml(yaml) = &md.children().unwrap()[0] else {
+            panic!("should be YAML!");
+        };
+        let mut ctx = ParseContext::new();
+        Metadata::parse_with(yaml, &mut ctx)?;
+        // Errors in entries are placed on the line of their key.
+        let rendered = ctx.errors()[0].render(content);
+        assert!(rendered.contains(" --> 3:1\n"));
+        assert!(rendered.ends_with("3 | created: 2024-13-45\n  | ^^^^^^^^^^^^^^^^^^^\n"));
+        assert!(ctx.diagnostics()[0].render(content).contains(" --> 4:1\n"));
+        assert!(ctx.errors()[1].render(content).contains(" --> 5:1\n"));
+
+        // Errors in the frontmatter as a whole are placed on all of it.
+        let content = "---\n- servings\n---\n";
+        let md = markdown::to_mdast(content, &get_parse_options())?;
+        let Node::Yaml(yaml) = &md.children().unwrap()[0] else {
+            panic!("should be YAML!");
+        };
+        let error = Metadata::parse(yaml).unwrap_err();
+        assert!(error.render(content).contains(" --> 1:1\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn normalize_tags() -> MDResult<()> {
+        let content = indoc! {"
+            ---
+            tags:
+              - \"#Vegan\"
+              - \"#veggie\"
+              - \"#vegan\"
+              - \"#Vegetarian\"
+              - \"#Quick/Weeknight\"
+            ---
+        "};
+        let md = markdown::to_mdast(content, &get_parse_options())?;
+        let Node::Yaml(yaml) = &md.children().unwrap()[0] else {
+            panic!("should be YAML!");
+        };
+        // Tags are kept as written by default.
+        let meta = Metadata::parse(yaml)?;
+        assert_eq!(meta.tags.len(), 5);
+
+        let normalization = TagNormalization::new().alias("#veggie", "vegetarian");
+        assert_eq!(normalization.normalize("VEGGIE"), "vegetarian");
+        let mut ctx = ParseContext::new().tag_normalization(normalization);
+        let meta = Metadata::parse_with(yaml, &mut ctx)?;
+        assert_eq!(meta.tags, vec!["vegan", "vegetarian", "quick/weeknight"]);
+        let warnings: Vec<String> = ctx
+            .diagnostics()
+            .iter()
+            .map(|d| d.error().message().to_string())
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "duplicate tag \"vegan\"",
+                "duplicate tag \"Vegetarian\" (normalized to \"vegetarian\")"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_category() -> MDResult<()> {
+        assert_eq!(Category::from_str("Main course")?, Category::Main);
+        assert_eq!(Category::from_str("appetizer")?.to_string(), "starter");
+        assert!(Category::from_str("elevenses").is_err());
+
+        let content = indoc! {"
+            ---
+            course: dessert
+            ---
+        "};
+        let md = markdown::to_mdast(content, &get_parse_options())?;
+        let Node::Yaml(yaml) = &md.children().unwrap()[0] else {
+            panic!("should be YAML!");
+        };
+        assert_eq!(Metadata::parse(yaml)?.category(), Some(Category::Dessert));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_glass() -> MDResult<()> {
+        let content = indoc! {"
+            ---
+            glass: Coupe, coupe
+            size | coupe: 9 cm°
+            ---
+        "};
+        let md = markdown::to_mdast(content, &get_parse_options())?;
+        let Node::Yaml(yaml) = &md.children().unwrap()[0] else {
+            panic!("should be YAML!");
+        };
+        let meta = Metadata::parse(yaml)?;
+        let glass = meta.glass().unwrap();
+        assert_eq!(glass.name(), "Coupe");
+        assert_eq!(glass.resolve_size(meta.sizes()), meta.sizes().get("coupe"));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nutrition() -> MDResult<()> {
+        let content = indoc! {"
+            ---
+            servings: 4
+            nutrition:
+              calories: 1200 kcal
+              protein: 48
+              fat: 2 oz
+              sodium: 1.2
+            ---
+        "};
+        let md = markdown::to_mdast(content, &get_parse_options())?;
+        let Node::Yaml(yaml) = &md.children().unwrap()[0] else {
+            panic!("should be YAML!");
+        };
+        let meta = Metadata::parse(yaml)?;
+        let nutrition = meta.nutrition().unwrap();
+        assert_eq!(nutrition.calories, Some(Amount::from_int(1200)));
+        assert_eq!(
+            nutrition.to_string(),
+            "1200 kcal, 48 g protein, 2 oz fat, 1.2 g sodium"
+        );
+        assert_eq!(
+            meta.nutrition_per_serving().unwrap().to_string(),
+            "300 kcal, 12 g protein, 0.5 oz fat, 0.3 g sodium"
+        );
+        let sum = nutrition.clone()
+            + Nutrition {
+                calories: Some(Amount::from_int(100)),
+                fat: Some(QuantityOf::new(Mass::Gram, Amount::from_int(10))),
+                ..Default::default()
+            };
+        assert_eq!(sum.calories, Some(Amount::from_int(1300)));
+        assert_eq!(sum.protein, nutrition.protein);
+        // Summed in the unit of the left-hand side.
+        assert_eq!(sum.fat.unwrap().unit, Mass::Ounce);
+        assert_eq!(sum.fat.unwrap().as_grams(), Amount::from_int(66));
+
+        let mut ctx = ParseContext::new();
+        let mut parse = |yaml: &str| Nutrition::parse(&to_yaml(yaml), &mut ctx);
+        assert!(parse("calories: a lot").is_err());
+        assert!(parse("protein: 4 L").is_err());
+        let nutrition = parse("{energy: 250, fiber: 4, vitamin C: 20%}")?;
+        assert_eq!(nutrition.to_string(), "250 kcal, 4 g fiber");
+        assert_eq!(ctx.diagnostics().len(), 1);
+
+        // Nutrition facts as 1271 has them, in a whole recipe.
+        let recipe = crate::recipe::Recipe::from_mdast(indoc! {"
+            ---
+            servings: 2
+            nutrition: {kcal: 300, protein: 12, sugar: 5 g}
+            ---
+            # Porridge
+            ## Ingredients
+
+            - Oats, 80 g
+
+            ## Instructions
+
+            - Cook the *oats*
+        "})?;
+        let nutrition = recipe.metadata().nutrition_per_serving().unwrap();
+        assert_eq!(nutrition.to_string(), "150 kcal, 6 g protein, 2.5 g sugar");
+        let sum = nutrition.clone() + nutrition;
+        assert_eq!(
+            sum.others,
+            [(
+                "sugar".to_string(),
+                QuantityOf::new(Mass::Gram, Amount::from_int(5))
+            )]
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,171 @@
+use std::fmt;
+
+use super::md_parser::{normalize_newlines, ErrorCode, MDError, MDResult, ParseContext};
+use markdown::mdast::Node;
+
+/// Which section a note comes from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NoteKind {
+    Note,
+    Tip,
+}
+
+impl NoteKind {
+    pub const ALL: [NoteKind; 2] = [NoteKind::Note, NoteKind::Tip];
+
+    /// Heading of the section holding notes of this kind.
+    pub fn heading(&self) -> &'static str {
+        match self {
+            Self::Note => "Notes",
+            Self::Tip => "Tips",
+        }
+    }
+}
+
+/// A free-form remark from the optional "Notes" or "Tips" sections following the instructions.
+/// Each paragraph or top-level list item is a note.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Note {
+    kind: NoteKind,
+    content: Vec<RichText>,
+}
+
+/// Inline formatting preserved in notes, and in steps for formatting other than references and
+/// timers.
+#[derive(Clone, PartialEq, Debug)]
+pub enum RichText {
+    Text(String),
+    Code(String),
+    Emphasis(Vec<RichText>),
+    Strong(Vec<RichText>),
+    Link { url: String, content: Vec<RichText> },
+}
+
+impl Note {
+    pub fn new(kind: NoteKind, content: Vec<RichText>) -> Self {
+        Self { kind, content }
+    }
+
+    /// Parses the content of a notes section, recording invalid notes in `ctx`.
+    pub(crate) fn parse_section(
+        kind: NoteKind,
+        nodes: &[Node],
+        ctx: &mut ParseContext,
+    ) -> Vec<Self> {
+        let mut notes = vec![];
+        let malformed = |e: MDError| e.or_code(ErrorCode::MalformedNote);
+        for node in nodes {
+            match node {
+                Node::List(list) => notes.extend(list.children.iter().filter_map(|item| {
+                    ctx.recover(Self::parse_item(kind, item).map_err(malformed))
+                })),
+                _ => {
+                    notes.extend(ctx.recover(Self::parse_paragraph(kind, node).map_err(malformed)))
+                }
+            }
+        }
+        notes
+    }
+
+    fn parse_item(kind: NoteKind, node: &Node) -> MDResult<Self> {
+        match node {
+            Node::ListItem(item) => match &item.children[..] {
+                [paragraph] => Self::parse_paragraph(kind, paragraph),
+                _ => Err(MDError::new(
+                    "expected note to be a single paragraph",
+                    Some(node),
+                )),
+            },
+            _ => Err(MDError::new("expected list item", Some(node))),
+        }
+    }
+
+    fn parse_paragraph(kind: NoteKind, node: &Node) -> MDResult<Self> {
+        match node {
+            Node::Paragraph(para) => Ok(Self {
+                kind,
+                content: RichText::parse_all(&para.children)?,
+            }),
+            _ => Err(MDError::new("expected paragraph or list", Some(node))),
+        }
+    }
+
+    pub fn kind(&self) -> NoteKind {
+        self.kind
+    }
+
+    pub fn content(&self) -> &[RichText] {
+        &self.content
+    }
+
+    /// Text content of the note, without formatting.
+    pub fn text(&self) -> String {
+        self.content.iter().map(|t| t.to_string()).collect()
+    }
+}
+
+impl RichText {
+    pub(super) fn parse_all(nodes: &[Node]) -> MDResult<Vec<Self>> {
+        nodes.iter().map(Self::parse).collect()
+    }
+
+    pub(super) fn parse(node: &Node) -> MDResult<Self> {
+        match node {
+            Node::Text(text) => Ok(Self::Text(normalize_newlines(&text.value))),
+            Node::Break(_) => Ok(Self::Text("\n".to_string())),
+            Node::InlineCode(code) => Ok(Self::Code(code.value.clone())),
+            Node::Emphasis(emphasis) => Ok(Self::Emphasis(Self::parse_all(&emphasis.children)?)),
+            Node::Strong(strong) => Ok(Self::Strong(Self::parse_all(&strong.children)?)),
+            Node::Link(link) => Ok(Self::Link {
+                url: link.url.clone(),
+                content: Self::parse_all(&link.children)?,
+            }),
+            _ => Err(MDError::new(
+                "unsupported element in formatted text",
+                Some(node),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RichText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) | Self::Code(text) => write!(f, "{}", text),
+            Self::Emphasis(content) | Self::Strong(content) | Self::Link { content, .. } => {
+                content.iter().try_for_each(|t| write!(f, "{}", t))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn parse_notes() {
+        let content = indoc! {"
+            Keeps for **3 days** in the [fridge](https://example.com).
+
+            - Use `00` flour
+            - Use *ripe*
+              lemons
+            - > quote
+        "};
+        let mdast = markdown::to_mdast(content, &markdown::ParseOptions::default()).unwrap();
+        let mut ctx = ParseContext::new();
+        let notes = Note::parse_section(NoteKind::Tip, mdast.children().unwrap(), &mut ctx);
+        assert_eq!(notes.len(), 3);
+        assert_eq!(notes[0].text(), "Keeps for 3 days in the fridge.");
+        assert_eq!(
+            notes[0].content()[1],
+            RichText::Strong(vec![RichText::Text("3 days".to_string())])
+        );
+        assert_eq!(notes[1].content()[1], RichText::Code("00".to_string()));
+        assert_eq!(notes[2].text(), "Use ripe\nlemons");
+        assert_eq!(notes[2].kind(), NoteKind::Tip);
+        assert_eq!(ctx.errors().len(), 1);
+    }
+}
@@ -0,0 +1,159 @@
+//! How good and how hard a recipe is, from the frontmatter:
+//!
+//! ```yaml
+//! rating: 4.5
+//! difficulty: medium
+//! ```
+//!
+//! Ratings go from 0 to 5 stars, by halves (a trailing "/5" is allowed). Difficulties are levels
+//! from 1 to 5, or one of "easy" (1), "medium" (3) and "hard" (5).
+
+use std::{fmt, str::FromStr};
+
+use super::md_parser::MDError;
+
+pub(super) const RATING: &str = "rating";
+pub(super) const DIFFICULTY: &str = "difficulty";
+
+/// Rating from 0 to 5 stars, by halves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rating(u8);
+
+impl Rating {
+    pub const MAX: Rating = Rating(10);
+
+    /// Rating of some number of half stars, up to 10.
+    pub fn from_half_stars(half_stars: u8) -> Option<Self> {
+        (half_stars <= Self::MAX.0).then_some(Self(half_stars))
+    }
+
+    pub fn half_stars(self) -> u8 {
+        self.0
+    }
+
+    pub fn stars(self) -> f64 {
+        f64::from(self.0) / 2.0
+    }
+}
+
+impl FromStr for Rating {
+    type Err = MDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            MDError::new(
+                &format!("invalid rating {:?}: expected 0 to 5, by halves", s),
+                None,
+            )
+        };
+        let s = s.trim();
+        let stars = s.strip_suffix("/5").unwrap_or(s).trim();
+        let half_stars = match stars.split_once('.') {
+            None => stars.parse::<u8>().ok().map(|n| n.saturating_mul(2)),
+            Some((whole, "0" | "00")) => whole.parse::<u8>().ok().map(|n| n.saturating_mul(2)),
+            Some((whole, "5" | "50")) => whole
+                .parse::<u8>()
+                .ok()
+                .map(|n| n.saturating_mul(2).saturating_add(1)),
+            Some(_) => None,
+        };
+        half_stars
+            .and_then(Self::from_half_stars)
+            .ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 % 2 {
+            0 => write!(f, "{}", self.0 / 2),
+            _ => write!(f, "{}.5", self.0 / 2),
+        }
+    }
+}
+
+/// Difficulty level, from 1 (easiest) to 5.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Difficulty(u8);
+
+impl Difficulty {
+    pub const EASY: Difficulty = Difficulty(1);
+    pub const MEDIUM: Difficulty = Difficulty(3);
+    pub const HARD: Difficulty = Difficulty(5);
+
+    pub fn new(level: u8) -> Option<Self> {
+        (1..=5).contains(&level).then_some(Self(level))
+    }
+
+    pub fn level(self) -> u8 {
+        self.0
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = MDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "easy" => Ok(Self::EASY),
+            "medium" => Ok(Self::MEDIUM),
+            "hard" => Ok(Self::HARD),
+            level => level.parse().ok().and_then(Self::new).ok_or_else(|| {
+                MDError::new(
+                    &format!(
+                        "invalid difficulty {:?}: expected easy, medium, hard or 1 to 5",
+                        s
+                    ),
+                    None,
+                )
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    /// Writes the named levels by their names, and the others as numbers.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::EASY => write!(f, "easy"),
+            Self::MEDIUM => write!(f, "medium"),
+            Self::HARD => write!(f, "hard"),
+            Self(level) => write!(f, "{}", level),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratings() -> Result<(), MDError> {
+        assert_eq!(Rating::from_str("4")?.half_stars(), 8);
+        assert_eq!(Rating::from_str("4.5")?.stars(), 4.5);
+        assert_eq!(Rating::from_str(" 3/5 ")?, Rating::from_str("3.0")?);
+        assert_eq!(Rating::from_str("0")?.to_string(), "0");
+        assert_eq!(Rating::from_str("2.50")?.to_string(), "2.5");
+        assert_eq!(Rating::from_str("5")?, Rating::MAX);
+        assert!(Rating::from_str("5.5").is_err());
+        assert!(Rating::from_str("4.2").is_err());
+        assert!(Rating::from_str("-1").is_err());
+        assert!(Rating::from_str("great").is_err());
+        assert!(Rating::from_str("4.5")? > Rating::from_str("4")?);
+        Ok(())
+    }
+
+    #[test]
+    fn difficulties() -> Result<(), MDError> {
+        assert_eq!(Difficulty::from_str("Easy")?, Difficulty::EASY);
+        assert_eq!(Difficulty::from_str("3")?, Difficulty::MEDIUM);
+        assert_eq!(Difficulty::from_str("4")?.level(), 4);
+        assert_eq!(Difficulty::from_str("4")?.to_string(), "4");
+        assert_eq!(Difficulty::HARD.to_string(), "hard");
+        assert!(Difficulty::from_str("0").is_err());
+        assert!(Difficulty::from_str("6").is_err());
+        assert!(Difficulty::from_str("tricky").is_err());
+        assert!(Difficulty::EASY < Difficulty::from_str("2")?);
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+//! Re-parsing of a single section of a recipe, for editors which parse the recipe again as it is
+//! being written (e.g., on every keystroke): only the section that changed is parsed again.
+
+use markdown::{mdast::Node, unist::Point};
+
+use super::{
+    ingredients::{Ingredients, RefMatching},
+    instructions::Instructions,
+    md_parser::{
+        get_parse_options, Diagnostic, ErrorCode, MDError, MDResult, ParseContext, Severity,
+    },
+    Recipe, RecipeParseOptions,
+};
+
+/// Section of a recipe which can be parsed again on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Section {
+    Ingredients,
+    Instructions,
+}
+
+impl Recipe {
+    /// Replaces a section with the parse of `new_markdown`, the section's content without its
+    /// heading, which starts at `start` in the recipe's file (e.g., the line after the heading).
+    /// Ingredient references in steps are resolved again, so that they follow the ingredients to
+    /// their new positions. Returns the section's warnings and hints, placed in the recipe's file.
+    /// The recipe is left as-is if the section has errors.
+    pub fn reparse_section(
+        &mut self,
+        section: Section,
+        new_markdown: &str,
+        start: &Point,
+    ) -> MDResult<Vec<Diagnostic>> {
+        let options = match self.language {
+            Some(language) => RecipeParseOptions::new().language(language),
+            None => RecipeParseOptions::new(),
+        };
+        self.reparse_section_with(section, new_markdown, start, &options)
+    }
+
+    /// Replaces a section as [`Recipe::reparse_section`] does, with non-default options (e.g.,
+    /// custom units). The options should be those the recipe was parsed with.
+    pub fn reparse_section_with(
+        &mut self,
+        section: Section,
+        new_markdown: &str,
+        start: &Point,
+        options: &RecipeParseOptions,
+    ) -> MDResult<Vec<Diagnostic>> {
+        let md = markdown::to_mdast(new_markdown, &get_parse_options())?;
+        let nodes = md.children().map_or(&[][..], Vec::as_slice);
+        // A new section heading changes the recipe's structure, which only a full parse handles.
+        let heading = nodes
+            .iter()
+            .find(|node| matches!(node, Node::Heading(heading) if heading.depth <= 2));
+        if let Some(heading) = heading {
+            return Err(MDError::new(
+                "expected the content of a single section, without its heading",
+                Some(heading),
+            )
+            .with_code(ErrorCode::UnknownSection)
+            .shifted(start));
+        }
+
+        let mut ctx = ParseContext::with_options(options.clone());
+        let sections = ctx.section_names().clone();
+        match section {
+            Section::Ingredients => {
+                let context = format!("while parsing section '{}'", sections.ingredients);
+                let result = ctx.shifted(start, |ctx| {
+                    ctx.within(&context, |ctx| {
+                        Ingredients::parse_with(nodes, ctx)
+                            .map_err(|e| e.or_code(ErrorCode::MalformedIngredient))
+                    })
+                });
+                if result.as_ref().is_ok_and(Ingredients::is_empty) {
+                    ctx.warn(
+                        MDError::new("no ingredients", None).with_code(ErrorCode::NoIngredients),
+                    );
+                }
+                let diagnostics = ctx.take_diagnostics();
+                self.ingredients = ctx.finish(result)?;
+                self.ingredients.iter_mut().for_each(|o| o.shift(start));
+                Ok(self.with_ref_warnings(diagnostics))
+            }
+            Section::Instructions => {
+                let context = format!("while parsing section '{}'", sections.instructions);
+                let result = ctx.shifted(start, |ctx| {
+                    ctx.within(&context, |ctx| {
+                        Instructions::parse_with(nodes, ctx)
+                            .map_err(|e| e.or_code(ErrorCode::MalformedStep))
+                    })
+                });
+                if result.as_ref().is_ok_and(|i| i.steps().is_empty()) {
+                    ctx.warn(
+                        MDError::new("no instructions", None).with_code(ErrorCode::NoInstructions),
+                    );
+                }
+                let diagnostics = ctx.take_diagnostics();
+                self.instructions = ctx.finish(result)?;
+                self.instructions.for_each_ref_mut(|r| r.shift(start));
+                Ok(self.with_ref_warnings(diagnostics))
+            }
+        }
+    }
+
+    /// Resolves the ingredient references again, adding a warning to `diagnostics` for each one
+    /// which does not resolve, as parsing does.
+    fn with_ref_warnings(&mut self, mut diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        let errors = self.resolve_ingredient_refs(RefMatching::Exact);
+        diagnostics.extend(
+            errors
+                .into_iter()
+                .map(|e| Diagnostic::new(Severity::Warning, e)),
+        );
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const TOAST: &str = indoc! {"
+        # Toast
+        ## Ingredients
+
+        - Bread, 2
+        - Butter, 10 g
+
+        ## Instructions
+
+        - Toast the *bread*
+        - Spread the *butter*
+    "};
+
+    fn ref_indices(recipe: &Recipe) -> Vec<Option<usize>> {
+        recipe
+            .instructions()
+            .refs()
+            .into_iter()
+            .map(|(_, r)| r.index())
+            .collect()
+    }
+
+    /// Where the content of a section starts in [`TOAST`].
+    fn start_of(heading: &str) -> Point {
+        let offset = TOAST.find(heading).unwrap() + heading.len() + 1;
+        let line = TOAST[..offset].matches('\n').count() + 1;
+        Point::new(line, 1, offset)
+    }
+
+    #[test]
+    fn reparse_sections() -> MDResult<()> {
+        let mut recipe = Recipe::from_mdast(TOAST)?;
+        let (ingredients, instructions) = (start_of("## Ingredients"), start_of("## Instructions"));
+        assert_eq!(ref_indices(&recipe), [Some(0), Some(1)]);
+
+        let diagnostics = recipe.reparse_section(
+            Section::Ingredients,
+            "- Butter, 10 g\n- Bread, 2\n- Jam\n",
+            &ingredients,
+        )?;
+        assert!(diagnostics.is_empty());
+        assert_eq!(recipe.ingredients().iter().count(), 3);
+        assert_eq!(ref_indices(&recipe), [Some(1), Some(0)]);
+        let whole = TOAST.replace(
+            "- Bread, 2\n- Butter, 10 g\n",
+            "- Butter, 10 g\n- Bread, 2\n- Jam\n",
+        );
+        assert_eq!(recipe, Recipe::from_mdast(&whole)?);
+
+        let diagnostics = recipe.reparse_section(
+            Section::Instructions,
+            "\n- Toast the *bread*\n- Spread the *jam*\n-\n",
+            &instructions,
+        )?;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code(), Some(ErrorCode::EmptyStep));
+        // Places are in the whole recipe, as when parsing it all.
+        let whole = TOAST.replace("- Spread the *butter*\n", "- Spread the *jam*\n-\n");
+        let rendered = diagnostics[0].render(&whole);
+        assert!(rendered.contains(" --> 11:1\n"));
+        assert_eq!(
+            rendered,
+            Recipe::from_mdast_with_diagnostics(&whole, false)?.1[0].render(&whole)
+        );
+        assert_eq!(ref_indices(&recipe), [Some(1), Some(2)]);
+
+        // Sections with errors, or with headings of other sections, are not replaced.
+        let before = recipe.clone();
+        let error = recipe
+            .reparse_section(Section::Ingredients, "- Bread, two slices\n", &ingredients)
+            .unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::InvalidQuantity));
+        assert!(error.render(TOAST).contains(" --> 3:"));
+        assert_eq!(error.context(), ["while parsing section 'Ingredients'"]);
+        let error = recipe
+            .reparse_section(
+                Section::Instructions,
+                "- Toast\n\n## Notes\n\nHot.\n",
+                &instructions,
+            )
+            .unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::UnknownSection));
+        assert_eq!(recipe, before);
+
+        let diagnostics = recipe.reparse_section(Section::Ingredients, "", &ingredients)?;
+        assert_eq!(diagnostics[0].code(), Some(ErrorCode::NoIngredients));
+        assert_eq!(ref_indices(&recipe), [None, None]);
+        // References which no longer resolve are reported where they are in the recipe.
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[1].code(), Some(ErrorCode::UnresolvedReference));
+        assert!(diagnostics[1].render(&whole).contains(" --> 9:13\n"));
+        Ok(())
+    }
+}
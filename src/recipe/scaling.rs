@@ -0,0 +1,327 @@
+//! Scaling of recipes, and the scale presets a recipe may suggest in its metadata:
+//!
+//! ```yaml
+//! scales:
+//!   - 0.5
+//!   - 1
+//!   - factor: 2
+//!     size | pan: 30 cm°
+//!     cook_time: 1 h
+//! ```
+//!
+//! Presets may override the sizes and times of the recipe, which rarely scale linearly.
+
+use std::{collections::HashMap, str::FromStr};
+
+use super::{
+    amount::Amount,
+    md_parser::{MDError, MDResult},
+    metadata::{Metadata, SizeInfo, COOK_TIME, PREP_TIME, SIZE_PREFIX, TOTAL_TIME},
+    unit::{Quantity, QuantityOf, Time, Unit},
+    Recipe,
+};
+
+pub(super) const SCALES: &str = "scales";
+const FACTOR: &str = "factor";
+
+/// A scale factor suggested by the author of a recipe.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScalePreset {
+    factor: Amount,
+    sizes: HashMap<String, SizeInfo>,
+    prep_time: Option<QuantityOf<Time>>,
+    cook_time: Option<QuantityOf<Time>>,
+    total_time: Option<QuantityOf<Time>>,
+}
+
+impl ScalePreset {
+    pub(super) fn parse_all(value: &saphyr::Yaml<'_>) -> MDResult<Vec<Self>> {
+        let presets = value
+            .as_sequence()
+            .ok_or(MDError::new(
+                &format!("expected list of scale presets under {:?}", SCALES),
+                None,
+            ))?
+            .iter()
+            .map(Self::parse)
+            .collect::<MDResult<Vec<_>>>()?;
+        for (i, preset) in presets.iter().enumerate() {
+            if presets[..i].iter().any(|p| p.factor == preset.factor) {
+                return Err(MDError::new(
+                    &format!("duplicate scale preset {}", preset.factor),
+                    None,
+                ));
+            }
+        }
+        Ok(presets)
+    }
+
+    fn parse(value: &saphyr::Yaml<'_>) -> MDResult<Self> {
+        let mut this = Self {
+            factor: Amount::ONE,
+            sizes: HashMap::new(),
+            prep_time: None,
+            cook_time: None,
+            total_time: None,
+        };
+        let factor = match value.as_mapping() {
+            Some(mapping) => {
+                let mut factor = None;
+                for (key, value) in mapping {
+                    let key = key
+                        .as_str()
+                        .ok_or(MDError::new("expected string key", None))?;
+                    match key {
+                        FACTOR => factor = Some(Metadata::scalar_str(value, key)?),
+                        PREP_TIME => Metadata::parse_time(value, key, &mut this.prep_time)?,
+                        COOK_TIME => Metadata::parse_time(value, key, &mut this.cook_time)?,
+                        TOTAL_TIME => Metadata::parse_time(value, key, &mut this.total_time)?,
+                        _ => match key.strip_prefix(SIZE_PREFIX) {
+                            Some(name) => Metadata::parse_size(name, value, &mut this.sizes)?,
+                            None => {
+                                return Err(MDError::new(
+                                    &format!("unknown scale preset key {:?}", key),
+                                    None,
+                                ))
+                            }
+                        },
+                    }
+                }
+                factor.ok_or(MDError::new("missing scale preset factor", None))?
+            }
+            None => Metadata::scalar_str(value, SCALES)?,
+        };
+        this.factor = Amount::from_str(&factor)
+            .ok()
+            .filter(|f| *f > Amount::ZERO)
+            .ok_or(MDError::new(
+                &format!("invalid scale factor {:?}", factor),
+                None,
+            ))?;
+        Ok(this)
+    }
+
+    pub fn factor(&self) -> Amount {
+        self.factor
+    }
+
+    /// Sizes replacing those of the recipe at this scale.
+    pub fn sizes(&self) -> &HashMap<String, SizeInfo> {
+        &self.sizes
+    }
+
+    pub fn prep_time(&self) -> Option<&QuantityOf<Time>> {
+        self.prep_time.as_ref()
+    }
+
+    pub fn cook_time(&self) -> Option<&QuantityOf<Time>> {
+        self.cook_time.as_ref()
+    }
+
+    pub fn total_time(&self) -> Option<&QuantityOf<Time>> {
+        self.total_time.as_ref()
+    }
+}
+
+impl Recipe {
+    /// Returns the recipe with ingredient quantities, servings, yield, and nutrition facts
+    /// multiplied by `factor`. Relative quantities (parts and percentages) are left as they are,
+    /// and scale-dependent text in steps is replaced by its text at `factor`.
+    pub fn scaled(&self, factor: Amount) -> Self {
+        let mut recipe = self.clone();
+        recipe
+            .ingredients
+            .map_quantities(|q| scale_quantity(q, factor));
+        recipe.instructions.resolve_conditionals(factor);
+        let metadata = &mut recipe.metadata;
+        metadata.servings = metadata.servings.map(|s| s.scale(factor));
+        metadata.yield_quantity = metadata.yield_quantity.take().map(|y| y.scale(factor));
+        metadata.nutrition = metadata.nutrition.take().map(|n| n.scale(factor));
+        recipe
+    }
+
+    /// Returns the recipe with the ingredient quantities and yield of one group multiplied by
+    /// `factor` (e.g., to make a double batch of the sauce only; see
+    /// [`IngredientGroup::batches_for`](super::ingredients::IngredientGroup::batches_for)), or
+    /// `None` if no group has this name.
+    pub fn scaled_group(&self, name: &str, factor: Amount) -> Option<Self> {
+        let mut recipe = self.clone();
+        recipe
+            .ingredients
+            .group_mut(name)?
+            .map_quantities(&mut |q| scale_quantity(q, factor));
+        Some(recipe)
+    }
+
+    /// Number of batches of the recipe needed for a quantity of it, as used by another recipe
+    /// (e.g., 2 for "500 g" of a recipe yielding 250 g). Counts without units are numbers of
+    /// batches, and no quantity means one batch. Returns `None` if the quantity cannot be
+    /// compared to the recipe's yield.
+    pub fn batches_for(&self, quantity: Option<&Quantity>) -> Option<Amount> {
+        let Some(quantity) = quantity.filter(|q| !q.is_to_taste()) else {
+            return Some(Amount::ONE);
+        };
+        if let Unit::Nominal(_) = quantity.unit {
+            return Some(quantity.amount);
+        }
+        let yield_quantity = self
+            .metadata
+            .yield_quantity()?
+            .convert_to(&quantity.unit)
+            .filter(|y| y.amount > Amount::ZERO)?;
+        Some(quantity.amount / yield_quantity.amount)
+    }
+
+    /// Returns the recipe with ingredient quantities converted to the unit chosen by `unit_for`.
+    /// Quantities for which it returns `None` or an incompatible unit are left as they are.
+    pub fn converted(&self, unit_for: impl Fn(&Quantity) -> Option<Unit>) -> Self {
+        let mut recipe = self.clone();
+        recipe.ingredients.map_quantities(|q| {
+            unit_for(q)
+                .and_then(|unit| q.convert_to(&unit))
+                .unwrap_or_else(|| q.clone())
+        });
+        recipe
+    }
+
+    /// Returns the recipe scaled by a preset, with the preset's sizes and times.
+    pub fn with_preset(&self, preset: &ScalePreset) -> Self {
+        let mut recipe = self.scaled(preset.factor);
+        let metadata = &mut recipe.metadata;
+        metadata.sizes.extend(preset.sizes.clone());
+        for (time, preset_time) in [
+            (&mut metadata.prep_time, preset.prep_time),
+            (&mut metadata.cook_time, preset.cook_time),
+            (&mut metadata.total_time, preset.total_time),
+        ] {
+            if preset_time.is_some() {
+                *time = preset_time;
+            }
+        }
+        recipe
+    }
+}
+
+/// Scales a quantity, leaving relative quantities (parts and percentages) and temperatures as
+/// they are.
+fn scale_quantity(quantity: &Quantity, factor: Amount) -> Quantity {
+    match quantity.unit {
+        Unit::Part(_) | Unit::Percent(_) | Unit::Temperature(_) => quantity.clone(),
+        _ => quantity.clone().scale(factor),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::{ingredients::Ingredients, Diagnostic};
+    use indoc::indoc;
+
+    const CAKE: &str = indoc! {"
+        ---
+        servings: 8
+        size | pan: 24 cm°
+        cook_time: 40 min
+        scales:
+          - 0.5
+          - 1
+          - factor: 2
+            size | pan: 30 cm°
+            cook_time: 55 min
+        ---
+        # Cake
+        ## Equipment
+
+        - Springform pan, pan
+
+        ## Ingredients
+
+        - Flour, 250 g
+        - Eggs, 4
+        - Salt, to taste
+
+        ## Instructions
+
+        - Bake for {1x: **40 min** | 2x: **55 min**}
+    "};
+
+    #[test]
+    fn presets() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(CAKE)?;
+        let presets = recipe.metadata().scales();
+        let factors: Vec<String> = presets.iter().map(|p| p.factor().to_string()).collect();
+        assert_eq!(factors, vec!["0.5", "1", "2"]);
+
+        let half = recipe.with_preset(&presets[0]);
+        let flour = half.ingredients().get(0).unwrap().ingredient();
+        assert_eq!(flour.quantity().unwrap().to_string(), "125 g");
+        assert_eq!(half.metadata().servings().unwrap().to_string(), "4");
+        assert_eq!(half.metadata().cook_time().unwrap().to_string(), "40 min");
+
+        let double = recipe.with_preset(&presets[2]);
+        let salt = double.ingredients().get(2).unwrap().ingredient();
+        assert!(salt.quantity().unwrap().is_to_taste());
+        assert_eq!(double.metadata().cook_time().unwrap().to_string(), "55 min");
+        let step = &double.instructions().steps()[0];
+        assert_eq!(step.description().len(), 2);
+        assert_eq!(step.text(), "Bake for 55 min");
+        let pan = &double.equipment().tools()[0];
+        let size = pan.resolve_size(double.metadata().sizes()).unwrap();
+        assert_eq!(size.to_string(), "30 cm°");
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_presets() {
+        let warnings = |md: &str| -> Vec<String> {
+            let (_, diagnostics) = Recipe::from_mdast_with_diagnostics(md, false).unwrap();
+            diagnostics.iter().map(Diagnostic::to_string).collect()
+        };
+        assert_eq!(
+            warnings(&CAKE.replace("size | pan: 30", "size | tin: 30")),
+            vec!["warning: scale preset 2 overrides unknown size \"tin\" @ 1:1-11:4\n"]
+        );
+        assert!(Recipe::from_mdast(&CAKE.replace("- 0.5", "- -1")).is_err());
+        assert!(Recipe::from_mdast(&CAKE.replace("- 0.5", "- 1")).is_err());
+        assert!(Recipe::from_mdast(&CAKE.replace("factor: 2", "servings: 2")).is_err());
+    }
+
+    #[test]
+    fn scaled_group() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(indoc! {"
+            # Pasta
+            ## Ingredients
+
+            ### Sauce (makes 200 mL)
+            - Tomatoes, 400 g
+            ### Pasta
+            - Spaghetti, 250 g
+
+            ## Instructions
+
+            - Cook
+        "})?;
+        let quantities = |recipe: &Recipe| -> Vec<String> {
+            recipe
+                .ingredients()
+                .iter()
+                .map(|o| o.ingredient().quantity().unwrap().to_string())
+                .collect()
+        };
+        let double_sauce = recipe.scaled_group("sauce", Amount::from_int(2)).unwrap();
+        assert_eq!(quantities(&double_sauce), ["800 g", "250 g"]);
+        let Ingredients::IngredientGroups(groups) = double_sauce.ingredients() else {
+            panic!("expected ingredient groups");
+        };
+        assert_eq!(groups[0].yields().unwrap().to_string(), "400 mL");
+        assert!(recipe.scaled_group("Dessert", Amount::ONE).is_none());
+
+        let half = recipe.scaled(Amount::new(1, 2));
+        assert_eq!(quantities(&half), ["200 g", "125 g"]);
+        let Ingredients::IngredientGroups(groups) = half.ingredients() else {
+            panic!("expected ingredient groups");
+        };
+        assert_eq!(groups[0].yields().unwrap().to_string(), "100 mL");
+        Ok(())
+    }
+}
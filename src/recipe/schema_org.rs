@@ -0,0 +1,643 @@
+//! Conversion to and from [schema.org `Recipe`](https://schema.org/Recipe) JSON-LD, as embedded in
+//! web pages for search engines.
+
+use std::str::FromStr;
+
+use serde_json::{json, Map, Value};
+
+use super::{
+    amount::Amount,
+    equipment::Equipment,
+    ingredients::{Ingredient, IngredientOptions, Ingredients},
+    instructions::{Instructions, Step},
+    language::Language,
+    md_parser::{MDError, MDResult},
+    metadata::{Category, Metadata},
+    notes::RichText,
+    source::Url,
+    unit::{Quantity, QuantityOf, Time, Unit},
+    Image, Recipe,
+};
+
+/// Words following the number of servings in `recipeYield` (e.g., "4 servings").
+const SERVING_WORDS: [&str; 5] = ["serving", "servings", "people", "portion", "portions"];
+/// Maximum number of words in a quantity at the start of an ingredient line (e.g., "1 1/2 cups").
+const MAX_QUANTITY_WORDS: usize = 3;
+
+impl Recipe {
+    /// Returns the recipe as a schema.org `Recipe` JSON-LD object. Instruction groups are exported
+    /// as `HowToSection`s, as are substeps, named after their parent step.
+    pub fn to_schema_org_json(&self) -> Value {
+        let meta = &self.metadata;
+        let mut json = Map::new();
+        json.insert("@context".into(), "https://schema.org".into());
+        json.insert("@type".into(), "Recipe".into());
+        json.insert("name".into(), self.name.clone().into());
+        if let Some(language) = self.language {
+            json.insert("inLanguage".into(), language.code().into());
+        }
+        if let Some(description) = self.description_text() {
+            json.insert("description".into(), description.into());
+        }
+        if !meta.aliases().is_empty() {
+            json.insert("alternateName".into(), meta.aliases().into());
+        }
+        if !meta.tags().is_empty() {
+            json.insert("keywords".into(), meta.tags().join(", ").into());
+        }
+        if let Some(category) = meta.category() {
+            json.insert("recipeCategory".into(), category.to_string().into());
+        }
+        if let Some(image) = self.image() {
+            json.insert("image".into(), image.url.clone().into());
+        }
+        if let Some(author) = meta.author() {
+            json.insert(
+                "author".into(),
+                json!({ "@type": "Person", "name": author }),
+            );
+        }
+        if let Some(source) = meta.source() {
+            json.insert("isBasedOn".into(), source.into());
+        }
+        if let Some(url) = meta.url() {
+            json.insert("url".into(), url.as_str().into());
+        }
+        let yields: Vec<String> = meta
+            .servings()
+            .map(|s| s.to_string())
+            .into_iter()
+            .chain(meta.yield_quantity().map(|y| y.to_string()))
+            .collect();
+        if !yields.is_empty() {
+            json.insert("recipeYield".into(), yields.into());
+        }
+        let total_time = meta.total_time().copied().or_else(|| self.estimated_time());
+        for (key, time) in [
+            ("prepTime", meta.prep_time().copied()),
+            ("cookTime", meta.cook_time().copied()),
+            ("totalTime", total_time),
+        ] {
+            if let Some(time) = time {
+                json.insert(key.into(), iso_duration(&time).into());
+            }
+        }
+        if let Some(nutrition) = meta.nutrition() {
+            let mut facts = Map::new();
+            facts.insert("@type".into(), "NutritionInformation".into());
+            if let Some(calories) = nutrition.calories {
+                facts.insert("calories".into(), format!("{} kcal", calories).into());
+            }
+            for (key, mass) in [
+                ("proteinContent", nutrition.protein),
+                ("fatContent", nutrition.fat),
+                ("carbohydrateContent", nutrition.carbs),
+                ("sodiumContent", nutrition.sodium),
+            ] {
+                if let Some(mass) = mass {
+                    facts.insert(key.into(), mass.to_string().into());
+                }
+            }
+            json.insert("nutrition".into(), facts.into());
+        }
+        json.insert(
+            "recipeIngredient".into(),
+            self.ingredients
+                .iter()
+                .map(|options| ingredient_text(options.ingredient()))
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        json.insert(
+            "recipeInstructions".into(),
+            instructions_json(&self.instructions).into(),
+        );
+        json.into()
+    }
+
+    /// Builds a recipe from a schema.org `Recipe` JSON-LD blob, as found in the pages of most
+    /// recipe websites. The blob may also be a list or an `@graph` containing the recipe.
+    ///
+    /// Ingredient lines such as "2 cups flour, sifted" are rewritten to this crate's syntax
+    /// ("flour, 2 cup (sifted)") before being parsed. Steps are imported as plain text, without
+    /// ingredient references or timers.
+    pub fn from_schema_org(json: &str) -> MDResult<Self> {
+        let json: Value = serde_json::from_str(json)
+            .map_err(|e| MDError::new(&format!("invalid JSON: {}", e), None).with_source(e))?;
+        let recipe = find_recipe(&json).ok_or(MDError::new("no schema.org Recipe found", None))?;
+        let name = recipe["name"]
+            .as_str()
+            .ok_or(MDError::new("missing recipe name", None))?;
+        let ingredients = strings(&recipe["recipeIngredient"])
+            .iter()
+            .map(|line| ingredient_from_text(line))
+            .collect::<MDResult<_>>()?;
+
+        let mut metadata = Metadata::default();
+        let keywords = strings(&recipe["keywords"]);
+        for keyword in keywords
+            .iter()
+            .flat_map(|k| k.split(','))
+            .filter_map(tag_from_keyword)
+        {
+            if !metadata.tags.contains(&keyword) {
+                metadata.tags.push(keyword);
+            }
+        }
+        metadata.category = strings(&recipe["recipeCategory"])
+            .into_iter()
+            .find_map(|c| Category::from_str(&c).ok());
+        for text in strings(&recipe["recipeYield"]) {
+            let words: Vec<&str> = text.split_whitespace().collect();
+            let servings = match words.split_last() {
+                Some((last, rest)) if SERVING_WORDS.contains(&&*last.to_lowercase()) => {
+                    rest.join(" ")
+                }
+                _ => text.to_string(),
+            };
+            if let Ok(servings) = QuantityOf::from_str(&servings) {
+                metadata.servings.get_or_insert(servings);
+            } else if let Ok(quantity) = Quantity::from_str(&text) {
+                metadata.yield_quantity.get_or_insert(quantity);
+            }
+        }
+        // Images are URLs or `ImageObject`s, the first of which is the cover image.
+        let images = match &recipe["image"] {
+            Value::Array(images) => images.first().cloned().unwrap_or_default(),
+            image => image.clone(),
+        };
+        metadata.image = strings(&images)
+            .into_iter()
+            .chain(strings(&images["url"]))
+            .next()
+            .map(|url| Image {
+                url,
+                alt: String::new(),
+            });
+        metadata.prep_time = recipe["prepTime"].as_str().and_then(parse_iso_duration);
+        metadata.cook_time = recipe["cookTime"].as_str().and_then(parse_iso_duration);
+        metadata.total_time = recipe["totalTime"].as_str().and_then(parse_iso_duration);
+        let description = strings(&recipe["description"])
+            .into_iter()
+            .flat_map(|text| {
+                let paragraphs: Vec<Vec<RichText>> = text
+                    .split("\n\n")
+                    .map(str::trim)
+                    .filter(|para| !para.is_empty())
+                    .map(|para| vec![RichText::Text(para.to_string())])
+                    .collect();
+                paragraphs
+            })
+            .collect();
+        metadata.aliases = strings(&recipe["alternateName"]);
+        // Authors are names, `Person`s or `Organization`s, the first of which is kept.
+        let authors = match &recipe["author"] {
+            Value::Array(authors) => authors.first().cloned().unwrap_or_default(),
+            author => author.clone(),
+        };
+        metadata.author = strings(&authors)
+            .into_iter()
+            .chain(strings(&authors["name"]))
+            .next();
+        metadata.source = strings(&recipe["isBasedOn"])
+            .into_iter()
+            .chain(strings(&recipe["isBasedOn"]["name"]))
+            .next();
+        metadata.url = strings(&recipe["url"])
+            .first()
+            .and_then(|url| Url::from_str(url).ok());
+        // Languages are IETF tags (e.g., "de-CH"), of which the primary language is enough.
+        let language = recipe["inLanguage"]
+            .as_str()
+            .and_then(|tag| Language::from_str(tag.split('-').next()?).ok());
+
+        Ok(Self {
+            name: name.trim().to_string(),
+            ingredients: Ingredients::IngredientList(ingredients),
+            equipment: Equipment::default(),
+            instructions: Instructions::new(steps_from_json(&recipe["recipeInstructions"])),
+            notes: vec![],
+            metadata,
+            cover: None,
+            description,
+            language,
+        })
+    }
+}
+
+fn find_recipe(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Object(object) => {
+            if strings(&value["@type"]).iter().any(|t| t == "Recipe") {
+                Some(value)
+            } else {
+                object.get("@graph").and_then(find_recipe)
+            }
+        }
+        Value::Array(values) => values.iter().find_map(find_recipe),
+        _ => None,
+    }
+}
+
+/// Strings of a property that may hold a single value or a list of values.
+fn strings(value: &Value) -> Vec<String> {
+    let string = |value: &Value| match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+    match value {
+        Value::Array(values) => values.iter().filter_map(string).collect(),
+        _ => string(value).into_iter().collect(),
+    }
+}
+
+/// Makes a tag out of a keyword (e.g., "Quick dinner" becomes "quick-dinner").
+fn tag_from_keyword(keyword: &str) -> Option<String> {
+    let tag: String = keyword
+        .trim()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '/' | '-' | '_'))
+        .collect();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// Parses an ingredient as written on recipe websites, with the quantity first and optional
+/// details after a comma or between parentheses (e.g., "2 cups flour, sifted").
+fn ingredient_from_text(line: &str) -> MDResult<IngredientOptions> {
+    let mut details = vec![];
+    let mut rest = String::new();
+    let mut depth = 0;
+    for c in line.chars() {
+        match c {
+            '(' => {
+                depth += 1;
+                if depth == 1 {
+                    details.push(String::new());
+                    continue;
+                }
+            }
+            ')' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        match details.last_mut() {
+            Some(detail) if depth > 0 => detail.push(c),
+            _ => rest.push(c),
+        }
+    }
+    let (mut name, mut quantity) = match rest.split_once(',') {
+        Some((name, detail)) => {
+            let detail = detail.trim();
+            match Quantity::from_str(detail) {
+                Ok(q) if q.is_to_taste() => (name.to_string(), Some(q)),
+                _ => {
+                    details.insert(0, detail.to_string());
+                    (name.to_string(), None)
+                }
+            }
+        }
+        None => (rest, None),
+    };
+    if quantity.is_none() {
+        if let Some((q, len)) = leading_quantity(&name) {
+            quantity = Some(q);
+            name = name[len..].to_string();
+        }
+    }
+
+    let sanitize = |s: &str| s.trim().replace(['/', '|'], " or ");
+    let mut text = sanitize(&name);
+    if text.is_empty() {
+        return Err(MDError::new(
+            &format!("no ingredient name in {:?}", line),
+            None,
+        ));
+    }
+    if let Some(quantity) = quantity {
+        text.push_str(&format!(", {}", quantity));
+    }
+    let details: Vec<String> = details
+        .iter()
+        .map(|d| sanitize(d).replace(['(', ')'], ""))
+        .filter(|d| !d.is_empty())
+        .collect();
+    if !details.is_empty() {
+        text.push_str(&format!(" ({})", details.join(", ")));
+    }
+    IngredientOptions::from_str(&text).map_err(|e| {
+        MDError::new(
+            &format!("failed to import ingredient {:?}: {}", line, e.message()),
+            None,
+        )
+    })
+}
+
+/// Finds the longest quantity in a known unit (or without unit) at the start of `text`, leaving at
+/// least one word for the name. Plural units are accepted (e.g., "2 cups"). Returns the quantity
+/// and the length of its text.
+fn leading_quantity(text: &str) -> Option<(Quantity, usize)> {
+    let ends: Vec<usize> = text
+        .char_indices()
+        .filter(|&(i, c)| c.is_whitespace() && i > 0 && !text[..i].ends_with(char::is_whitespace))
+        .map(|(i, _)| i)
+        .take(MAX_QUANTITY_WORDS)
+        .collect();
+    ends.iter().rev().find_map(|&end| {
+        let prefix = text[..end].trim();
+        [Some(prefix), prefix.strip_suffix('s')]
+            .into_iter()
+            .flatten()
+            .filter_map(|prefix| Quantity::from_str(prefix).ok())
+            .find(|q| !matches!(q.unit, Unit::Custom(_)))
+            .map(|q| (q, end))
+    })
+}
+
+fn steps_from_json(value: &Value) -> Vec<Step> {
+    match value {
+        Value::String(text) => text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| Step::from_text(line, vec![]))
+            .collect(),
+        Value::Array(values) => values.iter().filter_map(step_from_json).collect(),
+        _ => vec![],
+    }
+}
+
+fn step_from_json(value: &Value) -> Option<Step> {
+    match value {
+        Value::String(text) => Some(Step::from_text(text.trim(), vec![])),
+        Value::Object(object) => {
+            let text = object
+                .get("text")
+                .or(object.get("name"))
+                .and_then(Value::as_str)?;
+            let substeps = object
+                .get("itemListElement")
+                .map_or(vec![], steps_from_json);
+            Some(Step::from_text(text.trim(), substeps))
+        }
+        _ => None,
+    }
+}
+
+/// Parses an ISO 8601 duration (e.g., "PT1H30M"), expressed in the largest unit it is a whole
+/// number of.
+fn parse_iso_duration(s: &str) -> Option<QuantityOf<Time>> {
+    let rest = s.trim().strip_prefix('P')?;
+    let (mut secs, mut in_time, mut number) = (0i64, false, String::new());
+    for c in rest.chars() {
+        match c {
+            'T' if number.is_empty() => in_time = true,
+            '0'..='9' => number.push(c),
+            _ => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                let size = match (c, in_time) {
+                    ('W', false) => 7 * 86400,
+                    ('D', false) => 86400,
+                    ('H', true) => 3600,
+                    ('M', true) => 60,
+                    ('S', true) => 1,
+                    _ => return None,
+                };
+                secs = secs.checked_add(n.checked_mul(size)?)?;
+            }
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    let (unit, size) = [(Time::Day, 86400), (Time::Hour, 3600), (Time::Minute, 60)]
+        .into_iter()
+        .find(|(_, size)| secs >= *size && secs % size == 0)
+        .unwrap_or((Time::Second, 1));
+    Some(QuantityOf::new(unit, Amount::from_int(secs / size)))
+}
+
+/// Writes an ingredient the way recipe websites do (e.g., "50 mL Milk (cold)").
+fn ingredient_text(ingredient: &Ingredient) -> String {
+    let mut text = match ingredient.quantity() {
+        Some(quantity) if !quantity.is_to_taste() => {
+            format!("{} {}", quantity, ingredient.name())
+        }
+        Some(quantity) => format!("{}, {}", ingredient.name(), quantity),
+        None => ingredient.name().to_string(),
+    };
+    if let Some(info) = ingredient.info() {
+        text.push_str(&format!(" ({})", info));
+    }
+    text
+}
+
+/// Steps, with groups as `HowToSection`s.
+fn instructions_json(instructions: &Instructions) -> Vec<Value> {
+    if instructions.groups().next().is_none() {
+        return instructions.steps().iter().map(step_json).collect();
+    }
+    instructions
+        .groups()
+        .map(|(group, steps)| {
+            json!({
+                "@type": "HowToSection",
+                "name": group.name(),
+                "itemListElement": steps.iter().map(step_json).collect::<Vec<_>>(),
+            })
+        })
+        .collect()
+}
+
+fn step_json(step: &Step) -> Value {
+    if step.substeps().is_empty() {
+        json!({"@type": "HowToStep", "text": step.text()})
+    } else {
+        json!({
+            "@type": "HowToSection",
+            "name": step.text(),
+            "itemListElement": step.substeps().iter().map(step_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Formats a time as an ISO 8601 duration (e.g., "PT1H30M"). Ranges use their lower bound.
+pub(crate) fn iso_duration(time: &QuantityOf<Time>) -> String {
+    let secs = time.to_duration().as_secs();
+    let (days, hours, minutes, seconds) = (
+        secs / 86400,
+        secs % 86400 / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+    );
+    let mut iso = String::from("P");
+    if days > 0 {
+        iso.push_str(&format!("{}D", days));
+    }
+    if !secs.is_multiple_of(86400) || secs == 0 {
+        iso.push('T');
+        for (value, unit) in [(hours, 'H'), (minutes, 'M'), (seconds, 'S')] {
+            if value > 0 {
+                iso.push_str(&format!("{}{}", value, unit));
+            }
+        }
+        if secs == 0 {
+            iso.push_str("0S");
+        }
+    }
+    iso
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn durations() {
+        let iso = |s: &str| iso_duration(&QuantityOf::from_str(s).unwrap());
+        assert_eq!(iso("90 min"), "PT1H30M");
+        assert_eq!(iso("2 d"), "P2D");
+        assert_eq!(iso("1.5 d"), "P1DT12H");
+        assert_eq!(iso("0 s"), "PT0S");
+    }
+
+    #[test]
+    fn import() -> MDResult<()> {
+        let recipe = Recipe::from_schema_org(indoc! {r#"
+            {
+              "@context": "https://schema.org",
+              "@graph": [
+                {"@type": "WebPage", "name": "Pancakes | My blog"},
+                {
+                  "@type": ["Recipe"],
+                  "name": "Pancakes ",
+                  "keywords": "Breakfast, quick and easy",
+                  "recipeCategory": ["Brunch"],
+                  "recipeYield": ["8 pancakes", "4 servings"],
+                  "prepTime": "PT10M",
+                  "cookTime": "PT1H30M",
+                  "image": [{"@type": "ImageObject", "url": "https://example.org/pancakes.jpg"}],
+                  "recipeIngredient": [
+                    "1 1/2 cups flour (sifted)",
+                    "2 eggs",
+                    "250 mL milk, cold",
+                    "salt, to taste",
+                    "butter"
+                  ],
+                  "recipeInstructions": [
+                    {"@type": "HowToStep", "text": "Whisk everything together."},
+                    {
+                      "@type": "HowToSection",
+                      "name": "Cooking",
+                      "itemListElement": [{"@type": "HowToStep", "text": "Fry."}]
+                    }
+                  ]
+                }
+              ]
+            }
+        "#})?;
+        assert_eq!(recipe.name(), "Pancakes");
+        let lines: Vec<String> = recipe
+            .ingredients()
+            .iter()
+            .map(|options| ingredient_text(options.ingredient()))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "1.5 cup flour (sifted)",
+                "2 eggs",
+                "250 mL milk (cold)",
+                "salt, to taste",
+                "butter",
+            ]
+        );
+        let steps = recipe.instructions().steps();
+        assert_eq!(steps[1].text(), "Cooking");
+        assert_eq!(steps[1].substeps()[0].text(), "Fry.");
+
+        let metadata = recipe.metadata();
+        assert_eq!(metadata.tags(), ["breakfast", "quick-and-easy"]);
+        assert_eq!(metadata.category(), Some(Category::Breakfast));
+        assert_eq!(metadata.servings().unwrap().to_string(), "4");
+        assert_eq!(metadata.yield_quantity().unwrap().to_string(), "8 pancakes");
+        assert_eq!(metadata.cook_time().unwrap().to_string(), "90 min");
+        assert_eq!(recipe.to_schema_org_json()["cookTime"], "PT1H30M");
+        assert_eq!(
+            recipe.to_schema_org_json()["image"],
+            "https://example.org/pancakes.jpg"
+        );
+
+        assert!(Recipe::from_schema_org("{\"@type\": \"Person\"}").is_err());
+        assert_eq!(parse_iso_duration("P1DT12H").unwrap().to_string(), "36 h");
+        assert!(parse_iso_duration("PT1.5H").is_none());
+        assert!(parse_iso_duration("P99999999999999999W").is_none());
+        assert!(parse_iso_duration("P999999999999999999DT999999999999999999H").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn export() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(indoc! {"
+            ---
+            tags:
+              - \"#drink\"
+            servings: 4
+            prep_time: 10 min
+            ---
+            # Lemonade
+            ## Ingredients
+
+            - Lemons, 3
+            - Water, 1 L (cold)
+            - Sugar, to taste
+
+            ## Instructions
+
+            - Squeeze the *Lemons*
+            - Mix everything
+                - Stir for **2 min**
+        "})?;
+        let json = recipe.to_schema_org_json();
+        assert_eq!(json["@type"], "Recipe");
+        assert_eq!(json["keywords"], "drink");
+        assert_eq!(json["recipeYield"], json!(["4"]));
+        assert_eq!(json["prepTime"], "PT10M");
+        assert_eq!(json["totalTime"], "PT10M");
+        assert_eq!(
+            json["recipeIngredient"],
+            json!(["3 Lemons", "1 L Water (cold)", "Sugar, to taste"])
+        );
+        assert_eq!(
+            json["recipeInstructions"][1],
+            json!({
+                "@type": "HowToSection",
+                "name": "Mix everything",
+                "itemListElement": [{"@type": "HowToStep", "text": "Stir for 2 min"}],
+            })
+        );
+
+        let grouped = Recipe::from_mdast(
+            "# Tea\n## Ingredients\n\n- Tea\n\n## Instructions\n\n### Brew\n- Steep\n",
+        )?;
+        assert_eq!(
+            grouped.to_schema_org_json()["recipeInstructions"],
+            json!([{
+                "@type": "HowToSection",
+                "name": "Brew",
+                "itemListElement": [{"@type": "HowToStep", "text": "Steep"}],
+            }])
+        );
+        Ok(())
+    }
+}
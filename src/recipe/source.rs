@@ -0,0 +1,122 @@
+//! Where a recipe comes from, from the frontmatter:
+//!
+//! ```yaml
+//! source: Salt, Fat, Acid, Heat (p. 123)
+//! author: Samin Nosrat
+//! url: https://example.com/recipes/focaccia
+//! ```
+//!
+//! A `source` that is a URL (as clippers often write it) is taken as the `url`.
+
+use std::{fmt, str::FromStr};
+
+use super::md_parser::MDError;
+
+pub(super) const SOURCE: &str = "source";
+pub(super) const AUTHOR: &str = "author";
+pub(super) const URL: &str = "url";
+
+/// Absolute URL, with a scheme and a host (e.g., "https://example.com/recipes/focaccia").
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Url(String);
+
+impl Url {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Scheme, in lowercase (e.g., "https").
+    pub fn scheme(&self) -> &str {
+        self.0.split_once("://").map_or("", |(scheme, _)| scheme)
+    }
+
+    /// Host, without credentials or port (e.g., "example.com").
+    pub fn host(&self) -> &str {
+        self.host_and_port().0
+    }
+
+    fn host_and_port(&self) -> (&str, &str) {
+        let rest = self.0.split_once("://").map_or("", |(_, rest)| rest);
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = authority.rsplit('@').next().unwrap_or_default();
+        // IPv6 addresses are bracketed, as they contain colons.
+        let end = match host.starts_with('[') {
+            true => host.find(']').map_or(host.len(), |i| i + 1),
+            false => host.find(':').unwrap_or(host.len()),
+        };
+        host.split_at(end)
+    }
+}
+
+impl FromStr for Url {
+    type Err = MDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid =
+            |reason: &str| MDError::new(&format!("invalid URL {:?}: {}", s, reason), None);
+        if s.chars().any(char::is_whitespace) {
+            return Err(invalid("contains whitespace"));
+        }
+        let (scheme, _) = s
+            .split_once("://")
+            .ok_or_else(|| invalid("missing scheme"))?;
+        let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if !valid_scheme {
+            return Err(invalid("invalid scheme"));
+        }
+        let url = Self(format!("{}{}", scheme.to_lowercase(), &s[scheme.len()..]));
+        let (host, port) = url.host_and_port();
+        let valid_host = host.starts_with('[') && host.ends_with(']')
+            || !host.is_empty()
+                && host
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '-' | '.' | '_'));
+        if !valid_host {
+            return Err(invalid("invalid host"));
+        }
+        let valid_port = port.is_empty()
+            || port
+                .strip_prefix(':')
+                .is_some_and(|port| !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()));
+        if !valid_port {
+            return Err(invalid("invalid port"));
+        }
+        Ok(url)
+    }
+}
+
+impl fmt::Display for Url {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls() -> Result<(), MDError> {
+        let url = Url::from_str(" HTTPS://example.com/recipes/flan?print=1 ")?;
+        assert_eq!(url.as_str(), "https://example.com/recipes/flan?print=1");
+        assert_eq!(url.scheme(), "https");
+        assert_eq!(url.host(), "example.com");
+        assert_eq!(
+            Url::from_str("http://me@localhost:8080")?.host(),
+            "localhost"
+        );
+        assert_eq!(Url::from_str("http://[::1]:8080/")?.host(), "[::1]");
+        assert_eq!(Url::from_str("https://café.fr")?.host(), "café.fr");
+
+        assert!(Url::from_str("example.com/recipes").is_err());
+        assert!(Url::from_str("https://").is_err());
+        assert!(Url::from_str("https://exa mple.com").is_err());
+        assert!(Url::from_str("1http://example.com").is_err());
+        assert!(Url::from_str("https://example.com:port").is_err());
+        Ok(())
+    }
+}
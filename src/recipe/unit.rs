@@ -1,4 +1,16 @@
-use std::{error, fmt, num::ParseFloatError, str::FromStr};
+use super::{
+    amount::{Amount, NumberFormat},
+    language::Language,
+    md_parser::{MDError, MDResult},
+};
+use crate::{
+    canonical,
+    library::bundle::{Bundle, Conflict, MergePolicy, UNIT_SIZES},
+};
+use std::{
+    cmp::Ordering, collections::HashMap, error, fmt, num::ParseFloatError, ops::Add, str::FromStr,
+    time::Duration,
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Unit {
@@ -8,6 +20,14 @@ pub enum Unit {
     Distance(Distance),
     Temperature(Temperature),
     Time(Time),
+    Part(Part),
+    Percent(Percent),
+    /// A number of items of some size or form (e.g., "2 large" eggs, "3 cloves" of garlic), as
+    /// written. Counts of a size are compatible with plain counts and counts of the same size, while
+    /// counts of a form are only compatible with each other.
+    Count(String),
+    /// A unit defined in a [`UnitRegistry`] (e.g., a "stick" of butter as 113 g).
+    Registered(RegisteredUnit),
     Custom(String),
 }
 
@@ -27,15 +47,119 @@ impl FromStr for Unit {
             Ok(Self::Temperature(unit))
         } else if let Ok(unit) = Time::from_str(s) {
             Ok(Self::Time(unit))
+        } else if let Ok(unit) = Part::from_str(s) {
+            Ok(Self::Part(unit))
+        } else if let Ok(unit) = Percent::from_str(s) {
+            Ok(Self::Percent(unit))
+        } else if count_form(s).is_some() || is_count_size(s) {
+            Ok(Self::Count(s.to_string()))
         } else {
             Err(())
         }
     }
 }
 
-type FnUnit = fn(f32) -> f32;
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nominal(unit) => unit.fmt(f),
+            Self::Mass(unit) => unit.fmt(f),
+            Self::Volume(unit) => unit.fmt(f),
+            Self::Distance(unit) => unit.fmt(f),
+            Self::Temperature(unit) => unit.fmt(f),
+            Self::Time(unit) => unit.fmt(f),
+            Self::Part(unit) => unit.fmt(f),
+            Self::Percent(unit) => unit.fmt(f),
+            Self::Registered(unit) => f.write_str(&unit.name),
+            Self::Count(unit) | Self::Custom(unit) => f.write_str(unit),
+        }
+    }
+}
+
+type FnUnit = fn(Amount) -> Amount;
+
+/// Physical dimension of a unit, which units must share to be converted to one another.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    Mass,
+    Volume,
+    Length,
+    Temperature,
+    Time,
+    /// Numbers of items, with or without a size or form.
+    Count,
+    /// Parts, percentages, and custom units.
+    Other,
+}
 
 impl Unit {
+    /// Names of the unit, unless it is nominal, a part, a percentage, or a custom unit.
+    pub fn names(&self) -> Option<UnitNames> {
+        match self {
+            Self::Mass(unit) => Some(unit.names()),
+            Self::Volume(unit) => Some(unit.names()),
+            Self::Distance(unit) => Some(unit.names()),
+            Self::Temperature(unit) => Some(unit.names()),
+            Self::Time(unit) => Some(unit.names()),
+            Self::Nominal(_)
+            | Self::Part(_)
+            | Self::Percent(_)
+            | Self::Count(_)
+            | Self::Registered(_)
+            | Self::Custom(_) => None,
+        }
+    }
+
+    /// Names of the unit in a language, which are the English ones unless the language has its
+    /// own (e.g., "EL" for tablespoons in German).
+    pub fn names_in(&self, language: Language) -> Option<UnitNames> {
+        language.names_of(self).or_else(|| self.names())
+    }
+
+    /// Parses a unit written in a language, as [`Unit::from`] does for English.
+    pub fn from_str_in(s: &str, language: Language) -> Self {
+        language.unit(s).unwrap_or_else(|| Self::from(s))
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Self::Nominal(_) | Self::Count(_) => Dimension::Count,
+            Self::Registered(unit) => unit.unit.dimension(),
+            Self::Mass(_) => Dimension::Mass,
+            Self::Volume(_) => Dimension::Volume,
+            Self::Distance(_) => Dimension::Length,
+            Self::Temperature(_) => Dimension::Temperature,
+            Self::Time(_) => Dimension::Time,
+            Self::Part(_) | Self::Percent(_) | Self::Custom(_) => Dimension::Other,
+        }
+    }
+
+    /// Whether quantities in the unit can be converted to the other unit. Units of the same
+    /// dimension may still be incompatible (e.g., "cloves" and "heads", or two custom units).
+    pub fn is_compatible_with(&self, other: &Self) -> bool {
+        let sizes_differ = match (self.count_size(), other.count_size()) {
+            (Some(size), Some(other_size)) => size != other_size,
+            _ => false,
+        };
+        !sizes_differ && self.to_base().0 == other.to_base().0
+    }
+
+    /// Size of counted items (e.g., "large"), normalized so that spellings of the same size
+    /// compare equal.
+    fn count_size(&self) -> Option<String> {
+        match self {
+            Self::Count(unit) if is_count_size(unit) => {
+                Some(unit.to_ascii_lowercase().replace('-', " "))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the unit is a rough kitchen measure (see [`Volume::is_approximate`]).
+    pub fn is_approximate(&self) -> bool {
+        matches!(self, Self::Volume(volume) if volume.is_approximate())
+    }
+
     pub fn sanitize(self) -> (Self, FnUnit) {
         match self {
             Self::Nominal(nominal) => {
@@ -62,7 +186,57 @@ impl Unit {
                 let (unit, fn_unit) = time.sanitize();
                 (Self::Time(unit), fn_unit)
             }
-            Self::Custom(_) => (self, |q| q),
+            Self::Part(_)
+            | Self::Percent(_)
+            | Self::Count(_)
+            | Self::Registered(_)
+            | Self::Custom(_) => (self, |q| q),
+        }
+    }
+
+    /// Returns the base unit of the unit's kind and how to convert to it. Custom units are their
+    /// own base unit.
+    pub fn to_base(&self) -> (Self, BaseConversion) {
+        match self {
+            Self::Nominal(unit) => {
+                let (base, conv) = unit.to_base();
+                (Self::Nominal(base), conv)
+            }
+            Self::Mass(unit) => {
+                let (base, conv) = unit.to_base();
+                (Self::Mass(base), conv)
+            }
+            Self::Volume(unit) => {
+                let (base, conv) = unit.to_base();
+                (Self::Volume(base), conv)
+            }
+            Self::Distance(unit) => {
+                let (base, conv) = unit.to_base();
+                (Self::Distance(base), conv)
+            }
+            Self::Temperature(unit) => {
+                let (base, conv) = unit.to_base();
+                (Self::Temperature(base), conv)
+            }
+            Self::Time(unit) => {
+                let (base, conv) = unit.to_base();
+                (Self::Time(base), conv)
+            }
+            Self::Registered(unit) => {
+                let (base, conv) = unit.unit.to_base();
+                let conv = BaseConversion {
+                    factor: unit.amount * conv.factor,
+                    offset: conv.offset,
+                };
+                (base, conv)
+            }
+            Self::Count(unit) => match count_form(unit) {
+                Some(form) => (Self::Count(form.to_string()), BaseConversion::IDENTITY),
+                None => (Self::Nominal(Nominal), BaseConversion::IDENTITY),
+            },
+            Self::Part(_) | Self::Percent(_) | Self::Custom(_) => {
+                (self.clone(), BaseConversion::IDENTITY)
+            }
         }
     }
 }
@@ -76,10 +250,217 @@ impl From<&str> for Unit {
     }
 }
 
-pub trait UnitTrait<'a>: Clone + FromStr<Err = ()> {
+/// A unit defined as an amount of another unit, under the name it was written with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegisteredUnit {
+    name: String,
+    amount: Amount,
+    unit: Box<Unit>,
+}
+
+impl RegisteredUnit {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Size of one unit (e.g., "113 g" for a stick of butter).
+    pub fn size(&self) -> Quantity {
+        Quantity::new(&self.unit, self.amount)
+    }
+}
+
+/// Custom units defined by the user of the crate, which quantities in these units can be
+/// converted through (e.g., "2 sticks" of butter to "226 g").
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UnitRegistry {
+    /// Sizes by lowercase name.
+    units: HashMap<String, (Amount, Unit)>,
+}
+
+impl UnitRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines a unit under several names (e.g., "stick" and "sticks") as a quantity of another
+    /// unit. Units defined through others are flattened; ranges and approximations of the size
+    /// are ignored, and so are units whose size is not positive, which nothing converts to.
+    pub fn register(mut self, names: &[&str], size: &Quantity) -> Self {
+        let (amount, unit) = match &size.unit {
+            Unit::Registered(unit) => (size.amount * unit.amount, (*unit.unit).clone()),
+            unit => (size.amount, unit.clone()),
+        };
+        if amount <= Amount::ZERO {
+            return self;
+        }
+        for name in names {
+            self.units
+                .insert(name.to_lowercase(), (amount, unit.clone()));
+        }
+        self
+    }
+
+    /// The unit registered under a name (compared case-insensitively), named as given.
+    pub fn get(&self, name: &str) -> Option<Unit> {
+        let (amount, unit) = self.units.get(&name.to_lowercase())?;
+        Some(Unit::Registered(RegisteredUnit {
+            name: name.to_string(),
+            amount: *amount,
+            unit: Box::new(unit.clone()),
+        }))
+    }
+
+    /// Units of the `unit-sizes` table of a bundle, with sizes like "113 g".
+    pub fn from_bundle(bundle: &Bundle) -> MDResult<Self> {
+        let mut this = Self::new();
+        for (name, value) in bundle.table(UNIT_SIZES).into_iter().flatten() {
+            let invalid =
+                || MDError::new(&format!("invalid size of {:?}: {:?}", name, value), None);
+            let size = Quantity::from_str(value).map_err(|_| invalid())?;
+            if size.is_range() || size.amount <= Amount::ZERO {
+                return Err(invalid());
+            }
+            this = this.register(&[name], &size);
+        }
+        Ok(this)
+    }
+
+    /// Bundle with the units in its `unit-sizes` table, to share them (see
+    /// [`UnitRegistry::from_bundle`]).
+    pub fn to_bundle(&self) -> Bundle {
+        let mut bundle = Bundle::new();
+        for (name, (amount, unit)) in &self.units {
+            bundle.insert(
+                UNIT_SIZES,
+                name,
+                &canonical::quantity(&Quantity::new(unit, *amount)),
+            );
+        }
+        bundle
+    }
+
+    /// Adds the units of a bundle, returning the units defined with different sizes in both,
+    /// whose size is picked according to `policy`.
+    pub fn merge_bundle(
+        &mut self,
+        bundle: &Bundle,
+        policy: MergePolicy,
+    ) -> MDResult<Vec<Conflict>> {
+        let mut merged = self.to_bundle();
+        let conflicts = merged.merge(Self::from_bundle(bundle)?.to_bundle(), policy);
+        *self = Self::from_bundle(&merged)?;
+        Ok(conflicts)
+    }
+
+    /// Replaces a custom unit (or a count, e.g., "cans") by the registered unit of the same name.
+    pub fn resolve(&self, quantity: Quantity) -> Quantity {
+        let unit = match &quantity.unit {
+            Unit::Custom(name) | Unit::Count(name) => self.get(name),
+            _ => None,
+        };
+        match unit {
+            Some(unit) => Quantity { unit, ..quantity },
+            None => quantity,
+        }
+    }
+}
+
+/// Affine conversion of an amount to the base unit of its kind, such that
+/// `base_amount = amount * factor + offset`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BaseConversion {
+    pub factor: Amount,
+    pub offset: Amount,
+}
+
+impl BaseConversion {
+    const IDENTITY: Self = Self::factor(Amount::ONE);
+
+    const fn factor(factor: Amount) -> Self {
+        Self {
+            factor,
+            offset: Amount::ZERO,
+        }
+    }
+
+    fn apply(&self, amount: Amount) -> Amount {
+        amount * self.factor + self.offset
+    }
+
+    fn invert(&self, amount: Amount) -> Amount {
+        (amount - self.offset) / self.factor
+    }
+}
+
+pub trait UnitTrait<'a>: Clone + FromStr<Err = ()> + fmt::Display {
     fn sanitize(self) -> (Self, FnUnit) {
         (self.clone(), |q| q)
     }
+
+    /// Returns the base unit of the unit's kind (e.g., grams for masses) and how to convert to it.
+    fn to_base(&self) -> (Self, BaseConversion) {
+        (self.clone(), BaseConversion::IDENTITY)
+    }
+}
+
+/// Names of a unit, from which it is parsed case-insensitively.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UnitNames {
+    /// Name the unit is displayed with (e.g., "tbsp").
+    pub symbol: &'static str,
+    pub singular: &'static str,
+    pub plural: &'static str,
+    /// Other spellings (e.g., "tbs").
+    pub aliases: &'static [&'static str],
+}
+
+impl UnitNames {
+    pub(super) const fn new(
+        symbol: &'static str,
+        singular: &'static str,
+        plural: &'static str,
+        aliases: &'static [&'static str],
+    ) -> Self {
+        Self {
+            symbol,
+            singular,
+            plural,
+            aliases,
+        }
+    }
+
+    /// Every name, symbol first.
+    pub fn all(&self) -> impl Iterator<Item = &'static str> {
+        [self.symbol, self.singular, self.plural]
+            .into_iter()
+            .chain(self.aliases.iter().copied())
+    }
+
+    /// Long name for an amount (e.g., "tablespoon" for 1, "tablespoons" for 2).
+    pub fn long(&self, amount: Amount) -> &'static str {
+        match amount == Amount::ONE {
+            true => self.singular,
+            false => self.plural,
+        }
+    }
+}
+
+/// Unit kinds whose units are listed with their names, so that they can be parsed from and
+/// displayed with any of them.
+pub trait NamedUnit: Sized + Copy + 'static {
+    /// Every unit of the kind.
+    const ALL: &'static [Self];
+
+    fn names(self) -> UnitNames;
+
+    /// Finds the unit with a name, case-insensitively.
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|unit| unit.names().all().any(|n| n.to_lowercase() == name))
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -99,36 +480,168 @@ impl FromStr for Nominal {
 
 impl UnitTrait<'_> for Nominal {}
 
+impl fmt::Display for Nominal {
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// Proportion of a mix (e.g., "2 parts gin, 1 part vermouth"), which only takes a concrete size
+/// once the size of a part or of the whole mix is chosen.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Part;
+
+impl FromStr for Part {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.to_lowercase()[..] {
+            "part" | "parts" => Ok(Self),
+            _ => Err(()),
+        }
+    }
+}
+
+impl UnitTrait<'_> for Part {}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("parts")
+    }
+}
+
+/// Quantity relative to another one, such as a baker's percentage (e.g., "Water, 75%" for 75% of
+/// the flour's weight).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Percent;
+
+const PERCENT: &str = "%";
+
+/// Sizes describing counted items (e.g., "2 large").
+const COUNT_SIZES: [&str; 8] = [
+    "small",
+    "medium",
+    "large",
+    "extra-large",
+    "extra large",
+    "big",
+    "jumbo",
+    "whole",
+];
+
+/// Forms of counted items, singular and plural (e.g., "3 cloves").
+const COUNT_FORMS: [(&str, &str); 6] = [
+    ("clove", "cloves"),
+    ("slice", "slices"),
+    ("piece", "pieces"),
+    ("stalk", "stalks"),
+    ("head", "heads"),
+    ("can", "cans"),
+];
+
+fn is_count_size(s: &str) -> bool {
+    COUNT_SIZES.iter().any(|size| size.eq_ignore_ascii_case(s))
+}
+
+/// Singular of a form of counted items, if the string is one.
+fn count_form(s: &str) -> Option<&'static str> {
+    COUNT_FORMS
+        .iter()
+        .find(|(one, many)| one.eq_ignore_ascii_case(s) || many.eq_ignore_ascii_case(s))
+        .map(|(one, _)| *one)
+}
+
+impl FromStr for Percent {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            PERCENT => Ok(Self),
+            _ => Err(()),
+        }
+    }
+}
+
+impl UnitTrait<'_> for Percent {}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(PERCENT)
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Mass {
+    Microgram,
+    Milligram,
     Gram,
+    /// Ten grams, common in central European recipes.
+    Decagram,
     Kilogram,
     Ounce,
     Pound,
 }
 
+impl NamedUnit for Mass {
+    const ALL: &'static [Self] = &[
+        Self::Microgram,
+        Self::Milligram,
+        Self::Gram,
+        Self::Decagram,
+        Self::Kilogram,
+        Self::Ounce,
+        Self::Pound,
+    ];
+
+    fn names(self) -> UnitNames {
+        match self {
+            // Both the micro sign and the Greek letter mu are used for "micro".
+            Self::Microgram => UnitNames::new("µg", "microgram", "micrograms", &["μg", "mcg"]),
+            Self::Milligram => UnitNames::new("mg", "milligram", "milligrams", &[]),
+            Self::Gram => UnitNames::new("g", "gram", "grams", &["gramme", "grammes"]),
+            Self::Decagram => UnitNames::new("dag", "decagram", "decagrams", &["dkg"]),
+            Self::Kilogram => UnitNames::new("kg", "kilogram", "kilograms", &["kilo", "kilos"]),
+            Self::Ounce => UnitNames::new("oz", "ounce", "ounces", &[]),
+            Self::Pound => UnitNames::new("lbs", "pound", "pounds", &["lb"]),
+        }
+    }
+}
+
 impl FromStr for Mass {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match &s.to_lowercase()[..] {
-            "g" => Ok(Self::Gram),
-            "kg" => Ok(Self::Kilogram),
-            "oz" => Ok(Self::Ounce),
-            "lbs" => Ok(Self::Pound),
-            _ => Err(()),
-        }
+        Self::from_name(s).ok_or(())
+    }
+}
+
+impl fmt::Display for Mass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.names().symbol)
     }
 }
 
 impl UnitTrait<'_> for Mass {
     fn sanitize(self) -> (Self, FnUnit) {
         match self {
-            Self::Ounce => (Self::Gram, |q| q * 28.),
-            Self::Pound => (Self::Gram, |q| q * 450.),
+            Self::Ounce => (Self::Gram, |q| q * Amount::from_int(28)),
+            Self::Pound => (Self::Gram, |q| q * Amount::from_int(450)),
             _ => (self, |q| q),
         }
     }
+
+    fn to_base(&self) -> (Self, BaseConversion) {
+        let factor = match self {
+            Self::Microgram => Amount::new(1, 1_000_000),
+            Self::Milligram => Amount::new(1, 1000),
+            Self::Gram => Amount::ONE,
+            Self::Decagram => Amount::from_int(10),
+            Self::Kilogram => Amount::from_int(1000),
+            Self::Ounce => Amount::from_int(28),
+            Self::Pound => Amount::from_int(450),
+        };
+        (Self::Gram, BaseConversion::factor(factor))
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -141,38 +654,118 @@ pub enum Volume {
     FluidOunce,
     Cup,
     Gallon,
+    /// Kitchen measures that are only roughly a volume.
+    Drop,
+    Dash,
+    Pinch,
+    Handful,
+}
+
+impl Volume {
+    /// Whether the unit is a rough kitchen measure (e.g., a pinch), whose conversions to other
+    /// volumes are only indicative.
+    pub fn is_approximate(self) -> bool {
+        matches!(self, Self::Drop | Self::Dash | Self::Pinch | Self::Handful)
+    }
+}
+
+impl NamedUnit for Volume {
+    const ALL: &'static [Self] = &[
+        Self::Milliliter,
+        Self::Centiliter,
+        Self::Liter,
+        Self::Teaspoon,
+        Self::Tablespoon,
+        Self::FluidOunce,
+        Self::Cup,
+        Self::Gallon,
+        Self::Drop,
+        Self::Dash,
+        Self::Pinch,
+        Self::Handful,
+    ];
+
+    fn names(self) -> UnitNames {
+        match self {
+            Self::Milliliter => UnitNames::new(
+                "mL",
+                "milliliter",
+                "milliliters",
+                &["millilitre", "millilitres"],
+            ),
+            Self::Centiliter => UnitNames::new(
+                "cL",
+                "centiliter",
+                "centiliters",
+                &["centilitre", "centilitres"],
+            ),
+            Self::Liter => UnitNames::new("L", "liter", "liters", &["litre", "litres"]),
+            Self::Teaspoon => UnitNames::new("tsp", "teaspoon", "teaspoons", &["tsps"]),
+            Self::Tablespoon => {
+                UnitNames::new("tbsp", "tablespoon", "tablespoons", &["tbsps", "tbs"])
+            }
+            Self::FluidOunce => UnitNames::new(
+                "fl oz",
+                "fluid ounce",
+                "fluid ounces",
+                &["fl. oz.", "fl. oz"],
+            ),
+            Self::Cup => UnitNames::new("cup", "cup", "cups", &[]),
+            Self::Gallon => UnitNames::new("gal", "gallon", "gallons", &[]),
+            Self::Drop => UnitNames::new("drop", "drop", "drops", &[]),
+            Self::Dash => UnitNames::new("dash", "dash", "dashes", &[]),
+            Self::Pinch => UnitNames::new("pinch", "pinch", "pinches", &[]),
+            Self::Handful => UnitNames::new("handful", "handful", "handfuls", &[]),
+        }
+    }
 }
 
 impl FromStr for Volume {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match &s.to_lowercase()[..] {
-            "ml" => Ok(Self::Milliliter),
-            "cl" => Ok(Self::Centiliter),
-            "l" => Ok(Self::Liter),
-            "tsp" => Ok(Self::Teaspoon),
-            "tbsp" => Ok(Self::Tablespoon),
-            "fl oz" | "fl. oz." => Ok(Self::FluidOunce),
-            "cup" => Ok(Self::Cup),
-            "gal" => Ok(Self::Gallon),
-            _ => Err(()),
-        }
+        Self::from_name(s).ok_or(())
+    }
+}
+
+impl fmt::Display for Volume {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.names().symbol)
     }
 }
 
 impl UnitTrait<'_> for Volume {
     fn sanitize(self) -> (Self, FnUnit) {
         match self {
-            Self::Teaspoon => (Self::Milliliter, |q| q * 5.),
-            Self::Tablespoon => (Self::Milliliter, |q| q * 15.),
-            Self::Cup => (Self::Milliliter, |q| q * 240.),
+            Self::Teaspoon => (Self::Milliliter, |q| q * Amount::from_int(5)),
+            Self::Tablespoon => (Self::Milliliter, |q| q * Amount::from_int(15)),
+            Self::Cup => (Self::Milliliter, |q| q * Amount::from_int(240)),
             // Halfway between US and UK conventions; for more precision, use a better unit.
-            Self::FluidOunce => (Self::Milliliter, |q| q * 29.),
-            Self::Gallon => (Self::Liter, |q| q * 3.785),
+            Self::FluidOunce => (Self::Milliliter, |q| q * Amount::from_int(29)),
+            Self::Gallon => (Self::Liter, |q| q * Amount::new(3785, 1000)),
             _ => (self, |q| q),
         }
     }
+
+    fn to_base(&self) -> (Self, BaseConversion) {
+        let factor = match self {
+            Self::Milliliter => Amount::ONE,
+            Self::Centiliter => Amount::from_int(10),
+            Self::Liter => Amount::from_int(1000),
+            Self::Teaspoon => Amount::from_int(5),
+            Self::Tablespoon => Amount::from_int(15),
+            Self::FluidOunce => Amount::from_int(29),
+            Self::Cup => Amount::from_int(240),
+            Self::Gallon => Amount::from_int(3785),
+            // Common rules of thumb: 20 drops to the mL, 8 dashes or 16 pinches to the teaspoon,
+            // and a quarter cup to the handful.
+            Self::Drop => Amount::new(1, 20),
+            Self::Dash => Amount::new(5, 8),
+            Self::Pinch => Amount::new(5, 16),
+            Self::Handful => Amount::from_int(60),
+        };
+        (Self::Milliliter, BaseConversion::factor(factor))
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -182,120 +775,648 @@ pub enum Distance {
     Inches,
 }
 
+impl NamedUnit for Distance {
+    const ALL: &'static [Self] = &[Self::Millimeter, Self::Centimeter, Self::Inches];
+
+    fn names(self) -> UnitNames {
+        match self {
+            Self::Millimeter => UnitNames::new(
+                "mm",
+                "millimeter",
+                "millimeters",
+                &["millimetre", "millimetres"],
+            ),
+            Self::Centimeter => UnitNames::new(
+                "cm",
+                "centimeter",
+                "centimeters",
+                &["centimetre", "centimetres"],
+            ),
+            Self::Inches => UnitNames::new("in", "inch", "inches", &["in."]),
+        }
+    }
+}
+
 impl FromStr for Distance {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match &s.to_lowercase()[..] {
-            "mm" => Ok(Self::Millimeter),
-            "cm" => Ok(Self::Centimeter),
-            "in" => Ok(Self::Inches),
-            _ => Err(()),
-        }
+        Self::from_name(s).ok_or(())
+    }
+}
+
+impl fmt::Display for Distance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.names().symbol)
     }
 }
 
 impl UnitTrait<'_> for Distance {
     fn sanitize(self) -> (Self, FnUnit) {
         match self {
-            Self::Inches => (Self::Centimeter, |q| q * 2.5),
+            Self::Inches => (Self::Centimeter, |q| q * Amount::new(5, 2)),
             _ => (self, |q| q),
         }
     }
+
+    fn to_base(&self) -> (Self, BaseConversion) {
+        let factor = match self {
+            Self::Millimeter => 1,
+            Self::Centimeter => 10,
+            Self::Inches => 25,
+        };
+        (
+            Self::Millimeter,
+            BaseConversion::factor(Amount::from_int(factor)),
+        )
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Temperature {
     Celsius,
     Farenheit,
+    Kelvin,
+    /// Oven setting of British gas ovens, written before the amount (e.g., "gas mark 4").
+    GasMark,
+}
+
+/// Gas mark n is taken to be 250 + 25n °F, which is exact from gas mark 1 up.
+const GAS_MARK_TO_CELSIUS: BaseConversion = BaseConversion {
+    factor: Amount::new(125, 9),
+    offset: Amount::new(1090, 9),
+};
+
+const KELVIN_TO_CELSIUS: BaseConversion = BaseConversion {
+    factor: Amount::ONE,
+    offset: Amount::new(-5463, 20),
+};
+
+impl NamedUnit for Temperature {
+    const ALL: &'static [Self] = &[Self::Celsius, Self::Farenheit, Self::Kelvin, Self::GasMark];
+
+    fn names(self) -> UnitNames {
+        match self {
+            Self::Celsius => UnitNames::new(
+                "°C",
+                "degree Celsius",
+                "degrees Celsius",
+                &["c", "celsius", "degrees c"],
+            ),
+            Self::Farenheit => UnitNames::new(
+                "°F",
+                "degree Fahrenheit",
+                "degrees Fahrenheit",
+                &["f", "fahrenheit", "degrees f"],
+            ),
+            Self::Kelvin => UnitNames::new("K", "kelvin", "kelvins", &[]),
+            Self::GasMark => UnitNames::new(GAS_MARK, GAS_MARK, GAS_MARK, &[]),
+        }
+    }
 }
 
 impl FromStr for Temperature {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match &s.to_lowercase()[..] {
-            "°c" | "c" => Ok(Self::Celsius),
-            "°f" | "f" => Ok(Self::Farenheit),
-            _ => Err(()),
+        Self::from_name(s).ok_or(())
+    }
+}
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.names().symbol)
+    }
+}
+
+impl UnitTrait<'_> for Temperature {
+    fn sanitize(self) -> (Self, FnUnit) {
+        match self {
+            Self::Farenheit => (Self::Celsius, |f| {
+                (f - Amount::from_int(32)) * Amount::new(5, 9)
+            }),
+            Self::Kelvin => (Self::Celsius, |k| KELVIN_TO_CELSIUS.apply(k)),
+            Self::GasMark => (Self::Celsius, |g| GAS_MARK_TO_CELSIUS.apply(g)),
+            _ => (self, |q| q),
+        }
+    }
+
+    fn to_base(&self) -> (Self, BaseConversion) {
+        match self {
+            Self::Celsius => (Self::Celsius, BaseConversion::IDENTITY),
+            Self::Farenheit => (
+                Self::Celsius,
+                BaseConversion {
+                    factor: Amount::new(5, 9),
+                    offset: Amount::new(-160, 9),
+                },
+            ),
+            Self::Kelvin => (Self::Celsius, KELVIN_TO_CELSIUS),
+            Self::GasMark => (Self::Celsius, GAS_MARK_TO_CELSIUS),
         }
     }
-}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Time {
+    Second,
+    Minute,
+    Hour,
+    Day,
+}
+
+impl NamedUnit for Time {
+    const ALL: &'static [Self] = &[Self::Second, Self::Minute, Self::Hour, Self::Day];
+
+    fn names(self) -> UnitNames {
+        match self {
+            Self::Second => UnitNames::new("s", "second", "seconds", &["sec", "sec.", "secs"]),
+            Self::Minute => UnitNames::new("min", "minute", "minutes", &["min.", "mins"]),
+            Self::Hour => UnitNames::new("h", "hour", "hours", &["hr", "hrs"]),
+            Self::Day => UnitNames::new("d", "day", "days", &[]),
+        }
+    }
+}
+
+impl FromStr for Time {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s).ok_or(())
+    }
+}
+
+impl UnitTrait<'_> for Time {
+    fn to_base(&self) -> (Self, BaseConversion) {
+        let factor = match self {
+            Self::Second => 1,
+            Self::Minute => 60,
+            Self::Hour => 3600,
+            Self::Day => 86400,
+        };
+        (
+            Self::Second,
+            BaseConversion::factor(Amount::from_int(factor)),
+        )
+    }
+}
+
+impl QuantityOf<Time> {
+    pub fn as_seconds(&self) -> Amount {
+        self.unit.to_base().1.apply(self.amount)
+    }
+
+    /// Converts the time to a duration. For ranges (e.g., "30-40 min"), this is the lower bound.
+    /// Times too long for a [`Duration`] saturate to [`Duration::MAX`].
+    pub fn to_duration(&self) -> Duration {
+        Duration::try_from_secs_f64(self.as_seconds().to_f64().max(0.0)).unwrap_or(Duration::MAX)
+    }
+}
+
+/// Adds two durations, in the unit of the left-hand side. The sum is a range if either duration is.
+impl Add for QuantityOf<Time> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        add_in_unit(self, other)
+    }
+}
+
+impl QuantityOf<Temperature> {
+    /// Converts the temperature to another scale (e.g., "350 °F" to "176.67 °C").
+    pub fn convert_to(&self, unit: Temperature) -> Self {
+        let (_, from) = self.unit.to_base();
+        let (_, to) = unit.to_base();
+        let convert = |amount| to.invert(from.apply(amount));
+        Self {
+            unit,
+            amount: convert(self.amount),
+            max_amount: self.max_amount.map(convert),
+        }
+    }
+}
+
+impl QuantityOf<Mass> {
+    pub fn as_grams(&self) -> Amount {
+        self.unit.to_base().1.apply(self.amount)
+    }
+}
+
+/// Adds two masses, in the unit of the left-hand side. The sum is a range if either mass is.
+impl Add for QuantityOf<Mass> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        add_in_unit(self, other)
+    }
+}
+
+/// Adds two quantities of units converted by a factor (unlike temperatures), in the unit of the
+/// left-hand side.
+fn add_in_unit<T: for<'a> UnitTrait<'a>>(
+    this: QuantityOf<T>,
+    other: QuantityOf<T>,
+) -> QuantityOf<T> {
+    let (_, to) = this.unit.to_base();
+    let (_, from) = other.unit.to_base();
+    let convert = |amount| to.invert(from.apply(amount));
+    let max = |q: &QuantityOf<T>| q.max_amount.unwrap_or(q.amount);
+    QuantityOf {
+        amount: this.amount + convert(other.amount),
+        max_amount: (this.is_range() || other.is_range())
+            .then(|| max(&this) + convert(max(&other))),
+        unit: this.unit,
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.names().symbol)
+    }
+}
+
+/// Writes an amount (or an amount range like "2-3") followed by its unit (if any), separated by a
+/// space. The formatter's precision applies to the amount(s).
+fn fmt_amount_with_unit(
+    f: &mut fmt::Formatter<'_>,
+    amount: Amount,
+    max_amount: Option<Amount>,
+    unit: &dyn fmt::Display,
+) -> fmt::Result {
+    let fmt_one = |amount: Amount| match f.precision() {
+        Some(precision) => format!("{:.*}", precision, amount),
+        None => amount.to_string(),
+    };
+    let amount = match max_amount {
+        Some(max_amount) => format!("{}-{}", fmt_one(amount), fmt_one(max_amount)),
+        None => fmt_one(amount),
+    };
+    let unit = unit.to_string();
+    if unit.is_empty() {
+        write!(f, "{}", amount)
+    } else if unit == GAS_MARK {
+        write!(f, "{} {}", unit, amount)
+    } else if unit == PERCENT {
+        write!(f, "{}{}", amount, unit)
+    } else {
+        write!(f, "{} {}", amount, unit)
+    }
+}
+
+fn f_split_quantity(c: char) -> bool {
+    c.is_alphabetic() || c == '°' || c == '%'
+}
+
+const GAS_MARK: &str = "gas mark";
+
+/// Splits a quantity into its amount and unit, also when the unit comes first (e.g., "gas mark
+/// 4", "gm4").
+fn split_quantity(s: &str) -> (&str, &str) {
+    let trimmed = s.trim_start();
+    for prefix in [GAS_MARK, "gm"] {
+        let rest = trimmed
+            .get(..prefix.len())
+            .filter(|start| start.eq_ignore_ascii_case(prefix))
+            .map(|_| &trimmed[prefix.len()..]);
+        if let Some(rest) =
+            rest.filter(|r| r.trim_start().starts_with(|c: char| c.is_ascii_digit()))
+        {
+            return (rest, GAS_MARK);
+        }
+    }
+    let split_at = s.find(f_split_quantity).map_or(s.len(), |s| s);
+    s.split_at(split_at)
+}
+
+const TO_TASTE: &str = "to taste";
+const APPROXIMATION_PREFIXES: [&str; 7] = [
+    "~",
+    "≈",
+    "about ",
+    "approx. ",
+    "approximately ",
+    "ca. ",
+    "circa ",
+];
+
+/// Marks quantities that are not meant to be measured precisely.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Approximation {
+    /// The amount is indicative (e.g., "~200 g" or "about 2 cups").
+    About,
+    /// There is no amount, the cook decides (e.g., "to taste").
+    ToTaste,
+}
+
+impl Approximation {
+    /// Strips an approximation marker from the start of a quantity string, if any.
+    fn strip(s: &str) -> (&str, Option<Self>) {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case(TO_TASTE) {
+            return ("", Some(Self::ToTaste));
+        }
+        let lower = s.to_lowercase();
+        match APPROXIMATION_PREFIXES
+            .iter()
+            .find(|prefix| lower.starts_with(*prefix))
+        {
+            Some(prefix) => (s[prefix.len()..].trim_start(), Some(Self::About)),
+            None => (s, None),
+        }
+    }
+}
+
+const RANGE_SEPARATORS: [char; 3] = ['-', '–', '—'];
+
+/// Parses an amount or a range of amounts (e.g., "2-3" or "30–40"). Ranges are returned with their
+/// bounds in increasing order, and ranges with identical bounds collapse to a single amount.
+fn parse_amount_range(s: &str) -> Result<(Amount, Option<Amount>), ParseFloatError> {
+    let s = s.trim();
+    // Skip the first character, which may be the sign of a negative amount.
+    let separator = s
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| RANGE_SEPARATORS.contains(c));
+    match separator {
+        Some((idx, c)) => {
+            let min = Amount::from_str(&s[..idx])?;
+            let max = Amount::from_str(&s[idx + c.len_utf8()..])?;
+            Ok(match min.cmp(&max) {
+                Ordering::Less => (min, Some(max)),
+                Ordering::Equal => (min, None),
+                Ordering::Greater => (max, Some(min)),
+            })
+        }
+        None => Ok((Amount::from_str(s)?, None)),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quantity {
+    pub unit: Unit,
+    pub amount: Amount,
+    /// Upper bound of the amount when the quantity is a range (e.g., "2-3 tbsp").
+    pub max_amount: Option<Amount>,
+    pub approximation: Option<Approximation>,
+}
+
+impl Quantity {
+    pub fn new(unit: &Unit, amount: Amount) -> Self {
+        Self {
+            unit: unit.clone(),
+            amount,
+            max_amount: None,
+            approximation: None,
+        }
+    }
+
+    pub fn new_range(unit: &Unit, amount: Amount, max_amount: Amount) -> Self {
+        Self {
+            unit: unit.clone(),
+            amount,
+            max_amount: Some(max_amount),
+            approximation: None,
+        }
+    }
+
+    /// Quantity left to the cook's judgement ("to taste").
+    pub fn to_taste() -> Self {
+        Self {
+            unit: Unit::Nominal(Nominal),
+            amount: Amount::ZERO,
+            max_amount: None,
+            approximation: Some(Approximation::ToTaste),
+        }
+    }
+
+    pub fn is_to_taste(&self) -> bool {
+        self.approximation == Some(Approximation::ToTaste)
+    }
+
+    pub fn is_range(&self) -> bool {
+        self.max_amount.is_some()
+    }
+
+    pub fn sanitize(self) -> Self {
+        let (unit, fn_unit) = self.unit.sanitize();
+        Self {
+            unit,
+            amount: fn_unit(self.amount),
+            max_amount: self.max_amount.map(fn_unit),
+            approximation: self.approximation,
+        }
+    }
+
+    /// Multiplies the amount (both bounds for ranges) by a factor.
+    pub fn scale(self, factor: Amount) -> Self {
+        Self {
+            amount: self.amount * factor,
+            max_amount: self.max_amount.map(|a| a * factor),
+            ..self
+        }
+    }
+
+    /// Converts the quantity to another unit of the same kind, or returns `None` if the units are
+    /// incompatible. Converting from a rough kitchen measure (e.g., "1 pinch") to another unit makes
+    /// the quantity approximate.
+    pub fn convert_to(&self, unit: &Unit) -> Option<Self> {
+        let (base, from) = self.unit.to_base();
+        let (target_base, to) = unit.to_base();
+        let rough = self.unit.is_approximate() && self.unit != *unit;
+        (base == target_base && self.unit.is_compatible_with(unit)).then(|| {
+            let convert = |amount| to.invert(from.apply(amount));
+            Self {
+                unit: unit.clone(),
+                amount: convert(self.amount),
+                max_amount: self.max_amount.map(convert),
+                approximation: self.approximation.or(rough.then_some(Approximation::About)),
+            }
+        })
+    }
+
+    /// Converts a mass to a volume or a volume to a mass given a density in g/mL, or converts
+    /// the quantity as [`Quantity::convert_to`] does otherwise.
+    pub fn convert_with_density(&self, unit: &Unit, grams_per_ml: Amount) -> Option<Self> {
+        if let Some(converted) = self.convert_to(unit) {
+            return Some(converted);
+        }
+        let base = self.to_base();
+        let (amount_of, unit_of) = match (self.unit.dimension(), unit.dimension()) {
+            _ if grams_per_ml.is_zero() => return None,
+            (Dimension::Volume, Dimension::Mass) => (grams_per_ml, Unit::Mass(Mass::Gram)),
+            (Dimension::Mass, Dimension::Volume) => {
+                (Amount::ONE / grams_per_ml, Unit::Volume(Volume::Milliliter))
+            }
+            _ => return None,
+        };
+        Self {
+            unit: unit_of,
+            ..base.scale(amount_of)
+        }
+        .convert_to(unit)
+    }
+
+    /// Adds two quantities, in the unit of `self`. Returns `None` if the units are incompatible or
+    /// if either quantity is "to taste". The sum is a range if either quantity is, and is
+    /// approximate if either quantity is.
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        if self.is_to_taste() || other.is_to_taste() {
+            return None;
+        }
+        let other = other.convert_to(&self.unit)?;
+        let max = |q: &Self| q.max_amount.unwrap_or(q.amount);
+        Some(Self {
+            unit: self.unit.clone(),
+            amount: self.amount + other.amount,
+            max_amount: (self.is_range() || other.is_range()).then(|| max(self) + max(&other)),
+            approximation: self.approximation.or(other.approximation),
+        })
+    }
+
+    /// Converts the quantity to the base unit of its kind (e.g., grams for masses).
+    pub fn to_base(&self) -> Self {
+        let (base, _) = self.unit.to_base();
+        self.convert_to(&base)
+            .expect("a unit is always convertible to its base")
+    }
+
+    /// Compares the amounts of two quantities after converting them to a common unit. Returns
+    /// `None` if the units are incompatible or if either quantity is a range.
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        if self.is_range() || other.is_range() {
+            return None;
+        }
+        let other = other.convert_to(&self.unit)?;
+        Some(self.amount.cmp(&other.amount))
+    }
+
+    /// Returns whether two quantities are equal up to a relative tolerance (e.g., `1/20` for 5%),
+    /// after converting them to a common unit. Range bounds are compared pairwise, and quantities
+    /// with incompatible units are never equal.
+    pub fn approx_eq(&self, other: &Self, tolerance: Amount) -> bool {
+        let Some(other) = other.convert_to(&self.unit) else {
+            return false;
+        };
+        let close = |a: Amount, b: Amount| (a - b).abs() <= tolerance * a.abs().max(b.abs());
+        close(self.amount, other.amount)
+            && match (self.max_amount, other.max_amount) {
+                (Some(a), Some(b)) => close(a, b),
+                (None, None) => true,
+                _ => false,
+            }
+    }
 
-impl UnitTrait<'_> for Temperature {
-    fn sanitize(self) -> (Self, FnUnit) {
-        match self {
-            Self::Farenheit => (Self::Celsius, |f| (f - 32.) * 5. / 9.),
-            _ => (self, |q| q),
+    /// Returns the quantity with its amount written as a whole or mixed number (e.g., "1 1/2
+    /// cup"), or `None` if the amount is not close to a fraction with a common denominator.
+    pub fn as_fraction(&self) -> Option<String> {
+        if self.is_to_taste() {
+            return Some(TO_TASTE.to_string());
         }
+        let amount = match self.max_amount {
+            Some(max_amount) => Some(format!(
+                "{}-{}",
+                self.amount.as_fraction()?,
+                max_amount.as_fraction()?
+            )),
+            None => self.amount.as_fraction(),
+        };
+        amount.map(|amount| {
+            let unit = self.unit.to_string();
+            let approx = if self.approximation.is_some() {
+                "~"
+            } else {
+                ""
+            };
+            if unit.is_empty() {
+                format!("{}{}", approx, amount)
+            } else {
+                format!("{}{} {}", approx, amount, unit)
+            }
+        })
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum Time {
-    Second,
-    Minute,
-    Hour,
+impl Quantity {
+    /// Parses a quantity whose numbers are written in some format (e.g., "1,5 kg" with decimal
+    /// commas).
+    pub fn from_str_with(s: &str, numbers: NumberFormat) -> Result<Self, ParseFloatError> {
+        Self::from_str(&numbers.normalize(s))
+    }
+
+    /// Parses a quantity written in a language (e.g., "2 EL" in German).
+    pub fn from_str_in(
+        s: &str,
+        numbers: NumberFormat,
+        language: Language,
+    ) -> Result<Self, ParseFloatError> {
+        Self::from_str_with(s, numbers).map(|quantity| language.resolve(quantity))
+    }
 }
 
-impl FromStr for Time {
-    type Err = ();
+impl FromStr for Quantity {
+    type Err = ParseFloatError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match &s.to_lowercase()[..] {
-            "s" | "sec" | "sec." | "second" | "seconds" => Ok(Self::Second),
-            "min" | "min." | "minute" | "minutes" => Ok(Self::Minute),
-            "h" | "hour" | "hours" => Ok(Self::Hour),
-            _ => Err(()),
+        let (s, approximation) = Approximation::strip(s);
+        if approximation == Some(Approximation::ToTaste) {
+            return Ok(Self::to_taste());
         }
+        let (quantity, unit) = match split_quantity(s) {
+            (quantity, "") => (quantity, Unit::Nominal(Nominal)),
+            (quantity, unit) => (quantity, Unit::from(unit.trim())),
+        };
+        let (amount, max_amount) = parse_amount_range(quantity)?;
+        Ok(Self {
+            unit,
+            amount,
+            max_amount,
+            approximation,
+        })
     }
 }
 
-impl UnitTrait<'_> for Time {}
-
-fn f_split_quantity(c: char) -> bool {
-    c.is_alphabetic() || c == '°'
-}
-
-#[derive(Clone, Debug, PartialEq)]
-pub struct Quantity {
-    pub unit: Unit,
-    pub amount: f32,
-}
-
 impl Quantity {
-    pub fn new(unit: &Unit, amount: f32) -> Self {
-        Self {
-            unit: unit.clone(),
-            amount,
+    /// Displays the quantity with the unit names of a language (e.g., "2 EL" in German).
+    pub fn display_in(&self, language: Language) -> LocalizedQuantity<'_> {
+        LocalizedQuantity {
+            quantity: self,
+            language,
         }
     }
 
-    pub fn sanitize(self) -> Self {
-        let (unit, fn_unit) = self.unit.sanitize();
-        Self {
-            unit,
-            amount: fn_unit(self.amount),
+    fn fmt_with_unit(&self, f: &mut fmt::Formatter<'_>, unit: &dyn fmt::Display) -> fmt::Result {
+        match self.approximation {
+            Some(Approximation::ToTaste) => f.write_str(TO_TASTE),
+            Some(Approximation::About) => {
+                f.write_str("~")?;
+                fmt_amount_with_unit(f, self.amount, self.max_amount, unit)
+            }
+            None => fmt_amount_with_unit(f, self.amount, self.max_amount, unit),
         }
     }
 }
 
-impl FromStr for Quantity {
-    type Err = ParseFloatError;
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit: &dyn fmt::Display = match self.unit {
+            Unit::Part(_) if self.amount == Amount::ONE && !self.is_range() => &"part",
+            _ => &self.unit,
+        };
+        self.fmt_with_unit(f, unit)
+    }
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.find(f_split_quantity) {
-            Some(idx) => {
-                let (quantity, unit) = s.split_at(idx);
-                Ok(Self {
-                    unit: Unit::from(unit.trim()),
-                    amount: quantity.trim().parse::<f32>()?,
-                })
-            }
-            None => Ok(Self {
-                unit: Unit::Nominal(Nominal),
-                amount: s.trim().parse::<f32>()?,
-            }),
+/// A quantity displayed in a language, from [`Quantity::display_in`].
+pub struct LocalizedQuantity<'a> {
+    quantity: &'a Quantity,
+    language: Language,
+}
+
+impl fmt::Display for LocalizedQuantity<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.language.names_of(&self.quantity.unit) {
+            Some(names) => self.quantity.fmt_with_unit(f, &names.symbol),
+            None => self.quantity.fmt(f),
         }
     }
 }
@@ -308,6 +1429,8 @@ macro_rules! from_quantity_of {
                 Self {
                     unit: $unit_enum(value.unit),
                     amount: value.amount,
+                    max_amount: value.max_amount,
+                    approximation: None,
                 }
             }
         }
@@ -319,6 +1442,8 @@ from_quantity_of!(Unit::Volume, Volume);
 from_quantity_of!(Unit::Distance, Distance);
 from_quantity_of!(Unit::Temperature, Temperature);
 from_quantity_of!(Unit::Time, Time);
+from_quantity_of!(Unit::Part, Part);
+from_quantity_of!(Unit::Percent, Percent);
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ParseQuantityOfError {
@@ -337,27 +1462,67 @@ impl fmt::Display for ParseQuantityOfError {
     }
 }
 
-impl error::Error for ParseQuantityOfError {}
+impl error::Error for ParseQuantityOfError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::InvalidUnit(_) => None,
+            Self::InvalidAmount(_, e) => Some(e),
+        }
+    }
+}
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct QuantityOf<T: for<'a> UnitTrait<'a>> {
     pub unit: T,
-    pub amount: f32,
+    pub amount: Amount,
+    /// Upper bound of the amount when the quantity is a range (e.g., "30-40 min").
+    pub max_amount: Option<Amount>,
 }
 
 impl<T> QuantityOf<T>
 where
     T: for<'a> UnitTrait<'a>,
 {
-    fn sanitize(self) -> Self {
+    pub fn new(unit: T, amount: Amount) -> Self {
+        Self {
+            unit,
+            amount,
+            max_amount: None,
+        }
+    }
+
+    pub fn is_range(&self) -> bool {
+        self.max_amount.is_some()
+    }
+
+    pub fn sanitize(self) -> Self {
         let (unit, fn_unit) = self.unit.sanitize();
         Self {
-            unit: unit,
+            unit,
             amount: fn_unit(self.amount),
+            max_amount: self.max_amount.map(fn_unit),
+        }
+    }
+
+    /// Multiplies the amount (both bounds for ranges) by a factor.
+    pub fn scale(self, factor: Amount) -> Self {
+        Self {
+            amount: self.amount * factor,
+            max_amount: self.max_amount.map(|a| a * factor),
+            ..self
         }
     }
 }
 
+impl<T> fmt::Display for QuantityOf<T>
+where
+    T: for<'a> UnitTrait<'a>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_amount_with_unit(f, self.amount, self.max_amount, &self.unit)
+    }
+}
+
 impl<T> FromStr for QuantityOf<T>
 where
     T: for<'a> UnitTrait<'a>,
@@ -365,16 +1530,16 @@ where
     type Err = ParseQuantityOfError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let split_at = s.find(f_split_quantity).map_or(s.len(), |s| s);
-        let (quantity, unit) = s.split_at(split_at);
+        let (quantity, unit) = split_quantity(s);
         let quantity = quantity.trim();
         let unit = unit.trim();
+        let (amount, max_amount) = parse_amount_range(quantity)
+            .map_err(|e| ParseQuantityOfError::InvalidAmount(quantity.to_string(), e))?;
         Ok(Self {
             unit: T::from_str(unit)
                 .map_err(|_| ParseQuantityOfError::InvalidUnit(unit.to_string()))?,
-            amount: quantity
-                .parse::<f32>()
-                .map_err(|e| ParseQuantityOfError::InvalidAmount(quantity.to_string(), e))?,
+            amount,
+            max_amount,
         })
     }
 }
@@ -395,6 +1560,8 @@ mod tests {
                 Quantity {
                     unit: $unit.clone(),
                     amount: $amount,
+                    max_amount: None,
+                    approximation: None,
                 }
             );
         };
@@ -410,6 +1577,7 @@ mod tests {
                 QuantityOf::<$unitty> {
                     unit: unit_of_ty.clone(),
                     amount: $amount,
+                    max_amount: None,
                 }
             );
         };
@@ -417,15 +1585,153 @@ mod tests {
 
     #[test]
     fn parse_quantity() -> MDResult<()> {
-        assert_quantity!("1", Unit::Nominal(Nominal), 1.);
-        assert_quantity!("10 g", Unit::Mass(Mass::Gram), 10.);
-        assert_quantity!("50 mL", Unit::Volume(Volume::Milliliter), 50.);
-        assert_quantity!("50ML", Unit::Volume(Volume::Milliliter), 50.);
-        assert_quantity!("  50.111 Ml    ", Unit::Volume(Volume::Milliliter), 50.111);
-        assert_quantity!("2.5cm", Unit::Distance(Distance::Centimeter), 2.5);
-        assert_quantity!("180°C", Unit::Temperature(Temperature::Celsius), 180.);
-        assert_quantity!("60 sec.", Unit::Time(Time::Second), 60.);
-        assert_quantity!("  0.5 bunch    ", Unit::Custom("bunch".to_string()), 0.5);
+        assert_quantity!("1", Unit::Nominal(Nominal), Amount::from_int(1));
+        assert_quantity!("10 g", Unit::Mass(Mass::Gram), Amount::from_int(10));
+        assert_quantity!(
+            "50 mL",
+            Unit::Volume(Volume::Milliliter),
+            Amount::from_int(50)
+        );
+        assert_quantity!(
+            "50ML",
+            Unit::Volume(Volume::Milliliter),
+            Amount::from_int(50)
+        );
+        assert_quantity!(
+            "  50.111 Ml    ",
+            Unit::Volume(Volume::Milliliter),
+            Amount::new(50111, 1000)
+        );
+        assert_quantity!(
+            "2.5cm",
+            Unit::Distance(Distance::Centimeter),
+            Amount::new(5, 2)
+        );
+        assert_quantity!(
+            "180°C",
+            Unit::Temperature(Temperature::Celsius),
+            Amount::from_int(180)
+        );
+        assert_quantity!("60 sec.", Unit::Time(Time::Second), Amount::from_int(60));
+        assert_quantity!(
+            "  0.5 bunch    ",
+            Unit::Custom("bunch".to_string()),
+            Amount::new(1, 2)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_fractional_quantity() -> MDResult<()> {
+        assert_quantity!("1/2 tsp", Unit::Volume(Volume::Teaspoon), Amount::new(1, 2));
+        assert_quantity!(" 3 / 4 cup", Unit::Volume(Volume::Cup), Amount::new(3, 4));
+        assert_quantity!("½ cup", Unit::Volume(Volume::Cup), Amount::new(1, 2));
+        assert_quantity!("1 ½ cup", Unit::Volume(Volume::Cup), Amount::new(3, 2));
+        assert_quantity!("1½ cup", Unit::Volume(Volume::Cup), Amount::new(3, 2));
+        assert_quantity!("1 1/2 cup", Unit::Volume(Volume::Cup), Amount::new(3, 2));
+        assert_quantity!("2 1⁄4", Unit::Nominal(Nominal), Amount::new(9, 4));
+        assert_quantity_of!(Time, "1 1/2 h", Time::Hour, Amount::new(3, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_quantity_range() -> MDResult<()> {
+        let two_to_three_tbsp = Quantity::new_range(
+            &Unit::Volume(Volume::Tablespoon),
+            Amount::from_int(2),
+            Amount::from_int(3),
+        );
+        assert_eq!(Quantity::from_str("2-3 tbsp")?, two_to_three_tbsp);
+        assert_eq!(Quantity::from_str(" 2 – 3tbsp")?, two_to_three_tbsp);
+        // Bounds are reordered, and identical bounds collapse.
+        assert_eq!(Quantity::from_str("3-2 tbsp")?, two_to_three_tbsp);
+        assert!(!Quantity::from_str("2-2 tbsp")?.is_range());
+        // Negative amounts are not ranges.
+        assert_eq!(
+            Quantity::from_str("-18 °C")?,
+            Quantity::new(
+                &Unit::Temperature(Temperature::Celsius),
+                Amount::from_int(-18)
+            )
+        );
+        assert_eq!(
+            QuantityOf::<Time>::from_str("30—40 minutes").unwrap(),
+            QuantityOf {
+                unit: Time::Minute,
+                amount: Amount::from_int(30),
+                max_amount: Some(Amount::from_int(40)),
+            }
+        );
+        assert!(Quantity::from_str("2- tbsp").is_err());
+        assert!(Quantity::from_str("2-3-4 tbsp").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn quantity_range_conversions() -> MDResult<()> {
+        let q = Quantity::from_str("2-3 tbsp")?;
+        assert_eq!(q.to_string(), "2-3 tbsp");
+        assert_eq!(q.clone().sanitize().to_string(), "30-45 mL");
+        assert_eq!(
+            q.scale(Amount::new(1, 2)).as_fraction().unwrap(),
+            "1-1 1/2 tbsp"
+        );
+        let q = QuantityOf::<Time>::from_str("30-40 min").unwrap();
+        assert_eq!(q.scale(Amount::from_int(2)).to_string(), "60-80 min");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_approximate_quantity() -> MDResult<()> {
+        let about_200_g = Quantity {
+            unit: Unit::Mass(Mass::Gram),
+            amount: Amount::from_int(200),
+            max_amount: None,
+            approximation: Some(Approximation::About),
+        };
+        assert_eq!(Quantity::from_str("~200 g")?, about_200_g);
+        assert_eq!(Quantity::from_str("≈ 200g")?, about_200_g);
+        assert_eq!(Quantity::from_str("About 200 g")?, about_200_g);
+        assert_eq!(Quantity::from_str("approx. 200 g")?, about_200_g);
+        assert_eq!(Quantity::from_str("To taste")?, Quantity::to_taste());
+        assert_eq!(about_200_g.to_string(), "~200 g");
+        assert_eq!(Quantity::to_taste().to_string(), "to taste");
+        // Approximations survive conversions.
+        let q = Quantity::from_str("about 1-2 tbsp")?.sanitize();
+        assert_eq!(q.approximation, Some(Approximation::About));
+        assert_eq!(q.to_string(), "~15-30 mL");
+        assert!(Quantity::from_str("about").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_fractional_quantity_failures() {
+        // Division by zero.
+        assert!(Quantity::from_str("1/0 cup").is_err());
+        // Fractions must be made of whole numbers.
+        assert!(Quantity::from_str("1.5/2 cup").is_err());
+        assert!(Quantity::from_str("1 1/2/3 cup").is_err());
+        // Only a single fraction can follow the whole part.
+        assert!(Quantity::from_str("1 ½ ½ cup").is_err());
+    }
+
+    #[test]
+    fn quantity_as_fraction() -> MDResult<()> {
+        assert_eq!(
+            Quantity::from_str("1.5 cup")?.as_fraction().unwrap(),
+            "1 1/2 cup"
+        );
+        assert_eq!(Quantity::from_str("0.25")?.as_fraction().unwrap(), "1/4");
+        assert_eq!(
+            Quantity::from_str("⅓ tsp")?.as_fraction().unwrap(),
+            "1/3 tsp"
+        );
+        assert_eq!(
+            Quantity::from_str("2.375 L")?.as_fraction().unwrap(),
+            "2 3/8 L"
+        );
+        assert_eq!(Quantity::from_str("3 g")?.as_fraction().unwrap(), "3 g");
+        assert!(Quantity::from_str("0.1 g")?.as_fraction().is_none());
         Ok(())
     }
 
@@ -443,25 +1749,428 @@ mod tests {
     fn quantity_sanitize() {
         let q = Quantity {
             unit: Unit::Distance(Distance::Inches),
-            amount: 3.,
+            amount: Amount::from_int(3),
+            max_amount: None,
+            approximation: None,
         }
         .sanitize();
-        assert_eq!(q.amount, 7.5);
+        assert_eq!(q.amount, Amount::new(15, 2));
         let q = Quantity {
             unit: Unit::Nominal(Nominal),
-            amount: 3.,
+            amount: Amount::from_int(3),
+            max_amount: None,
+            approximation: None,
         }
         .sanitize();
-        assert_eq!(q.amount, 3.);
+        assert_eq!(q.amount, Amount::from_int(3));
+
+        // Conversions are exact.
+        let q = Quantity::new(&Unit::Volume(Volume::Teaspoon), Amount::new(1, 3)).sanitize();
+        assert_eq!(q.amount, Amount::new(5, 3));
+        let q = Quantity::new(
+            &Unit::Temperature(Temperature::Farenheit),
+            Amount::from_int(350),
+        )
+        .sanitize();
+        assert_eq!(q.amount, Amount::new(1590, 9));
+        assert_eq!(q.to_string(), "176.67 °C");
+    }
+
+    #[test]
+    fn quantity_convert() -> MDResult<()> {
+        let q = Quantity::from_str("2 cup")?;
+        assert_eq!(
+            q.convert_to(&Unit::Volume(Volume::Liter))
+                .unwrap()
+                .to_string(),
+            "0.48 L"
+        );
+        assert_eq!(q.to_base().to_string(), "480 mL");
+        let q = Quantity::from_str("212 °F")?;
+        assert_eq!(
+            q.convert_to(&Unit::Temperature(Temperature::Celsius))
+                .unwrap()
+                .amount,
+            Amount::from_int(100)
+        );
+        let q = Quantity::from_str("100 °C")?;
+        assert_eq!(
+            q.convert_to(&Unit::Temperature(Temperature::Farenheit))
+                .unwrap()
+                .amount,
+            Amount::from_int(212)
+        );
+        // Incompatible units.
+        assert!(q.convert_to(&Unit::Mass(Mass::Gram)).is_none());
+        assert!(Quantity::from_str("1 bunch")?
+            .convert_to(&Unit::Custom("sprig".to_string()))
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn long_unit_names() -> MDResult<()> {
+        let unit = |s: &str| Quantity::from_str(s).map(|q| q.unit);
+        assert_eq!(unit("2 tablespoons")?, Unit::Volume(Volume::Tablespoon));
+        assert_eq!(unit("1 Teaspoon")?, Unit::Volume(Volume::Teaspoon));
+        assert_eq!(unit("200 milliliters")?, Unit::Volume(Volume::Milliliter));
+        assert_eq!(unit("1.5 litres")?, Unit::Volume(Volume::Liter));
+        assert_eq!(unit("3 fluid ounces")?, Unit::Volume(Volume::FluidOunce));
+        assert_eq!(
+            unit("20 centimetres")?,
+            Unit::Distance(Distance::Centimeter)
+        );
+        assert_eq!(unit("9 inches")?, Unit::Distance(Distance::Inches));
+        assert_eq!(unit("2 hours")?, Unit::Time(Time::Hour));
+        assert_eq!(unit("3 hrs")?, Unit::Time(Time::Hour));
+        assert_eq!(
+            unit("180 celsius")?,
+            Unit::Temperature(Temperature::Celsius)
+        );
+        assert_eq!(unit("2 sprigs")?, Unit::Custom("sprigs".to_string()));
+        assert_eq!(Quantity::from_str("2 tablespoons")?.to_string(), "2 tbsp");
+
+        let names = Volume::Tablespoon.names();
+        assert_eq!(names.long(Amount::ONE), "tablespoon");
+        assert_eq!(names.long(Amount::from_int(2)), "tablespoons");
+        assert!(names.all().any(|n| n == "tbs"));
+        assert_eq!(Volume::from_name("LITRE"), Some(Volume::Liter));
+        assert_eq!(
+            Unit::Time(Time::Minute).names().map(|n| n.plural),
+            Some("minutes")
+        );
+        assert_eq!(Unit::Custom("pinch".to_string()).names(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn kitchen_measures() -> MDResult<()> {
+        let pinches = Quantity::from_str("2 pinches")?;
+        assert_eq!(pinches.unit, Unit::Volume(Volume::Pinch));
+        assert!(pinches.unit.is_approximate());
+        assert_eq!(pinches.to_string(), "2 pinch");
+        assert_eq!(
+            Quantity::from_str("1 Dash")?.unit,
+            Unit::Volume(Volume::Dash)
+        );
+        assert_eq!(
+            Quantity::from_str("3 drops")?.unit,
+            Unit::Volume(Volume::Drop)
+        );
+        assert_eq!(
+            Quantity::from_str("1 handful")?.unit,
+            Unit::Volume(Volume::Handful)
+        );
+
+        // Kitchen measures add up with each other, and with other volumes as approximations.
+        let sum = pinches
+            .checked_add(&Quantity::from_str("1 pinch")?)
+            .unwrap();
+        assert_eq!(sum.to_string(), "3 pinch");
+        assert_eq!(sum.approximation, None);
+        let sum = Quantity::from_str("1 tsp")?
+            .checked_add(&Quantity::from_str("8 pinches")?)
+            .unwrap();
+        assert_eq!(sum.to_string(), "~1.5 tsp");
+        let ml = Quantity::from_str("20 drops")?
+            .convert_to(&Unit::Volume(Volume::Milliliter))
+            .unwrap();
+        assert_eq!(ml.to_string(), "~1 mL");
+        assert!(!Unit::Volume(Volume::Teaspoon).is_approximate());
+        Ok(())
+    }
+
+    #[test]
+    fn counts() -> MDResult<()> {
+        let large = Quantity::from_str("2 large")?;
+        assert_eq!(large.unit, Unit::Count("large".to_string()));
+        assert_eq!(large.to_string(), "2 large");
+        assert_eq!(
+            Quantity::from_str("1 Extra Large")?.unit,
+            Unit::Count("Extra Large".to_string())
+        );
+        let cloves = Quantity::from_str("3 cloves")?;
+        assert_eq!(cloves.unit, Unit::Count("cloves".to_string()));
+        assert_eq!(cloves.unit.names(), None);
+
+        // Sizes are plain counts, forms are not.
+        let sum = large.checked_add(&Quantity::from_str("1")?).unwrap();
+        assert_eq!(sum.to_string(), "3 large");
+        assert!(Quantity::from_str("1")?
+            .checked_add(&Quantity::from_str("2 small")?)
+            .is_some());
+        // Counts of different sizes are kept apart.
+        let sum = large.checked_add(&Quantity::from_str("1 Large")?).unwrap();
+        assert_eq!(sum.to_string(), "3 large");
+        assert!(large.checked_add(&Quantity::from_str("3 small")?).is_none());
+        assert!(Quantity::from_str("1 extra-large")?
+            .checked_add(&Quantity::from_str("1 extra large")?)
+            .is_some());
+        let sum = cloves.checked_add(&Quantity::from_str("1 clove")?).unwrap();
+        assert_eq!(sum.to_string(), "4 cloves");
+        assert!(cloves.checked_add(&Quantity::from_str("2")?).is_none());
+        assert!(cloves.checked_add(&Quantity::from_str("1 head")?).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn dimensions() -> MDResult<()> {
+        let unit = |s: &str| Quantity::from_str(s).map(|q| q.unit);
+        assert_eq!(unit("2 kg")?.dimension(), Dimension::Mass);
+        assert_eq!(unit("1 pinch")?.dimension(), Dimension::Volume);
+        assert_eq!(unit("3 in")?.dimension(), Dimension::Length);
+        assert_eq!(unit("gas mark 4")?.dimension(), Dimension::Temperature);
+        assert_eq!(unit("1 h")?.dimension(), Dimension::Time);
+        assert_eq!(unit("2")?.dimension(), Dimension::Count);
+        assert_eq!(unit("2 cloves")?.dimension(), Dimension::Count);
+        assert_eq!(unit("2 parts")?.dimension(), Dimension::Other);
+        assert_eq!(unit("1 bunch")?.dimension(), Dimension::Other);
+
+        assert!(unit("1 lb")?.is_compatible_with(&unit("20 g")?));
+        assert!(!unit("1 g")?.is_compatible_with(&unit("1 mL")?));
+        assert!(unit("2 large")?.is_compatible_with(&unit("2")?));
+        assert!(!unit("2 large")?.is_compatible_with(&unit("2 small")?));
+        assert!(!unit("2 cloves")?.is_compatible_with(&unit("2")?));
+        assert!(!unit("1 bunch")?.is_compatible_with(&unit("1 sprig")?));
+        Ok(())
+    }
+
+    #[test]
+    fn registry() -> MDResult<()> {
+        let registry = UnitRegistry::new()
+            .register(&["stick", "sticks"], &Quantity::from_str("113 g")?)
+            .register(&["can", "cans"], &Quantity::from_str("400 g")?)
+            .register(&["shot"], &Quantity::from_str("3 cL")?);
+        let sticks = registry.resolve(Quantity::from_str("2 Sticks")?);
+        assert_eq!(sticks.to_string(), "2 Sticks");
+        assert_eq!(sticks.unit.dimension(), Dimension::Mass);
+        assert_eq!(
+            sticks
+                .convert_to(&Unit::Mass(Mass::Gram))
+                .unwrap()
+                .to_string(),
+            "226 g"
+        );
+        let stick = registry.get("stick").unwrap();
+        let sum = sticks
+            .checked_add(&Quantity::new(&stick, Amount::new(1, 2)))
+            .unwrap();
+        assert_eq!(sum.to_string(), "2.5 Sticks");
+        assert_eq!(
+            registry.resolve(Quantity::from_str("1 can")?).unit,
+            registry.get("can").unwrap()
+        );
+        assert_eq!(
+            registry
+                .resolve(Quantity::from_str("2 shot")?)
+                .to_base()
+                .to_string(),
+            "60 mL"
+        );
+        let bunch = Quantity::from_str("1 bunch")?;
+        assert_eq!(registry.resolve(bunch.clone()), bunch);
+        // Units of no size are not registered, as nothing could be converted to them.
+        let registry = registry
+            .register(&["nothing"], &Quantity::from_str("0 g")?)
+            .register(&["debt"], &Quantity::from_str("-1 stick")?);
+        assert_eq!(registry.get("nothing"), None);
+        assert_eq!(registry.get("debt"), None);
+        if let Unit::Registered(unit) = stick {
+            assert_eq!(unit.size().to_string(), "113 g");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn registry_bundles() -> MDResult<()> {
+        let mut registry = UnitRegistry::new()
+            .register(&["Stick"], &Quantity::from_str("113 g")?)
+            .register(&["third"], &Quantity::from_str("1/3 cup")?);
+        let bundle = registry.to_bundle();
+        assert_eq!(bundle.get(UNIT_SIZES, "stick"), Some("113 g"));
+        assert_eq!(bundle.get(UNIT_SIZES, "third"), Some("1/3 cup"));
+        assert_eq!(UnitRegistry::from_bundle(&bundle)?, registry);
+
+        let mut theirs = Bundle::new();
+        theirs.insert(UNIT_SIZES, "stick", "4 oz");
+        theirs.insert(UNIT_SIZES, "can", "400g");
+        let conflicts = registry.merge_bundle(&theirs, MergePolicy::KeepOurs)?;
+        assert_eq!(
+            conflicts,
+            vec![Conflict {
+                table: UNIT_SIZES.to_string(),
+                key: "stick".to_string(),
+                ours: "113 g".to_string(),
+                theirs: "4 oz".to_string(),
+            }]
+        );
+        assert_eq!(
+            registry.get("stick").unwrap().to_base().1.factor,
+            Amount::from_int(113)
+        );
+        assert!(registry.get("can").is_some());
+        registry.merge_bundle(&theirs, MergePolicy::TakeTheirs)?;
+        assert_eq!(registry.to_bundle().get(UNIT_SIZES, "stick"), Some("4 oz"));
+
+        for size in ["0 g", "1-2 g", "a lot of"] {
+            let mut bundle = Bundle::new();
+            bundle.insert(UNIT_SIZES, "stick", size);
+            assert!(UnitRegistry::from_bundle(&bundle).is_err(), "{}", size);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn mass_units() -> MDResult<()> {
+        let grams = |s: &str| -> MDResult<String> {
+            let q = Quantity::from_str(s)?;
+            Ok(q.convert_to(&Unit::Mass(Mass::Gram)).unwrap().to_string())
+        };
+        assert_eq!(grams("200 grams")?, "200 g");
+        assert_eq!(grams("1 Kilogram")?, "1000 g");
+        assert_eq!(grams("500 mg")?, "0.5 g");
+        assert_eq!(grams("250 milligrams")?, "0.25 g");
+        assert_eq!(grams("5 dag")?, "50 g");
+        assert_eq!(grams("2 ounces")?, "56 g");
+        assert_eq!(grams("1 lb")?, "450 g");
+        assert_eq!(grams("2 pounds")?, "900 g");
+        let micrograms = Quantity::from_str("500 µg")?;
+        assert_eq!(
+            micrograms
+                .convert_to(&Unit::Mass(Mass::Milligram))
+                .unwrap()
+                .to_string(),
+            "0.5 mg"
+        );
+        assert_eq!(
+            Quantity::from_str("500 μg")?,
+            Quantity::from_str("500 mcg")?
+        );
+        assert_eq!(Quantity::from_str("20 Dag")?.to_string(), "20 dag");
+        Ok(())
+    }
+
+    #[test]
+    fn quantity_compare() -> MDResult<()> {
+        let q = |s: &str| Quantity::from_str(s);
+        assert!(q("15 mL")?.approx_eq(&q("1 tbsp")?, Amount::ZERO));
+        assert!(q("1 cup")?.approx_eq(&q("250 mL")?, Amount::new(1, 20)));
+        assert!(!q("1 cup")?.approx_eq(&q("300 mL")?, Amount::new(1, 20)));
+        assert!(q("2-3 tbsp")?.approx_eq(&q("30-45 mL")?, Amount::ZERO));
+        assert!(!q("2-3 tbsp")?.approx_eq(&q("30 mL")?, Amount::new(1, 2)));
+        assert!(!q("15 g")?.approx_eq(&q("15 mL")?, Amount::ONE));
+        assert_eq!(q("1 kg")?.compare(&q("900 g")?), Some(Ordering::Greater));
+        assert_eq!(q("1 lbs")?.compare(&q("450 g")?), Some(Ordering::Equal));
+        assert_eq!(q("1 bunch")?.compare(&q("2 bunch")?), Some(Ordering::Less));
+        assert_eq!(q("1 kg")?.compare(&q("1 L")?), None);
+        assert_eq!(q("1-2 kg")?.compare(&q("1 kg")?), None);
+        Ok(())
+    }
+
+    #[test]
+    fn parts() -> MDResult<()> {
+        let q = |s: &str| Quantity::from_str(s);
+        assert_eq!(q("2 parts")?.unit, Unit::Part(Part));
+        assert_eq!(q("1 Part")?.to_string(), "1 part");
+        assert_eq!(q("1.5 part")?.to_string(), "1.5 parts");
+        assert_eq!(q("1 part")?.checked_add(&q("30 mL")?), None);
+        Ok(())
+    }
+
+    #[test]
+    fn percents_and_days() -> MDResult<()> {
+        let q = |s: &str| Quantity::from_str(s);
+        assert_eq!(q("75%")?.unit, Unit::Percent(Percent));
+        assert_eq!(q("2.5 %")?.to_string(), "2.5%");
+        assert_eq!(q("2 days")?.to_base().to_string(), "172800 s");
+        Ok(())
+    }
+
+    #[test]
+    fn quantity_add() -> MDResult<()> {
+        let q = |s: &str| Quantity::from_str(s);
+        assert_eq!(q("1 kg")?.checked_add(&q("500 g")?), Some(q("1.5 kg")?));
+        assert_eq!(
+            q("1 tbsp")?.checked_add(&q("1-2 tsp")?),
+            Some(q("4/3-5/3 tbsp")?)
+        );
+        assert_eq!(q("2")?.checked_add(&q("~1")?), Some(q("~3")?));
+        assert_eq!(q("1 kg")?.checked_add(&q("1 L")?), None);
+        assert_eq!(q("1 kg")?.checked_add(&q("to taste")?), None);
+        Ok(())
+    }
+
+    #[test]
+    fn time_add() -> Result<(), ParseQuantityOfError> {
+        let t = |s: &str| QuantityOf::<Time>::from_str(s);
+        assert_eq!((t("1 h")? + t("30 min")?).to_string(), "1.5 h");
+        assert_eq!((t("10 min")? + t("20-30 min")?).to_string(), "30-40 min");
+        assert_eq!(t("90 s")?.to_duration(), Duration::from_secs(90));
+        assert_eq!(t("-5 min")?.to_duration(), Duration::ZERO);
+        let long = t("1000000000000000 d")?.to_duration();
+        assert_eq!(long.as_secs(), i64::MAX as u64 + 1);
+        Ok(())
+    }
+
+    #[test]
+    fn temperature_convert() -> Result<(), ParseQuantityOfError> {
+        let t = |s: &str| QuantityOf::<Temperature>::from_str(s);
+        let celsius = t("356-392 °F")?.convert_to(Temperature::Celsius);
+        assert_eq!(celsius.to_string(), "180-200 °C");
+        let fahrenheit = t("100°C")?.convert_to(Temperature::Farenheit);
+        assert_eq!(fahrenheit.to_string(), "212 °F");
+
+        let gas_mark = t("Gas mark 4")?;
+        assert_eq!(gas_mark.unit, Temperature::GasMark);
+        assert_eq!(gas_mark.amount, Amount::from_int(4));
+        assert_eq!(gas_mark.to_string(), "gas mark 4");
+        assert_eq!(t("gm6")?, t("gas mark 6")?);
+        assert_eq!(
+            t("gas mark 4")?
+                .convert_to(Temperature::Farenheit)
+                .to_string(),
+            "350 °F"
+        );
+        assert_eq!(
+            t("425 °F")?.convert_to(Temperature::GasMark).to_string(),
+            "gas mark 7"
+        );
+        assert_eq!(
+            t("400K")?.convert_to(Temperature::Celsius).to_string(),
+            "126.85 °C"
+        );
+        assert_eq!(
+            t("0 °C")?.convert_to(Temperature::Kelvin).to_string(),
+            "273.15 K"
+        );
+        assert_eq!(
+            t("gas mark 4")?.sanitize(),
+            t("gas mark 4")?.convert_to(Temperature::Celsius)
+        );
+        assert_eq!(
+            Quantity::from_str("gas mark 5").unwrap().unit,
+            Unit::Temperature(Temperature::GasMark)
+        );
+        Ok(())
     }
 
     #[test]
     fn parse_quantity_of() -> MDResult<()> {
-        assert_quantity_of!(Nominal, "1", Nominal, 1.);
-        assert_quantity_of!(Volume, "50 mL", Volume::Milliliter, 50.);
-        assert_quantity_of!(Volume, "50ML", Volume::Milliliter, 50.);
-        assert_quantity_of!(Volume, "  50 Ml    ", Volume::Milliliter, 50.);
-        assert_quantity_of!(Temperature, "180°C", Temperature::Celsius, 180.);
+        assert_quantity_of!(Nominal, "1", Nominal, Amount::from_int(1));
+        assert_quantity_of!(Volume, "50 mL", Volume::Milliliter, Amount::from_int(50));
+        assert_quantity_of!(Volume, "50ML", Volume::Milliliter, Amount::from_int(50));
+        assert_quantity_of!(
+            Volume,
+            "  50 Ml    ",
+            Volume::Milliliter,
+            Amount::from_int(50)
+        );
+        assert_quantity_of!(
+            Temperature,
+            "180°C",
+            Temperature::Celsius,
+            Amount::from_int(180)
+        );
         Ok(())
     }
 
@@ -493,15 +2202,49 @@ mod tests {
     fn quantity_of_sanitize() {
         let q = QuantityOf::<Distance> {
             unit: Distance::Inches,
-            amount: 3.,
+            amount: Amount::from_int(3),
+            max_amount: None,
         }
         .sanitize();
-        assert_eq!(q.amount, 7.5);
+        assert_eq!(q.amount, Amount::new(15, 2));
         let q = QuantityOf::<Nominal> {
             unit: Nominal,
-            amount: 3.,
+            amount: Amount::from_int(3),
+            max_amount: None,
         }
         .sanitize();
-        assert_eq!(q.amount, 3.);
+        assert_eq!(q.amount, Amount::from_int(3));
+    }
+
+    #[test]
+    fn display_quantity() -> MDResult<()> {
+        assert_eq!(Quantity::from_str("1")?.to_string(), "1");
+        assert_eq!(Quantity::from_str("15ml")?.to_string(), "15 mL");
+        assert_eq!(Quantity::from_str("180°c")?.to_string(), "180 °C");
+        assert_eq!(Quantity::from_str("1 bunch")?.to_string(), "1 bunch");
+        assert_eq!(Quantity::from_str("2 fl. oz.")?.to_string(), "2 fl oz");
+        // Amounts are rounded, and trailing zeros are dropped by default.
+        assert_eq!(Quantity::from_str("0.126 kg")?.to_string(), "0.13 kg");
+        assert_eq!(Quantity::from_str("2.50 tsp")?.to_string(), "2.5 tsp");
+        // An explicit precision overrides the default policy.
+        assert_eq!(format!("{:.1}", Quantity::from_str("15ml")?), "15.0 mL");
+        assert_eq!(format!("{:.0}", Quantity::from_str("2.6 h")?), "3 h");
+        Ok(())
+    }
+
+    #[test]
+    fn display_quantity_of() {
+        let q = QuantityOf::<Time> {
+            unit: Time::Minute,
+            amount: Amount::from_int(10),
+            max_amount: None,
+        };
+        assert_eq!(q.to_string(), "10 min");
+        let q = QuantityOf::<Nominal> {
+            unit: Nominal,
+            amount: Amount::from_int(3),
+            max_amount: None,
+        };
+        assert_eq!(q.to_string(), "3");
     }
 }
@@ -0,0 +1,281 @@
+//! Built-in reference data, and lints suggesting to use it in recipes.
+
+use crate::recipe::{
+    amount::Amount,
+    unit::{QuantityOf, Temperature},
+    Diagnostic, ErrorCode, MDError, Recipe, Severity,
+};
+use std::fmt;
+
+/// Kinds of meat with distinct doneness temperatures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Meat {
+    Beef,
+    Lamb,
+    Veal,
+    Pork,
+    Poultry,
+    /// Ground or minced meat of any kind.
+    Ground,
+    Fish,
+}
+
+impl Meat {
+    pub const ALL: [Meat; 7] = [
+        Meat::Beef,
+        Meat::Lamb,
+        Meat::Veal,
+        Meat::Pork,
+        Meat::Poultry,
+        Meat::Ground,
+        Meat::Fish,
+    ];
+
+    /// Words naming the meat in recipes.
+    fn words(&self) -> &'static [&'static str] {
+        match self {
+            Self::Beef => &["beef", "steak", "steaks", "sirloin", "ribeye"],
+            Self::Lamb => &["lamb", "mutton"],
+            Self::Veal => &["veal"],
+            Self::Pork => &["pork"],
+            Self::Poultry => &["chicken", "turkey"],
+            Self::Ground => &["burger", "burgers", "meatball", "meatballs", "meatloaf"],
+            Self::Fish => &["fish", "salmon", "cod", "trout", "halibut"],
+        }
+    }
+
+    /// Finds the meat mentioned in a text, if any. Meats described as ground or minced (e.g.,
+    /// "ground beef") are [`Meat::Ground`].
+    pub fn find_in(text: &str) -> Option<Self> {
+        let text = text.to_lowercase();
+        let words: Vec<&str> = text
+            .split(|c: char| !c.is_alphanumeric() && c != '-')
+            .collect();
+        let meat = Self::ALL
+            .into_iter()
+            .find(|meat| words.iter().any(|w| meat.words().contains(w)))?;
+        let is_ground = words
+            .iter()
+            .any(|w| matches!(*w, "ground" | "minced" | "mince"));
+        Some(match meat {
+            Self::Beef | Self::Lamb | Self::Veal | Self::Pork | Self::Poultry if is_ground => {
+                Self::Ground
+            }
+            _ => meat,
+        })
+    }
+}
+
+impl fmt::Display for Meat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Beef => "beef",
+            Self::Lamb => "lamb",
+            Self::Veal => "veal",
+            Self::Pork => "pork",
+            Self::Poultry => "poultry",
+            Self::Ground => "ground meat",
+            Self::Fish => "fish",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Doneness {
+    Rare,
+    MediumRare,
+    Medium,
+    MediumWell,
+    WellDone,
+    /// Minimum safe temperature, for meats that are not served pink.
+    Safe,
+}
+
+impl fmt::Display for Doneness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Rare => "rare",
+            Self::MediumRare => "medium rare",
+            Self::Medium => "medium",
+            Self::MediumWell => "medium well",
+            Self::WellDone => "well done",
+            Self::Safe => "safe",
+        })
+    }
+}
+
+/// Internal temperature of a meat at a doneness, in both scales. Values are rounded in each scale
+/// rather than converted from one another, as in published charts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DonenessTemperature {
+    pub meat: Meat,
+    pub doneness: Doneness,
+    pub celsius: i64,
+    pub fahrenheit: i64,
+}
+
+impl DonenessTemperature {
+    const fn new(meat: Meat, doneness: Doneness, celsius: i64, fahrenheit: i64) -> Self {
+        Self {
+            meat,
+            doneness,
+            celsius,
+            fahrenheit,
+        }
+    }
+
+    pub fn in_celsius(&self) -> QuantityOf<Temperature> {
+        QuantityOf::new(Temperature::Celsius, Amount::from_int(self.celsius))
+    }
+
+    pub fn in_fahrenheit(&self) -> QuantityOf<Temperature> {
+        QuantityOf::new(Temperature::Farenheit, Amount::from_int(self.fahrenheit))
+    }
+}
+
+impl fmt::Display for DonenessTemperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} / {}", self.in_celsius(), self.in_fahrenheit())
+    }
+}
+
+const DONENESS: [DonenessTemperature; 21] = {
+    use Doneness::*;
+    use Meat::*;
+    [
+        DonenessTemperature::new(Beef, Rare, 52, 125),
+        DonenessTemperature::new(Beef, MediumRare, 57, 135),
+        DonenessTemperature::new(Beef, Medium, 63, 145),
+        DonenessTemperature::new(Beef, MediumWell, 66, 150),
+        DonenessTemperature::new(Beef, WellDone, 71, 160),
+        DonenessTemperature::new(Lamb, Rare, 52, 125),
+        DonenessTemperature::new(Lamb, MediumRare, 57, 135),
+        DonenessTemperature::new(Lamb, Medium, 63, 145),
+        DonenessTemperature::new(Lamb, MediumWell, 66, 150),
+        DonenessTemperature::new(Lamb, WellDone, 71, 160),
+        DonenessTemperature::new(Veal, Rare, 52, 125),
+        DonenessTemperature::new(Veal, MediumRare, 57, 135),
+        DonenessTemperature::new(Veal, Medium, 63, 145),
+        DonenessTemperature::new(Veal, MediumWell, 66, 150),
+        DonenessTemperature::new(Veal, WellDone, 71, 160),
+        DonenessTemperature::new(Pork, Medium, 63, 145),
+        DonenessTemperature::new(Pork, MediumWell, 66, 150),
+        DonenessTemperature::new(Pork, WellDone, 71, 160),
+        DonenessTemperature::new(Poultry, Safe, 74, 165),
+        DonenessTemperature::new(Ground, Safe, 71, 160),
+        DonenessTemperature::new(Fish, Safe, 63, 145),
+    ]
+};
+
+/// Internal temperatures of meats by doneness, from rarest to most done.
+pub fn doneness() -> &'static [DonenessTemperature] {
+    &DONENESS
+}
+
+/// Internal temperature of a meat at a doneness, or `None` if the meat is not served that way.
+pub fn doneness_temperature(
+    meat: Meat,
+    doneness: Doneness,
+) -> Option<&'static DonenessTemperature> {
+    DONENESS
+        .iter()
+        .find(|t| t.meat == meat && t.doneness == doneness)
+}
+
+/// Lowest internal temperature deemed safe for a meat.
+pub fn safe_temperature(meat: Meat) -> &'static DonenessTemperature {
+    DONENESS
+        .iter()
+        .find(|t| t.meat == meat && matches!(t.doneness, Doneness::Medium | Doneness::Safe))
+        .expect("every meat has a safe temperature")
+}
+
+/// Verbs of steps cooking food through (possibly conjugated, e.g., "grilled").
+const COOKING_VERBS: [&str; 11] = [
+    "cook", "grill", "roast", "sear", "bake", "fry", "fried", "broil", "braise", "smoke",
+    "barbecue",
+];
+
+/// Hints at steps that cook meat without giving a target internal temperature (e.g., "Grill the
+/// steaks" rather than "Grill the steaks to 57 °C").
+pub fn lint_doneness(recipe: &Recipe) -> Vec<Diagnostic> {
+    recipe
+        .instructions()
+        .flatten()
+        .into_iter()
+        .filter_map(|flat| {
+            let text = flat.step.text();
+            let lower = text.to_lowercase();
+            let cooks = lower
+                .split(|c: char| !c.is_alphabetic())
+                .any(|w| COOKING_VERBS.iter().any(|v| w.starts_with(v)));
+            let has_temperature = lower.contains('°') || lower.contains("degrees");
+            if !cooks || has_temperature {
+                return None;
+            }
+            let meat = Meat::find_in(&text)?;
+            Some(Diagnostic::new(
+                Severity::Hint,
+                MDError::new(
+                    &format!(
+                        "step {} cooks {} without a target temperature (at least {})",
+                        flat.path,
+                        meat,
+                        safe_temperature(meat)
+                    ),
+                    None,
+                )
+                .with_code(ErrorCode::MissingDonenessTemperature),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::MDResult;
+    use indoc::indoc;
+
+    #[test]
+    fn table() {
+        for meat in Meat::ALL {
+            assert!(doneness().iter().any(|t| t.meat == meat));
+            safe_temperature(meat);
+        }
+        let steak = doneness_temperature(Meat::Beef, Doneness::MediumRare).unwrap();
+        assert_eq!(steak.to_string(), "57 °C / 135 °F");
+        assert!(doneness_temperature(Meat::Poultry, Doneness::Rare).is_none());
+        assert_eq!(safe_temperature(Meat::Poultry).celsius, 74);
+
+        assert_eq!(Meat::find_in("Grilled Chicken thighs"), Some(Meat::Poultry));
+        assert_eq!(Meat::find_in("ground beef"), Some(Meat::Ground));
+        assert_eq!(Meat::find_in("ground pepper"), None);
+    }
+
+    #[test]
+    fn lint() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(indoc! {"
+            # Dinner
+            ## Ingredients
+
+            - Chicken breasts, 2
+            - Steaks, 2
+            - Potatoes, 500 g
+
+            ## Instructions
+
+            - Roast the *potatoes*
+            - Cook the *chicken breasts* for **20 min**
+            - Sear the *steaks* to 57 °C
+        "})?;
+        let hints = lint_doneness(&recipe);
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].severity(), Severity::Hint);
+        assert_eq!(
+            hints[0].error().message(),
+            "step 2 cooks poultry without a target temperature (at least 74 °C / 165 °F)"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+//! Rendering of recipes to other formats.
+
+pub mod html;
+pub mod latex;
+pub mod markdown;
+pub mod text;
+
+use crate::recipe::{ingredients::Ingredient, unit::Quantity};
+
+/// Which ingredients renderers annotate with their quantity as written in the recipe, once scaled
+/// or converted (e.g., "450 g (1 lb)").
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OriginalQuantities {
+    #[default]
+    Hidden,
+    /// Only ingredients whose unit was converted.
+    Converted,
+    /// Every ingredient whose quantity changed.
+    Changed,
+}
+
+impl OriginalQuantities {
+    /// Original quantity to show next to the quantity of an ingredient, if any.
+    pub fn annotation<'a>(&self, ingredient: &'a Ingredient) -> Option<&'a Quantity> {
+        let original = ingredient.original_quantity()?;
+        match self {
+            Self::Hidden => None,
+            Self::Converted => ingredient
+                .quantity()
+                .filter(|q| q.unit != original.unit)
+                .and(Some(original)),
+            Self::Changed => Some(original),
+        }
+    }
+}
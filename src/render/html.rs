@@ -0,0 +1,645 @@
+//! Semantic HTML for recipes, for static-site generators.
+//!
+//! Quantities and timers carry their values in data attributes (`data-amount`, `data-unit`,
+//! `data-seconds`) so that scripts can scale or convert them, and ingredient references point to
+//! their ingredient line (`data-ingredient`). The scale presets of the recipe are listed in the
+//! `data-scales` attribute of the article, for scaling toggles, and every branch of scale-dependent
+//! text is rendered with its `data-scale`, all but that for scale 1 being `hidden`. Every element
+//! gets a CSS class, which can be changed with [`HtmlRenderer::classes`].
+
+use std::fmt::Write;
+
+use super::OriginalQuantities;
+use crate::recipe::{
+    amount::Amount,
+    ingredients::{Ingredient, IngredientOptions, Ingredients},
+    instructions::{Numbering, Step, TextElem},
+    notes::{NoteKind, RichText},
+    schema_org::iso_duration,
+    unit::{Quantity, Temperature},
+    Recipe,
+};
+
+/// Elements of the rendered recipe that get a CSS class.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Element {
+    Recipe,
+    Title,
+    Metadata,
+    Ingredients,
+    IngredientGroup,
+    Ingredient,
+    Quantity,
+    OriginalQuantity,
+    Info,
+    Equipment,
+    Instructions,
+    InstructionGroup,
+    Step,
+    IngredientRef,
+    Timer,
+    Temperature,
+    Conditional,
+    Link,
+    Image,
+    Notes,
+    Description,
+}
+
+impl Element {
+    /// Default class (e.g., "recipe-ingredient").
+    pub fn default_class(&self) -> &'static str {
+        match self {
+            Self::Recipe => "recipe",
+            Self::Title => "recipe-title",
+            Self::Metadata => "recipe-metadata",
+            Self::Ingredients => "recipe-ingredients",
+            Self::IngredientGroup => "recipe-ingredient-group",
+            Self::Ingredient => "recipe-ingredient",
+            Self::Quantity => "recipe-quantity",
+            Self::OriginalQuantity => "recipe-original-quantity",
+            Self::Info => "recipe-info",
+            Self::Equipment => "recipe-equipment",
+            Self::Instructions => "recipe-instructions",
+            Self::InstructionGroup => "recipe-instruction-group",
+            Self::Step => "recipe-step",
+            Self::IngredientRef => "recipe-ingredient-ref",
+            Self::Timer => "recipe-timer",
+            Self::Temperature => "recipe-temperature",
+            Self::Conditional => "recipe-conditional",
+            Self::Link => "recipe-link",
+            Self::Image => "recipe-image",
+            Self::Notes => "recipe-notes",
+            Self::Description => "recipe-description",
+        }
+    }
+}
+
+type Classes = Box<dyn Fn(Element) -> Option<String>>;
+
+pub struct HtmlRenderer {
+    classes: Classes,
+    original_quantities: OriginalQuantities,
+}
+
+impl Default for HtmlRenderer {
+    fn default() -> Self {
+        Self {
+            classes: Box::new(|element| Some(element.default_class().to_string())),
+            original_quantities: OriginalQuantities::default(),
+        }
+    }
+}
+
+/// Renders a recipe with the default classes.
+pub fn render(recipe: &Recipe) -> String {
+    HtmlRenderer::new().render(recipe)
+}
+
+impl HtmlRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the class of each element (`None` for no class attribute).
+    pub fn classes(mut self, classes: impl Fn(Element) -> Option<String> + 'static) -> Self {
+        self.classes = Box::new(classes);
+        self
+    }
+
+    /// Sets which scaled or converted quantities are followed by their original quantity.
+    pub fn original_quantities(mut self, original_quantities: OriginalQuantities) -> Self {
+        self.original_quantities = original_quantities;
+        self
+    }
+
+    pub fn render(&self, recipe: &Recipe) -> String {
+        let mut html = String::new();
+        let scales: Vec<String> = recipe
+            .metadata()
+            .scales()
+            .iter()
+            .map(|preset| preset.factor().to_string())
+            .collect();
+        let attributes = match scales.is_empty() {
+            true => String::new(),
+            false => format!(" data-scales=\"{}\"", scales.join(" ")),
+        };
+        html.push_str(&self.open("article", Element::Recipe, &attributes));
+        html.push_str(&self.open("h1", Element::Title, ""));
+        html.push_str(&escape(recipe.name()));
+        html.push_str("</h1>\n");
+        if let Some(image) = recipe.image() {
+            html.push_str(&self.image(&image.url, &image.alt));
+            html.push('\n');
+        }
+        if !recipe.description().is_empty() {
+            html.push_str(&self.open("div", Element::Description, ""));
+            for paragraph in recipe.description() {
+                html.push_str("<p>");
+                rich_text(&mut html, paragraph);
+                html.push_str("</p>\n");
+            }
+            html.push_str("</div>\n");
+        }
+        self.metadata(&mut html, recipe);
+
+        html.push_str(&self.open("section", Element::Ingredients, ""));
+        html.push_str("<h2>Ingredients</h2>\n");
+        match recipe.ingredients() {
+            Ingredients::IngredientList(list) => self.ingredient_list(&mut html, list),
+            Ingredients::IngredientGroups(groups) => {
+                for group in groups {
+                    html.push_str(&self.open("section", Element::IngredientGroup, ""));
+                    let _ = write!(html, "<h3>{}", escape(group.name()));
+                    if let Some(yields) = group.yields() {
+                        let _ = write!(html, " (makes {})", escape(&yields.to_string()));
+                    }
+                    html.push_str("</h3>\n");
+                    self.ingredient_list(&mut html, group.ingredients());
+                    html.push_str("</section>\n");
+                }
+            }
+        }
+        html.push_str("</section>\n");
+
+        if !recipe.equipment().is_empty() {
+            html.push_str(&self.open("section", Element::Equipment, ""));
+            html.push_str("<h2>Equipment</h2>\n<ul>\n");
+            for tool in recipe.equipment().tools() {
+                let _ = writeln!(html, "<li>{}</li>", escape(tool.name()));
+            }
+            html.push_str("</ul>\n</section>\n");
+        }
+
+        html.push_str(&self.open("section", Element::Instructions, ""));
+        html.push_str("<h2>Instructions</h2>\n");
+        let instructions = recipe.instructions();
+        match instructions.groups().next() {
+            None => self.steps(&mut html, instructions.steps(), instructions.numbering()),
+            Some(_) => {
+                for (group, steps) in instructions.groups() {
+                    html.push_str(&self.open("section", Element::InstructionGroup, ""));
+                    let _ = writeln!(html, "<h3>{}</h3>", escape(group.name()));
+                    self.steps(&mut html, steps, group.numbering());
+                    html.push_str("</section>\n");
+                }
+            }
+        }
+        html.push_str("</section>\n");
+
+        for kind in NoteKind::ALL {
+            let notes: Vec<_> = recipe.notes().iter().filter(|n| n.kind() == kind).collect();
+            if notes.is_empty() {
+                continue;
+            }
+            html.push_str(&self.open("section", Element::Notes, ""));
+            let _ = writeln!(html, "<h2>{}</h2>", kind.heading());
+            for note in notes {
+                html.push_str("<p>");
+                rich_text(&mut html, note.content());
+                html.push_str("</p>\n");
+            }
+            html.push_str("</section>\n");
+        }
+        html.push_str("</article>\n");
+        html
+    }
+
+    /// Opening tag with the class of `element`, other attributes, and a newline for block
+    /// elements.
+    fn image(&self, url: &str, alt: &str) -> String {
+        let attributes = format!(" src=\"{}\" alt=\"{}\"", escape(url), escape(alt));
+        self.open("img", Element::Image, &attributes)
+    }
+
+    fn open(&self, tag: &str, element: Element, attributes: &str) -> String {
+        let mut open = format!("<{}", tag);
+        if let Some(class) = (self.classes)(element) {
+            let _ = write!(open, " class=\"{}\"", escape(&class));
+        }
+        open.push_str(attributes);
+        open.push('>');
+        if matches!(tag, "article" | "section" | "div" | "dl" | "ul" | "ol") {
+            open.push('\n');
+        }
+        open
+    }
+
+    fn metadata(&self, html: &mut String, recipe: &Recipe) {
+        let metadata = recipe.metadata();
+        let mut entries = vec![];
+        if let Some(servings) = metadata.servings() {
+            entries.push(("Servings", servings.to_string()));
+        }
+        if let Some(quantity) = metadata.yield_quantity() {
+            entries.push(("Yield", quantity.to_string()));
+        }
+        for (name, time) in [
+            ("Prep time", metadata.prep_time()),
+            ("Cook time", metadata.cook_time()),
+            ("Total time", metadata.total_time()),
+        ] {
+            if let Some(time) = time {
+                entries.push((name, time.to_string()));
+            }
+        }
+        if let Some(author) = metadata.author() {
+            entries.push(("Author", author.to_string()));
+        }
+        // The source links to the URL, named after the URL's host if it has no name.
+        let source = metadata.source().or(metadata.url().map(|url| url.host()));
+        if entries.is_empty() && source.is_none() {
+            return;
+        }
+        html.push_str(&self.open("dl", Element::Metadata, ""));
+        for (name, value) in entries {
+            let _ = writeln!(html, "<dt>{}</dt><dd>{}</dd>", name, escape(&value));
+        }
+        if let Some(source) = source {
+            let source = match metadata.url() {
+                Some(url) => {
+                    let attributes = format!(" href=\"{}\"", escape(url.as_str()));
+                    let open = self.open("a", Element::Link, &attributes);
+                    format!("{}{}</a>", open, escape(source))
+                }
+                None => escape(source),
+            };
+            let _ = writeln!(html, "<dt>Source</dt><dd>{}</dd>", source);
+        }
+        html.push_str("</dl>\n");
+    }
+
+    fn ingredient_list(&self, html: &mut String, list: &[IngredientOptions]) {
+        html.push_str("<ul>\n");
+        for options in list {
+            html.push_str(&self.open("li", Element::Ingredient, ""));
+            self.ingredient(html, options.ingredient());
+            for alternative in options.alternatives() {
+                html.push_str(" or ");
+                self.ingredient(html, alternative);
+            }
+            html.push_str("</li>\n");
+        }
+        html.push_str("</ul>\n");
+    }
+
+    fn ingredient(&self, html: &mut String, ingredient: &Ingredient) {
+        if let Some(quantity) = ingredient.quantity() {
+            self.quantity(html, quantity);
+            if let Some(original) = self.original_quantities.annotation(ingredient) {
+                html.push(' ');
+                html.push_str(&self.open("span", Element::OriginalQuantity, ""));
+                let _ = write!(html, "({})</span>", escape(&original.to_string()));
+            }
+            for alt in ingredient.alt_quantities() {
+                html.push_str(" / ");
+                self.quantity(html, alt);
+            }
+            html.push(' ');
+        }
+        html.push_str(&escape(ingredient.name()));
+        if let Some(info) = ingredient.info() {
+            html.push(' ');
+            html.push_str(&self.open("span", Element::Info, ""));
+            let _ = write!(html, "({})</span>", escape(info));
+        }
+    }
+
+    fn quantity(&self, html: &mut String, quantity: &Quantity) {
+        let mut attributes = String::new();
+        if !quantity.is_to_taste() {
+            let _ = write!(attributes, " data-amount=\"{}\"", quantity.amount);
+            if let Some(max) = quantity.max_amount {
+                let _ = write!(attributes, " data-max-amount=\"{}\"", max);
+            }
+            let _ = write!(
+                attributes,
+                " data-unit=\"{}\"",
+                escape(&quantity.unit.to_string())
+            );
+        }
+        html.push_str(&self.open("span", Element::Quantity, &attributes));
+        html.push_str(&escape(&quantity.to_string()));
+        html.push_str("</span>");
+    }
+
+    /// Writes steps as an ordered list, starting at the number of numbered steps.
+    fn steps(&self, html: &mut String, steps: &[Step], numbering: Numbering) {
+        match numbering {
+            Numbering::Numbers { start } if start != 1 => {
+                let _ = writeln!(html, "<ol start=\"{}\">", start);
+            }
+            _ => html.push_str("<ol>\n"),
+        }
+        for step in steps {
+            html.push_str(&self.open("li", Element::Step, ""));
+            self.description(html, step.description());
+            if !step.substeps().is_empty() {
+                html.push('\n');
+                self.steps(html, step.substeps(), step.substep_numbering());
+            }
+            html.push_str("</li>\n");
+        }
+        html.push_str("</ol>\n");
+    }
+
+    fn description(&self, html: &mut String, elems: &[TextElem]) {
+        for elem in elems {
+            match elem {
+                TextElem::Text(text) => html.push_str(&escape(text)),
+                TextElem::IngredientRef(r) => {
+                    let attributes = r
+                        .index()
+                        .map_or(String::new(), |i| format!(" data-ingredient=\"{}\"", i));
+                    html.push_str(&self.open("span", Element::IngredientRef, &attributes));
+                    html.push_str(&escape(r.name()));
+                    html.push_str("</span>");
+                }
+                TextElem::Timer(time) => {
+                    let attributes = format!(
+                        " datetime=\"{}\" data-seconds=\"{}\"",
+                        iso_duration(time),
+                        time.as_seconds()
+                    );
+                    html.push_str(&self.open("time", Element::Timer, &attributes));
+                    html.push_str(&escape(&time.to_string()));
+                    html.push_str("</time>");
+                }
+                TextElem::Temperature(temperature) => {
+                    let celsius = temperature.convert_to(Temperature::Celsius);
+                    let attributes = format!(" data-celsius=\"{:.0}\"", celsius.amount);
+                    html.push_str(&self.open("span", Element::Temperature, &attributes));
+                    html.push_str(&escape(&temperature.to_string()));
+                    html.push_str("</span>");
+                }
+                TextElem::Conditional(conditional) => {
+                    // Every branch is rendered for scaling toggles, showing that for scale 1.
+                    let shown = conditional.resolve(Amount::ONE);
+                    for (factor, branch) in conditional.branches() {
+                        let mut attributes = format!(" data-scale=\"{}\"", factor);
+                        if !std::ptr::eq(branch.as_slice(), shown) {
+                            attributes.push_str(" hidden");
+                        }
+                        html.push_str(&self.open("span", Element::Conditional, &attributes));
+                        self.description(html, branch);
+                        html.push_str("</span>");
+                    }
+                }
+                TextElem::Image { url, alt } => html.push_str(&self.image(url, alt)),
+                TextElem::Formatted(text) => rich_text(html, std::slice::from_ref(text)),
+                TextElem::Link { text, url } => {
+                    let attributes = format!(" href=\"{}\"", escape(url));
+                    html.push_str(&self.open("a", Element::Link, &attributes));
+                    html.push_str(&escape(text));
+                    html.push_str("</a>");
+                }
+                TextElem::WikiLink { target, .. } => {
+                    // Targets name notes rather than URLs, so pages resolve them.
+                    let attributes = format!(" data-target=\"{}\"", escape(target));
+                    html.push_str(&self.open("span", Element::Link, &attributes));
+                    html.push_str(&escape(&elem.to_string()));
+                    html.push_str("</span>");
+                }
+            }
+        }
+    }
+}
+
+fn rich_text(html: &mut String, content: &[RichText]) {
+    for text in content {
+        match text {
+            RichText::Text(text) => html.push_str(&escape(text)),
+            RichText::Code(code) => {
+                let _ = write!(html, "<code>{}</code>", escape(code));
+            }
+            RichText::Emphasis(content) => {
+                html.push_str("<em>");
+                rich_text(html, content);
+                html.push_str("</em>");
+            }
+            RichText::Strong(content) => {
+                html.push_str("<strong>");
+                rich_text(html, content);
+                html.push_str("</strong>");
+            }
+            RichText::Link { url, content } => {
+                let _ = write!(html, "<a href=\"{}\">", escape(url));
+                rich_text(html, content);
+                html.push_str("</a>");
+            }
+        }
+    }
+}
+
+/// Escapes text for use in HTML content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::{
+        unit::{Mass, Unit},
+        MDResult,
+    };
+    use indoc::indoc;
+
+    const RECIPE: &str = indoc! {"
+        ---
+        servings: 2
+        ---
+        # Mac & cheese
+        ## Ingredients
+
+        - Macaroni, 200 g
+        - Cheese, 100-150 g (grated)
+
+        ## Instructions
+
+        - Boil the *macaroni* for **10 min**
+            - Drain
+        - Stir in the *cheese*
+
+        ## Tips
+
+        Use *aged* cheddar.
+    "};
+
+    #[test]
+    fn render_recipe() -> MDResult<()> {
+        let html = render(&Recipe::from_mdast(RECIPE)?);
+        assert_eq!(
+            html,
+            indoc! {r#"
+                <article class="recipe">
+                <h1 class="recipe-title">Mac &amp; cheese</h1>
+                <dl class="recipe-metadata">
+                <dt>Servings</dt><dd>2</dd>
+                </dl>
+                <section class="recipe-ingredients">
+                <h2>Ingredients</h2>
+                <ul>
+                <li class="recipe-ingredient"><span class="recipe-quantity" data-amount="200" data-unit="g">200 g</span> Macaroni</li>
+                <li class="recipe-ingredient"><span class="recipe-quantity" data-amount="100" data-max-amount="150" data-unit="g">100-150 g</span> Cheese <span class="recipe-info">(grated)</span></li>
+                </ul>
+                </section>
+                <section class="recipe-instructions">
+                <h2>Instructions</h2>
+                <ol>
+                <li class="recipe-step">Boil the <span class="recipe-ingredient-ref" data-ingredient="0">macaroni</span> for <time class="recipe-timer" datetime="PT10M" data-seconds="600">10 min</time>
+                <ol>
+                <li class="recipe-step">Drain</li>
+                </ol>
+                </li>
+                <li class="recipe-step">Stir in the <span class="recipe-ingredient-ref" data-ingredient="1">cheese</span></li>
+                </ol>
+                </section>
+                <section class="recipe-notes">
+                <h2>Tips</h2>
+                <p>Use <em>aged</em> cheddar.</p>
+                </section>
+                </article>
+            "#}
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn original_quantities() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(RECIPE)?
+            .scaled(Amount::from_int(2))
+            .converted(|q| {
+                (q.amount > Amount::from_int(250)).then_some(Unit::Mass(Mass::Kilogram))
+            });
+        let renderer = HtmlRenderer::new().original_quantities(OriginalQuantities::Converted);
+        let html = renderer.render(&recipe);
+        assert!(html.contains(concat!(
+            r#"<span class="recipe-quantity" data-amount="0.4" data-unit="kg">0.4 kg</span> "#,
+            r#"<span class="recipe-original-quantity">(200 g)</span> Macaroni"#,
+        )));
+        assert!(html.contains(r#"data-unit="g">200-300 g</span> Cheese"#));
+        Ok(())
+    }
+
+    #[test]
+    fn classes() -> MDResult<()> {
+        let renderer = HtmlRenderer::new().classes(|element| match element {
+            Element::Recipe => Some("card".to_string()),
+            _ => None,
+        });
+        let html = renderer.render(&Recipe::from_mdast(RECIPE)?);
+        assert!(html.starts_with("<article class=\"card\">\n<h1>Mac &amp; cheese</h1>"));
+        assert_eq!(html.matches("class=").count(), 1);
+
+        let recipe = Recipe::from_mdast(&RECIPE.replace("servings: 2", "scales: [0.5, 1, 2]"))?;
+        assert!(render(&recipe).starts_with("<article class=\"recipe\" data-scales=\"0.5 1 2\">"));
+        Ok(())
+    }
+
+    #[test]
+    fn conditionals() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(
+            &RECIPE.replace("for **10 min**", "for {1x: **10 min** | 2x: **12 min**}"),
+        )?;
+        assert!(render(&recipe).contains(concat!(
+            "<span class=\"recipe-conditional\" data-scale=\"1\"><time class=\"recipe-timer\" datetime=\"PT10M\" data-seconds=\"600\">10 min</time></span>",
+            "<span class=\"recipe-conditional\" data-scale=\"2\" hidden><time class=\"recipe-timer\" datetime=\"PT12M\" data-seconds=\"720\">12 min</time></span>",
+        )));
+        Ok(())
+    }
+
+    #[test]
+    fn images() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(
+            &RECIPE
+                .replace("servings: 2", "servings: 2\nimage: mac.jpg")
+                .replace("for **10 min**", "![Drained pasta](<drained & ready.jpg>)"),
+        )?;
+        let html = render(&recipe);
+        assert!(html.contains("</h1>\n<img class=\"recipe-image\" src=\"mac.jpg\" alt=\"\">\n"));
+        assert!(html.contains(
+            r#"<img class="recipe-image" src="drained &amp; ready.jpg" alt="Drained pasta">"#
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn links() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(&RECIPE.replace(
+            "for **10 min**",
+            "like [[Pasta|pasta]], see [the box](https://example.org/?a&b)",
+        ))?;
+        let html = render(&recipe);
+        assert!(html.contains(r#"like <span class="recipe-link" data-target="Pasta">pasta</span>"#));
+        assert!(html.contains(
+            r#"see <a class="recipe-link" href="https://example.org/?a&amp;b">the box</a>"#
+        ));
+
+        let recipe = Recipe::from_mdast(&RECIPE.replace(
+            "servings: 2",
+            "servings: 2\nauthor: Ada\nsource: https://example.org/mac",
+        ))?;
+        let html = render(&recipe);
+        assert!(html.contains("<dt>Author</dt><dd>Ada</dd>"));
+        assert!(html.contains(
+            r#"<dt>Source</dt><dd><a class="recipe-link" href="https://example.org/mac">example.org</a></dd>"#
+        ));
+        Ok(())
+    }
+    #[test]
+    fn formatting() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(&RECIPE.replace(
+            "for **10 min**",
+            "with `00` flour and *finely **red** onion*",
+        ))?;
+        let html = render(&recipe);
+        assert!(html
+            .contains("with <code>00</code> flour and <em>finely <strong>red</strong> onion</em>"));
+        Ok(())
+    }
+    #[test]
+    fn numbered_steps() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(
+            &RECIPE
+                .replace("- Boil", "3. Boil")
+                .replace("- Stir", "4. Stir"),
+        )?;
+        let html = render(&recipe);
+        assert!(html.contains("<ol start=\"3\">\n"));
+        assert_eq!(html.matches("<ol>\n").count(), 1);
+        Ok(())
+    }
+    #[test]
+    fn instruction_groups() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(
+            &RECIPE.replace("## Instructions\n", "## Instructions\n\n### Pot\n"),
+        )?;
+        assert_eq!(recipe.instructions().groups().count(), 1);
+        let html = render(&recipe);
+        assert!(html.contains("<section class=\"recipe-instruction-group\">\n<h3>Pot</h3>\n<ol>\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn temperatures() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(&RECIPE.replace("- Boil", "- Boil at **212°F**"))?;
+        let html = render(&recipe);
+        assert!(
+            html.contains("<span class=\"recipe-temperature\" data-celsius=\"100\">212 °F</span>")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+//! LaTeX for printable cookbooks.
+//!
+//! Each recipe is rendered as a `\section` with its ingredients in a table and its steps in an
+//! `enumerate` environment. [`document`] wraps several recipes in a complete document with a table
+//! of contents, which compiles with `pdflatex` and only requires the `hyperref` package.
+
+use std::fmt::Write;
+
+use crate::recipe::{
+    amount::Amount,
+    ingredients::{Ingredient, IngredientOptions, Ingredients},
+    instructions::{Step, TextElem},
+    notes::{NoteKind, RichText},
+    Recipe,
+};
+
+const PREAMBLE: &str = "\\documentclass{article}\n\\usepackage[utf8]{inputenc}\n\\usepackage[T1]{fontenc}\n\\usepackage{hyperref}\n";
+
+/// Renders a recipe as a LaTeX fragment, to be included in a document.
+pub fn render(recipe: &Recipe) -> String {
+    let mut latex = String::new();
+    let _ = writeln!(latex, "\\section{{{}}}", escape(recipe.name()));
+    metadata(&mut latex, recipe);
+
+    latex.push_str("\\subsection*{Ingredients}\n");
+    match recipe.ingredients() {
+        Ingredients::IngredientList(list) => ingredient_table(&mut latex, list),
+        Ingredients::IngredientGroups(groups) => {
+            for group in groups {
+                let name = match group.yields() {
+                    Some(yields) => format!("{} (makes {})", group.name(), yields),
+                    None => group.name().to_string(),
+                };
+                let _ = writeln!(latex, "\\subsubsection*{{{}}}", escape(&name));
+                ingredient_table(&mut latex, group.ingredients());
+            }
+        }
+    }
+
+    if !recipe.equipment().is_empty() {
+        latex.push_str("\\subsection*{Equipment}\n\\begin{itemize}\n");
+        for tool in recipe.equipment().tools() {
+            let _ = writeln!(latex, "\\item {}", escape(tool.name()));
+        }
+        latex.push_str("\\end{itemize}\n");
+    }
+
+    latex.push_str("\\subsection*{Instructions}\n");
+    let instructions = recipe.instructions();
+    match instructions.groups().next() {
+        None => steps(&mut latex, instructions.steps()),
+        Some(_) => {
+            for (group, group_steps) in instructions.groups() {
+                let _ = writeln!(latex, "\\subsubsection*{{{}}}", escape(group.name()));
+                steps(&mut latex, group_steps);
+            }
+        }
+    }
+
+    for kind in NoteKind::ALL {
+        let notes: Vec<_> = recipe.notes().iter().filter(|n| n.kind() == kind).collect();
+        if notes.is_empty() {
+            continue;
+        }
+        let _ = writeln!(latex, "\\subsection*{{{}}}", kind.heading());
+        for note in notes {
+            rich_text(&mut latex, note.content());
+            latex.push_str("\n\n");
+        }
+    }
+    latex
+}
+
+/// Renders recipes as a complete document, with a table of contents.
+pub fn document<'a>(title: &str, recipes: impl IntoIterator<Item = &'a Recipe>) -> String {
+    let mut latex = PREAMBLE.to_string();
+    let _ = writeln!(latex, "\\title{{{}}}\n\\date{{}}", escape(title));
+    latex.push_str("\\begin{document}\n\\maketitle\n\\tableofcontents\n");
+    for recipe in recipes {
+        latex.push_str("\\clearpage\n");
+        latex.push_str(&render(recipe));
+    }
+    latex.push_str("\\end{document}\n");
+    latex
+}
+
+fn metadata(latex: &mut String, recipe: &Recipe) {
+    let metadata = recipe.metadata();
+    let mut entries = vec![];
+    if let Some(servings) = metadata.servings() {
+        entries.push(format!("\\textbf{{Servings:}} {}", servings));
+    }
+    if let Some(quantity) = metadata.yield_quantity() {
+        entries.push(format!(
+            "\\textbf{{Yield:}} {}",
+            escape(&quantity.to_string())
+        ));
+    }
+    for (name, time) in [
+        ("Prep time", metadata.prep_time()),
+        ("Cook time", metadata.cook_time()),
+        ("Total time", metadata.total_time()),
+    ] {
+        if let Some(time) = time {
+            entries.push(format!(
+                "\\textbf{{{}:}} {}",
+                name,
+                escape(&time.to_string())
+            ));
+        }
+    }
+    if !entries.is_empty() {
+        latex.push_str(&entries.join(" \\quad "));
+        latex.push_str("\n\n");
+    }
+}
+
+fn ingredient_table(latex: &mut String, list: &[IngredientOptions]) {
+    latex.push_str("\\begin{tabular}{@{}rl@{}}\n");
+    for options in list {
+        let ingredient = options.ingredient();
+        let _ = write!(latex, "{} & ", escape(&quantity_text(ingredient)));
+        latex.push_str(&ingredient_text(ingredient));
+        for alternative in options.alternatives() {
+            latex.push_str(" or ");
+            if alternative.quantity().is_some() {
+                let _ = write!(latex, "{} ", escape(&quantity_text(alternative)));
+            }
+            latex.push_str(&ingredient_text(alternative));
+        }
+        latex.push_str(" \\\\\n");
+    }
+    latex.push_str("\\end{tabular}\n");
+}
+
+fn quantity_text(ingredient: &Ingredient) -> String {
+    let mut quantities: Vec<String> = ingredient
+        .quantity()
+        .into_iter()
+        .map(|q| q.to_string())
+        .collect();
+    quantities.extend(ingredient.alt_quantities().iter().map(|q| q.to_string()));
+    quantities.join(" / ")
+}
+
+fn ingredient_text(ingredient: &Ingredient) -> String {
+    let mut text = escape(ingredient.name());
+    if let Some(info) = ingredient.info() {
+        let _ = write!(text, " \\emph{{({})}}", escape(info));
+    }
+    text
+}
+
+fn steps(latex: &mut String, steps: &[Step]) {
+    latex.push_str("\\begin{enumerate}\n");
+    for step in steps {
+        latex.push_str("\\item ");
+        description(latex, step.description());
+        latex.push('\n');
+        if !step.substeps().is_empty() {
+            self::steps(latex, step.substeps());
+        }
+    }
+    latex.push_str("\\end{enumerate}\n");
+}
+
+/// Writes the description of a step, at scale 1 for scale-dependent text.
+fn description(latex: &mut String, elems: &[TextElem]) {
+    for elem in elems {
+        match elem {
+            TextElem::Text(text) => latex.push_str(&escape(text)),
+            TextElem::IngredientRef(r) => latex.push_str(&escape(r.name())),
+            TextElem::Timer(_) | TextElem::Temperature(_) => {
+                let _ = write!(latex, "\\textbf{{{}}}", escape(&elem.to_string()));
+            }
+            TextElem::Conditional(conditional) => {
+                description(latex, conditional.resolve(Amount::ONE))
+            }
+            TextElem::Link { text, url } => {
+                let _ = write!(latex, "\\href{{{}}}{{{}}}", escape_url(url), escape(text));
+            }
+            TextElem::WikiLink { .. } => latex.push_str(&escape(&elem.to_string())),
+            TextElem::Formatted(text) => rich_text(latex, std::slice::from_ref(text)),
+            // Images may be remote, so they are left out.
+            TextElem::Image { .. } => (),
+        }
+    }
+}
+
+fn rich_text(latex: &mut String, content: &[RichText]) {
+    for text in content {
+        match text {
+            RichText::Text(text) => latex.push_str(&escape(text)),
+            RichText::Code(code) => {
+                let _ = write!(latex, "\\texttt{{{}}}", escape(code));
+            }
+            RichText::Emphasis(content) => {
+                latex.push_str("\\emph{");
+                rich_text(latex, content);
+                latex.push('}');
+            }
+            RichText::Strong(content) => {
+                latex.push_str("\\textbf{");
+                rich_text(latex, content);
+                latex.push('}');
+            }
+            RichText::Link { url, content } => {
+                let _ = write!(latex, "\\href{{{}}}{{", escape_url(url));
+                rich_text(latex, content);
+                latex.push('}');
+            }
+        }
+    }
+}
+
+/// Escapes the characters that LaTeX treats specially in text.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes a URL for `\href`, in which only a few characters are special.
+fn escape_url(url: &str) -> String {
+    let mut escaped = String::with_capacity(url.len());
+    for c in url.chars() {
+        if matches!(c, '%' | '#' | '\\' | '{' | '}') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::MDResult;
+    use indoc::indoc;
+
+    const RECIPE: &str = indoc! {"
+        ---
+        servings: 2
+        ---
+        # Mac & cheese
+        ## Ingredients
+
+        - Macaroni, 200 g
+        - Cheese, 100-150 g (grated)
+        - Pepper
+
+        ## Instructions
+
+        - Boil the *macaroni* for **10 min**
+            - Drain
+        - Stir in the *cheese*
+
+        ## Tips
+
+        Use *aged* cheddar, about 50% of the [cheese](https://example.com/#cheddar).
+    "};
+
+    #[test]
+    fn render_recipe() -> MDResult<()> {
+        let latex = render(&Recipe::from_mdast(RECIPE)?);
+        assert_eq!(
+            latex,
+            indoc! {r"
+                \section{Mac \& cheese}
+                \textbf{Servings:} 2
+
+                \subsection*{Ingredients}
+                \begin{tabular}{@{}rl@{}}
+                200 g & Macaroni \\
+                100-150 g & Cheese \emph{(grated)} \\
+                 & Pepper \\
+                \end{tabular}
+                \subsection*{Instructions}
+                \begin{enumerate}
+                \item Boil the macaroni for \textbf{10 min}
+                \begin{enumerate}
+                \item Drain
+                \end{enumerate}
+                \item Stir in the cheese
+                \end{enumerate}
+                \subsection*{Tips}
+                Use \emph{aged} cheddar, about 50\% of the \href{https://example.com/\#cheddar}{cheese}.
+
+            "}
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn render_document() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(RECIPE)?;
+        let latex = document("Pasta_book", [&recipe, &recipe]);
+        assert!(latex.starts_with("\\documentclass{article}\n"));
+        assert!(latex.contains("\\title{Pasta\\_book}\n"));
+        assert_eq!(latex.matches("\\section{Mac \\& cheese}").count(), 2);
+        assert!(latex.ends_with("\\end{enumerate}\n\\subsection*{Tips}\nUse \\emph{aged} cheddar, about 50\\% of the \\href{https://example.com/\\#cheddar}{cheese}.\n\n\\end{document}\n"));
+        assert_eq!(
+            escape("a\\b~c^d"),
+            "a\\textbackslash{}b\\textasciitilde{}c\\textasciicircum{}d"
+        );
+        Ok(())
+    }
+}
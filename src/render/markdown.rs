@@ -0,0 +1,1049 @@
+//! Markdown in the [canonical](crate::canonical) format, which parses back to the same recipe.
+
+use std::{collections::HashMap, fmt::Write};
+
+use saphyr::LoadableYamlNode;
+
+use crate::{
+    canonical::{self, RULES, SIZES_KEY},
+    recipe::{
+        canning::{AltitudeRange, Canning},
+        coffee::BrewRatio,
+        density::DensityTable,
+        equipment::{Tool, ToolSize},
+        ingredients::{Ingredient, IngredientGroup, IngredientOptions, Ingredients, LinkStyle},
+        instructions::{Instructions, Numbering, Step, TextElem},
+        language::Language,
+        metadata::{KeyAliases, MetaValue, Metadata, Nutrition, SizeInfo, UnitMod},
+        note_name,
+        notes::{NoteKind, RichText},
+        scaling::ScalePreset,
+        Recipe,
+    },
+};
+
+/// Renders a recipe as canonical Markdown.
+pub fn render(recipe: &Recipe) -> String {
+    let mut md = String::new();
+    frontmatter(&mut md, recipe.metadata());
+    let _ = writeln!(md, "# {}", escape(recipe.name()));
+    let blank_lines = "\n".repeat(RULES.blank_lines);
+    if let Some(cover) = recipe.leading_image() {
+        md.push_str(&blank_lines);
+        let _ = writeln!(md, "{}", image(&cover.url, &cover.alt));
+        md.push_str(&blank_lines);
+    }
+    for (i, paragraph) in recipe.description().iter().enumerate() {
+        if i == 0 && recipe.leading_image().is_none() {
+            md.push_str(&blank_lines);
+        }
+        let _ = writeln!(md, "{}", rich_text(paragraph));
+        md.push_str(&blank_lines);
+    }
+
+    // Headings and units stay in the recipe's language.
+    let names = recipe.section_names();
+    let language = recipe.language().unwrap_or(Language::English);
+    let mut sections = vec![(
+        names.ingredients.as_str(),
+        ingredients(recipe.ingredients(), language),
+    )];
+    if !recipe.equipment().is_empty() {
+        let tools = recipe.equipment().tools().iter().map(tool);
+        sections.push((&names.equipment, list(tools)));
+    }
+    sections.push((&names.instructions, instructions(recipe.instructions())));
+    for kind in NoteKind::ALL {
+        let notes: Vec<String> = recipe
+            .notes()
+            .iter()
+            .filter(|n| n.kind() == kind)
+            .map(|n| rich_text(n.content()).replace('\n', "\n  "))
+            .collect();
+        if !notes.is_empty() {
+            sections.push((names.note_heading(kind), list(notes.into_iter())));
+        }
+    }
+
+    for (i, (heading, content)) in sections.iter().enumerate() {
+        if i > 0 {
+            md.push_str(&blank_lines);
+        }
+        let _ = writeln!(md, "## {}", heading);
+        if !content.is_empty() {
+            md.push_str(&blank_lines);
+            md.push_str(content);
+        }
+    }
+    md
+}
+
+fn list(items: impl Iterator<Item = String>) -> String {
+    items.fold(String::new(), |mut md, item| {
+        let _ = writeln!(md, "{} {}", RULES.list_marker, item);
+        md
+    })
+}
+
+fn ingredients(ingredients: &Ingredients, language: Language) -> String {
+    match ingredients {
+        Ingredients::IngredientList(list) => ingredient_list(list, language),
+        Ingredients::IngredientGroups(groups) => groups
+            .iter()
+            .map(|group| {
+                format!(
+                    "### {}\n{}",
+                    group_heading(group, language),
+                    ingredient_list(group.ingredients(), language)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(&"\n".repeat(RULES.blank_lines)),
+    }
+}
+
+fn group_heading(group: &IngredientGroup, language: Language) -> String {
+    let mut heading = match group.sub_recipe() {
+        Some(target) => sub_recipe_link(group.name(), target, group.link_style()),
+        None => escape(group.name()),
+    };
+    if let Some(yields) = group.yields() {
+        let _ = write!(
+            heading,
+            " (makes {})",
+            canonical::quantity_in(yields, language)
+        );
+    }
+    heading
+}
+
+fn instructions(instructions: &Instructions) -> String {
+    let mut md = String::new();
+    if instructions.groups().next().is_none() {
+        steps(&mut md, instructions.steps(), instructions.numbering(), 0);
+        return md;
+    }
+    for (i, (group, group_steps)) in instructions.groups().enumerate() {
+        if i > 0 {
+            md.push_str(&"\n".repeat(RULES.blank_lines));
+        }
+        let _ = writeln!(md, "### {}", escape(group.name()));
+        steps(&mut md, group_steps, group.numbering(), 0);
+    }
+    md
+}
+
+fn ingredient_list(lines: &[IngredientOptions], language: Language) -> String {
+    list(lines.iter().map(|options| {
+        std::iter::once(options.ingredient())
+            .chain(options.alternatives())
+            .map(|i| ingredient(i, language))
+            .collect::<Vec<_>>()
+            .join(RULES.alternative_separator)
+    }))
+}
+
+fn ingredient(ingredient: &Ingredient, language: Language) -> String {
+    let mut line = match ingredient.sub_recipe() {
+        Some(target) => sub_recipe_link(ingredient.name(), target, ingredient.link_style()),
+        None => escape(ingredient.name()),
+    };
+    if let Some(quantity) = ingredient.quantity() {
+        line.push_str(RULES.quantity_separator);
+        line.push_str(&canonical::quantity_in(quantity, language));
+        for alt in ingredient.alt_quantities() {
+            line.push_str(RULES.alt_quantity_separator);
+            line.push_str(&canonical::quantity_in(alt, language));
+        }
+    }
+    if let Some(info) = ingredient.info() {
+        let _ = write!(line, " ({})", escape(info));
+    }
+    line
+}
+
+/// Writes a link named `name` to another recipe. Wikilinks only have an alias if the name is not
+/// the note's (e.g., "[[Pizza dough]]" but "[[Pizza dough|Dough]]").
+fn sub_recipe_link(name: &str, target: &str, style: LinkStyle) -> String {
+    match style {
+        LinkStyle::Markdown => format!("[{}]({})", escape(name), destination(target)),
+        LinkStyle::Wiki if note_name(target) == name => format!("[[{}]]", target),
+        LinkStyle::Wiki => format!("[[{}|{}]]", target, name),
+    }
+}
+
+fn tool(tool: &Tool) -> String {
+    match tool.size() {
+        Some(ToolSize::Inline(size)) => format!("{}, {}", escape(tool.name()), size_info(size)),
+        Some(ToolSize::Ref(name)) => format!("{}, {}", escape(tool.name()), escape(name)),
+        None => escape(tool.name()),
+    }
+}
+
+fn size_info(size: &SizeInfo) -> String {
+    let quantity = canonical::quantity_of(size.quantity());
+    match size.unit_mod() {
+        Some(UnitMod::RadialDistance) => format!("{}°", quantity),
+        None => quantity,
+    }
+}
+
+fn steps(md: &mut String, steps: &[Step], numbering: Numbering, indent: usize) {
+    let spaces = " ".repeat(indent);
+    for (i, step) in steps.iter().enumerate() {
+        let marker = match numbering {
+            Numbering::Bullets => RULES.list_marker.to_string(),
+            Numbering::Numbers { start } => {
+                format!("{}{}", start as usize + i, RULES.number_delimiter)
+            }
+        };
+        // Continuation lines are aligned with the text after the list marker.
+        let continuation = format!("\n{}{}", spaces, " ".repeat(marker.len() + 1));
+        let text = text(step.description()).replace('\n', &continuation);
+        match text.is_empty() {
+            true => {
+                let _ = writeln!(md, "{}{}", spaces, marker);
+            }
+            false => {
+                let _ = writeln!(md, "{}{} {}", spaces, marker, text);
+            }
+        }
+        let substep_indent = indent + RULES.indent.max(marker.len() + 1);
+        self::steps(
+            md,
+            step.substeps(),
+            step.substep_numbering(),
+            substep_indent,
+        );
+    }
+}
+
+fn text(elems: &[TextElem]) -> String {
+    let mut text = String::new();
+    for elem in elems {
+        match elem {
+            TextElem::Text(s) => text.push_str(&escape(s)),
+            TextElem::IngredientRef(r) => {
+                let marker = RULES.reference_marker;
+                let _ = write!(text, "{}{}{}", marker, escape(r.name()), marker);
+            }
+            TextElem::Timer(timer) => {
+                let marker = RULES.timer_marker;
+                let _ = write!(
+                    text,
+                    "{}{}{}",
+                    marker,
+                    canonical::quantity_of(timer),
+                    marker
+                );
+            }
+            TextElem::Temperature(temperature) => {
+                let marker = RULES.timer_marker;
+                let _ = write!(
+                    text,
+                    "{}{}{}",
+                    marker,
+                    canonical::quantity_of(temperature),
+                    marker
+                );
+            }
+            TextElem::Conditional(conditional) => {
+                let branches: Vec<String> = conditional
+                    .branches()
+                    .iter()
+                    .map(|(factor, elems)| {
+                        format!("{}x: {}", canonical::amount(*factor), self::text(elems))
+                    })
+                    .collect();
+                let _ = write!(text, "{{{}}}", branches.join(RULES.branch_separator));
+            }
+            TextElem::Image { url, alt } => text.push_str(&image(url, alt)),
+            TextElem::Formatted(formatted) => {
+                text.push_str(&rich_text(std::slice::from_ref(formatted)))
+            }
+            TextElem::Link { text: link, url } => {
+                let _ = write!(text, "[{}]({})", escape(link), destination(url));
+            }
+            TextElem::WikiLink { target, alias } => match alias {
+                Some(alias) => {
+                    let _ = write!(text, "[[{}|{}]]", target, alias);
+                }
+                None => {
+                    let _ = write!(text, "[[{}]]", target);
+                }
+            },
+        }
+    }
+    text
+}
+
+fn rich_text(content: &[RichText]) -> String {
+    let mut text = String::new();
+    for elem in content {
+        match elem {
+            RichText::Text(s) => text.push_str(&escape(s)),
+            RichText::Code(code) => {
+                // Backticks in the code require a longer fence.
+                let fence = "`".repeat(longest_run(code, '`') + 1);
+                let pad = if code.starts_with('`') || code.ends_with('`') {
+                    " "
+                } else {
+                    ""
+                };
+                let _ = write!(text, "{}{}{}{}{}", fence, pad, code, pad, fence);
+            }
+            RichText::Emphasis(content) => {
+                let _ = write!(text, "*{}*", rich_text(content));
+            }
+            RichText::Strong(content) => {
+                let _ = write!(text, "**{}**", rich_text(content));
+            }
+            RichText::Link { url, content } => {
+                let _ = write!(text, "[{}]({})", rich_text(content), destination(url));
+            }
+        }
+    }
+    text
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|other| other != c)
+        .map(str::len)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Escapes the characters of text which Markdown would otherwise interpret.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        let line_start = i == 0 || text[..i].ends_with('\n');
+        let special = match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '{' | '}' => true,
+            // Entity references (e.g., "&amp;").
+            '&' => is_entity(&text[i + 1..]),
+            // Headings, block quotes, and list markers.
+            '#' | '>' | '-' | '+' => line_start,
+            // Ordered list markers (e.g., "1.").
+            '.' | ')' => {
+                let line = text[..i].rsplit('\n').next().unwrap_or_default();
+                !line.is_empty() && line.chars().all(|c| c.is_ascii_digit())
+            }
+            _ => false,
+        };
+        if special {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn is_entity(text: &str) -> bool {
+    let name = text.split(';').next().unwrap_or_default();
+    text.len() > name.len()
+        && !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '#')
+}
+
+/// Writes a link destination, in angle brackets if it contains spaces or parentheses.
+fn image(url: &str, alt: &str) -> String {
+    format!("![{}]({})", escape(alt), destination(url))
+}
+
+fn destination(url: &str) -> String {
+    match url.contains([' ', '(', ')', '<', '>']) {
+        true => format!("<{}>", url.replace('<', "\\<").replace('>', "\\>")),
+        false => url.to_string(),
+    }
+}
+
+/// YAML value in the frontmatter.
+enum Yaml {
+    /// Scalar read back as text, so that "4" may be written as a number.
+    Text(String),
+    /// Scalar read back as a string.
+    String(String),
+    /// Scalar written as is (e.g., numbers, booleans, and dates).
+    Raw(String),
+    List(Vec<Yaml>),
+    Map(Vec<(String, Yaml)>),
+}
+
+/// Replaces the frontmatter of a recipe's Markdown with `metadata`, leaving the rest of the file
+/// as it is (e.g., after adding a tag with [`Metadata::add_tag`]). Keys keep their order in the
+/// original frontmatter, and new keys follow it in canonical order.
+pub fn update_frontmatter(source: &str, metadata: &Metadata) -> String {
+    let (yaml, body) = split_frontmatter(source);
+    // Keys as written, under their canonical names.
+    let aliases = KeyAliases::default();
+    let order: Vec<String> = saphyr::Yaml::load_from_str(yaml)
+        .ok()
+        .into_iter()
+        .flatten()
+        .filter_map(|doc| doc.as_mapping().cloned())
+        .flat_map(|mapping| mapping.into_iter().map(|(key, _)| key))
+        .filter_map(|key| Some(aliases.canonical(key.as_str()?).into_owned()))
+        .collect();
+    let mut entries = frontmatter_entries(metadata);
+    entries.sort_by_key(|(key, _)| order.iter().position(|k| k == key).unwrap_or(usize::MAX));
+
+    let mut md = String::new();
+    write_frontmatter(&mut md, &entries);
+    // Files with Windows line endings keep them.
+    if source
+        .split('\n')
+        .next()
+        .is_some_and(|line| line.ends_with('\r'))
+    {
+        md = md.replace('\n', "\r\n");
+    }
+    md.push_str(body);
+    md
+}
+
+/// Splits Markdown into its frontmatter, without delimiters, and the rest. Lines may end with
+/// "\n" or "\r\n".
+fn split_frontmatter(source: &str) -> (&str, &str) {
+    let delimiter = RULES.frontmatter_delimiter;
+    let Some(rest) = source.strip_prefix(delimiter).and_then(|rest| {
+        rest.strip_prefix('\n')
+            .or_else(|| rest.strip_prefix("\r\n"))
+    }) else {
+        return ("", source);
+    };
+    let mut start = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == delimiter {
+            return (&rest[..start], &rest[start + line.len()..]);
+        }
+        start += line.len();
+    }
+    ("", source)
+}
+
+fn frontmatter(md: &mut String, metadata: &Metadata) {
+    write_frontmatter(md, &frontmatter_entries(metadata));
+}
+
+fn frontmatter_entries(metadata: &Metadata) -> Vec<(String, Yaml)> {
+    let mut entries: Vec<(String, Yaml)> = vec![];
+    for key in RULES.key_order {
+        match *key {
+            SIZES_KEY => entries.extend(sizes(metadata.sizes())),
+            key => entries.extend(metadata_entry(metadata, key).map(|v| (key.to_string(), v))),
+        }
+    }
+    let mut others: Vec<_> = metadata.others().iter().collect();
+    others.sort_by_key(|(key, _)| *key);
+    entries.extend(others.into_iter().map(|(k, v)| (k.clone(), meta_value(v))));
+    entries
+}
+
+fn write_frontmatter(md: &mut String, entries: &[(String, Yaml)]) {
+    if entries.is_empty() {
+        return;
+    }
+    let _ = writeln!(md, "{}", RULES.frontmatter_delimiter);
+    for (key, value) in entries {
+        yaml_entry(md, 0, key, value);
+    }
+    let _ = writeln!(md, "{}", RULES.frontmatter_delimiter);
+}
+
+fn metadata_entry(metadata: &Metadata, key: &str) -> Option<Yaml> {
+    let text = |s: String| Some(Yaml::Text(s));
+    let date = |date| meta_value(&MetaValue::Date(date));
+    match key {
+        "tags" => Some(metadata.tags())
+            .filter(|tags| !tags.is_empty())
+            .map(|tags| Yaml::List(tags.iter().map(|t| Yaml::Text(format!("#{}", t))).collect())),
+        "aliases" => Some(metadata.aliases())
+            .filter(|aliases| !aliases.is_empty())
+            .map(|aliases| Yaml::List(aliases.iter().map(|a| Yaml::String(a.clone())).collect())),
+        "category" => text(metadata.category()?.to_string()),
+        "image" => text(metadata.image()?.url.clone()),
+        "source" => Some(Yaml::String(metadata.source()?.to_string())),
+        "author" => Some(Yaml::String(metadata.author()?.to_string())),
+        "url" => text(metadata.url()?.to_string()),
+        "rating" => text(metadata.rating()?.to_string()),
+        "difficulty" => text(metadata.difficulty()?.to_string()),
+        "created" => Some(date(metadata.created()?)),
+        "last_cooked" => Some(date(metadata.last_cooked_entry()?)),
+        "cook_log" => Some(metadata.cook_log())
+            .filter(|log| !log.is_empty())
+            .map(|log| Yaml::List(log.iter().copied().map(date).collect())),
+        "servings" => text(canonical::quantity_of(metadata.servings()?)),
+        "yield" => text(canonical::quantity(metadata.yield_quantity()?)),
+        "prep_time" => text(canonical::quantity_of(metadata.prep_time()?)),
+        "cook_time" => text(canonical::quantity_of(metadata.cook_time()?)),
+        "total_time" => text(canonical::quantity_of(metadata.total_time()?)),
+        "glass" => text(tool(metadata.glass()?)),
+        "nutrition" => Some(nutrition(metadata.nutrition()?)),
+        "ratio" => text(ratio(metadata.ratio()?)),
+        "canning" => Some(canning(metadata.canning()?)),
+        "scales" => Some(metadata.scales())
+            .filter(|scales| !scales.is_empty())
+            .map(|scales| Yaml::List(scales.iter().map(scale_preset).collect())),
+        "densities" => Some(metadata.densities())
+            .filter(|densities| !densities.is_empty())
+            .map(densities),
+        _ => None,
+    }
+}
+
+fn sizes(sizes: &HashMap<String, SizeInfo>) -> Vec<(String, Yaml)> {
+    let mut sizes: Vec<_> = sizes.iter().collect();
+    sizes.sort_by_key(|(name, _)| *name);
+    sizes
+        .into_iter()
+        .map(|(name, size)| (format!("size | {}", name), Yaml::Text(size_info(size))))
+        .collect()
+}
+
+fn nutrition(nutrition: &Nutrition) -> Yaml {
+    let mut facts = vec![];
+    if let Some(calories) = nutrition.calories {
+        facts.push((
+            "calories".to_string(),
+            Yaml::Text(canonical::amount(calories)),
+        ));
+    }
+    for (name, mass) in [
+        ("protein", &nutrition.protein),
+        ("fat", &nutrition.fat),
+        ("carbs", &nutrition.carbs),
+        ("sodium", &nutrition.sodium),
+    ] {
+        if let Some(mass) = mass {
+            facts.push((name.to_string(), Yaml::Text(canonical::quantity_of(mass))));
+        }
+    }
+    for (name, mass) in &nutrition.others {
+        facts.push((name.clone(), Yaml::Text(canonical::quantity_of(mass))));
+    }
+    Yaml::Map(facts)
+}
+
+fn densities(densities: &DensityTable) -> Yaml {
+    Yaml::Map(
+        densities
+            .iter()
+            .map(|(name, density)| {
+                let density = format!("{} g/mL", canonical::amount(density));
+                (name.to_string(), Yaml::Text(density))
+            })
+            .collect(),
+    )
+}
+
+fn ratio(ratio: &BrewRatio) -> String {
+    let mut text = format!(
+        "{}:{}",
+        canonical::amount(ratio.dose),
+        canonical::amount(ratio.output)
+    );
+    if let Some(time) = &ratio.time {
+        let _ = write!(text, " in {}", canonical::quantity_of(time));
+    }
+    text
+}
+
+fn canning(canning: &Canning) -> Yaml {
+    let mut entries = vec![(
+        "method".to_string(),
+        Yaml::Text(canning.method().to_string()),
+    )];
+    if let Some(headspace) = canning.headspace() {
+        entries.push((
+            "headspace".to_string(),
+            Yaml::Text(canonical::quantity_of(headspace)),
+        ));
+    }
+    let processing = canning.processing().iter().map(|entry| {
+        let mut fields = vec![(
+            "jar".to_string(),
+            Yaml::Text(canonical::quantity(&entry.jar)),
+        )];
+        if entry.altitude != AltitudeRange::default() {
+            fields.push((
+                "altitude".to_string(),
+                Yaml::Text(altitude(&entry.altitude)),
+            ));
+        }
+        fields.push((
+            "time".to_string(),
+            Yaml::Text(canonical::quantity_of(&entry.time)),
+        ));
+        if let Some(pressure) = entry.pressure {
+            fields.push((
+                "pressure".to_string(),
+                Yaml::Text(format!("{} psi", canonical::amount(pressure))),
+            ));
+        }
+        Yaml::Map(fields)
+    });
+    entries.push(("processing".to_string(), Yaml::List(processing.collect())));
+    Yaml::Map(entries)
+}
+
+fn altitude(range: &AltitudeRange) -> String {
+    match range.max {
+        Some(max) => format!(
+            "{}-{} {}",
+            canonical::amount(range.min),
+            canonical::amount(max),
+            range.unit
+        ),
+        None => format!("{}+ {}", canonical::amount(range.min), range.unit),
+    }
+}
+
+fn scale_preset(preset: &ScalePreset) -> Yaml {
+    let factor = Yaml::Text(canonical::amount(preset.factor()));
+    let times = [
+        ("prep_time", preset.prep_time()),
+        ("cook_time", preset.cook_time()),
+        ("total_time", preset.total_time()),
+    ];
+    if preset.sizes().is_empty() && times.iter().all(|(_, time)| time.is_none()) {
+        return factor;
+    }
+    let mut entries = vec![("factor".to_string(), factor)];
+    entries.extend(sizes(preset.sizes()));
+    for (key, time) in times {
+        if let Some(time) = time {
+            entries.push((key.to_string(), Yaml::Text(canonical::quantity_of(time))));
+        }
+    }
+    Yaml::Map(entries)
+}
+
+fn meta_value(value: &MetaValue) -> Yaml {
+    match value {
+        MetaValue::Null => Yaml::Raw("null".to_string()),
+        MetaValue::Bool(b) => Yaml::Raw(b.to_string()),
+        MetaValue::Integer(i) => Yaml::Raw(i.to_string()),
+        MetaValue::Float(x) if x.is_nan() => Yaml::Raw(".nan".to_string()),
+        MetaValue::Float(x) if x.is_infinite() => {
+            Yaml::Raw(if *x > 0. { ".inf" } else { "-.inf" }.to_string())
+        }
+        MetaValue::Float(x) => Yaml::Raw(format!("{:?}", x)),
+        MetaValue::String(s) => Yaml::String(s.clone()),
+        MetaValue::Date(_) => Yaml::Raw(value.to_string()),
+        MetaValue::List(values) => Yaml::List(values.iter().map(meta_value).collect()),
+        MetaValue::Map(entries) => Yaml::Map(
+            entries
+                .iter()
+                .map(|(key, value)| (key.clone(), meta_value(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn yaml_entry(md: &mut String, indent: usize, key: &str, value: &Yaml) {
+    let prefix = format!("{}{}:", " ".repeat(indent), scalar(key, false));
+    match value {
+        Yaml::List(items) if !items.is_empty() => {
+            let _ = writeln!(md, "{}", prefix);
+            for item in items {
+                yaml_item(md, indent + RULES.yaml_indent, item);
+            }
+        }
+        Yaml::Map(entries) if !entries.is_empty() => {
+            let _ = writeln!(md, "{}", prefix);
+            for (key, value) in entries {
+                yaml_entry(md, indent + RULES.yaml_indent, key, value);
+            }
+        }
+        _ => {
+            let _ = writeln!(md, "{} {}", prefix, inline_yaml(value));
+        }
+    }
+}
+
+fn yaml_item(md: &mut String, indent: usize, item: &Yaml) {
+    let prefix = format!("{}- ", " ".repeat(indent));
+    // Collections start on the line of the dash, and continue aligned with their first entry.
+    let mut nested = String::new();
+    match item {
+        Yaml::List(items) if !items.is_empty() => {
+            for item in items {
+                yaml_item(&mut nested, indent + 2, item);
+            }
+        }
+        Yaml::Map(entries) if !entries.is_empty() => {
+            for (key, value) in entries {
+                yaml_entry(&mut nested, indent + 2, key, value);
+            }
+        }
+        _ => {
+            let _ = writeln!(md, "{}{}", prefix, inline_yaml(item));
+            return;
+        }
+    }
+    md.push_str(&prefix);
+    md.push_str(&nested[indent + 2..]);
+}
+
+/// Writes a scalar or an empty collection.
+fn inline_yaml(value: &Yaml) -> String {
+    match value {
+        Yaml::Text(s) => scalar(s, true),
+        Yaml::String(s) => scalar(s, false),
+        Yaml::Raw(s) => s.clone(),
+        Yaml::List(_) => "[]".to_string(),
+        Yaml::Map(_) => "{}".to_string(),
+    }
+}
+
+/// Writes a string as a YAML scalar, which is only quoted if it would not be read back as is.
+/// Strings read back as text may be written as numbers.
+fn scalar(s: &str, text: bool) -> String {
+    let read_back = saphyr::Yaml::load_from_str(s)
+        .ok()
+        .filter(|docs| docs.len() == 1);
+    let plain = read_back.is_some_and(|docs| match &docs[0] {
+        value if value.as_str() == Some(s) => true,
+        value if text => {
+            value.as_integer().map(|i| i.to_string()).as_deref() == Some(s)
+                || value.as_floating_point().map(|f| f.to_string()).as_deref() == Some(s)
+        }
+        _ => false,
+    });
+    if plain {
+        return s.to_string();
+    }
+    let mut quoted = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn canonical_recipe_is_stable() {
+        let canonical = indoc! {r##"
+            ---
+            tags:
+              - "#dinner"
+              - "#italian/pizza"
+            category: main
+            image: img/pizza.jpg
+            author: "4"
+            url: https://example.org/pizza
+            rating: 4.5
+            difficulty: 2
+            servings: 2-3
+            yield: ~1.2 kg
+            prep_time: 1 1/2 h
+            cook_time: 12 min
+            size | pan: 30 cm°
+            glass: Coupe
+            nutrition:
+              calories: 1800
+              protein: 60 g
+            ratio: 1:2 in 25-30 s
+            canning:
+              method: pressure
+              headspace: 1 in
+              processing:
+                - jar: 500 mL
+                  altitude: 0-1000 ft
+                  time: 25 min
+                  pressure: 10 psi
+            scales:
+              - 1/2
+              - factor: 2
+                size | pan: 40 cm°
+                cook_time: 15 min
+            densities:
+              flour: 0.53 g/mL
+              semolina: 2/3 g/mL
+            rated: 2024-03-01
+            sources:
+              - book: Pizza
+                page: 12
+              - "#web"
+            ---
+            # Pizza \*deluxe\*
+
+            ![Pizza \[baked\]](<img/pizza baked.jpg>)
+
+            ## Ingredients
+
+            ### Dough
+            - [Pizza dough](<bases/pizza dough.md>), 1
+            - Flour, 1/7 kg / 150 g (type 00) | Semolina, 150 g
+
+            ### Topping
+            - Tomatoes, 400 g
+            - Salt, to taste
+
+            ## Equipment
+
+            - Pizza stone, 30 cm°
+            - Pan, pan
+
+            ## Instructions
+
+            - Stretch the *Pizza dough* to \{30 cm\}
+                - Let it rest for **10-15 min**
+                  until it relaxes
+            - Then {1x: bake for **12 min** | 2x: bake for **15 min**}
+            - Slide it onto the stone ![Pizza on the stone](img/stone.jpg)
+
+            ## Notes
+
+            - Use `00` flour, see [the guide](https://example.org/guide).
+
+            ## Tips
+
+            - **Really** hot oven.
+        "##};
+        let recipe = Recipe::from_mdast(canonical).unwrap();
+        assert_eq!(render(&recipe), canonical);
+        assert_eq!(Recipe::from_mdast(&render(&recipe)).unwrap(), recipe);
+    }
+
+    #[test]
+    fn round_trip() {
+        let content = indoc! {"
+            ---
+            Tags: [\"#snack\"]
+            serves: 4
+            ---
+            Lemonade
+            ========
+            ## Ingredients
+            * Lemons,3
+            * Sugar , 0.5 cup ( fine )
+            ## Instructions
+            1. Squeeze the _Lemons_
+            2. Add 1. the *Sugar* & stir for __2 min__
+        "};
+        let recipe = Recipe::from_mdast(content).unwrap();
+        let written = render(&recipe);
+        assert_eq!(Recipe::from_mdast(&written).unwrap(), recipe);
+        assert_eq!(render(&Recipe::from_mdast(&written).unwrap()), written);
+        assert_eq!(
+            written,
+            indoc! {"
+                ---
+                tags:
+                  - \"#snack\"
+                servings: 4
+                ---
+                # Lemonade
+                ## Ingredients
+
+                - Lemons, 3
+                - Sugar, 1/2 cup (fine)
+
+                ## Instructions
+
+                1. Squeeze the *Lemons*
+                2. Add 1. the *Sugar* & stir for **2 min**
+            "}
+        );
+    }
+
+    #[test]
+    fn numbered_steps() {
+        let canonical = indoc! {"
+            # Bread
+            ## Ingredients
+
+            - Flour, 500 g
+
+            ## Instructions
+
+            3. Knead the *flour*
+                - Until smooth
+            4. Shape
+               and proof
+                1. Shape into a ball
+                2. Proof
+        "};
+        let recipe = Recipe::from_mdast(canonical).unwrap();
+        let instructions = recipe.instructions();
+        assert_eq!(instructions.numbering(), Numbering::Numbers { start: 3 });
+        assert_eq!(
+            instructions.steps()[0].substep_numbering(),
+            Numbering::Bullets
+        );
+        assert_eq!(
+            instructions.steps()[1].substep_numbering(),
+            Numbering::Numbers { start: 1 }
+        );
+        assert_eq!(render(&recipe), canonical);
+
+        // Markers too long for the indentation push substeps further.
+        let long = canonical.replace("3. Knead", "99. Knead");
+        let written = render(&Recipe::from_mdast(&long).unwrap());
+        assert!(written.contains("100. Shape\n     and proof\n     1. Shape into a ball\n"));
+        assert_eq!(
+            Recipe::from_mdast(&written).unwrap(),
+            Recipe::from_mdast(&long).unwrap()
+        );
+    }
+
+    #[test]
+    fn ingredient_group_metadata() {
+        let canonical = indoc! {"
+            # Pizza
+            ## Ingredients
+
+            ### [Dough](dough.md) (makes 2)
+            - Flour, 500 g
+
+            ### Sauce (makes 200 mL)
+            - Tomatoes, 400 g
+
+            ## Instructions
+
+            - Bake
+        "};
+        let recipe = Recipe::from_mdast(canonical).unwrap();
+        assert_eq!(render(&recipe), canonical);
+    }
+
+    #[test]
+    fn wikilinks() {
+        let canonical = indoc! {"
+            # Pizza
+            ## Ingredients
+
+            ### [[Tomato sauce|Sauce]] (makes 200 mL)
+            - [[bases/Pizza dough]], 1
+            - [[Pesto|Basil pesto]], 50 g
+
+            ## Instructions
+
+            - Spread the [[Tomato sauce|sauce]]
+        "};
+        let recipe = Recipe::from_mdast(canonical).unwrap();
+        assert_eq!(render(&recipe), canonical);
+    }
+
+    #[test]
+    fn instruction_groups() {
+        let canonical = indoc! {"
+            # Pizza
+            ## Ingredients
+
+            - Flour, 500 g
+
+            ## Instructions
+
+            ### Dough
+            1. Knead the *flour*
+            2. Rest
+
+            ### Topping
+            - Spread the sauce
+                - Evenly
+        "};
+        let recipe = Recipe::from_mdast(canonical).unwrap();
+        assert_eq!(recipe.instructions().groups().count(), 2);
+        assert_eq!(render(&recipe), canonical);
+    }
+
+    #[test]
+    fn escape_markdown() {
+        assert_eq!(
+            escape("1. *a* [b] &amp; & c_d"),
+            "1\\. \\*a\\* \\[b\\] \\&amp; & c\\_d"
+        );
+        assert_eq!(escape("# x\n- y"), "\\# x\n\\- y");
+        assert_eq!(scalar("#tag", false), "\"#tag\"");
+        assert_eq!(scalar("4", true), "4");
+        assert_eq!(scalar("4", false), "\"4\"");
+        assert_eq!(scalar("a: b", true), "\"a: b\"");
+        assert_eq!(scalar("1:2 in 25 s", true), "1:2 in 25 s");
+    }
+
+    #[test]
+    fn update_frontmatter_in_place() {
+        let source = indoc! {r##"
+            ---
+            Serves: 4
+            zz_custom: keep me
+            tags:
+              - "#dinner"
+            cook: 20 min
+            ---
+            # Soup
+            Written *by hand*, unlike canonical Markdown.
+
+            ## Ingredients
+            * Water,   1 L
+
+            ## Instructions
+            * Boil
+        "##};
+        let mut recipe = Recipe::from_mdast(source).unwrap();
+        let metadata = recipe.metadata_mut();
+        metadata.add_tag("#soup").unwrap();
+        metadata.add_tag("dinner").unwrap();
+        assert!(metadata.add_tag("two words").is_err());
+        metadata.set_cook_time(None);
+        metadata.set_rating(Some("4".parse().unwrap()));
+        metadata
+            .set_other("checked", MetaValue::Bool(true))
+            .unwrap();
+        assert!(metadata.set_other("serves", MetaValue::Integer(2)).is_err());
+
+        let (_, body) = split_frontmatter(source);
+        assert_eq!(
+            update_frontmatter(source, recipe.metadata()),
+            format!(
+                "{}{}",
+                indoc! {r##"
+                    ---
+                    servings: 4
+                    zz_custom: keep me
+                    tags:
+                      - "#dinner"
+                      - "#soup"
+                    rating: 4
+                    checked: true
+                    ---
+                "##},
+                body
+            )
+        );
+
+        // Windows line endings are kept, and the frontmatter is still replaced.
+        let crlf = source.replace('\n', "\r\n");
+        let updated = update_frontmatter(&crlf, recipe.metadata());
+        assert_eq!(updated.matches("---\r\n").count(), 2);
+        assert!(!updated.replace("\r\n", "").contains('\n'));
+        assert_eq!(
+            Recipe::from_mdast(&updated).unwrap().metadata(),
+            recipe.metadata()
+        );
+
+        // Recipes without a frontmatter get one.
+        let source = "# Water\n";
+        let mut metadata = Metadata::default();
+        metadata.add_tag("basics").unwrap();
+        assert_eq!(
+            update_frontmatter(source, &metadata),
+            "---\ntags:\n  - \"#basics\"\n---\n# Water\n"
+        );
+        assert_eq!(update_frontmatter(source, &Metadata::default()), source);
+    }
+}
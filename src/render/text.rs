@@ -0,0 +1,427 @@
+//! Plain-text rendering of recipes for terminals, wrapped to a fixed width.
+//!
+//! Ingredient quantities are aligned in a column and steps are numbered hierarchically (e.g.,
+//! "2.1."), with wrapped lines indented under their text. Timers may be highlighted with ANSI
+//! colors.
+
+use std::fmt::Write;
+
+use super::OriginalQuantities;
+use crate::recipe::{
+    amount::Amount,
+    ingredients::{Ingredient, IngredientOptions, Ingredients},
+    instructions::{Step, TextElem},
+    notes::NoteKind,
+    Recipe,
+};
+
+const DEFAULT_WIDTH: usize = 80;
+const INDENT: &str = "  ";
+const TIMER_STYLE: &str = "\x1b[1;33m";
+const RESET_STYLE: &str = "\x1b[0m";
+
+#[derive(Clone, Debug)]
+pub struct TextRenderer {
+    width: usize,
+    color: bool,
+    original_quantities: OriginalQuantities,
+}
+
+impl Default for TextRenderer {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_WIDTH,
+            color: false,
+            original_quantities: OriginalQuantities::default(),
+        }
+    }
+}
+
+/// Renders a recipe 80 columns wide, without colors.
+pub fn render(recipe: &Recipe) -> String {
+    TextRenderer::new().render(recipe)
+}
+
+/// A word to wrap, with its text as displayed and as written (with escape codes).
+struct Word {
+    visible: String,
+    styled: String,
+}
+
+impl Word {
+    fn len(&self) -> usize {
+        self.visible.chars().count()
+    }
+}
+
+impl TextRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of columns of lines. Words longer than that are not broken.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Whether to highlight timers with ANSI colors.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets which scaled or converted quantities are followed by their original quantity.
+    pub fn original_quantities(mut self, original_quantities: OriginalQuantities) -> Self {
+        self.original_quantities = original_quantities;
+        self
+    }
+
+    pub fn render(&self, recipe: &Recipe) -> String {
+        let mut text = String::new();
+        heading(&mut text, recipe.name(), '=');
+        for paragraph in recipe.description() {
+            let paragraph: String = paragraph.iter().map(|t| t.to_string()).collect();
+            self.wrap(&mut text, "", &plain_words(&paragraph));
+            text.push('\n');
+        }
+
+        let metadata = recipe.metadata();
+        let mut facts = vec![];
+        if let Some(servings) = metadata.servings() {
+            facts.push(format!("Servings: {}", servings));
+        }
+        if let Some(quantity) = metadata.yield_quantity() {
+            facts.push(format!("Yield: {}", quantity));
+        }
+        if let Some(time) = recipe.estimated_time() {
+            facts.push(format!("Time: {}", time));
+        }
+        if !facts.is_empty() {
+            let words = facts.join(" | ");
+            self.wrap(&mut text, "", &plain_words(&words));
+            text.push('\n');
+        }
+
+        heading(&mut text, "Ingredients", '-');
+        match recipe.ingredients() {
+            Ingredients::IngredientList(list) => self.ingredient_list(&mut text, list),
+            Ingredients::IngredientGroups(groups) => {
+                for (i, group) in groups.iter().enumerate() {
+                    if i > 0 {
+                        text.push('\n');
+                    }
+                    match group.yields() {
+                        Some(yields) => {
+                            let _ = writeln!(text, "{} (makes {}):", group.name(), yields);
+                        }
+                        None => {
+                            let _ = writeln!(text, "{}:", group.name());
+                        }
+                    }
+                    self.ingredient_list(&mut text, group.ingredients());
+                }
+            }
+        }
+
+        if !recipe.equipment().is_empty() {
+            text.push('\n');
+            heading(&mut text, "Equipment", '-');
+            for tool in recipe.equipment().tools() {
+                let prefix = format!("{}- ", INDENT);
+                self.wrap(&mut text, &prefix, &plain_words(tool.name()));
+            }
+        }
+
+        text.push('\n');
+        heading(&mut text, "Instructions", '-');
+        let instructions = recipe.instructions();
+        match instructions.groups().next() {
+            None => self.steps(&mut text, instructions.steps(), ""),
+            Some(_) => {
+                for (i, (group, steps)) in instructions.groups().enumerate() {
+                    if i > 0 {
+                        text.push('\n');
+                    }
+                    let _ = writeln!(text, "{}:", group.name());
+                    self.steps(&mut text, steps, "");
+                }
+            }
+        }
+
+        for kind in NoteKind::ALL {
+            let notes: Vec<_> = recipe.notes().iter().filter(|n| n.kind() == kind).collect();
+            if notes.is_empty() {
+                continue;
+            }
+            text.push('\n');
+            heading(&mut text, kind.heading(), '-');
+            for note in notes {
+                let prefix = format!("{}- ", INDENT);
+                self.wrap(&mut text, &prefix, &plain_words(&note.text()));
+            }
+        }
+        text
+    }
+
+    fn ingredient_list(&self, text: &mut String, list: &[IngredientOptions]) {
+        let quantities: Vec<String> = list
+            .iter()
+            .map(|options| self.quantity_text(options.ingredient()))
+            .collect();
+        let column = quantities
+            .iter()
+            .map(|q| q.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (options, quantity) in list.iter().zip(quantities) {
+            let mut line = self.ingredient_text(options.ingredient(), false);
+            for alternative in options.alternatives() {
+                line.push_str(" or ");
+                line.push_str(&self.ingredient_text(alternative, true));
+            }
+            let prefix = match column {
+                0 => INDENT.to_string(),
+                _ => format!("{}{:<width$}  ", INDENT, quantity, width = column),
+            };
+            self.wrap(text, &prefix, &plain_words(&line));
+        }
+    }
+
+    fn steps(&self, text: &mut String, steps: &[Step], number: &str) {
+        for (i, step) in steps.iter().enumerate() {
+            let number = format!("{}{}.", number, i + 1);
+            let depth = number.matches('.').count() - 1;
+            let prefix = format!("{}{}{} ", INDENT, "   ".repeat(depth), number);
+            self.wrap(text, &prefix, &self.step_words(step.description()));
+            self.steps(text, step.substeps(), &number);
+        }
+    }
+
+    fn step_words(&self, description: &[TextElem]) -> Vec<Word> {
+        let mut words = vec![];
+        let mut current = Word {
+            visible: String::new(),
+            styled: String::new(),
+        };
+        self.push_words(description, &mut words, &mut current);
+        if !current.visible.is_empty() {
+            words.push(current);
+        }
+        words
+    }
+
+    /// Splits elements into words, the last of which is left in `current` as the next element
+    /// may continue it. Scale-dependent text is written at scale 1.
+    fn push_words(&self, elems: &[TextElem], words: &mut Vec<Word>, current: &mut Word) {
+        for elem in elems {
+            match elem {
+                TextElem::Text(text) => {
+                    for c in text.chars() {
+                        if c.is_whitespace() {
+                            if !current.visible.is_empty() {
+                                words.push(std::mem::replace(
+                                    current,
+                                    Word {
+                                        visible: String::new(),
+                                        styled: String::new(),
+                                    },
+                                ));
+                            }
+                        } else {
+                            current.visible.push(c);
+                            current.styled.push(c);
+                        }
+                    }
+                }
+                TextElem::IngredientRef(r) => {
+                    current.visible.push_str(r.name());
+                    current.styled.push_str(r.name());
+                }
+                TextElem::Timer(_) | TextElem::Temperature(_) => {
+                    // Kept on one line with its unit.
+                    let time = elem.to_string();
+                    current.visible.push_str(&time);
+                    match self.color {
+                        true => {
+                            let _ =
+                                write!(current.styled, "{}{}{}", TIMER_STYLE, time, RESET_STYLE);
+                        }
+                        false => current.styled.push_str(&time),
+                    }
+                }
+                TextElem::Conditional(conditional) => {
+                    self.push_words(conditional.resolve(Amount::ONE), words, current)
+                }
+                TextElem::Image { .. } => (),
+                TextElem::Link { .. } | TextElem::WikiLink { .. } | TextElem::Formatted(_) => {
+                    self.push_words(&[TextElem::Text(elem.to_string())], words, current)
+                }
+            }
+        }
+    }
+
+    fn quantity_text(&self, ingredient: &Ingredient) -> String {
+        let mut quantities: Vec<String> = ingredient
+            .quantity()
+            .into_iter()
+            .map(|q| q.to_string())
+            .collect();
+        if let Some(original) = self.original_quantities.annotation(ingredient) {
+            quantities[0] = format!("{} ({})", quantities[0], original);
+        }
+        quantities.extend(ingredient.alt_quantities().iter().map(|q| q.to_string()));
+        quantities.join(" / ")
+    }
+
+    /// Name and information of an ingredient, along with its quantity for alternatives, whose
+    /// quantities are not in the column.
+    fn ingredient_text(&self, ingredient: &Ingredient, with_quantity: bool) -> String {
+        let mut text = ingredient.name().to_string();
+        if with_quantity && ingredient.quantity().is_some() {
+            let _ = write!(text, ", {}", self.quantity_text(ingredient));
+        }
+        if let Some(info) = ingredient.info() {
+            let _ = write!(text, " ({})", info);
+        }
+        text
+    }
+
+    /// Writes words after `prefix`, wrapping lines to the width with a hanging indent as long as
+    /// the prefix.
+    fn wrap(&self, text: &mut String, prefix: &str, words: &[Word]) {
+        let indent = prefix.chars().count();
+        text.push_str(prefix);
+        let mut column = indent;
+        for (i, word) in words.iter().enumerate() {
+            if i > 0 {
+                if column + 1 + word.len() > self.width {
+                    text.push('\n');
+                    text.push_str(&" ".repeat(indent));
+                    column = indent;
+                } else {
+                    text.push(' ');
+                    column += 1;
+                }
+            }
+            text.push_str(&word.styled);
+            column += word.len();
+        }
+        text.push('\n');
+    }
+}
+
+fn heading(text: &mut String, title: &str, underline: char) {
+    let _ = writeln!(text, "{}", title);
+    let _ = writeln!(
+        text,
+        "{}",
+        underline.to_string().repeat(title.chars().count())
+    );
+    text.push('\n');
+}
+
+fn plain_words(text: &str) -> Vec<Word> {
+    text.split_whitespace()
+        .map(|word| Word {
+            visible: word.to_string(),
+            styled: word.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::MDResult;
+    use indoc::indoc;
+
+    const RECIPE: &str = indoc! {"
+        ---
+        servings: 2
+        ---
+        # Mac & cheese
+        ## Ingredients
+
+        - Macaroni, 200 g
+        - Cheese, 100-150 g (grated) | Cream cheese, 100 g
+        - Pepper
+
+        ## Instructions
+
+        - Boil the *macaroni* in plenty of salted water for **10 min**, stirring from time to time
+            - Drain
+        - Stir in the *cheese*
+
+        ## Tips
+
+        Use *aged* cheddar.
+    "};
+
+    #[test]
+    fn render_recipe() -> MDResult<()> {
+        let text = TextRenderer::new()
+            .width(40)
+            .render(&Recipe::from_mdast(RECIPE)?);
+        assert_eq!(
+            text,
+            indoc! {"
+                Mac & cheese
+                ============
+
+                Servings: 2 | Time: 10 min
+
+                Ingredients
+                -----------
+
+                  200 g      Macaroni
+                  100-150 g  Cheese (grated) or Cream
+                             cheese, 100 g
+                             Pepper
+
+                Instructions
+                ------------
+
+                  1. Boil the macaroni in plenty of
+                     salted water for 10 min, stirring
+                     from time to time
+                     1.1. Drain
+                  2. Stir in the cheese
+
+                Tips
+                ----
+
+                  - Use aged cheddar.
+            "}
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn original_quantities() -> MDResult<()> {
+        let recipe = Recipe::from_mdast(RECIPE)?.scaled(Amount::new(1, 2));
+        let renderer = TextRenderer::new().original_quantities(OriginalQuantities::Changed);
+        let text = renderer.render(&recipe);
+        let lines: Vec<&str> = text.lines().skip(8).take(3).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "  100 g (200 g)        Macaroni",
+                "  50-75 g (100-150 g)  Cheese (grated) or Cream cheese, 50 g (100 g)",
+                "                       Pepper",
+            ]
+        );
+        let renderer = renderer.original_quantities(OriginalQuantities::Converted);
+        assert!(renderer.render(&recipe).contains("  100 g    Macaroni\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn color() -> MDResult<()> {
+        let text = TextRenderer::new()
+            .color(true)
+            .render(&Recipe::from_mdast(RECIPE)?);
+        assert!(text.contains("for \x1b[1;33m10 min\x1b[0m, stirring"));
+        Ok(())
+    }
+}
@@ -0,0 +1,295 @@
+//! Shopping lists aggregating the ingredients of one or more recipes.
+
+use crate::{
+    library::{LibraryEntry, RecipeLibrary},
+    recipe::{
+        amount::Amount,
+        ingredients::{Ingredient, IngredientOptions},
+        unit::{Dimension, Quantity},
+        Recipe,
+    },
+};
+use std::{collections::HashMap, fmt::Write, path::Path};
+
+/// An ingredient to buy, with its quantities merged across recipes. Quantities which cannot be
+/// converted to a common unit (e.g., "2" and "300 g" of onions) are kept separately.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShoppingItem {
+    name: String,
+    quantities: Vec<Quantity>,
+    recipes: Vec<String>,
+}
+
+impl ShoppingItem {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn quantities(&self) -> &[Quantity] {
+        &self.quantities
+    }
+
+    /// Quantities of a dimension (e.g., the masses of an ingredient bought both by weight and by
+    /// count).
+    pub fn quantities_of(&self, dimension: Dimension) -> impl Iterator<Item = &Quantity> {
+        self.quantities
+            .iter()
+            .filter(move |q| !q.is_to_taste() && q.unit.dimension() == dimension)
+    }
+
+    /// Names of the recipes using the ingredient.
+    pub fn recipes(&self) -> &[String] {
+        &self.recipes
+    }
+
+    fn add_quantity(&mut self, quantity: &Quantity) {
+        if quantity.is_to_taste() {
+            if !self.quantities.iter().any(|q| q.is_to_taste()) {
+                self.quantities.push(quantity.clone());
+            }
+            return;
+        }
+        let quantity = quantity.clone().sanitize();
+        match self
+            .quantities
+            .iter_mut()
+            .find_map(|q| q.checked_add(&quantity).map(|sum| (q, sum)))
+        {
+            Some((q, sum)) => *q = sum,
+            None => self.quantities.push(quantity),
+        }
+    }
+}
+
+/// Ingredients of several recipes, merged by name (case-insensitively) in order of appearance.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShoppingList {
+    items: Vec<ShoppingItem>,
+    by_name: HashMap<String, usize>,
+}
+
+impl ShoppingList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_recipes<'a>(recipes: impl IntoIterator<Item = &'a Recipe>) -> Self {
+        let mut list = Self::new();
+        for recipe in recipes {
+            list.add_recipe(recipe);
+        }
+        list
+    }
+
+    /// Adds all ingredients of a recipe, picking the primary ingredient of each line over its
+    /// alternatives.
+    pub fn add_recipe(&mut self, recipe: &Recipe) {
+        self.add_recipe_with(recipe, IngredientOptions::ingredient);
+    }
+
+    /// Adds all ingredients of a recipe, using `choose` to pick among alternatives.
+    pub fn add_recipe_with(
+        &mut self,
+        recipe: &Recipe,
+        mut choose: impl FnMut(&IngredientOptions) -> &Ingredient,
+    ) {
+        for options in recipe.ingredients().iter() {
+            self.add(choose(options), recipe.name());
+        }
+    }
+
+    /// Adds the ingredients of a library entry, replacing those made from other recipes of the
+    /// library with the ingredients of these recipes, scaled to the quantity needed. Links that
+    /// cannot be resolved or scaled (or that loop back) are added as plain ingredients.
+    pub fn add_entry(&mut self, entry: &LibraryEntry, library: &RecipeLibrary) {
+        self.add_entry_scaled(entry, library, Amount::ONE, &mut vec![]);
+    }
+
+    fn add_entry_scaled<'a>(
+        &mut self,
+        entry: &'a LibraryEntry,
+        library: &'a RecipeLibrary,
+        factor: Amount,
+        visiting: &mut Vec<&'a Path>,
+    ) {
+        visiting.push(entry.path());
+        let recipe = entry.recipe().scaled(factor);
+        for options in recipe.ingredients().iter() {
+            let ingredient = options.ingredient();
+            let sub_recipe = ingredient
+                .sub_recipe()
+                .and_then(|target| library.resolve_link(entry, target))
+                .filter(|sub| !visiting.contains(&sub.path()))
+                .and_then(|sub| Some((sub, sub.recipe().batches_for(ingredient.quantity())?)));
+            match sub_recipe {
+                Some((sub, batches)) => self.add_entry_scaled(sub, library, batches, visiting),
+                None => self.add(ingredient, recipe.name()),
+            }
+        }
+        visiting.pop();
+    }
+
+    pub fn add(&mut self, ingredient: &Ingredient, recipe: &str) {
+        let key = ingredient.name().to_lowercase();
+        let idx = *self.by_name.entry(key).or_insert_with(|| {
+            self.items.push(ShoppingItem {
+                name: ingredient.name().to_string(),
+                quantities: vec![],
+                recipes: vec![],
+            });
+            self.items.len() - 1
+        });
+        let item = &mut self.items[idx];
+        if let Some(quantity) = ingredient.quantity() {
+            item.add_quantity(quantity);
+        }
+        if !item.recipes.iter().any(|r| r == recipe) {
+            item.recipes.push(recipe.to_string());
+        }
+    }
+
+    pub fn items(&self) -> &[ShoppingItem] {
+        &self.items
+    }
+
+    /// Renders the list as a Markdown task list (e.g., "- [ ] Flour, 1.5 kg").
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        for item in &self.items {
+            write!(md, "- [ ] {}", item.name).unwrap();
+            let quantities: Vec<String> = item.quantities.iter().map(|q| q.to_string()).collect();
+            if !quantities.is_empty() {
+                write!(md, ", {}", quantities.join(" + ")).unwrap();
+            }
+            md.push('\n');
+        }
+        md
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn shopping_list() {
+        let pancakes = Recipe::from_mdast(indoc! {"
+            # Pancakes
+            ## Ingredients
+
+            - Flour, 250 g
+            - Milk, 1/2 L
+            - Butter, 2 tbsp | Oil, 2 tbsp
+            - Salt, to taste
+
+            ## Instructions
+        "})
+        .unwrap();
+        let bread = Recipe::from_mdast(indoc! {"
+            # Bread
+            ## Ingredients
+
+            - flour, 1 kg
+            - Milk, 1 cup
+            - Salt, 1 tsp
+            - Oil, 1 tbsp
+
+            ## Instructions
+        "})
+        .unwrap();
+
+        let list = ShoppingList::from_recipes([&pancakes, &bread]);
+        assert_eq!(
+            list.to_markdown(),
+            indoc! {"
+                - [ ] Flour, 1250 g
+                - [ ] Milk, 0.74 L
+                - [ ] Butter, 30 mL
+                - [ ] Salt, to taste + 5 mL
+                - [ ] Oil, 15 mL
+            "}
+        );
+        assert_eq!(list.items()[0].recipes(), ["Pancakes", "Bread"]);
+        let salt: Vec<String> = list.items()[3]
+            .quantities_of(Dimension::Volume)
+            .map(|q| q.to_string())
+            .collect();
+        assert_eq!(salt, ["5 mL"]);
+        assert_eq!(list.items()[3].quantities_of(Dimension::Mass).count(), 0);
+
+        // Alternatives can be picked instead of primary ingredients.
+        let mut list = ShoppingList::new();
+        list.add_recipe_with(&pancakes, |opts| {
+            opts.alternatives().first().unwrap_or(opts.ingredient())
+        });
+        assert_eq!(list.items()[2].name(), "Oil");
+    }
+
+    #[test]
+    fn expand_sub_recipes() {
+        let mut library = RecipeLibrary::default();
+        let mut insert = |path: &str, content: &str| {
+            library.insert(path.into(), Recipe::from_mdast(content).unwrap());
+        };
+        insert(
+            "pizza.md",
+            indoc! {"
+                # Pizza
+                ## Ingredients
+
+                - [Dough](bases/dough.md), 2
+                - [Sauce](bases/sauce.md), 300 g
+                - [Toppings](toppings.md), 1
+                - Mozzarella, 125 g
+
+                ## Instructions
+            "},
+        );
+        insert(
+            "bases/dough.md",
+            indoc! {"
+                ---
+                yield: 500 g
+                ---
+                # Dough
+                ## Ingredients
+
+                - Flour, 300 g
+                - [Pizza](../pizza.md), 1
+
+                ## Instructions
+            "},
+        );
+        insert(
+            "bases/sauce.md",
+            indoc! {"
+                ---
+                yield: 600 g
+                ---
+                # Sauce
+                ## Ingredients
+
+                - Tomatoes, 800 g
+                - Mozzarella, 25 g
+
+                ## Instructions
+            "},
+        );
+
+        let mut list = ShoppingList::new();
+        let pizza = library.by_name("Pizza").next().unwrap();
+        list.add_entry(pizza, &library);
+        // Unresolved and cyclic links are kept as ingredients.
+        assert_eq!(
+            list.to_markdown(),
+            indoc! {"
+                - [ ] Flour, 600 g
+                - [ ] Pizza, 2
+                - [ ] Tomatoes, 400 g
+                - [ ] Mozzarella, 137.5 g
+                - [ ] Toppings, 1
+            "}
+        );
+    }
+}
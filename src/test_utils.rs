@@ -0,0 +1,318 @@
+//! Helpers for downstream crates writing tests against the recipe model.
+//!
+//! Recipes are built by generating their Markdown representation and running it through the regular
+//! parser, so that built recipes are always identical to parsed ones.
+
+use crate::{recipe::Recipe, render::markdown};
+use std::fmt::Write;
+
+/// Small, complete recipe exercising metadata, ingredients, and nested steps.
+pub const SIMPLE_RECIPE: &str = "---
+tags:
+  - \"#dessert\"
+yield: 1.2 L
+---
+# Lemonade
+## Ingredients
+
+- Lemons, 3
+- Sugar, 100 g / 1/2 cup
+- Water, 1 L (cold)
+
+## Instructions
+
+- Squeeze the *Lemons*
+- Mix everything
+    - Stir for **2 min**
+";
+
+/// Recipe with ingredient groups.
+pub const GROUPED_RECIPE: &str = "# Pizza
+## Ingredients
+
+### Dough
+- Flour, 500 g
+- Water, 300 mL
+
+### Topping
+- Tomato sauce, 200 mL
+- Mozzarella, 1
+
+## Instructions
+
+- Knead the *Flour* and *Water*
+- Bake for **15 min**
+";
+
+pub fn simple_recipe() -> Recipe {
+    parse_fixture(SIMPLE_RECIPE)
+}
+
+pub fn grouped_recipe() -> Recipe {
+    parse_fixture(GROUPED_RECIPE)
+}
+
+#[track_caller]
+fn parse_fixture(content: &str) -> Recipe {
+    Recipe::from_mdast(content).unwrap_or_else(|e| panic!("invalid fixture: {}", e))
+}
+
+/// Builds a [`Recipe`] from its parts, each written in the recipe Markdown syntax.
+#[derive(Clone, Debug, Default)]
+pub struct RecipeBuilder {
+    name: String,
+    tags: Vec<String>,
+    frontmatter: Vec<String>,
+    ingredients: Vec<String>,
+    groups: Vec<(String, Vec<String>)>,
+    steps: Vec<(String, Vec<String>)>,
+}
+
+impl RecipeBuilder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a tag (without the leading '#').
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Adds a raw `key: value` frontmatter entry.
+    pub fn meta(mut self, key: &str, value: &str) -> Self {
+        self.frontmatter.push(format!("{}: {}", key, value));
+        self
+    }
+
+    /// Adds an ingredient line (e.g., "Milk, 50 mL (cold)") outside of any group.
+    pub fn ingredient(mut self, line: &str) -> Self {
+        self.ingredients.push(line.to_string());
+        self
+    }
+
+    /// Adds an ingredient group with its ingredient lines.
+    pub fn group(mut self, name: &str, lines: &[&str]) -> Self {
+        self.groups.push((
+            name.to_string(),
+            lines.iter().map(|l| l.to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Adds a top-level step (e.g., "Bake for **10 min**").
+    pub fn step(mut self, text: &str) -> Self {
+        self.steps.push((text.to_string(), vec![]));
+        self
+    }
+
+    /// Adds a substep to the last top-level step.
+    pub fn substep(mut self, text: &str) -> Self {
+        self.steps
+            .last_mut()
+            .expect("a substep must follow a step")
+            .1
+            .push(text.to_string());
+        self
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        if !self.tags.is_empty() || !self.frontmatter.is_empty() {
+            md.push_str("---\n");
+            if !self.tags.is_empty() {
+                md.push_str("tags:\n");
+                for tag in &self.tags {
+                    writeln!(md, "  - \"#{}\"", tag).unwrap();
+                }
+            }
+            for line in &self.frontmatter {
+                writeln!(md, "{}", line).unwrap();
+            }
+            md.push_str("---\n");
+        }
+        writeln!(md, "# {}\n## Ingredients\n", self.name).unwrap();
+        for line in &self.ingredients {
+            writeln!(md, "- {}", line).unwrap();
+        }
+        for (name, lines) in &self.groups {
+            writeln!(md, "\n### {}", name).unwrap();
+            for line in lines {
+                writeln!(md, "- {}", line).unwrap();
+            }
+        }
+        md.push_str("\n## Instructions\n\n");
+        for (step, substeps) in &self.steps {
+            writeln!(md, "- {}", step).unwrap();
+            for substep in substeps {
+                writeln!(md, "    - {}", substep).unwrap();
+            }
+        }
+        md
+    }
+
+    #[track_caller]
+    pub fn build(&self) -> Recipe {
+        let md = self.to_markdown();
+        Recipe::from_mdast(&md).unwrap_or_else(|e| panic!("invalid recipe: {}\n{}", e, md))
+    }
+}
+
+/// Asserts that two recipes are equal, naming the parts which differ.
+#[track_caller]
+pub fn assert_recipe_eq(left: &Recipe, right: &Recipe) {
+    let sections: [(&str, bool); 9] = [
+        ("name", left.name() == right.name()),
+        ("metadata", left.metadata() == right.metadata()),
+        ("cover", left.leading_image() == right.leading_image()),
+        ("description", left.description() == right.description()),
+        ("language", left.language() == right.language()),
+        ("ingredients", left.ingredients() == right.ingredients()),
+        ("equipment", left.equipment() == right.equipment()),
+        ("instructions", left.instructions() == right.instructions()),
+        ("notes", left.notes() == right.notes()),
+    ];
+    let mut differing: Vec<&str> = sections
+        .iter()
+        .filter_map(|(name, eq)| (!eq).then_some(*name))
+        .collect();
+    // Parts without an accessor above still make the recipes differ.
+    if differing.is_empty() && left != right {
+        differing.push("other fields");
+    }
+    if !differing.is_empty() {
+        panic!(
+            "recipes differ in {}\n  left: {:#?}\n right: {:#?}",
+            differing.join(", "),
+            left,
+            right
+        );
+    }
+}
+
+/// Asserts that a recipe written as canonical Markdown parses back to the same recipe.
+#[track_caller]
+pub fn assert_round_trip(recipe: &Recipe) {
+    let md = markdown::render(recipe);
+    let parsed = Recipe::from_mdast(&md)
+        .unwrap_or_else(|e| panic!("canonical Markdown does not parse: {}\n{}", e, md));
+    assert_recipe_eq(&parsed, recipe);
+}
+
+/// Asserts that Markdown is in canonical form, i.e., that it is written back unchanged once
+/// parsed, and that it round-trips.
+#[track_caller]
+pub fn assert_canonical(content: &str) {
+    let recipe = parse_fixture(content);
+    let written = markdown::render(&recipe);
+    if written != content {
+        panic!(
+            "Markdown is not canonical\n  left: {}\n right: {}",
+            content, written
+        );
+    }
+    assert_round_trip(&recipe);
+}
+
+/// Checks [`assert_round_trip`] on recipes built from every combination of the given ingredient
+/// lines and steps (one ingredient and one step per recipe), as a cheap property test of custom
+/// syntax against the writer.
+#[track_caller]
+pub fn assert_round_trips(ingredients: &[&str], steps: &[&str]) {
+    for ingredient in ingredients {
+        for step in steps {
+            let recipe = RecipeBuilder::new("Round trip")
+                .ingredient(ingredient)
+                .step(step)
+                .build();
+            assert_round_trip(&recipe);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures_parse() {
+        simple_recipe();
+        grouped_recipe();
+    }
+
+    #[test]
+    fn builder_matches_fixture() {
+        let built = RecipeBuilder::new("Lemonade")
+            .tag("dessert")
+            .meta("yield", "1.2 L")
+            .ingredient("Lemons, 3")
+            .ingredient("Sugar, 100 g / 1/2 cup")
+            .ingredient("Water, 1 L (cold)")
+            .step("Squeeze the *Lemons*")
+            .step("Mix everything")
+            .substep("Stir for **2 min**")
+            .build();
+        assert_recipe_eq(&built, &simple_recipe());
+    }
+
+    #[test]
+    fn fixtures_are_canonical() {
+        assert_canonical(SIMPLE_RECIPE);
+        assert_canonical(GROUPED_RECIPE);
+    }
+
+    #[test]
+    fn round_trips() {
+        assert_round_trips(
+            &[
+                "Lemons, 3",
+                "Sugar, ~1 1/2-2 cup / 300 g (fine)",
+                "Salt, to taste | Pepper",
+                "Flour, 0.125 kg",
+                "Water, 75%",
+                "Gin, 1 part",
+                "Oats_and_bran, 1/7 L",
+                "[Dough](dough.md), 2",
+                "[[Pizza dough|Dough]], 1",
+            ],
+            &[
+                "Squeeze the *Lemons* for **2-3 min**",
+                "Bake at **180°C**, then at *400 °F*",
+                "Bake at **gas mark 4**, or **GM5**, or **450K**",
+                "Add salt \\*not\\* [pepper] & wait {1x: **10 min** | 2.5x: **15 min**}",
+                "1\\. Wait # 2 > 3",
+                "Mix\n  well",
+                "Mix **well** now, then bake for **1 h 30 min**",
+                "Add the `00` flour and *chopped **red** onion*, then **stir `well`**",
+                "Knead the [[Pizza dough|dough]] and the [[Focaccia]]",
+                "Fold, see [this technique](<fold (1).md>) and []()",
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Markdown is not canonical")]
+    fn assert_canonical_reports_differences() {
+        assert_canonical(&SIMPLE_RECIPE.replace("Lemons, 3", "Lemons,3"));
+    }
+
+    #[test]
+    #[should_panic(expected = "recipes differ in name")]
+    fn assert_recipe_eq_reports_differences() {
+        assert_recipe_eq(
+            &RecipeBuilder::new("A").build(),
+            &RecipeBuilder::new("B").build(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "recipes differ in description")]
+    fn assert_recipe_eq_compares_description() {
+        let described =
+            SIMPLE_RECIPE.replace("# Lemonade\n", "# Lemonade\n\nFreshly squeezed.\n\n");
+        assert_recipe_eq(&Recipe::from_mdast(&described).unwrap(), &simple_recipe());
+    }
+}